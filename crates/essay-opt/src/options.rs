@@ -63,6 +63,12 @@ pub(crate) fn derive_opt(attr: proc_macro::TokenStream, item: proc_macro::TokenS
     // struct_token.
     // println!("Item: {}", item.as);
 
+    // lint attributes also cover the generated setters, like the
+    // needless ..self update of a single-option struct
+    let lint_attrs = attrs.iter()
+        .filter(|attr| attr.path().is_ident("allow"))
+        .collect::<Vec<_>>();
+
     let trait_methods = trait_methods(&fields, &ident);
     let arg_methods = arg_methods(&fields);
     let nil_methods = nil_methods(&fields, &ident);
@@ -81,6 +87,7 @@ pub(crate) fn derive_opt(attr: proc_macro::TokenStream, item: proc_macro::TokenS
             fn into_arg(self) -> #ident;
         }
 
+        #(#lint_attrs)*
         impl #opt for #ident {
             #arg_methods
 
@@ -4,7 +4,7 @@ mod tile;
 mod transpose;
 
 pub use stack::{
-    concatenate, concatenate_axis,
+    concatenate, concatenate_axis, try_concatenate, try_concatenate_axis,
     stack, stack_axis,
     dstack, hstack, vstack,
 };
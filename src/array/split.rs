@@ -1,4 +1,4 @@
-use crate::tensor::{Axis, Type, Tensor, unsafe_init};
+use crate::tensor::{Axis, Type, Tensor, TensorError};
 
 impl<D: Type + Clone> Tensor<D> {
     #[inline]
@@ -25,6 +25,23 @@ impl<D: Type + Clone> Tensor<D> {
     pub fn dsplit(&self, sections: impl IntoSections) -> Vec<Tensor<D>> {
         split_axis(Axis::axis(2), self, sections)
     }
+
+    #[inline]
+    pub fn try_split(
+        &self, 
+        sections: impl IntoSections
+    ) -> Result<Vec<Tensor<D>>, TensorError> {
+        try_split_axis(None, self, sections)
+    }
+
+    #[inline]
+    pub fn try_split_axis(
+        &self, 
+        axis: impl Into<Axis>, 
+        sections: impl IntoSections
+    ) -> Result<Vec<Tensor<D>>, TensorError> {
+        try_split_axis(axis, self, sections)
+    }
 }
 
 fn split_axis<T: Type + Clone>(
@@ -32,19 +49,43 @@ fn split_axis<T: Type + Clone>(
     tensor: impl Into<Tensor<T>>, 
     sections: impl IntoSections,
 ) -> Vec<Tensor<T>> {
+    try_split_axis(axis, tensor, sections).unwrap_or_else(|err| panic!("{}", err))
+}
+
+fn try_split_axis<T: Type + Clone>(
+    axis: impl Into<Axis>,
+    tensor: impl Into<Tensor<T>>, 
+    sections: impl IntoSections,
+) -> Result<Vec<Tensor<T>>, TensorError> {
     let tensor = tensor.into();
     let section = sections.into_sections();
     let axis : Axis = axis.into();
 
-    let axis = axis.axis_from_rank(tensor.rank());
+    if tensor.rank() == 0 {
+        return Err(TensorError::InvalidShape {
+            op: "split",
+            shape: tensor.shape().as_vec(),
+            reason: "requires rank > 0",
+        });
+    }
+
+    let axis = axis.try_axis_from_rank("split", tensor.rank())?;
 
     let len = tensor.shape().dim(axis);
     let mut cuts = Vec::<usize>::new();
 
     match section {
         Sections::SplitEqual(n) => {
+            if n == 0 || len % n != 0 {
+                return Err(TensorError::InvalidArgument {
+                    op: "split",
+                    reason: format!("{} sections do not evenly divide {:?} on axis {}",
+                        n, tensor.shape().as_vec(), axis
+                    ),
+                });
+            }
+
             let step = len / n;
-            assert!(step > 0);
             let mut i = 0;
             while i < len {
                 i += step;
@@ -55,81 +96,29 @@ fn split_axis<T: Type + Clone>(
         Sections::SplitCuts(split_cuts) => {
             cuts.append(&mut split_cuts.clone());
             cuts.push(len);
-        }
-    }
 
-    if axis == 0 {
-        let mut slices = Vec::<Tensor<T>>::new();
-
-        let mut prev = 0;
-        for i in cuts {
-            if i != prev {
-                slices.push(tensor.subslice(prev, i - prev));
+            if cuts.windows(2).any(|w| w[1] < w[0]) || cuts.iter().any(|cut| *cut > len) {
+                return Err(TensorError::InvalidArgument {
+                    op: "split",
+                    reason: format!("cuts {:?} are invalid for {:?} on axis {}",
+                        split_cuts, tensor.shape().as_vec(), axis
+                    ),
+                });
             }
-            prev = i;
         }
-    
-
-        slices
-    } else {
-        split_by_axis(tensor, axis, cuts)
     }
-}
-
-fn split_by_axis<T: Type + Clone>(
-    tensor: Tensor<T>, 
-    axis: usize, 
-    cuts: Vec<usize>
-) -> Vec<Tensor<T>> {
-    let n_outer = tensor.shape().sublen(0, axis);
-    let n_inner = if axis < tensor.rank() {
-        tensor.shape().sublen(axis + 1, tensor.rank())
-    } else {
-        1
-    };
-    let axis_len = tensor.shape().dim(axis);
-
-    let mut prev = 0;
-    let x = tensor.as_slice();
 
     let mut slices = Vec::<Tensor<T>>::new();
 
-    for cut in cuts {
-        if cut == prev {
-            continue;
-        }
-
-        let mut shape = Vec::<usize>::new();
-        for i in 0..axis {
-            shape.push(tensor.shape().dim(i))
-        }
-        shape.push(cut - prev);
-        for i in axis + 1..tensor.shape().rank() {
-            shape.push(tensor.shape().dim(i))
+    let mut prev = 0;
+    for i in cuts {
+        if i != prev {
+            slices.push(tensor.slice_axis(axis as isize, prev, i - prev, 1));
         }
-
-        let data = unsafe {
-            unsafe_init::<T>(n_outer * n_inner * (cut - prev), shape, |o| {
-                for j in 0..n_outer {
-                    for k in prev..cut {
-                        for i in 0..n_inner {
-                            let v = x[j * axis_len * n_inner + k * n_inner + i].clone();
-
-                            o.add(j * n_inner * (cut - prev) + (k - prev) * n_inner + i)
-                                .write(v);
-                        }
-                    }
-                }
-            })
-        };
-
-        slices.push(data);
-
-        prev = cut;
+        prev = i;
     }
 
-
-    slices
+    Ok(slices)
 }
 
 pub trait IntoSections {
@@ -174,7 +163,7 @@ pub enum Sections {
 
 #[cfg(test)]
 mod test {
-    use crate::ten;
+    use crate::{ten, tensor::TensorError};
 
     #[test]
     fn test_split() {
@@ -196,6 +185,16 @@ mod test {
             vec![ten![[1.], [3.]], ten![[2.], [4.]]],
         );
     }
+
+    #[test]
+    fn split_axis_shares_data() {
+        let a = ten![[1., 2., 3.], [4., 5., 6.]];
+        let split = a.split_axis(1, [1]);
+
+        assert_eq!(split, vec![ten![[1.], [4.]], ten![[2., 3.], [5., 6.]]]);
+        assert_eq!(split[1].strides(), vec![3, 1]);
+        assert_eq!(split[1].offset(), 1);
+    }
     
     #[test]
     fn test_vsplit() {
@@ -228,4 +227,28 @@ mod test {
             ],
         );
     }
+
+    #[test]
+    fn try_split_errors() {
+        let t = ten![[1, 2, 3], [4, 5, 6]];
+
+        assert_eq!(t.try_split_axis(1, 3).unwrap().len(), 3);
+
+        assert!(matches!(
+            t.try_split_axis(1, 2).unwrap_err(), 
+            TensorError::InvalidArgument { op: "split", .. }
+        ));
+        assert!(matches!(
+            t.try_split_axis(1, [2, 1]).unwrap_err(), 
+            TensorError::InvalidArgument { op: "split", .. }
+        ));
+        assert!(matches!(
+            t.try_split_axis(1, [4]).unwrap_err(), 
+            TensorError::InvalidArgument { op: "split", .. }
+        ));
+        assert_eq!(
+            t.try_split_axis(2, 1).unwrap_err(),
+            TensorError::AxisOutOfRange { op: "split", axis: 2, rank: 2 }
+        );
+    }
 }
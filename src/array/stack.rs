@@ -1,6 +1,13 @@
-use crate::tensor::{Axis, Shape, Type, IntoTensorList, Tensor, unsafe_init};
+use crate::tensor::{Axis, Shape, Type, IntoTensorList, Tensor, TensorError, unsafe_init};
 
 pub fn concatenate<T>(x: impl IntoTensorList<T>) -> Tensor<T>
+where
+    T: Type + Clone
+{
+    try_concatenate(x).unwrap_or_else(|err| panic!("{}", err))
+}
+
+pub fn try_concatenate<T>(x: impl IntoTensorList<T>) -> Result<Tensor<T>, TensorError>
 where
     T: Type + Clone
 {
@@ -12,6 +19,16 @@ where
 }
 
 pub fn concatenate_axis<T>(axis: impl Into<Axis>, x: impl IntoTensorList<T>) -> Tensor<T>
+where
+    T: Type + Clone
+{
+    try_concatenate_axis(axis, x).unwrap_or_else(|err| panic!("{}", err))
+}
+
+pub fn try_concatenate_axis<T>(
+    axis: impl Into<Axis>, 
+    x: impl IntoTensorList<T>
+) -> Result<Tensor<T>, TensorError>
 where
     T: Type + Clone
 {
@@ -21,9 +38,8 @@ where
 
     x.into_list(&mut vec);
 
-    // concat_vec(vec, axis)
-    let axis = if vec.len() > 0 {
-        axis.axis_from_rank(vec[0].rank())
+    let axis = if ! vec.is_empty() {
+        axis.try_axis_from_rank("concatenate", vec[0].rank())?
     } else {
         0
     };
@@ -119,6 +135,30 @@ impl<T: Type + Clone> Tensor<T> {
         concatenate_axis(axis, vec)
     }
 
+    pub fn try_concatenate(
+        &self, 
+        others: impl IntoTensorList<T>
+    ) -> Result<Tensor<T>, TensorError> {
+        let mut vec = Vec::<Tensor<T>>::new();
+        vec.push(self.clone());
+
+        others.into_list(&mut vec);
+
+        try_concatenate(vec)
+    }
+
+    pub fn try_concatenate_axis(
+        &self, axis: impl Into<Axis>, 
+        others: impl IntoTensorList<T>
+    ) -> Result<Tensor<T>, TensorError> {
+        let mut vec = Vec::<Tensor<T>>::new();
+        vec.push(self.clone());
+
+        others.into_list(&mut vec);
+
+        try_concatenate_axis(axis, vec)
+    }
+
     pub fn stack(&self, others: impl IntoTensorList<T>, axis: impl Into<Axis>) -> Tensor<T> {
         let mut vec = Vec::<Tensor<T>>::new();
         vec.push(self.clone());
@@ -153,35 +193,18 @@ impl<T: Type + Clone> Tensor<T> {
         let axis : Axis = axis.into();
     
         let axis = axis.axis_from_rank(self.rank());
-    
-        let cuts = Vec::<usize>::new();
-    
-        if axis == 0 {
-            let mut slices = Vec::<Tensor<T>>::new();
-    
-            let mut prev = 0;
-            for i in cuts {
-                if i != prev {
-                    slices.push(self.subslice(prev, i - prev));
-                }
-                prev = i;
-            }
-        
-    
-            slices
-        } else {
-            self.split_axis(Axis::axis(axis as isize), cuts)
-        }
+
+        (0..self.dim(axis)).map(|i| self.index_axis(axis, i)).collect()
     }
 }
 
 pub(crate) fn concat_axis<T: Type + Clone>(
     axis: usize,
     args: Vec<Tensor<T>>, 
-) -> Tensor<T> {
-    let shape = args[0].shape();
+) -> Result<Tensor<T>, TensorError> {
+    let axis_len = validate_concat(&args, axis)?;
 
-    let axis_len = validate_concat(&args, axis);
+    let shape = args[0].shape();
 
     let shape_inner = shape.clone().remove(axis);
 
@@ -196,13 +219,14 @@ pub(crate) fn concat_axis<T: Type + Clone>(
     let shape = shape_inner.insert(axis, axis_len);
 
     unsafe {
-        unsafe_init::<T>(o_len, shape, |o| {
+        Ok(unsafe_init::<T>(o_len, shape, |o| {
             let j_stride = n_inner * axis_len;
             let mut t = 0;
 
             for tensor in args.iter() {
                 let n_axis = tensor.shape().dim(axis);
 
+                let tensor = tensor.to_contiguous();
                 let x = tensor.as_slice();
 
                 for j in 0..n_outer {
@@ -217,38 +241,48 @@ pub(crate) fn concat_axis<T: Type + Clone>(
 
                 t += n_axis * n_inner;
             }
-
-        })
+        }))
     }
 }
 
-fn validate_concat<T: Type>(args: &Vec<Tensor<T>>, axis: usize) -> usize {
+fn validate_concat<T: Type>(args: &[Tensor<T>], axis: usize) -> Result<usize, TensorError> {
+    if args.is_empty() {
+        return Err(TensorError::InvalidArgument {
+            op: "concatenate",
+            reason: "requires at least one tensor".to_string(),
+        });
+    }
+
     let shape = args[0].shape();
 
+    if axis >= shape.rank() {
+        return Err(TensorError::AxisOutOfRange {
+            op: "concatenate",
+            axis: axis as isize,
+            rank: shape.rank(),
+        });
+    }
+
     let mut axis_len = 0;
 
-    // TODO: split out validation
     for x in args {
         let x_shape = x.shape();
-    
-        assert_eq!(shape.rank(), x_shape.rank(),
-            "concat() tensor shape must match {:?} {:?}", 
-            shape.as_vec(), x_shape.as_vec()
-        );
-    
-        for i in 0..shape.rank() {
-            if i == axis {
-                axis_len += x_shape.dim(axis);
-            } else {
-                assert_eq!(x_shape.dim(i), shape.dim(i),
-                    "concat() tensor shape must match {:?} {:?}", 
-                    shape.as_vec(), x_shape.as_vec()
-                );
-            }
+
+        let is_match = shape.rank() == x_shape.rank()
+            && (0..shape.rank()).all(|i| i == axis || x_shape.dim(i) == shape.dim(i));
+
+        if ! is_match {
+            return Err(TensorError::ShapeMismatch {
+                op: "concatenate",
+                a: shape.as_vec(),
+                b: x_shape.as_vec(),
+            });
         }
+
+        axis_len += x_shape.dim(axis);
     }
 
-    axis_len
+    Ok(axis_len)
 }
 
 fn stack_vec<T>(axis: impl Into<Axis>, x: Vec<Tensor<T>>) -> Tensor<T>
@@ -288,6 +322,7 @@ where
             for (j, x) in x.iter().enumerate() {
                 assert_eq!(x_len, x.size());
 
+                let x = x.to_contiguous();
                 let x = x.as_slice();
 
                 for k in 0..n_outer {
@@ -339,7 +374,13 @@ where
 
 #[cfg(test)]
 mod test {
-    use crate::{array::{concatenate, concatenate_axis, dstack, hstack, stack, stack_axis, vstack}, tensor::Axis, ten};
+    use crate::{
+        array::{
+            concatenate, concatenate_axis, dstack, hstack, stack, stack_axis, vstack,
+            try_concatenate, try_concatenate_axis,
+        }, 
+        tensor::{Axis, Tensor, TensorError}, ten
+    };
     
     #[test]
     fn test_concat() {
@@ -519,4 +560,34 @@ mod test {
             [[1., 2.]], [[10., 20.]]
         ]);
     }
+
+    #[test]
+    fn test_unstack() {
+        let a = ten![[1., 2., 3.], [4., 5., 6.]];
+
+        assert_eq!(a.unstack(0), vec![ten![1., 2., 3.], ten![4., 5., 6.]]);
+        assert_eq!(a.unstack(1), vec![ten![1., 4.], ten![2., 5.], ten![3., 6.]]);
+        assert_eq!(a.unstack(-1)[2].strides(), vec![3]);
+    }
+
+    #[test]
+    fn try_concatenate_errors() {
+        assert_eq!(
+            try_concatenate_axis(1, [ten![[1], [2]], ten![[3], [4]]]).unwrap(),
+            ten![[1, 3], [2, 4]]
+        );
+
+        assert_eq!(
+            try_concatenate([ten![[1, 2]], ten![[3, 4, 5]]]).unwrap_err(),
+            TensorError::ShapeMismatch { op: "concatenate", a: vec![1, 2], b: vec![1, 3] }
+        );
+        assert_eq!(
+            ten![1, 2].try_concatenate_axis(1, [ten![3]]).unwrap_err(),
+            TensorError::AxisOutOfRange { op: "concatenate", axis: 1, rank: 1 }
+        );
+        assert!(matches!(
+            try_concatenate(Vec::<Tensor<i32>>::new()).unwrap_err(),
+            TensorError::InvalidArgument { op: "concatenate", .. }
+        ));
+    }
 }
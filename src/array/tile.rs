@@ -13,7 +13,7 @@ pub fn tile<T>(tensor: impl Into<Tensor<T>>, multiples: impl Into<Tensor<usize>>
 where
     T: Type + Clone
 {
-    let tensor = tensor.into();
+    let tensor = tensor.into().to_contiguous();
 
     let shape = tensor.shape();
 
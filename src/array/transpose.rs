@@ -1,34 +1,20 @@
-use crate::tensor::{Type, Tensor, unsafe_init};
+use crate::tensor::{Type, Tensor};
 
+///
+/// Transposes the last two axes as a view that shares the tensor's data.
+/// A vector [n] becomes a column [n, 1].
+///
 pub fn transpose<T: Type + Clone>(tensor: impl Into<Tensor<T>>) -> Tensor<T> {
     let tensor: Tensor<T> = tensor.into();
 
-    let cols = tensor.cols().max(1);
-    let rows = tensor.rows().max(1);
-    let size = tensor.shape().size();
-    let n_inner = cols * rows;
-    let batch = size / n_inner;
-
-    let mut shape = tensor.shape().clone();
-    if shape.rank() == 1 {
-        shape = shape.rinsert(0, 1);
-    } else {
-        shape = shape.with_cols(rows).with_rows(cols);
-    }
+    match tensor.rank() {
+        0 => tensor,
+        1 => {
+            let len = tensor.size();
 
-    unsafe {
-        unsafe_init::<T>(size, shape, |o| {
-            let x = tensor.as_slice();
-
-            for n in 0..batch {
-                for j in 0..rows {
-                    for i in 0..cols {
-                        o.add(n * n_inner + i * rows + j)
-                            .write(x[n * n_inner + j * cols + i].clone());
-                    }
-                }
-            }
-        })
+            tensor.reshape([len, 1])
+        }
+        _ => tensor.swapaxes(-2, -1),
     }
 }
 
@@ -60,4 +46,22 @@ mod test {
             ten![[1., 3., 5.], [2., 4., 6.]],
         );
     }
+
+    #[test]
+    fn transpose_is_view() {
+        let a = ten![[1., 2., 3.], [4., 5., 6.]];
+        let t = a.t();
+
+        assert!(! t.is_contiguous());
+        assert_eq!(t.shape().as_vec(), vec![3, 2]);
+        assert_eq!(t.strides(), vec![1, 3]);
+        assert_eq!(t, ten![[1., 4.], [2., 5.], [3., 6.]]);
+        assert_eq!(t.t(), a);
+        assert!(t.t().is_contiguous());
+
+        assert_eq!(
+            transpose(ten![[[1, 2], [3, 4]], [[5, 6], [7, 8]]]),
+            ten![[[1, 3], [2, 4]], [[5, 7], [6, 8]]],
+        );
+    }
 }
@@ -0,0 +1,4 @@
+mod ops;
+mod tape;
+
+pub use tape::{Grads, Tape, Var};
@@ -0,0 +1,538 @@
+use std::{f32::consts::{LN_10, LN_2}, ops};
+
+use crate::{
+    linalg::Transpose,
+    tensor::{Axis, Shape, Tensor},
+};
+
+use super::Var;
+
+macro_rules! unary {
+    ($id:ident, |$x:ident, $y:ident| $df:expr) => {
+        #[inline]
+        pub fn $id(&self) -> Var {
+            self.unary(self.value().$id(), |$x, $y| $df)
+        }
+    }
+}
+
+impl Var {
+    ///
+    /// Records `f` over each item, where `df` is its derivative.
+    ///
+    pub fn map(
+        &self,
+        mut f: impl FnMut(f32) -> f32,
+        df: impl Fn(f32) -> f32 + 'static
+    ) -> Var {
+        self.unary(self.value().map(|x| f(*x)), move |x, _| df(x))
+    }
+
+    ///
+    /// Records `f` over broadcast item pairs, where `df` returns the partial
+    /// derivatives with respect to each argument.
+    ///
+    pub fn map2(
+        &self,
+        rhs: &Var,
+        mut f: impl FnMut(f32, f32) -> f32,
+        df: impl Fn(f32, f32) -> (f32, f32) + 'static
+    ) -> Var {
+        let value = self.value().map2(rhs.value(), |a, b| f(*a, *b));
+
+        self.binary(rhs, value, df)
+    }
+
+    // Elementwise op where df(x, y) is the derivative from the argument
+    // and result items
+    fn unary(&self, value: Tensor, df: impl Fn(f32, f32) -> f32 + 'static) -> Var {
+        let x = self.value().clone();
+        let y = value.clone();
+
+        self.unary_op(value, move |g| {
+            g * x.map2(&y, |x, y| df(*x, *y))
+        })
+    }
+
+    fn binary(
+        &self,
+        rhs: &Var,
+        value: Tensor,
+        df: impl Fn(f32, f32) -> (f32, f32) + 'static
+    ) -> Var {
+        let a = self.value().clone();
+        let b = rhs.value().clone();
+
+        self.binary_op(rhs, value, move |g| {
+            let d = a.map2(&b, |a, b| df(*a, *b));
+
+            (
+                sum_to(&(g * d.map(|d| d.0)), a.shape()),
+                sum_to(&(g * d.map(|d| d.1)), b.shape()),
+            )
+        })
+    }
+
+    unary!(abs, |x, _y| x.signum());
+    unary!(square, |x, _y| 2. * x);
+    unary!(floor, |_x, _y| 0.);
+    unary!(ceil, |_x, _y| 0.);
+    unary!(round, |_x, _y| 0.);
+    unary!(trunc, |_x, _y| 0.);
+    unary!(fract, |_x, _y| 1.);
+    unary!(signum, |_x, _y| 0.);
+    unary!(recip, |_x, y| - y * y);
+
+    #[inline]
+    pub fn powi(&self, n: i32) -> Var {
+        let value = self.value().map(|x| x.powi(n));
+
+        self.unary(value, move |x, _| n as f32 * x.powi(n - 1))
+    }
+
+    #[inline]
+    pub fn powf(&self, rhs: &Var) -> Var {
+        self.map2(rhs, |a, b| a.powf(b), |a, b| {
+            (b * a.powf(b - 1.), a.powf(b) * a.ln())
+        })
+    }
+
+    unary!(sqrt, |_x, y| 0.5 / y);
+    unary!(exp, |_x, y| y);
+    unary!(exp2, |_x, y| y * LN_2);
+    unary!(ln, |x, _y| x.recip());
+    unary!(log2, |x, _y| (x * LN_2).recip());
+    unary!(log10, |x, _y| (x * LN_10).recip());
+    unary!(to_degrees, |_x, _y| 1f32.to_degrees());
+    unary!(to_radians, |_x, _y| 1f32.to_radians());
+    unary!(cbrt, |_x, y| (3. * y * y).recip());
+
+    #[inline]
+    pub fn hypot(&self, rhs: &Var) -> Var {
+        self.map2(rhs, |a, b| a.hypot(b), |a, b| {
+            let h = a.hypot(b);
+
+            (a / h, b / h)
+        })
+    }
+
+    unary!(sin, |x, _y| x.cos());
+    unary!(asin, |x, _y| (1. - x * x).sqrt().recip());
+    unary!(sinh, |x, _y| x.cosh());
+    unary!(asinh, |x, _y| (x * x + 1.).sqrt().recip());
+    unary!(cos, |x, _y| - x.sin());
+    unary!(acos, |x, _y| - (1. - x * x).sqrt().recip());
+    unary!(cosh, |x, _y| x.sinh());
+    unary!(acosh, |x, _y| (x * x - 1.).sqrt().recip());
+    unary!(tan, |_x, y| 1. + y * y);
+    unary!(atan, |x, _y| (1. + x * x).recip());
+    unary!(tanh, |_x, y| 1. - y * y);
+    unary!(atanh, |x, _y| (1. - x * x).recip());
+
+    #[inline]
+    pub fn atan2(&self, rhs: &Var) -> Var {
+        self.map2(rhs, |a, b| a.atan2(b), |a, b| {
+            let r2 = a * a + b * b;
+
+            (b / r2, - a / r2)
+        })
+    }
+
+    unary!(exp_m1, |_x, y| y + 1.);
+    unary!(ln_1p, |x, _y| (1. + x).recip());
+
+    pub fn reduce_sum(&self) -> Var {
+        let shape = self.shape().clone();
+
+        self.unary_op(self.value().reduce_sum(), move |g| {
+            Tensor::fill(shape.clone(), g[0])
+        })
+    }
+
+    pub fn reduce_sum_axis(&self, axis: impl Into<Axis>) -> Var {
+        let axis = axis.into();
+
+        let shape = self.shape().clone();
+        let (_, _, len, inner) = axis.reduce(&shape);
+
+        self.unary_op(self.value().reduce_sum_axis(axis), move |g| {
+            let g = g.to_contiguous();
+            let g = g.as_slice();
+
+            let mut vec = Vec::with_capacity(shape.size());
+
+            for outer in g.chunks(inner) {
+                for _ in 0..len {
+                    vec.extend_from_slice(outer);
+                }
+            }
+
+            Tensor::from_vec(vec, shape.clone())
+        })
+    }
+
+    pub fn reduce_mean(&self) -> Var {
+        let n = self.value().size() as f32;
+
+        self.reduce_sum() / n
+    }
+
+    pub fn reduce_mean_axis(&self, axis: impl Into<Axis>) -> Var {
+        let axis = axis.into();
+        let (_, _, len, _) = axis.reduce(self.shape());
+
+        self.reduce_sum_axis(axis) / len as f32
+    }
+
+    pub fn matmul(&self, rhs: &Var) -> Var {
+        let a = self.value().clone();
+        let b = rhs.value().clone();
+
+        self.binary_op(rhs, a.matmul(&b), move |g| {
+            (
+                sum_to(&g.matmul_t(&b, Transpose::TransposeB), a.shape()),
+                sum_to(&a.matmul_t(g, Transpose::TransposeA), b.shape()),
+            )
+        })
+    }
+
+    pub fn reshape(&self, shape: impl Into<Shape>) -> Var {
+        let x_shape = self.shape().clone();
+        let value = self.value().to_contiguous().reshape(shape);
+
+        self.unary_op(value, move |g| g.to_contiguous().reshape(x_shape.clone()))
+    }
+}
+
+///
+/// Sums a broadcast gradient back to the argument's shape, over the extra
+/// leading dims and any dims of size 1.
+///
+fn sum_to(grad: &Tensor, shape: &Shape) -> Tensor {
+    if grad.shape() == shape {
+        return grad.clone();
+    }
+
+    let grad_shape = grad.shape().clone();
+    let offset = grad_shape.rank() - shape.rank();
+
+    let grad = grad.to_contiguous();
+
+    let mut vec = vec![0f32; shape.size()];
+    let mut index = vec![0; grad_shape.rank()];
+
+    for value in grad.as_slice() {
+        let mut i = 0;
+
+        for j in 0..shape.rank() {
+            let dim = shape.dim(j);
+
+            i = i * dim + if dim == 1 { 0 } else { index[j + offset] };
+        }
+
+        vec[i] += value;
+
+        for j in (0..index.len()).rev() {
+            index[j] += 1;
+
+            if index[j] < grad_shape.dim(j) {
+                break;
+            }
+
+            index[j] = 0;
+        }
+    }
+
+    Tensor::from_vec(vec, shape.clone())
+}
+
+//
+// Arithmetic: Var with Var, and Var with f32 on either side
+//
+
+macro_rules! var_ops {
+    ($op:ident, $fun:ident, |$a:ident, $b:ident| $da:expr, $db:expr) => {
+        impl ops::$op<&Var> for &Var {
+            type Output = Var;
+
+            fn $fun(self, rhs: &Var) -> Var {
+                self.binary(rhs, ops::$op::$fun(self.value(), rhs.value()), |$a, $b| ($da, $db))
+            }
+        }
+
+        impl ops::$op<Var> for &Var {
+            type Output = Var;
+
+            fn $fun(self, rhs: Var) -> Var {
+                ops::$op::$fun(self, &rhs)
+            }
+        }
+
+        impl ops::$op<&Var> for Var {
+            type Output = Var;
+
+            fn $fun(self, rhs: &Var) -> Var {
+                ops::$op::$fun(&self, rhs)
+            }
+        }
+
+        impl ops::$op<Var> for Var {
+            type Output = Var;
+
+            fn $fun(self, rhs: Var) -> Var {
+                ops::$op::$fun(&self, &rhs)
+            }
+        }
+
+        impl ops::$op<f32> for &Var {
+            type Output = Var;
+
+            fn $fun(self, $b: f32) -> Var {
+                self.unary(ops::$op::$fun(self.value(), $b), move |$a, _| $da)
+            }
+        }
+
+        impl ops::$op<f32> for Var {
+            type Output = Var;
+
+            fn $fun(self, rhs: f32) -> Var {
+                ops::$op::$fun(&self, rhs)
+            }
+        }
+
+        impl ops::$op<&Var> for f32 {
+            type Output = Var;
+
+            fn $fun(self, rhs: &Var) -> Var {
+                let $a = self;
+
+                rhs.unary(ops::$op::$fun($a, rhs.value()), move |$b, _| $db)
+            }
+        }
+
+        impl ops::$op<Var> for f32 {
+            type Output = Var;
+
+            fn $fun(self, rhs: Var) -> Var {
+                ops::$op::$fun(self, &rhs)
+            }
+        }
+    }
+}
+
+#[allow(unused_variables)]
+mod arith {
+    use super::*;
+
+    var_ops!(Add, add, |a, b| 1., 1.);
+    var_ops!(Sub, sub, |a, b| 1., -1.);
+    var_ops!(Mul, mul, |a, b| b, a);
+    var_ops!(Div, div, |a, b| b.recip(), - a / (b * b));
+}
+
+impl ops::Neg for &Var {
+    type Output = Var;
+
+    fn neg(self) -> Var {
+        self.unary_op(- self.value(), |g| - g)
+    }
+}
+
+impl ops::Neg for Var {
+    type Output = Var;
+
+    fn neg(self) -> Var {
+        - &self
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{autodiff::{Tape, Var}, ten, tensor::Tensor, test::assert_close};
+
+    // central differences of the summed result for each item of x
+    fn check_grad(x: Tensor, f: impl Fn(&Var) -> Var) {
+        let tape = Tape::new();
+        let var = tape.var(x.clone());
+        let grads = f(&var).reduce_sum().backward();
+
+        let eps = 1e-3;
+        let expected = Tensor::from_vec(
+            (0..x.size()).map(|i| {
+                let shift = |d: f32| {
+                    let vec: Vec<f32> = x.iter().enumerate()
+                        .map(|(j, v)| if i == j { v + d } else { *v })
+                        .collect();
+
+                    let tape = Tape::new();
+                    let var = tape.var(Tensor::from_vec(vec, x.shape().clone()));
+                    f(&var).reduce_sum().value()[0]
+                };
+
+                (shift(eps) - shift(-eps)) / (2. * eps)
+            }).collect(),
+            x.shape().clone(),
+        );
+
+        // f32 differences lose precision with the size of the gradient
+        let scale = expected.iter().fold(1f32, |a, v| a.max(v.abs()));
+        assert_close(&grads.grad(&var), &expected, 1e-3 * scale as f64);
+    }
+
+    #[test]
+    fn autodiff_arith() {
+        let tape = Tape::new();
+        let x = tape.var(ten![1., 2., 3.]);
+        let y = tape.var(ten![4., 5., 6.]);
+
+        let z = (&x * &y + &x / 2. - 1. / &y).reduce_sum();
+        assert_eq!(z.value().shape().as_vec(), Vec::<usize>::new());
+
+        let grads = z.backward();
+        assert_close(&grads.grad(&x), &ten![4.5, 5.5, 6.5], 1e-3);
+        assert_close(&grads.grad(&y), &ten![1. + 1. / 16., 2. + 1. / 25., 3. + 1. / 36.], 1e-3);
+
+        // x used twice accumulates both paths
+        let grads = (&x * &x - &x).reduce_sum().backward();
+        assert_eq!(grads.grad(&x), ten![1., 3., 5.]);
+
+        let grads = (- &x).reduce_sum().backward();
+        assert_eq!(grads.grad(&x), ten![-1., -1., -1.]);
+    }
+
+    #[test]
+    fn autodiff_unused_leaf() {
+        let tape = Tape::new();
+        let x = tape.var(ten![1., 2.]);
+        let y = tape.var(ten![[3., 4.]]);
+
+        let grads = x.exp().reduce_sum().backward();
+
+        assert!(grads.get(&y).is_none());
+        assert_eq!(grads.grad(&y), ten![[0., 0.]]);
+    }
+
+    #[test]
+    fn autodiff_broadcast() {
+        let tape = Tape::new();
+        let x = tape.var(ten![[1., 2.], [3., 4.], [5., 6.]]);
+        let b = tape.var(ten![10., 20.]);
+
+        let grads = (&x * &b).reduce_sum().backward();
+
+        assert_eq!(grads.grad(&x), ten![[10., 20.], [10., 20.], [10., 20.]]);
+        assert_eq!(grads.grad(&b), ten![9., 12.]);
+    }
+
+    #[test]
+    fn autodiff_float() {
+        let x = ten![[0.3, 0.5], [0.7, 0.9]];
+
+        check_grad(x.clone(), |x| x.sqrt());
+        check_grad(x.clone(), |x| x.exp());
+        check_grad(x.clone(), |x| x.exp2());
+        check_grad(x.clone(), |x| x.ln());
+        check_grad(x.clone(), |x| x.log2());
+        check_grad(x.clone(), |x| x.log10());
+        check_grad(x.clone(), |x| x.recip());
+        check_grad(x.clone(), |x| x.cbrt());
+        check_grad(x.clone(), |x| x.sin());
+        check_grad(x.clone(), |x| x.cos());
+        check_grad(x.clone(), |x| x.tan());
+        check_grad(x.clone(), |x| x.asin());
+        check_grad(x.clone(), |x| x.acos());
+        check_grad(x.clone(), |x| x.atan());
+        check_grad(x.clone(), |x| x.sinh());
+        check_grad(x.clone(), |x| x.cosh());
+        check_grad(x.clone(), |x| x.tanh());
+        check_grad(x.clone(), |x| x.asinh());
+        check_grad(x.clone(), |x| (x + 1.).acosh());
+        check_grad(x.clone(), |x| x.atanh());
+        check_grad(x.clone(), |x| x.exp_m1());
+        check_grad(x.clone(), |x| x.ln_1p());
+        check_grad(x.clone(), |x| x.square());
+        check_grad(x.clone(), |x| x.powi(3));
+        check_grad(x.clone(), |x| x.powf(&x.exp()));
+        check_grad(x.clone(), |x| x.hypot(&x.square()));
+        check_grad(x.clone(), |x| x.atan2(&(x + 1.)));
+        check_grad(x.clone(), |x| x.map(|v| v * v * v, |v| 3. * v * v));
+        check_grad(x.clone(), |x| x.map2(&x.sin(), |a, b| a * b, |a, b| (b, a)));
+        check_grad(x, |x| (2. - x) / (x + 1.));
+    }
+
+    #[test]
+    fn autodiff_reduce() {
+        let x = ten![[[0.1, 0.2, 0.3], [0.4, 0.5, 0.6]], [[0.7, 0.8, 0.9], [1.0, 1.1, 1.2]]];
+
+        check_grad(x.clone(), |x| x.reduce_sum_axis(0).square());
+        check_grad(x.clone(), |x| x.reduce_sum_axis(1).square());
+        check_grad(x.clone(), |x| x.reduce_sum_axis(-1).square());
+        check_grad(x.clone(), |x| x.reduce_mean_axis(1).square());
+        check_grad(x.clone(), |x| x.reduce_mean().square());
+        check_grad(ten![1., 2., 3.], |x| x.reduce_sum_axis(0).square());
+
+        let tape = Tape::new();
+        let v = tape.var(x);
+        let grads = v.reduce_sum_axis(1).backward_with(ten![[1., 2., 3.], [4., 5., 6.]]);
+
+        assert_eq!(
+            grads.grad(&v),
+            ten![[[1., 2., 3.], [1., 2., 3.]], [[4., 5., 6.], [4., 5., 6.]]]
+        );
+    }
+
+    #[test]
+    fn autodiff_matmul() {
+        let tape = Tape::new();
+        let a = tape.var(ten![[1., 2.], [3., 4.], [5., 6.]]);
+        let b = tape.var(ten![[1., 0., 2.], [0., 1., 3.]]);
+
+        let grads = a.matmul(&b).reduce_sum().backward();
+
+        assert_eq!(grads.grad(&a), ten![[3., 4.], [3., 4.], [3., 4.]]);
+        assert_eq!(grads.grad(&b), ten![[9., 9., 9.], [12., 12., 12.]]);
+
+        check_grad(ten![[0.5, -1.], [2., 0.25]], |a| a.matmul(&a.exp()).square());
+
+        // broadcast batch
+        let w = ten![[1., 2.], [3., 4.]];
+        check_grad(ten![[[1., 0.], [2., 1.]], [[0., 3.], [1., 1.]]], |x| {
+            x.matmul(&x.tape().var(w.clone())).square()
+        });
+        check_grad(w.clone(), |w| {
+            w.tape().var(ten![[[1., 0.], [2., 1.]], [[0., 3.], [1., 1.]]]).matmul(w).square()
+        });
+    }
+
+    #[test]
+    fn autodiff_reshape() {
+        check_grad(ten![[1., 2., 3.], [4., 5., 6.]], |x| {
+            x.reshape([3, 2]).matmul(&x).square()
+        });
+    }
+
+    #[test]
+    fn autodiff_linear_regression() {
+        let x = ten![[0.], [1.], [2.], [3.]];
+        let y = ten![[1.], [3.], [5.], [7.]];
+
+        let mut w = ten![[0.]];
+        let mut b = ten![0.];
+
+        for _ in 0..500 {
+            let tape = Tape::new();
+            let w_var = tape.var(w.clone());
+            let b_var = tape.var(b.clone());
+
+            let pred = tape.var(x.clone()).matmul(&w_var) + &b_var;
+            let loss = (pred - tape.var(y.clone())).square().reduce_mean();
+
+            let grads = loss.backward();
+            w = &w - grads.grad(&w_var) * 0.05;
+            b = &b - grads.grad(&b_var) * 0.05;
+        }
+
+        assert_close(&w, &ten![[2.]], 1e-3);
+        assert_close(&b, &ten![1.], 1e-3);
+    }
+}
@@ -0,0 +1,197 @@
+use std::{cell::RefCell, fmt, rc::Rc};
+
+use crate::tensor::{Shape, Tensor};
+
+///
+/// Records the operations on its variables, so `Var::backward` can
+/// propagate gradients in reverse from any recorded result.
+///
+#[derive(Clone, Default)]
+pub struct Tape {
+    nodes: Rc<RefCell<Vec<Node>>>,
+}
+
+// Maps the output gradient to the gradient of each argument node
+type Backward = Box<dyn Fn(&Tensor) -> Vec<(usize, Tensor)>>;
+
+struct Node {
+    backward: Option<Backward>,
+}
+
+impl Tape {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    ///
+    /// Creates a leaf variable, whose gradient is reported by `backward`.
+    ///
+    pub fn var(&self, value: impl Into<Tensor>) -> Var {
+        self.push(value.into(), None)
+    }
+
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.nodes.borrow().len()
+    }
+
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.nodes.borrow().is_empty()
+    }
+
+    pub(super) fn push(&self, value: Tensor, backward: Option<Backward>) -> Var {
+        let mut nodes = self.nodes.borrow_mut();
+
+        let id = nodes.len();
+        nodes.push(Node { backward });
+
+        Var { tape: self.clone(), id, value }
+    }
+
+    fn is_same(&self, other: &Tape) -> bool {
+        Rc::ptr_eq(&self.nodes, &other.nodes)
+    }
+}
+
+impl fmt::Debug for Tape {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Tape").field("len", &self.len()).finish()
+    }
+}
+
+///
+/// A tensor value recorded on a `Tape`.
+///
+#[derive(Clone)]
+pub struct Var {
+    tape: Tape,
+    id: usize,
+    value: Tensor,
+}
+
+impl Var {
+    #[inline]
+    pub fn value(&self) -> &Tensor {
+        &self.value
+    }
+
+    #[inline]
+    pub fn shape(&self) -> &Shape {
+        self.value.shape()
+    }
+
+    #[inline]
+    pub fn tape(&self) -> &Tape {
+        &self.tape
+    }
+
+    ///
+    /// Records a result of this variable, where `backward` maps the
+    /// result's gradient to this variable's gradient.
+    ///
+    pub(super) fn unary_op(
+        &self,
+        value: Tensor,
+        backward: impl Fn(&Tensor) -> Tensor + 'static
+    ) -> Var {
+        let id = self.id;
+
+        self.tape.push(value, Some(Box::new(move |g| vec![(id, backward(g))])))
+    }
+
+    pub(super) fn binary_op(
+        &self,
+        rhs: &Var,
+        value: Tensor,
+        backward: impl Fn(&Tensor) -> (Tensor, Tensor) + 'static
+    ) -> Var {
+        assert!(self.tape.is_same(&rhs.tape), "autodiff variables must share a tape");
+
+        let (a, b) = (self.id, rhs.id);
+
+        self.tape.push(value, Some(Box::new(move |g| {
+            let (g_a, g_b) = backward(g);
+
+            vec![(a, g_a), (b, g_b)]
+        })))
+    }
+
+    ///
+    /// Propagates gradients from this variable, seeded with ones.
+    ///
+    pub fn backward(&self) -> Grads {
+        self.backward_with(Tensor::ones(self.shape().clone()))
+    }
+
+    ///
+    /// Propagates gradients from this variable, seeded with the gradient
+    /// of some later value with respect to it.
+    ///
+    pub fn backward_with(&self, grad: impl Into<Tensor>) -> Grads {
+        let grad = grad.into();
+
+        assert_eq!(
+            grad.shape(), self.shape(),
+            "backward gradient must match the variable's shape"
+        );
+
+        let nodes = self.tape.nodes.borrow();
+
+        let mut grads: Vec<Option<Tensor>> = vec![None; self.id + 1];
+        grads[self.id] = Some(grad);
+
+        for id in (0..=self.id).rev() {
+            let (Some(backward), Some(grad)) = (&nodes[id].backward, &grads[id]) else {
+                continue;
+            };
+
+            for (arg, arg_grad) in backward(grad) {
+                grads[arg] = Some(match grads[arg].take() {
+                    Some(prev) => prev + arg_grad,
+                    None => arg_grad,
+                });
+            }
+
+            // only leaf gradients are reported
+            grads[id] = None;
+        }
+
+        Grads { tape: self.tape.clone(), grads }
+    }
+}
+
+impl fmt::Debug for Var {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Var").field("id", &self.id).field("value", &self.value).finish()
+    }
+}
+
+///
+/// Leaf gradients from `Var::backward`.
+///
+pub struct Grads {
+    tape: Tape,
+    grads: Vec<Option<Tensor>>,
+}
+
+impl Grads {
+    ///
+    /// The gradient of the leaf, or None if the result doesn't depend on it.
+    ///
+    pub fn get(&self, var: &Var) -> Option<&Tensor> {
+        assert!(self.tape.is_same(&var.tape), "autodiff variables must share a tape");
+
+        self.grads.get(var.id).and_then(|grad| grad.as_ref())
+    }
+
+    ///
+    /// The gradient of the leaf, with zeros if the result doesn't depend on it.
+    ///
+    pub fn grad(&self, var: &Var) -> Tensor {
+        match self.get(var) {
+            Some(grad) => grad.clone(),
+            None => Tensor::zeros(var.shape().clone()),
+        }
+    }
+}
@@ -105,6 +105,7 @@ where
 
     let size = tensor.shape().size();
     let shape = tensor.shape().clone();
+    let tensor = tensor.to_contiguous();
 
     unsafe {
         unsafe_init::<T>(size, shape, |o| {
@@ -136,6 +137,7 @@ where
 
     let shape = tensor.shape().clone();
     let size = tensor.shape().size();
+    let tensor = tensor.to_contiguous();
 
     unsafe {
         unsafe_init::<T>(size, shape, |o| {
@@ -231,6 +233,8 @@ where
         let n = size / len;
         assert!(n % k_s == 0);
 
+        let x = x.to_contiguous();
+
         unsafe_init::<T>(size, shape, |o| {
             let x = x.as_slice();
 
@@ -0,0 +1,474 @@
+use std::fs;
+
+use essay_opt::derive_opt;
+
+use crate::tensor::{Tensor, TensorError, Type};
+
+#[derive_opt(CsvOpt)]
+#[derive(Default)]
+pub struct CsvArg {
+    /// Field delimiter, defaulting to ','
+    delimiter: Option<char>,
+    /// First row is a header of column names
+    header: bool,
+    /// Selected column indices, in order
+    columns: Option<Vec<usize>>,
+    /// Selected column names from the header, or the header written
+    /// by `write_csv`
+    column_names: Option<Vec<String>>,
+}
+
+///
+/// Field types of a CSV tensor. Missing fields, which are empty or NA,
+/// parse as NaN for floats and None for `Option`. Numbers ignore the
+/// whitespace around them, while strings keep it.
+///
+pub trait CsvType: Type + Clone {
+    fn parse_field(field: &str) -> Option<Self>;
+
+    fn format_field(&self) -> String;
+}
+
+fn is_missing(field: &str) -> bool {
+    matches!(field, "" | "NA" | "N/A" | "NaN" | "nan" | "null")
+}
+
+macro_rules! csv_floats {
+    ($($ty:ty)*) => {
+        $(
+            impl CsvType for $ty {
+                fn parse_field(field: &str) -> Option<Self> {
+                    let field = field.trim();
+
+                    if is_missing(field) {
+                        Some(<$ty>::NAN)
+                    } else {
+                        field.parse::<$ty>().ok()
+                    }
+                }
+
+                fn format_field(&self) -> String {
+                    self.to_string()
+                }
+            }
+        )*
+    }
+}
+
+macro_rules! csv_ints {
+    ($($ty:ty)*) => {
+        $(
+            impl CsvType for $ty {
+                fn parse_field(field: &str) -> Option<Self> {
+                    field.trim().parse::<$ty>().ok()
+                }
+
+                fn format_field(&self) -> String {
+                    self.to_string()
+                }
+            }
+        )*
+    }
+}
+
+csv_floats!(f32 f64);
+csv_ints!(i8 i16 i32 i64 isize u8 u16 u32 u64 usize bool);
+
+impl CsvType for String {
+    fn parse_field(field: &str) -> Option<Self> {
+        Some(field.to_string())
+    }
+
+    fn format_field(&self) -> String {
+        self.clone()
+    }
+}
+
+impl<T: CsvType> CsvType for Option<T> {
+    fn parse_field(field: &str) -> Option<Self> {
+        if is_missing(field.trim()) {
+            Some(None)
+        } else {
+            T::parse_field(field).map(Some)
+        }
+    }
+
+    fn format_field(&self) -> String {
+        match self {
+            Some(value) => value.format_field(),
+            None => String::new(),
+        }
+    }
+}
+
+///
+/// Reads the selected columns of a CSV file as a [rows, columns] tensor.
+///
+pub fn read_csv<T: CsvType>(
+    filename: impl Into<Tensor<String>>,
+    opt: impl CsvOpt
+) -> Result<Tensor<T>, TensorError> {
+    let text = fs::read_to_string(&filename.into()[0])
+        .map_err(|err| TensorError::Io { op: "read_csv", reason: err.to_string() })?;
+
+    decode_csv(&text, opt)
+}
+
+///
+/// Parses CSV text as a [rows, columns] tensor.
+///
+pub fn decode_csv<T: CsvType>(text: &str, opt: impl CsvOpt) -> Result<Tensor<T>, TensorError> {
+    let opt = opt.into_arg();
+    let delimiter = opt.delimiter.unwrap_or(',');
+
+    let mut records = parse_records(text, delimiter)?;
+
+    let header = if opt.header && ! records.is_empty() {
+        Some(records.remove(0))
+    } else {
+        None
+    };
+
+    let n_fields = match (&header, records.first()) {
+        (Some(header), _) => header.len(),
+        (None, Some(first)) => first.len(),
+        (None, None) => 0,
+    };
+
+    let columns: Vec<usize> = match (&opt.columns, &opt.column_names) {
+        (Some(_), Some(_)) => {
+            return Err(invalid_argument("columns and column_names are exclusive".to_string()));
+        }
+        (Some(columns), None) => columns.clone(),
+        (None, Some(names)) => {
+            let header = header.as_ref()
+                .ok_or_else(|| invalid_argument("column_names requires a header".to_string()))?;
+
+            names.iter().map(|name| {
+                header.iter().position(|h| h == name)
+                    .ok_or_else(|| invalid_argument(format!("no column named {:?}", name)))
+            }).collect::<Result<Vec<usize>, TensorError>>()?
+        }
+        (None, None) => (0..n_fields).collect(),
+    };
+
+    if let Some(column) = columns.iter().find(|c| **c >= n_fields) {
+        return Err(invalid_argument(format!(
+            "column {} is out of range for {} columns", column, n_fields
+        )));
+    }
+
+    let mut items = Vec::<T>::with_capacity(records.len() * columns.len());
+
+    for (i, record) in records.iter().enumerate() {
+        // rows are numbered from 1 as in a text editor, including the header
+        let row = i + 1 + header.is_some() as usize;
+
+        if record.len() != n_fields {
+            return Err(decode_error(format!(
+                "row {} has {} fields, expected {}", row, record.len(), n_fields
+            )));
+        }
+
+        for column in &columns {
+            let field = &record[*column];
+
+            let value = T::parse_field(field).ok_or_else(|| decode_error(format!(
+                "invalid field {:?} in row {} column {}", field, row, column
+            )))?;
+
+            items.push(value);
+        }
+    }
+
+    Ok(Tensor::from_vec(items, [records.len(), columns.len()]))
+}
+
+///
+/// Writes a rank-1 tensor as a single column or a rank-2 tensor as rows,
+/// with `column_names` as an optional header row.
+///
+pub fn write_csv<T: CsvType>(
+    filename: impl Into<Tensor<String>>,
+    tensor: &Tensor<T>,
+    opt: impl CsvOpt
+) -> Result<(), TensorError> {
+    let text = encode_csv(tensor, opt)?;
+
+    fs::write(&filename.into()[0], text)
+        .map_err(|err| TensorError::Io { op: "write_csv", reason: err.to_string() })
+}
+
+pub fn encode_csv<T: CsvType>(tensor: &Tensor<T>, opt: impl CsvOpt) -> Result<String, TensorError> {
+    let opt = opt.into_arg();
+    let delimiter = opt.delimiter.unwrap_or(',');
+
+    let cols = match tensor.rank() {
+        1 => 1,
+        2 => tensor.cols(),
+        _ => return Err(TensorError::InvalidShape {
+            op: "write_csv",
+            shape: tensor.shape().as_vec(),
+            reason: "requires a rank-1 or rank-2 tensor",
+        }),
+    };
+
+    let mut text = String::new();
+
+    if let Some(names) = &opt.column_names {
+        if names.len() != cols {
+            return Err(TensorError::InvalidArgument {
+                op: "write_csv",
+                reason: format!("{} column names for {} columns", names.len(), cols),
+            });
+        }
+
+        write_record(&mut text, names.iter().cloned(), delimiter);
+    }
+
+    let tensor = tensor.to_contiguous();
+
+    for row in tensor.as_slice().chunks(cols.max(1)) {
+        write_record(&mut text, row.iter().map(|v| v.format_field()), delimiter);
+    }
+
+    Ok(text)
+}
+
+fn write_record(text: &mut String, fields: impl Iterator<Item = String>, delimiter: char) {
+    let fields: Vec<String> = fields.collect();
+
+    // a lone blank field is quoted, because blank lines are skipped
+    let is_blank_line = fields.len() == 1 && fields[0].trim().is_empty();
+
+    for (i, field) in fields.into_iter().enumerate() {
+        if i > 0 {
+            text.push(delimiter);
+        }
+
+        if is_blank_line || field.contains([delimiter, '"', '\n', '\r']) {
+            text.push('"');
+            text.push_str(&field.replace('"', "\"\""));
+            text.push('"');
+        } else {
+            text.push_str(&field);
+        }
+    }
+
+    text.push('\n');
+}
+
+// Splits the text into records of fields, with RFC 4180 quoting. Blank
+// lines are skipped.
+fn parse_records(text: &str, delimiter: char) -> Result<Vec<Vec<String>>, TensorError> {
+    let mut records = Vec::<Vec<String>>::new();
+    let mut record = Vec::<String>::new();
+    let mut field = String::new();
+
+    let mut is_quoted = false;
+    let mut is_blank = true;
+
+    let mut chars = text.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        if is_quoted {
+            match c {
+                '"' if chars.peek() == Some(&'"') => {
+                    chars.next();
+                    field.push('"');
+                }
+                '"' => is_quoted = false,
+                c => field.push(c),
+            }
+
+            continue;
+        }
+
+        match c {
+            '"' => {
+                is_quoted = true;
+                is_blank = false;
+            }
+            '\r' if chars.peek() == Some(&'\n') => {}
+            '\n' | '\r' => {
+                if ! is_blank {
+                    record.push(std::mem::take(&mut field));
+                    records.push(std::mem::take(&mut record));
+                }
+
+                field.clear();
+                is_blank = true;
+            }
+            c if c == delimiter => {
+                record.push(std::mem::take(&mut field));
+                is_blank = false;
+            }
+            c => {
+                field.push(c);
+                is_blank &= c.is_whitespace();
+            }
+        }
+    }
+
+    if is_quoted {
+        return Err(decode_error("unterminated quoted field".to_string()));
+    }
+
+    if ! is_blank {
+        record.push(field);
+        records.push(record);
+    }
+
+    Ok(records)
+}
+
+fn decode_error(reason: String) -> TensorError {
+    TensorError::Decode { op: "read_csv", reason }
+}
+
+fn invalid_argument(reason: String) -> TensorError {
+    TensorError::InvalidArgument { op: "read_csv", reason }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        io::{decode_csv, encode_csv, read_csv, write_csv, CsvOpt},
+        ten, tensor::{Tensor, TensorError},
+    };
+
+    #[test]
+    fn csv_numeric() {
+        let text = "1,2,3\n4,5.5,-6\n";
+
+        assert_eq!(decode_csv::<f32>(text, ()).unwrap(), ten![[1., 2., 3.], [4., 5.5, -6.]]);
+        assert_eq!(
+            decode_csv::<f64>("1 ; 2\r\n\r\n3;4", ().delimiter(';')).unwrap(),
+            ten![[1f64, 2.], [3., 4.]]
+        );
+        assert_eq!(decode_csv::<i64>("7\n8\n", ()).unwrap(), ten![[7i64], [8]]);
+        assert_eq!(decode_csv::<f32>("", ()).unwrap().shape().as_vec(), &[0, 0]);
+    }
+
+    #[test]
+    fn csv_missing() {
+        let text = "1,,3\nNA,5,nan\n";
+
+        let x = decode_csv::<f32>(text, ()).unwrap();
+        assert_eq!(x[(0, 0)], 1.);
+        assert!(x[(0, 1)].is_nan());
+        assert!(x[(1, 0)].is_nan());
+        assert!(x[(1, 2)].is_nan());
+
+        assert_eq!(
+            decode_csv::<Option<i32>>(text, ()).unwrap(),
+            Tensor::from_vec(vec![Some(1), None, Some(3), None, Some(5), None], [2, 3])
+        );
+
+        assert!(matches!(
+            decode_csv::<i32>(text, ()).unwrap_err(),
+            TensorError::Decode { op: "read_csv", .. }
+        ));
+
+        assert_eq!(
+            decode_csv::<Option<i32>>("1, NA\n", ()).unwrap(),
+            Tensor::from_vec(vec![Some(1), None], [1, 2])
+        );
+    }
+
+    #[test]
+    fn csv_header_and_columns() {
+        let text = "name,age,score\n\"Smith, J\",31,1.5\n\"O\"\"Neil\",42,2.5\n";
+
+        assert_eq!(
+            decode_csv::<f32>(text, ().header(true).column_names(vec!["score".into(), "age".into()])).unwrap(),
+            ten![[1.5, 31.], [2.5, 42.]]
+        );
+
+        assert_eq!(
+            decode_csv::<String>(text, ().header(true).columns(vec![0])).unwrap(),
+            Tensor::from_vec(vec!["Smith, J".to_string(), "O\"Neil".to_string()], [2, 1])
+        );
+
+        // strings keep their whitespace so they round trip
+        let names = Tensor::from_vec(vec![" a".to_string(), "b ".to_string()], [1, 2]);
+        assert_eq!(decode_csv::<String>(&encode_csv(&names, ()).unwrap(), ()).unwrap(), names);
+        assert_eq!(decode_csv::<i32>(" 1 ,2\t", ()).unwrap(), ten![[1, 2]]);
+
+        assert!(matches!(
+            decode_csv::<f32>(text, ().header(true).columns(vec![3])).unwrap_err(),
+            TensorError::InvalidArgument { op: "read_csv", .. }
+        ));
+
+        assert!(matches!(
+            decode_csv::<f32>(text, ().column_names(vec!["age".into()])).unwrap_err(),
+            TensorError::InvalidArgument { op: "read_csv", .. }
+        ));
+    }
+
+    #[test]
+    fn csv_errors() {
+        assert!(matches!(
+            decode_csv::<f32>("1,2\n3\n", ()).unwrap_err(),
+            TensorError::Decode { op: "read_csv", .. }
+        ));
+
+        assert!(matches!(
+            decode_csv::<String>("\"open\n", ()).unwrap_err(),
+            TensorError::Decode { op: "read_csv", .. }
+        ));
+
+        assert!(matches!(
+            read_csv::<f32>("/nonexistent/file.csv", ()).unwrap_err(),
+            TensorError::Io { op: "read_csv", .. }
+        ));
+    }
+
+    #[test]
+    fn csv_single_column_round_trip() {
+        // empty fields in a single column are quoted, not blank lines
+        let x = Tensor::from_vec(vec![Some(1.5), None, Some(2.)], [3]);
+        let text = encode_csv(&x, ()).unwrap();
+
+        assert_eq!(text, "1.5\n\"\"\n2\n");
+        assert_eq!(decode_csv::<Option<f64>>(&text, ()).unwrap(), x.reshape([3, 1]));
+
+        let x = Tensor::from_vec(vec!["a".to_string(), "".to_string(), " ".to_string()], [3, 1]);
+        assert_eq!(decode_csv::<String>(&encode_csv(&x, ()).unwrap(), ()).unwrap(), x);
+    }
+
+    #[test]
+    fn csv_write() {
+        assert_eq!(encode_csv(&ten![[1., 2.5], [-3., 4.]], ()).unwrap(), "1,2.5\n-3,4\n");
+        assert_eq!(encode_csv(&ten![1, 2, 3], ()).unwrap(), "1\n2\n3\n");
+
+        let names = Tensor::from_vec(vec!["a,b".to_string(), "say \"hi\"".to_string()], [1, 2]);
+        assert_eq!(
+            encode_csv(&names, ().delimiter(',').column_names(vec!["x".into(), "y".into()])).unwrap(),
+            "x,y\n\"a,b\",\"say \"\"hi\"\"\"\n"
+        );
+
+        assert_eq!(
+            encode_csv(&Tensor::from_vec(vec![Some(1.5), None], [1, 2]), ().delimiter('\t')).unwrap(),
+            "1.5\t\n"
+        );
+
+        assert!(matches!(
+            encode_csv(&Tensor::<f32>::zeros([1, 1, 1]), ()).unwrap_err(),
+            TensorError::InvalidShape { op: "write_csv", .. }
+        ));
+    }
+
+    #[test]
+    fn csv_file() {
+        let path = std::env::temp_dir().join(format!("essay-csv-{}.csv", std::process::id()));
+        let path = path.to_str().unwrap();
+
+        let x = ten![[1., 2.], [3., 4.]];
+        write_csv(path, &x, ().column_names(vec!["a".into(), "b".into()])).unwrap();
+
+        assert_eq!(read_csv::<f32>(path, ().header(true)).unwrap(), x);
+
+        std::fs::remove_file(path).unwrap();
+    }
+}
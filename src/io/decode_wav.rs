@@ -1,65 +1,173 @@
-use crate::tensor::Tensor;
+use crate::tensor::{Tensor, TensorError};
+
+use super::wav::{WavFormat, WAVE_FORMAT_EXTENSIBLE};
 
 pub fn decode_wav(
     contents: impl Into<Tensor<u8>>
 ) -> (Tensor<f32>, Tensor<usize>) {
-    let contents = contents.into();
+    try_decode_wav(contents).unwrap_or_else(|err| panic!("{}", err))
+}
+
+///
+/// Decodes a WAV file into [samples, channels] audio in [-1, 1] and the
+/// sample rate. Supports 8, 16, 24 and 32-bit PCM, 32 and 64-bit float
+/// and WAVE_FORMAT_EXTENSIBLE, skipping chunks other than fmt and data.
+///
+pub fn try_decode_wav(
+    contents: impl Into<Tensor<u8>>
+) -> Result<(Tensor<f32>, Tensor<usize>), TensorError> {
+    let contents = contents.into().to_contiguous();
 
     let mut cursor = Cursor::new(contents.as_slice());
 
-    assert_eq!(cursor.read_u32_big(), 0x5249_4646); // TIFF
+    expect("RIFF tag", cursor.read_u32_big()?, 0x5249_4646)?;
+
+    let riff_size = cursor.read_u32_little()?;
+    if riff_size as usize > contents.size() - 8 {
+        return Err(decode_error(format!(
+            "RIFF size {} exceeds the data size {}", riff_size, contents.size() - 8
+        )));
+    }
 
-    assert_eq!(cursor.read_u32_little(), (contents.as_slice().len() - 8) as u32);
-    assert_eq!(cursor.read_u32_big(), 0x5741_5645); // Wave
+    expect("WAVE tag", cursor.read_u32_big()?, 0x5741_5645)?;
 
-    assert_eq!(cursor.read_u32_big(), 0x666d_7420); // fmt
-    assert_eq!(cursor.read_u32_little(), 16);
+    let mut meta: Option<Meta> = None;
 
-    let meta = Meta {
-        fmt: cursor.read_u16_little(),
-        n_channels: cursor.read_u16_little(),
-        sample_rate: cursor.read_u32_little(),
-        _byte_rate: cursor.read_u32_little(),
-        _block_align: cursor.read_u16_little(),
-        bits_per_sample: cursor.read_u16_little(),
-    };
+    loop {
+        let tag = cursor.read_u32_big()?;
+        let size = cursor.read_u32_little()? as usize;
+
+        match tag {
+            FMT_TAG => {
+                meta = Some(read_fmt(&mut cursor, size)?);
+            }
+            DATA_TAG => {
+                let meta = meta.ok_or_else(|| {
+                    decode_error("data chunk before fmt chunk".to_string())
+                })?;
+
+                // streaming writers may leave the size unset
+                let size = size.min(cursor.remaining());
+
+                return Ok((
+                    read_samples(&meta, cursor.read_bytes(size)?),
+                    Tensor::from(meta.sample_rate as usize)
+                ));
+            }
+            _ => {
+                // chunks are padded to an even size
+                cursor.skip(size + size % 2)?;
+            }
+        }
+    }
+}
+
+const FMT_TAG: u32 = 0x666d_7420;
+const DATA_TAG: u32 = 0x6461_7461;
+
+fn read_fmt(cursor: &mut Cursor, size: usize) -> Result<Meta, TensorError> {
+    if size < 16 {
+        return Err(decode_error(format!("fmt size {} is less than 16", size)));
+    }
+
+    let start = cursor.index;
+
+    let mut tag = cursor.read_u16_little()?;
+    let n_channels = cursor.read_u16_little()?;
+    let sample_rate = cursor.read_u32_little()?;
+    let _byte_rate = cursor.read_u32_little()?;
+    let block_align = cursor.read_u16_little()?;
+    let bits_per_sample = cursor.read_u16_little()?;
+
+    if tag == WAVE_FORMAT_EXTENSIBLE {
+        if size < 40 {
+            return Err(decode_error(format!("extensible fmt size {} is less than 40", size)));
+        }
 
-    assert_eq!(meta.fmt, 1);
-    assert_eq!(meta.bits_per_sample, 16);
+        let _cb_size = cursor.read_u16_little()?;
+        let _valid_bits = cursor.read_u16_little()?;
+        let _channel_mask = cursor.read_u32_little()?;
 
-    //println!("Meta: {:?}", &meta);
+        // the sub-format GUID starts with the format tag
+        tag = cursor.read_u16_little()?;
+    }
 
-    assert_eq!(cursor.read_u32_big(), 0x6461_7461);
-    let size = cursor.read_u32_little();
+    cursor.skip(start + size + size % 2 - cursor.index)?;
 
-    let n_samples = size / 2;
-    //let len = meta.n_channels * 
+    let format = WavFormat::from_tag(tag, bits_per_sample).ok_or_else(|| {
+        decode_error(format!(
+            "unsupported format {:#x} with {} bits per sample", tag, bits_per_sample
+        ))
+    })?;
 
-    let mut data = Vec::new();
-    data.reserve_exact(n_samples as usize);
+    if n_channels == 0 {
+        return Err(decode_error("zero channels".to_string()));
+    }
 
-    for _ in 0..n_samples as usize {
-        let item = cursor.read_i16_little();
-        data.push((item as f32) / (0x8000 as f32));
+    let frame_size = n_channels as usize * format.bytes_per_sample();
+    if block_align as usize != frame_size {
+        return Err(decode_error(format!(
+            "block align {} doesn't match {} channels of {} bits", 
+            block_align, n_channels, bits_per_sample
+        )));
     }
 
-    (
-        Tensor::from_vec(data, [
-            n_samples as usize / meta.n_channels as usize, 
-            meta.n_channels as usize
-        ]), 
-        Tensor::from(meta.sample_rate as usize)
-    )
+    Ok(Meta { format, n_channels, sample_rate })
+}
+
+fn read_samples(meta: &Meta, data: &[u8]) -> Tensor<f32> {
+    let n_channels = meta.n_channels as usize;
+    let width = meta.format.bytes_per_sample();
+    let n_frames = data.len() / (width * n_channels);
+
+    let data = &data[..n_frames * n_channels * width];
+
+    let samples: Vec<f32> = match meta.format {
+        WavFormat::Pcm8 => data.iter()
+            .map(|v| (*v as f32 - 128.) / 128.)
+            .collect(),
+        WavFormat::Pcm16 => data.chunks_exact(2)
+            .map(|v| i16::from_le_bytes([v[0], v[1]]) as f32 / 0x8000 as f32)
+            .collect(),
+        WavFormat::Pcm24 => data.chunks_exact(3)
+            // sign-extends by reading into the high bytes of an i32
+            .map(|v| (i32::from_le_bytes([0, v[0], v[1], v[2]]) >> 8) as f32 / 0x80_0000 as f32)
+            .collect(),
+        WavFormat::Pcm32 => data.chunks_exact(4)
+            .map(|v| (i32::from_le_bytes([v[0], v[1], v[2], v[3]]) as f64 / 0x8000_0000u32 as f64) as f32)
+            .collect(),
+        WavFormat::Float32 => data.chunks_exact(4)
+            .map(|v| f32::from_le_bytes([v[0], v[1], v[2], v[3]]))
+            .collect(),
+        WavFormat::Float64 => data.chunks_exact(8)
+            .map(|v| f64::from_le_bytes(v.try_into().unwrap()) as f32)
+            .collect(),
+    };
+
+    Tensor::from_vec(samples, [n_frames, n_channels])
+}
+
+fn expect<V: PartialEq + std::fmt::LowerHex>(
+    name: &str, 
+    value: V, 
+    expected: V
+) -> Result<(), TensorError> {
+    if value == expected {
+        Ok(())
+    } else {
+        Err(decode_error(format!("unexpected {} {:#x}, expected {:#x}", name, value, expected)))
+    }
+}
+
+fn decode_error(reason: String) -> TensorError {
+    TensorError::Decode { op: "decode_wav", reason }
 }
 
 #[derive(Debug)]
 struct Meta {
-    fmt: u16,
+    format: WavFormat,
     n_channels: u16,
     sample_rate: u32,
-    _byte_rate: u32,
-    _block_align: u16,
-    bits_per_sample: u16,
 }
 
 struct Cursor<'a> {
@@ -75,48 +183,61 @@ impl<'a> Cursor<'a> {
         }
     }
 
-    fn read(&mut self) -> u8 {
-        let v = self.slice[self.index];
-        self.index += 1;
-        v
+    fn read(&mut self) -> Result<u8, TensorError> {
+        match self.slice.get(self.index) {
+            Some(v) => {
+                self.index += 1;
+                Ok(*v)
+            }
+            None => Err(decode_error(format!("unexpected end of data at {}", self.index))),
+        }
     }
 
-    fn _read_u16(&mut self) -> u16 {
-        0x100 * self.read() as u16 + self.read() as u16
+    fn remaining(&self) -> usize {
+        self.slice.len() - self.index
     }
 
-    fn read_u16_little(&mut self) -> u16 {
-        self.read() as u16 + 0x100 * self.read() as u16
+    fn read_bytes(&mut self, len: usize) -> Result<&'a [u8], TensorError> {
+        match self.slice.get(self.index..self.index + len) {
+            Some(bytes) => {
+                self.index += len;
+                Ok(bytes)
+            }
+            None => Err(decode_error(format!("unexpected end of data at {}", self.index))),
+        }
     }
 
-    fn read_i16_little(&mut self) -> i16 {
-        (self.read() as u16 + 0x100 * self.read() as u16) as i16
+    fn skip(&mut self, len: usize) -> Result<(), TensorError> {
+        self.read_bytes(len)?;
+
+        Ok(())
     }
 
-    fn read_u32_big(&mut self) -> u32 {
-        0x0100_0000 * self.read() as u32 +
-        0x01_0000 * self.read() as u32 +
-        0x100 * self.read() as u32 +
-        self.read() as u32
+    fn read_u16_little(&mut self) -> Result<u16, TensorError> {
+        Ok(self.read()? as u16 + 0x100 * self.read()? as u16)
     }
 
-    fn read_u32_little(&mut self) -> u32 {
-        self.read() as u32 +
-        0x0100 * self.read() as u32 +
-        0x1_0000 * self.read() as u32 +
-        0x100_0000 * self.read() as u32
+    fn read_u32_big(&mut self) -> Result<u32, TensorError> {
+        Ok(0x0100_0000 * self.read()? as u32 +
+            0x01_0000 * self.read()? as u32 +
+            0x100 * self.read()? as u32 +
+            self.read()? as u32)
     }
 
-    fn _read_slice(&mut self, slice: &mut [u8]) {
-        for i in 0..slice.len() {
-            slice[i] = self.slice[i + self.index] 
-        }
+    fn read_u32_little(&mut self) -> Result<u32, TensorError> {
+        Ok(self.read()? as u32 +
+            0x0100 * self.read()? as u32 +
+            0x1_0000 * self.read()? as u32 +
+            0x100_0000 * self.read()? as u32)
     }
 }
 
 #[cfg(test)]
 mod test {
-    use crate::{io::{decode_wav::decode_wav, read_file}, ten, tensor::Shape};
+    use crate::{
+        io::{decode_wav::{decode_wav, try_decode_wav}, read_file}, 
+        ten, tensor::{scalar, Shape, Tensor, TensorError},
+    };
 
     #[test]
     fn test_wav() {
@@ -126,4 +247,129 @@ mod test {
         assert_eq!(audio.shape(), &Shape::from([118080, 1]));
         assert_eq!(rate, ten!(16000));
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn try_decode_wav_errors() {
+        let err = try_decode_wav(Tensor::<u8>::from_vec(vec![0x52, 0x49], [2])).unwrap_err();
+        assert!(matches!(err, TensorError::Decode { .. }));
+
+        let err = try_decode_wav(Tensor::<u8>::from_vec(b"RIFX0000".to_vec(), [8])).unwrap_err();
+        assert!(matches!(err, TensorError::Decode { .. }));
+    }
+
+    #[test]
+    fn try_decode_wav_mono() {
+        let mut data = Vec::<u8>::new();
+        data.extend(b"RIFF");
+        data.extend(40u32.to_le_bytes());
+        data.extend(b"WAVEfmt ");
+        data.extend(16u32.to_le_bytes());
+        data.extend(1u16.to_le_bytes());
+        data.extend(1u16.to_le_bytes());
+        data.extend(8000u32.to_le_bytes());
+        data.extend(16000u32.to_le_bytes());
+        data.extend(2u16.to_le_bytes());
+        data.extend(16u16.to_le_bytes());
+        data.extend(b"data");
+        data.extend(4u32.to_le_bytes());
+        data.extend(0x4000i16.to_le_bytes());
+        data.extend((-0x8000i16).to_le_bytes());
+
+        let len = data.len();
+        let (audio, rate) = try_decode_wav(Tensor::from_vec(data, [len])).unwrap();
+
+        assert_eq!(audio, ten![[0.5], [-1.]]);
+        assert_eq!(rate, scalar(8000));
+    }
+
+    fn riff(chunks: &[u8]) -> Tensor<u8> {
+        let mut data = Vec::<u8>::new();
+        data.extend(b"RIFF");
+        data.extend((4 + chunks.len() as u32).to_le_bytes());
+        data.extend(b"WAVE");
+        data.extend(chunks);
+
+        let len = data.len();
+        Tensor::from_vec(data, [len])
+    }
+
+    fn chunk(tag: &[u8], body: &[u8]) -> Vec<u8> {
+        let mut data = tag.to_vec();
+        data.extend((body.len() as u32).to_le_bytes());
+        data.extend(body);
+        if body.len() % 2 == 1 {
+            data.push(0);
+        }
+        data
+    }
+
+    fn fmt(tag: u16, n_channels: u16, bits: u16) -> Vec<u8> {
+        let block_align = n_channels * bits / 8;
+
+        let mut data = Vec::<u8>::new();
+        data.extend(tag.to_le_bytes());
+        data.extend(n_channels.to_le_bytes());
+        data.extend(8000u32.to_le_bytes());
+        data.extend((8000 * block_align as u32).to_le_bytes());
+        data.extend(block_align.to_le_bytes());
+        data.extend(bits.to_le_bytes());
+        data
+    }
+
+    #[test]
+    fn decode_wav_skips_chunks() {
+        let mut chunks = chunk(b"LIST", b"INFOodd");
+        chunks.extend(chunk(b"fmt ", &fmt(1, 2, 8)));
+        chunks.extend(chunk(b"fact", &1u32.to_le_bytes()));
+        chunks.extend(chunk(b"data", &[0xc0, 0x00]));
+
+        let (audio, rate) = try_decode_wav(riff(&chunks)).unwrap();
+
+        assert_eq!(audio, ten![[0.5, -1.]]);
+        assert_eq!(rate, scalar(8000));
+    }
+
+    #[test]
+    fn decode_wav_extensible_pcm24() {
+        let mut ext = fmt(0xfffe, 1, 24);
+        ext.extend(22u16.to_le_bytes());
+        ext.extend(24u16.to_le_bytes());
+        ext.extend(4u32.to_le_bytes());
+        ext.extend(1u16.to_le_bytes());
+        ext.extend([0u8; 14]);
+
+        let mut chunks = chunk(b"fmt ", &ext);
+        chunks.extend(chunk(b"data", &[0x00, 0x00, 0x40, 0x00, 0x00, 0x80]));
+
+        let (audio, _) = try_decode_wav(riff(&chunks)).unwrap();
+
+        assert_eq!(audio, ten![[0.5], [-1.]]);
+    }
+
+    #[test]
+    fn decode_wav_format_errors() {
+        // mu-law
+        let mut chunks = chunk(b"fmt ", &fmt(7, 1, 8));
+        chunks.extend(chunk(b"data", &[0, 0]));
+
+        assert!(matches!(
+            try_decode_wav(riff(&chunks)).unwrap_err(),
+            TensorError::Decode { op: "decode_wav", .. }
+        ));
+
+        let chunks = chunk(b"data", &[0, 0]);
+
+        assert!(matches!(
+            try_decode_wav(riff(&chunks)).unwrap_err(),
+            TensorError::Decode { op: "decode_wav", .. }
+        ));
+
+        // missing data chunk
+        let chunks = chunk(b"fmt ", &fmt(1, 1, 16));
+
+        assert!(matches!(
+            try_decode_wav(riff(&chunks)).unwrap_err(),
+            TensorError::Decode { op: "decode_wav", .. }
+        ));
+    }
+}
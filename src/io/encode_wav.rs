@@ -0,0 +1,200 @@
+use crate::tensor::{Tensor, TensorError};
+
+use super::wav::WavFormat;
+
+pub fn encode_wav(
+    samples: impl Into<Tensor<f32>>,
+    sample_rate: usize,
+    format: WavFormat,
+) -> Tensor<u8> {
+    try_encode_wav(samples, sample_rate, format).unwrap_or_else(|err| panic!("{}", err))
+}
+
+///
+/// Encodes [samples, channels] audio, or mono [samples] audio, as a WAV
+/// file. PCM samples are clamped to [-1, 1], the inverse of `decode_wav`.
+///
+pub fn try_encode_wav(
+    samples: impl Into<Tensor<f32>>,
+    sample_rate: usize,
+    format: WavFormat,
+) -> Result<Tensor<u8>, TensorError> {
+    let samples = samples.into().to_contiguous();
+
+    let n_channels = match samples.rank() {
+        1 => 1,
+        2 => samples.cols(),
+        _ => return Err(TensorError::InvalidShape {
+            op: "encode_wav",
+            shape: samples.shape().as_vec(),
+            reason: "requires [samples] or [samples, channels]",
+        })
+    };
+
+    if n_channels == 0 || n_channels > u16::MAX as usize {
+        return Err(encode_error(format!("invalid channel count {}", n_channels)));
+    }
+
+    let sample_rate = u32::try_from(sample_rate)
+        .map_err(|_| encode_error(format!("sample rate {} is too large", sample_rate)))?;
+
+    let width = format.bytes_per_sample();
+
+    let block_align = u16::try_from(n_channels * width).map_err(|_| {
+        encode_error(format!("{} channels are too many for {:?}", n_channels, format))
+    })?;
+    let byte_rate = sample_rate.checked_mul(block_align as u32)
+        .ok_or_else(|| encode_error(format!("sample rate {} is too large", sample_rate)))?;
+
+    let data_size = samples.size() * width;
+
+    let is_float = matches!(format, WavFormat::Float32 | WavFormat::Float64);
+
+    // non-PCM formats have an extended fmt and a fact chunk
+    let fmt_size = if is_float { 18 } else { 16 };
+    let fact_size = if is_float { 12 } else { 0 };
+    let riff_size = 4 + (8 + fmt_size) + fact_size + 8 + data_size + data_size % 2;
+
+    if riff_size > u32::MAX as usize {
+        return Err(encode_error(format!("data size {} is too large", data_size)));
+    }
+
+    let mut out = Vec::<u8>::with_capacity(8 + riff_size);
+
+    out.extend(b"RIFF");
+    out.extend((riff_size as u32).to_le_bytes());
+    out.extend(b"WAVE");
+
+    out.extend(b"fmt ");
+    out.extend((fmt_size as u32).to_le_bytes());
+    out.extend(format.tag().to_le_bytes());
+    out.extend((n_channels as u16).to_le_bytes());
+    out.extend(sample_rate.to_le_bytes());
+    out.extend(byte_rate.to_le_bytes());
+    out.extend(block_align.to_le_bytes());
+    out.extend(format.bits_per_sample().to_le_bytes());
+
+    if is_float {
+        out.extend(0u16.to_le_bytes());
+
+        out.extend(b"fact");
+        out.extend(4u32.to_le_bytes());
+        out.extend(((samples.size() / n_channels) as u32).to_le_bytes());
+    }
+
+    out.extend(b"data");
+    out.extend((data_size as u32).to_le_bytes());
+
+    for v in samples.as_slice() {
+        match format {
+            WavFormat::Pcm8 => {
+                out.push((v * 128. + 128.).round().clamp(0., 255.) as u8);
+            }
+            WavFormat::Pcm16 => {
+                out.extend((quantize(*v, 0x8000 as f64) as i16).to_le_bytes());
+            }
+            WavFormat::Pcm24 => {
+                let v = quantize(*v, 0x80_0000 as f64) as i32;
+                out.extend(&v.to_le_bytes()[..3]);
+            }
+            WavFormat::Pcm32 => {
+                out.extend((quantize(*v, 0x8000_0000u32 as f64) as i32).to_le_bytes());
+            }
+            WavFormat::Float32 => {
+                out.extend(v.to_le_bytes());
+            }
+            WavFormat::Float64 => {
+                out.extend((*v as f64).to_le_bytes());
+            }
+        }
+    }
+
+    if data_size % 2 == 1 {
+        out.push(0);
+    }
+
+    let len = out.len();
+
+    Ok(Tensor::from_vec(out, [len]))
+}
+
+// Scales [-1, 1] to a signed integer range [-scale, scale - 1]
+fn quantize(v: f32, scale: f64) -> i64 {
+    (v as f64 * scale).round().clamp(-scale, scale - 1.) as i64
+}
+
+fn encode_error(reason: String) -> TensorError {
+    TensorError::InvalidArgument { op: "encode_wav", reason }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        io::{decode_wav, encode_wav, try_encode_wav, WavFormat},
+        ten, tensor::{scalar, Tensor, TensorError},
+    };
+
+    #[test]
+    fn encode_wav_round_trip() {
+        let audio = ten![[0.5, -0.25], [-1., 0.75], [0., 0.125]];
+
+        for format in [
+            WavFormat::Pcm8, WavFormat::Pcm16, WavFormat::Pcm24, WavFormat::Pcm32,
+            WavFormat::Float32, WavFormat::Float64,
+        ] {
+            let (decoded, rate) = decode_wav(encode_wav(&audio, 44100, format));
+
+            assert_eq!(decoded, audio, "{:?}", format);
+            assert_eq!(rate, scalar(44100));
+        }
+    }
+
+    #[test]
+    fn encode_wav_pcm16_layout() {
+        let data = encode_wav(ten![0.5, -1., 1.], 8000, WavFormat::Pcm16);
+
+        assert_eq!(data.size(), 44 + 6);
+        assert_eq!(&data.as_slice()[0..4], b"RIFF");
+        assert_eq!(&data.as_slice()[4..8], &42u32.to_le_bytes());
+        assert_eq!(&data.as_slice()[44..], &[0x00, 0x40, 0x00, 0x80, 0xff, 0x7f]);
+
+        let (audio, _) = decode_wav(data);
+        assert_eq!(audio.shape().as_vec(), &[3, 1]);
+    }
+
+    #[test]
+    fn encode_wav_odd_size() {
+        let data = encode_wav(ten![0.5, -0.5, 0.], 8000, WavFormat::Pcm8);
+
+        // the 3-byte data chunk is padded to an even size
+        assert_eq!(data.size(), 44 + 4);
+
+        let (audio, _) = decode_wav(data);
+        assert_eq!(audio, ten![[0.5], [-0.5], [0.]]);
+    }
+
+    #[test]
+    fn try_encode_wav_errors() {
+        assert!(matches!(
+            try_encode_wav(Tensor::<f32>::zeros([2, 2, 2]), 8000, WavFormat::Pcm16).unwrap_err(),
+            TensorError::InvalidShape { op: "encode_wav", .. }
+        ));
+
+        assert!(matches!(
+            try_encode_wav(Tensor::<f32>::zeros([2, 0]), 8000, WavFormat::Pcm16).unwrap_err(),
+            TensorError::InvalidArgument { op: "encode_wav", .. }
+        ));
+
+        // the block align of 20000 f64 channels doesn't fit in a u16
+        assert!(matches!(
+            try_encode_wav(Tensor::<f32>::zeros([1, 20000]), 8000, WavFormat::Float64).unwrap_err(),
+            TensorError::InvalidArgument { op: "encode_wav", .. }
+        ));
+
+        // the byte rate doesn't fit in a u32
+        assert!(matches!(
+            try_encode_wav(ten![[0., 0.]], u32::MAX as usize, WavFormat::Pcm16).unwrap_err(),
+            TensorError::InvalidArgument { op: "encode_wav", .. }
+        ));
+    }
+}
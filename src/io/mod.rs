@@ -1,5 +1,17 @@
+mod csv;
 mod decode_wav;
+mod encode_wav;
+mod npy;
+mod npz;
 mod read_file;
+pub mod safetensors;
+mod wav;
+mod zip;
 
-pub use decode_wav::decode_wav;
-pub use read_file::read_file;
\ No newline at end of file
+pub use csv::{decode_csv, encode_csv, read_csv, write_csv, CsvArg, CsvOpt, CsvType};
+pub use decode_wav::{decode_wav, try_decode_wav};
+pub use encode_wav::{encode_wav, try_encode_wav};
+pub use npy::{decode_npy, encode_npy, read_npy, try_decode_npy, write_npy, NpyType};
+pub use npz::{decode_npz, encode_npz, read_npz, try_decode_npz, try_encode_npz, write_npz, Npz};
+pub use read_file::read_file;
+pub use wav::WavFormat;
@@ -0,0 +1,467 @@
+use std::fs;
+
+use num_complex::Complex;
+
+use crate::tensor::{Tensor, TensorError, Type};
+
+///
+/// Item types with a NumPy dtype, like "f4" for f32. Items are stored
+/// little-endian, but big-endian files are also read.
+///
+pub trait NpyType: Type + Clone {
+    /// dtype kind and item size without the byte order
+    const DESCR: &'static str;
+
+    /// Item size in bytes
+    const SIZE: usize;
+
+    fn from_npy_bytes(bytes: &[u8], is_big_endian: bool) -> Self;
+
+    fn to_npy_bytes(&self, out: &mut Vec<u8>);
+}
+
+macro_rules! npy_types {
+    ($($ty:ty, $repr:ty, $descr:literal;)*) => {
+        $(
+            impl NpyType for $ty {
+                const DESCR: &'static str = $descr;
+                const SIZE: usize = std::mem::size_of::<$repr>();
+
+                #[inline]
+                fn from_npy_bytes(bytes: &[u8], is_big_endian: bool) -> Self {
+                    let bytes = bytes.try_into().unwrap();
+
+                    if is_big_endian {
+                        <$repr>::from_be_bytes(bytes) as $ty
+                    } else {
+                        <$repr>::from_le_bytes(bytes) as $ty
+                    }
+                }
+
+                #[inline]
+                fn to_npy_bytes(&self, out: &mut Vec<u8>) {
+                    out.extend((*self as $repr).to_le_bytes());
+                }
+            }
+        )*
+    }
+}
+
+npy_types!(
+    i8, i8, "i1";
+    i16, i16, "i2";
+    i32, i32, "i4";
+    i64, i64, "i8";
+    isize, i64, "i8";
+    u8, u8, "u1";
+    u16, u16, "u2";
+    u32, u32, "u4";
+    u64, u64, "u8";
+    usize, u64, "u8";
+    f32, f32, "f4";
+    f64, f64, "f8";
+);
+
+impl NpyType for bool {
+    const DESCR: &'static str = "b1";
+    const SIZE: usize = 1;
+
+    #[inline]
+    fn from_npy_bytes(bytes: &[u8], _is_big_endian: bool) -> Self {
+        bytes[0] != 0
+    }
+
+    #[inline]
+    fn to_npy_bytes(&self, out: &mut Vec<u8>) {
+        out.push(*self as u8);
+    }
+}
+
+impl NpyType for Complex<f32> {
+    const DESCR: &'static str = "c8";
+    const SIZE: usize = 8;
+
+    #[inline]
+    fn from_npy_bytes(bytes: &[u8], is_big_endian: bool) -> Self {
+        Complex::new(
+            f32::from_npy_bytes(&bytes[..4], is_big_endian),
+            f32::from_npy_bytes(&bytes[4..], is_big_endian),
+        )
+    }
+
+    #[inline]
+    fn to_npy_bytes(&self, out: &mut Vec<u8>) {
+        self.re.to_npy_bytes(out);
+        self.im.to_npy_bytes(out);
+    }
+}
+
+impl NpyType for Complex<f64> {
+    const DESCR: &'static str = "c16";
+    const SIZE: usize = 16;
+
+    #[inline]
+    fn from_npy_bytes(bytes: &[u8], is_big_endian: bool) -> Self {
+        Complex::new(
+            f64::from_npy_bytes(&bytes[..8], is_big_endian),
+            f64::from_npy_bytes(&bytes[8..], is_big_endian),
+        )
+    }
+
+    #[inline]
+    fn to_npy_bytes(&self, out: &mut Vec<u8>) {
+        self.re.to_npy_bytes(out);
+        self.im.to_npy_bytes(out);
+    }
+}
+
+const MAGIC: &[u8] = b"\x93NUMPY";
+
+pub fn read_npy<T: NpyType>(filename: impl Into<Tensor<String>>) -> Result<Tensor<T>, TensorError> {
+    let data = fs::read(&filename.into()[0])
+        .map_err(|err| TensorError::Io { op: "read_npy", reason: err.to_string() })?;
+
+    let len = data.len();
+
+    try_decode_npy(Tensor::from_vec(data, [len]))
+}
+
+pub fn write_npy<T: NpyType>(
+    filename: impl Into<Tensor<String>>,
+    tensor: &Tensor<T>
+) -> Result<(), TensorError> {
+    fs::write(&filename.into()[0], encode_npy(tensor).as_slice())
+        .map_err(|err| TensorError::Io { op: "write_npy", reason: err.to_string() })
+}
+
+pub fn decode_npy<T: NpyType>(contents: impl Into<Tensor<u8>>) -> Tensor<T> {
+    try_decode_npy(contents).unwrap_or_else(|err| panic!("{}", err))
+}
+
+///
+/// Decodes a .npy file, where the dtype must match T. Fortran order data is
+/// reordered into the tensor's C order.
+///
+pub fn try_decode_npy<T: NpyType>(contents: impl Into<Tensor<u8>>) -> Result<Tensor<T>, TensorError> {
+    let contents = contents.into().to_contiguous();
+    let data = contents.as_slice();
+
+    if data.len() < 10 || &data[..6] != MAGIC {
+        return Err(decode_error("missing NUMPY magic".to_string()));
+    }
+
+    let (header_len, offset) = match data[6] {
+        1 => (u16::from_le_bytes([data[8], data[9]]) as usize, 10),
+        2 | 3 if data.len() >= 12 => {
+            (u32::from_le_bytes([data[8], data[9], data[10], data[11]]) as usize, 12)
+        }
+        version => return Err(decode_error(format!("unsupported version {}", version))),
+    };
+
+    let header = data.get(offset..offset + header_len)
+        .ok_or_else(|| decode_error("truncated header".to_string()))?;
+
+    let header = std::str::from_utf8(header)
+        .map_err(|_| decode_error("header is not utf-8".to_string()))?;
+
+    let header = Header::parse(header)?;
+
+    let (is_big_endian, item_descr) = match header.descr.chars().next() {
+        Some('<' | '|' | '=') => (false, &header.descr[1..]),
+        Some('>') => (true, &header.descr[1..]),
+        _ => return Err(decode_error(format!("invalid descr {:?}", header.descr))),
+    };
+
+    if item_descr != T::DESCR {
+        return Err(TensorError::DType {
+            op: "decode_npy",
+            expected: T::DESCR.to_string(),
+            found: header.descr,
+        });
+    }
+
+    let len = header.shape.iter()
+        .try_fold(T::SIZE, |len, dim| len.checked_mul(*dim))
+        .ok_or_else(|| decode_error(format!("shape {:?} is too large", header.shape)))?;
+
+    let body = &data[offset + header_len..];
+
+    if body.len() < len {
+        return Err(decode_error(format!(
+            "data has {} bytes, expected {} for shape {:?}", body.len(), len, header.shape
+        )));
+    }
+
+    let items: Vec<T> = body[..len]
+        .chunks_exact(T::SIZE)
+        .map(|bytes| T::from_npy_bytes(bytes, is_big_endian))
+        .collect();
+
+    let items = if header.fortran_order {
+        fortran_to_c(&items, &header.shape)
+    } else {
+        items
+    };
+
+    Ok(Tensor::from_vec(items, header.shape.as_slice()))
+}
+
+///
+/// Encodes the tensor as a version 1.0 .npy file in C order, or version
+/// 2.0 if the header is too large.
+///
+pub fn encode_npy<T: NpyType>(tensor: &Tensor<T>) -> Tensor<u8> {
+    let tensor = tensor.to_contiguous();
+
+    let dims = tensor.shape().as_vec();
+    let shape = match dims.len() {
+        1 => format!("({},)", dims[0]),
+        _ => format!(
+            "({})",
+            dims.iter().map(|d| d.to_string()).collect::<Vec<String>>().join(", ")
+        ),
+    };
+
+    let order = if T::SIZE == 1 { "|" } else { "<" };
+
+    let mut header = format!(
+        "{{'descr': '{}{}', 'fortran_order': False, 'shape': {}, }}",
+        order, T::DESCR, shape
+    );
+
+    let is_v1 = header.len() + 11 <= u16::MAX as usize;
+    let preamble = if is_v1 { 10 } else { 12 };
+
+    // the header ends with a newline and aligns the data to 64 bytes
+    let padded = (preamble + header.len() + 1).div_ceil(64) * 64;
+    header.extend(std::iter::repeat_n(' ', padded - preamble - header.len() - 1));
+    header.push('\n');
+
+    let mut out = Vec::<u8>::with_capacity(padded + tensor.size() * T::SIZE);
+    out.extend(MAGIC);
+
+    if is_v1 {
+        out.extend([1, 0]);
+        out.extend((header.len() as u16).to_le_bytes());
+    } else {
+        out.extend([2, 0]);
+        out.extend((header.len() as u32).to_le_bytes());
+    }
+
+    out.extend(header.as_bytes());
+
+    for item in tensor.as_slice() {
+        item.to_npy_bytes(&mut out);
+    }
+
+    let len = out.len();
+
+    Tensor::from_vec(out, [len])
+}
+
+// Reorders column-major items into row-major order
+fn fortran_to_c<T: Clone>(items: &[T], dims: &[usize]) -> Vec<T> {
+    let mut strides = vec![1; dims.len()];
+    for i in 1..dims.len() {
+        strides[i] = strides[i - 1] * dims[i - 1];
+    }
+
+    let mut index = vec![0; dims.len()];
+    let mut out = Vec::<T>::with_capacity(items.len());
+
+    for _ in 0..items.len() {
+        let offset: usize = index.iter().zip(&strides).map(|(i, s)| i * s).sum();
+        out.push(items[offset].clone());
+
+        for (i, dim) in index.iter_mut().zip(dims).rev() {
+            *i += 1;
+
+            if *i < *dim {
+                break;
+            }
+
+            *i = 0;
+        }
+    }
+
+    out
+}
+
+fn decode_error(reason: String) -> TensorError {
+    TensorError::Decode { op: "decode_npy", reason }
+}
+
+// Header dict like {'descr': '<f4', 'fortran_order': False, 'shape': (3, 4), }
+struct Header {
+    descr: String,
+    fortran_order: bool,
+    shape: Vec<usize>,
+}
+
+impl Header {
+    fn parse(header: &str) -> Result<Self, TensorError> {
+        let descr = Self::value(header, "descr")?;
+        let descr = descr.strip_prefix('\'')
+            .and_then(|v| v.split('\'').next())
+            .ok_or_else(|| decode_error(format!("invalid descr in {}", header)))?;
+
+        let fortran_order = Self::value(header, "fortran_order")?;
+        let fortran_order = if fortran_order.starts_with("True") {
+            true
+        } else if fortran_order.starts_with("False") {
+            false
+        } else {
+            return Err(decode_error(format!("invalid fortran_order in {}", header)));
+        };
+
+        let shape = Self::value(header, "shape")?;
+        let shape = shape.strip_prefix('(')
+            .and_then(|v| v.split(')').next())
+            .ok_or_else(|| decode_error(format!("invalid shape in {}", header)))?;
+
+        let shape = shape.split(',')
+            .map(|dim| dim.trim())
+            .filter(|dim| ! dim.is_empty())
+            .map(|dim| dim.parse::<usize>())
+            .collect::<Result<Vec<usize>, _>>()
+            .map_err(|_| decode_error(format!("invalid shape in {}", header)))?;
+
+        Ok(Self { descr: descr.to_string(), fortran_order, shape })
+    }
+
+    // Text following the dict key
+    fn value<'a>(header: &'a str, key: &str) -> Result<&'a str, TensorError> {
+        let pattern = format!("'{}':", key);
+
+        match header.find(&pattern) {
+            Some(i) => Ok(header[i + pattern.len()..].trim_start()),
+            None => Err(decode_error(format!("missing '{}' in {}", key, header))),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use num_complex::Complex;
+
+    use crate::{
+        io::{decode_npy, encode_npy, read_npy, try_decode_npy, write_npy},
+        ten, tensor::{scalar, Tensor, TensorError},
+    };
+
+    fn npy(descr: &str, fortran_order: bool, shape: &str, body: &[u8]) -> Tensor<u8> {
+        let header = format!(
+            "{{'descr': '{}', 'fortran_order': {}, 'shape': {}, }}\n",
+            descr, if fortran_order { "True" } else { "False" }, shape
+        );
+
+        let mut data = b"\x93NUMPY\x01\x00".to_vec();
+        data.extend((header.len() as u16).to_le_bytes());
+        data.extend(header.as_bytes());
+        data.extend(body);
+
+        Tensor::from(data)
+    }
+
+    #[test]
+    fn npy_round_trip() {
+        let x = ten![[1., 2., 3.], [4., 5., 6.]];
+        let data = encode_npy(&x);
+
+        assert_eq!(data.size() % 64, (6 * 4) % 64);
+        assert_eq!(decode_npy::<f32>(&data), x);
+
+        let x = ten![[1i64, -2], [3, -4]];
+        assert_eq!(decode_npy::<i64>(encode_npy(&x)), x);
+
+        let x = ten![true, false, true];
+        assert_eq!(decode_npy::<bool>(encode_npy(&x)), x);
+
+        let x = Tensor::from(vec![Complex::new(1f64, -1.), Complex::new(0.5, 2.)]);
+        assert_eq!(decode_npy::<Complex<f64>>(encode_npy(&x)), x);
+
+        let x = scalar(7u16);
+        assert_eq!(decode_npy::<u16>(encode_npy(&x)), x);
+
+        let x = Tensor::<f64>::zeros([0, 3]);
+        assert_eq!(decode_npy::<f64>(encode_npy(&x)).shape().as_vec(), &[0, 3]);
+    }
+
+    #[test]
+    fn npy_header_format() {
+        let data = encode_npy(&ten![1u8, 2, 3]);
+        let header = std::str::from_utf8(&data.as_slice()[10..128]).unwrap();
+
+        assert!(header.starts_with("{'descr': '|u1', 'fortran_order': False, 'shape': (3,), }"));
+        assert!(header.ends_with(" \n"));
+        assert_eq!(&data.as_slice()[128..], &[1, 2, 3]);
+    }
+
+    #[test]
+    fn npy_fortran_order() {
+        let body: Vec<u8> = [1i32, 4, 2, 5, 3, 6].iter().flat_map(|v| v.to_le_bytes()).collect();
+
+        assert_eq!(
+            decode_npy::<i32>(npy("<i4", true, "(2, 3)", &body)),
+            ten![[1, 2, 3], [4, 5, 6]]
+        );
+    }
+
+    #[test]
+    fn npy_big_endian() {
+        let body: Vec<u8> = [1.5f64, -2.].iter().flat_map(|v| v.to_be_bytes()).collect();
+
+        assert_eq!(decode_npy::<f64>(npy(">f8", false, "(2,)", &body)), ten![1.5f64, -2.]);
+    }
+
+    #[test]
+    fn npy_errors() {
+        let data = npy("<f8", false, "(1,)", &[0; 8]);
+
+        assert_eq!(
+            try_decode_npy::<f32>(&data).unwrap_err(),
+            TensorError::DType { op: "decode_npy", expected: "f4".to_string(), found: "<f8".to_string() }
+        );
+
+        assert!(matches!(
+            try_decode_npy::<f64>(npy("<f8", false, "(2,)", &[0; 8])).unwrap_err(),
+            TensorError::Decode { op: "decode_npy", .. }
+        ));
+
+        assert!(matches!(
+            try_decode_npy::<f64>(Tensor::from(b"NUMPY".to_vec())).unwrap_err(),
+            TensorError::Decode { op: "decode_npy", .. }
+        ));
+
+        // malformed descr and shapes whose size overflows
+        for data in [
+            npy("", false, "(1,)", &[0; 8]),
+            npy("é8", false, "(1,)", &[0; 8]),
+            npy("<f8", false, "(4611686018427387904, 4)", &[0; 8]),
+            npy("<f8", false, "(18446744073709551615, 2)", &[0; 8]),
+        ] {
+            assert!(matches!(
+                try_decode_npy::<f64>(data).unwrap_err(),
+                TensorError::Decode { op: "decode_npy", .. }
+            ));
+        }
+
+        assert!(matches!(
+            read_npy::<f32>("/nonexistent/file.npy").unwrap_err(),
+            TensorError::Io { op: "read_npy", .. }
+        ));
+    }
+
+    #[test]
+    fn npy_file() {
+        let path = std::env::temp_dir().join(format!("essay-npy-{}.npy", std::process::id()));
+        let path = path.to_str().unwrap();
+
+        let x = ten![[1., 2.], [3., 4.]];
+        write_npy(path, &x).unwrap();
+
+        assert_eq!(read_npy::<f32>(path).unwrap(), x);
+
+        std::fs::remove_file(path).unwrap();
+    }
+}
@@ -0,0 +1,210 @@
+use std::fs;
+
+use crate::tensor::{Tensor, TensorError};
+
+use super::{npy::{encode_npy, try_decode_npy, NpyType}, zip::{read_zip, write_zip}};
+
+///
+/// Named tensors of a .npz archive. Each tensor is kept as its encoded
+/// .npy data, so an archive can hold tensors of different item types.
+///
+#[derive(Clone, Debug, Default)]
+pub struct Npz {
+    arrays: Vec<(String, Vec<u8>)>,
+}
+
+impl Npz {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.arrays.len()
+    }
+
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.arrays.is_empty()
+    }
+
+    pub fn names(&self) -> impl Iterator<Item = &str> {
+        self.arrays.iter().map(|(name, _)| name.as_str())
+    }
+
+    pub fn contains(&self, name: &str) -> bool {
+        self.arrays.iter().any(|(key, _)| key == name)
+    }
+
+    ///
+    /// Decodes the named tensor, where the item type must match the
+    /// stored dtype.
+    ///
+    pub fn get<T: NpyType>(&self, name: &str) -> Result<Tensor<T>, TensorError> {
+        match self.arrays.iter().find(|(key, _)| key == name) {
+            Some((_, data)) => try_decode_npy(Tensor::from(data.clone())),
+            None => Err(TensorError::InvalidArgument {
+                op: "npz",
+                reason: format!("no array named {:?}", name),
+            }),
+        }
+    }
+
+    ///
+    /// Adds the tensor, replacing any existing tensor with the same name.
+    ///
+    pub fn insert<T: NpyType>(&mut self, name: &str, tensor: &Tensor<T>) {
+        let data = encode_npy(tensor).as_slice().to_vec();
+
+        match self.arrays.iter_mut().find(|(key, _)| key == name) {
+            Some((_, value)) => *value = data,
+            None => self.arrays.push((name.to_string(), data)),
+        }
+    }
+}
+
+pub fn read_npz(filename: impl Into<Tensor<String>>) -> Result<Npz, TensorError> {
+    let data = fs::read(&filename.into()[0])
+        .map_err(|err| TensorError::Io { op: "read_npz", reason: err.to_string() })?;
+
+    let len = data.len();
+
+    try_decode_npz(Tensor::from_vec(data, [len]))
+}
+
+pub fn write_npz(filename: impl Into<Tensor<String>>, npz: &Npz) -> Result<(), TensorError> {
+    let data = try_encode_npz(npz)?;
+
+    fs::write(&filename.into()[0], data.as_slice())
+        .map_err(|err| TensorError::Io { op: "write_npz", reason: err.to_string() })
+}
+
+pub fn decode_npz(contents: impl Into<Tensor<u8>>) -> Npz {
+    try_decode_npz(contents).unwrap_or_else(|err| panic!("{}", err))
+}
+
+///
+/// Decodes a .npz archive from `np.savez` or `np.savez_compressed`. The
+/// names are the archive entries without the .npy suffix.
+///
+pub fn try_decode_npz(contents: impl Into<Tensor<u8>>) -> Result<Npz, TensorError> {
+    let contents = contents.into().to_contiguous();
+
+    let arrays = read_zip("decode_npz", contents.as_slice())?
+        .into_iter()
+        .map(|(name, data)| {
+            let name = name.strip_suffix(".npy").map_or(name.clone(), |n| n.to_string());
+
+            (name, data)
+        })
+        .collect();
+
+    Ok(Npz { arrays })
+}
+
+pub fn encode_npz(npz: &Npz) -> Tensor<u8> {
+    try_encode_npz(npz).unwrap_or_else(|err| panic!("{}", err))
+}
+
+///
+/// Encodes the archive uncompressed like `np.savez`.
+///
+pub fn try_encode_npz(npz: &Npz) -> Result<Tensor<u8>, TensorError> {
+    let entries: Vec<(String, Vec<u8>)> = npz.arrays.iter()
+        .map(|(name, data)| (format!("{}.npy", name), data.clone()))
+        .collect();
+
+    let data = write_zip("encode_npz", &entries)?;
+    let len = data.len();
+
+    Ok(Tensor::from_vec(data, [len]))
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        io::{decode_npz, encode_npz, read_npz, write_npz, Npz},
+        ten, tensor::{Tensor, TensorError},
+    };
+
+    #[test]
+    fn npz_round_trip() {
+        let mut npz = Npz::new();
+        npz.insert("x", &ten![[1f32, 2.], [3., 4.]]);
+        npz.insert("labels", &ten![1i64, 0, 1]);
+        npz.insert("x", &ten![5f32, 6.]);
+
+        let npz = decode_npz(encode_npz(&npz));
+
+        assert_eq!(npz.len(), 2);
+        assert_eq!(npz.names().collect::<Vec<&str>>(), vec!["x", "labels"]);
+        assert_eq!(npz.get::<f32>("x").unwrap(), ten![5., 6.]);
+        assert_eq!(npz.get::<i64>("labels").unwrap(), ten![1i64, 0, 1]);
+
+        assert!(matches!(
+            npz.get::<f64>("x").unwrap_err(),
+            TensorError::DType { .. }
+        ));
+        assert!(matches!(
+            npz.get::<f32>("y").unwrap_err(),
+            TensorError::InvalidArgument { op: "npz", .. }
+        ));
+    }
+
+    #[test]
+    fn npz_compressed() {
+        // zipfile.ZIP_DEFLATED archive of a.npy and b.npy, like np.savez_compressed
+        let data: Vec<u8> = vec![
+            0x50, 0x4b, 0x03, 0x04, 0x14, 0x00, 0x00, 0x00, 0x08, 0x00, 0xe9, 0x2a,
+            0x51, 0x5d, 0x69, 0x6f, 0x19, 0x35, 0x50, 0x00, 0x00, 0x00, 0x98, 0x00,
+            0x00, 0x00, 0x05, 0x00, 0x00, 0x00, 0x61, 0x2e, 0x6e, 0x70, 0x79, 0x9b,
+            0xec, 0x17, 0xea, 0x1b, 0x10, 0xc9, 0xc8, 0x50, 0xc6, 0x50, 0xad, 0x9e,
+            0x92, 0x5a, 0x9c, 0x5c, 0xa4, 0x6e, 0xa5, 0xa0, 0x6e, 0x93, 0x66, 0xa1,
+            0xae, 0xa3, 0xa0, 0x9e, 0x96, 0x5f, 0x54, 0x52, 0x94, 0x98, 0x17, 0x9f,
+            0x5f, 0x94, 0x92, 0x0a, 0x12, 0x77, 0x4b, 0xcc, 0x29, 0x4e, 0x05, 0x8a,
+            0x17, 0x67, 0x24, 0x16, 0xa4, 0x02, 0xf9, 0x1a, 0xc6, 0x3a, 0x9a, 0x3a,
+            0x0a, 0xb5, 0x0a, 0x14, 0x00, 0x2e, 0x06, 0x30, 0xf8, 0x61, 0x0f, 0xa1,
+            0x19, 0x0e, 0x40, 0x28, 0x0e, 0x07, 0x00, 0x50, 0x4b, 0x03, 0x04, 0x14,
+            0x00, 0x00, 0x00, 0x08, 0x00, 0xe9, 0x2a, 0x51, 0x5d, 0x7b, 0xc8, 0x61,
+            0x7c, 0x52, 0x00, 0x00, 0x00, 0x90, 0x00, 0x00, 0x00, 0x05, 0x00, 0x00,
+            0x00, 0x62, 0x2e, 0x6e, 0x70, 0x79, 0x9b, 0xec, 0x17, 0xea, 0x1b, 0x10,
+            0xc9, 0xc8, 0x50, 0xc6, 0x50, 0xad, 0x9e, 0x92, 0x5a, 0x9c, 0x5c, 0xa4,
+            0x6e, 0xa5, 0xa0, 0x6e, 0x93, 0x69, 0xa2, 0xae, 0xa3, 0xa0, 0x9e, 0x96,
+            0x5f, 0x54, 0x52, 0x94, 0x98, 0x17, 0x9f, 0x5f, 0x94, 0x92, 0x0a, 0x12,
+            0x77, 0x4b, 0xcc, 0x29, 0x4e, 0x05, 0x8a, 0x17, 0x67, 0x24, 0x16, 0xa4,
+            0x02, 0xf9, 0x1a, 0x46, 0x3a, 0x0a, 0x46, 0x9a, 0x3a, 0x0a, 0xb5, 0x0a,
+            0x64, 0x03, 0x2e, 0x46, 0x06, 0x06, 0x06, 0x26, 0x20, 0x66, 0x06, 0x62,
+            0x16, 0x20, 0x06, 0x00, 0x50, 0x4b, 0x01, 0x02, 0x14, 0x03, 0x14, 0x00,
+            0x00, 0x00, 0x08, 0x00, 0xe9, 0x2a, 0x51, 0x5d, 0x69, 0x6f, 0x19, 0x35,
+            0x50, 0x00, 0x00, 0x00, 0x98, 0x00, 0x00, 0x00, 0x05, 0x00, 0x00, 0x00,
+            0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x80, 0x01, 0x00, 0x00,
+            0x00, 0x00, 0x61, 0x2e, 0x6e, 0x70, 0x79, 0x50, 0x4b, 0x01, 0x02, 0x14,
+            0x03, 0x14, 0x00, 0x00, 0x00, 0x08, 0x00, 0xe9, 0x2a, 0x51, 0x5d, 0x7b,
+            0xc8, 0x61, 0x7c, 0x52, 0x00, 0x00, 0x00, 0x90, 0x00, 0x00, 0x00, 0x05,
+            0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x80,
+            0x01, 0x73, 0x00, 0x00, 0x00, 0x62, 0x2e, 0x6e, 0x70, 0x79, 0x50, 0x4b,
+            0x05, 0x06, 0x00, 0x00, 0x00, 0x00, 0x02, 0x00, 0x02, 0x00, 0x66, 0x00,
+            0x00, 0x00, 0xe8, 0x00, 0x00, 0x00, 0x00, 0x00,
+        ];
+
+        let npz = decode_npz(Tensor::from(data));
+
+        assert!(npz.contains("a"));
+        assert_eq!(npz.get::<f64>("a").unwrap(), ten![1.5f64, -2., 3.]);
+        assert_eq!(npz.get::<i32>("b").unwrap(), ten![[1, 2], [3, 4]]);
+    }
+
+    #[test]
+    fn npz_file() {
+        let path = std::env::temp_dir().join(format!("essay-npz-{}.npz", std::process::id()));
+        let path = path.to_str().unwrap();
+
+        let mut npz = Npz::new();
+        npz.insert("flags", &ten![true, false]);
+        write_npz(path, &npz).unwrap();
+
+        assert_eq!(read_npz(path).unwrap().get::<bool>("flags").unwrap(), ten![true, false]);
+
+        std::fs::remove_file(path).unwrap();
+    }
+}
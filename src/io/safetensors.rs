@@ -0,0 +1,855 @@
+//! Reads and writes the safetensors format, an 8-byte little-endian header
+//! length, a JSON header mapping names to dtype, shape and data offsets,
+//! and the raw little-endian tensor data.
+
+use std::{any::Any, collections::HashMap, fs::{self, File}, mem, ptr::NonNull, sync::Arc};
+
+use memmap2::MmapOptions;
+
+use crate::tensor::{unsafe_from_owner, Tensor, TensorError};
+
+use super::npy::NpyType;
+
+///
+/// Item types with a safetensors dtype, like "F32" for f32.
+///
+pub trait SafetensorsType: NpyType {
+    const DTYPE: &'static str;
+
+    /// Checks the raw bytes are valid items, which matters for zero-copy
+    /// types like bool that don't allow every bit pattern.
+    fn is_valid(_bytes: &[u8]) -> bool {
+        true
+    }
+}
+
+macro_rules! safetensors_types {
+    ($($ty:ty, $dtype:literal;)*) => {
+        $(
+            impl SafetensorsType for $ty {
+                const DTYPE: &'static str = $dtype;
+            }
+        )*
+    }
+}
+
+safetensors_types!(
+    i8, "I8";
+    i16, "I16";
+    i32, "I32";
+    i64, "I64";
+    u8, "U8";
+    u16, "U16";
+    u32, "U32";
+    u64, "U64";
+    f32, "F32";
+    f64, "F64";
+);
+
+impl SafetensorsType for bool {
+    const DTYPE: &'static str = "BOOL";
+
+    fn is_valid(bytes: &[u8]) -> bool {
+        bytes.iter().all(|b| *b <= 1)
+    }
+}
+
+///
+/// Loads every tensor in the file, where each dtype must match T. Files
+/// that mix dtypes are read with `Safetensors::read` instead.
+///
+pub fn load<T: SafetensorsType>(
+    filename: impl Into<Tensor<String>>
+) -> Result<HashMap<String, Tensor<T>>, TensorError> {
+    let data = fs::read(&filename.into()[0])
+        .map_err(|err| TensorError::Io { op: "safetensors::load", reason: err.to_string() })?;
+
+    decode(&data)
+}
+
+///
+/// Loads every tensor in the file like `load`, but over a private
+/// memory map of the file. Aligned tensors borrow the mapped data
+/// without a copy, and writes to them are not written back to the file.
+///
+pub fn load_mmap<T: SafetensorsType>(
+    filename: impl Into<Tensor<String>>
+) -> Result<HashMap<String, Tensor<T>>, TensorError> {
+    let io_error = |err: std::io::Error| TensorError::Io {
+        op: "safetensors::load_mmap",
+        reason: err.to_string()
+    };
+
+    let file = File::open(&filename.into()[0]).map_err(io_error)?;
+
+    // unsafe: the map is copy-on-write, but another process truncating
+    // the file while it's mapped is undefined behavior
+    let mut mmap = unsafe { MmapOptions::new().map_copy(&file) }.map_err(io_error)?;
+
+    let base = mmap.as_mut_ptr();
+    let entries = Header::parse(&mmap)?;
+    entries.check_dtype::<T>()?;
+    let data_start = Header::data_start(&mmap)?;
+
+    let owner: Arc<dyn Any + Send + Sync> = Arc::new(mmap);
+    let mmap: &[u8] = unsafe { std::slice::from_raw_parts(base, data_start + entries.data_len) };
+
+    let mut tensors = HashMap::<String, Tensor<T>>::new();
+
+    for entry in entries.entries {
+        let bytes = &mmap[data_start + entry.begin..data_start + entry.end];
+        let len = bytes.len() / T::SIZE;
+
+        let is_zero_copy = cfg!(target_endian = "little")
+            && len > 0
+            && T::SIZE == mem::size_of::<T>()
+            && (bytes.as_ptr() as usize).is_multiple_of(mem::align_of::<T>())
+            && T::is_valid(bytes);
+
+        let tensor = if is_zero_copy {
+            unsafe {
+                let ptr = NonNull::new_unchecked(base.add(data_start + entry.begin) as *mut T);
+
+                unsafe_from_owner(ptr, len, entry.shape.as_slice(), owner.clone())
+            }
+        } else {
+            decode_items(bytes, &entry.shape)
+        };
+
+        tensors.insert(entry.name, tensor);
+    }
+
+    Ok(tensors)
+}
+
+///
+/// The tensors of a safetensors file with any mix of dtypes, decoded by
+/// name with `get`.
+///
+#[derive(Clone, Debug)]
+pub struct Safetensors {
+    contents: Vec<u8>,
+    data_start: usize,
+    entries: Vec<Entry>,
+}
+
+impl Safetensors {
+    pub fn read(filename: impl Into<Tensor<String>>) -> Result<Self, TensorError> {
+        let contents = fs::read(&filename.into()[0])
+            .map_err(|err| TensorError::Io { op: "safetensors::read", reason: err.to_string() })?;
+
+        Self::from_vec(contents)
+    }
+
+    pub fn decode(contents: &[u8]) -> Result<Self, TensorError> {
+        Self::from_vec(contents.to_vec())
+    }
+
+    fn from_vec(contents: Vec<u8>) -> Result<Self, TensorError> {
+        let header = Header::parse(&contents)?;
+        let data_start = Header::data_start(&contents)?;
+
+        Ok(Self { contents, data_start, entries: header.entries })
+    }
+
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    ///
+    /// Names in the order of their data in the file.
+    ///
+    pub fn names(&self) -> impl Iterator<Item = &str> {
+        self.entries.iter().map(|entry| entry.name.as_str())
+    }
+
+    pub fn contains(&self, name: &str) -> bool {
+        self.entry(name).is_some()
+    }
+
+    pub fn dtype(&self, name: &str) -> Option<&str> {
+        self.entry(name).map(|entry| entry.dtype.as_str())
+    }
+
+    pub fn shape(&self, name: &str) -> Option<&[usize]> {
+        self.entry(name).map(|entry| entry.shape.as_slice())
+    }
+
+    ///
+    /// Decodes the named tensor, whose dtype must match T.
+    ///
+    pub fn get<T: SafetensorsType>(&self, name: &str) -> Result<Tensor<T>, TensorError> {
+        let Some(entry) = self.entry(name) else {
+            return Err(TensorError::InvalidArgument {
+                op: "safetensors::get",
+                reason: format!("no tensor named {:?}", name),
+            });
+        };
+
+        if entry.dtype != T::DTYPE {
+            return Err(dtype_error::<T>(&entry.dtype));
+        }
+
+        let bytes = &self.contents[self.data_start + entry.begin..self.data_start + entry.end];
+
+        Ok(decode_items(bytes, &entry.shape))
+    }
+
+    fn entry(&self, name: &str) -> Option<&Entry> {
+        self.entries.iter().find(|entry| entry.name == name)
+    }
+}
+
+pub fn save<T: SafetensorsType>(
+    filename: impl Into<Tensor<String>>,
+    tensors: &HashMap<String, Tensor<T>>
+) -> Result<(), TensorError> {
+    fs::write(&filename.into()[0], encode(tensors).as_slice())
+        .map_err(|err| TensorError::Io { op: "safetensors::save", reason: err.to_string() })
+}
+
+///
+/// Decodes safetensors data, where each dtype must match T.
+///
+pub fn decode<T: SafetensorsType>(
+    contents: &[u8]
+) -> Result<HashMap<String, Tensor<T>>, TensorError> {
+    let header = Header::parse(contents)?;
+    header.check_dtype::<T>()?;
+
+    let data_start = Header::data_start(contents)?;
+
+    Ok(header.entries.into_iter().map(|entry| {
+        let bytes = &contents[data_start + entry.begin..data_start + entry.end];
+
+        (entry.name, decode_items(bytes, &entry.shape))
+    }).collect())
+}
+
+///
+/// Encodes the tensors sorted by name, with the header padded so the data
+/// starts on an 8-byte boundary.
+///
+pub fn encode<T: SafetensorsType>(tensors: &HashMap<String, Tensor<T>>) -> Tensor<u8> {
+    let mut names: Vec<&String> = tensors.keys().collect();
+    names.sort();
+
+    let mut header = String::from("{");
+    let mut offset = 0;
+
+    for (i, name) in names.iter().enumerate() {
+        let tensor = &tensors[*name];
+        let size = tensor.size() * T::SIZE;
+
+        if i > 0 {
+            header.push(',');
+        }
+
+        let shape: Vec<String> = tensor.shape().as_vec().iter().map(|d| d.to_string()).collect();
+
+        header.push_str(&format!(
+            "{}:{{\"dtype\":\"{}\",\"shape\":[{}],\"data_offsets\":[{},{}]}}",
+            json_string(name), T::DTYPE, shape.join(","), offset, offset + size
+        ));
+
+        offset += size;
+    }
+
+    header.push('}');
+
+    while ! header.len().is_multiple_of(8) {
+        header.push(' ');
+    }
+
+    let mut out = Vec::<u8>::with_capacity(8 + header.len() + offset);
+    out.extend((header.len() as u64).to_le_bytes());
+    out.extend(header.as_bytes());
+
+    for name in names {
+        for item in tensors[name].to_contiguous().as_slice() {
+            item.to_npy_bytes(&mut out);
+        }
+    }
+
+    let len = out.len();
+
+    Tensor::from_vec(out, [len])
+}
+
+fn decode_items<T: SafetensorsType>(bytes: &[u8], shape: &[usize]) -> Tensor<T> {
+    let items: Vec<T> = bytes.chunks_exact(T::SIZE)
+        .map(|b| T::from_npy_bytes(b, false))
+        .collect();
+
+    Tensor::from_vec(items, shape)
+}
+
+fn decode_error(reason: String) -> TensorError {
+    TensorError::Decode { op: "safetensors", reason }
+}
+
+#[derive(Clone, Debug)]
+struct Entry {
+    name: String,
+    dtype: String,
+    shape: Vec<usize>,
+    begin: usize,
+    end: usize,
+}
+
+struct Header {
+    entries: Vec<Entry>,
+    data_len: usize,
+}
+
+impl Header {
+    fn data_start(contents: &[u8]) -> Result<usize, TensorError> {
+        let len: [u8; 8] = contents.get(..8)
+            .ok_or_else(|| decode_error("missing header length".to_string()))?
+            .try_into().unwrap();
+
+        let len = u64::from_le_bytes(len) as usize;
+
+        match len.checked_add(8) {
+            Some(start) if start <= contents.len() => Ok(start),
+            _ => Err(decode_error(format!("header length {} exceeds the data", len))),
+        }
+    }
+
+    // Parses and validates the entries against the data size
+    fn parse(contents: &[u8]) -> Result<Header, TensorError> {
+        let start = Self::data_start(contents)?;
+        let data_len = contents.len() - start;
+
+        let mut parser = Parser { bytes: &contents[8..start], index: 0 };
+        let json = parser.value()?;
+        parser.end()?;
+
+        let Json::Object(items) = json else {
+            return Err(decode_error("header is not a JSON object".to_string()));
+        };
+
+        let mut entries = Vec::<Entry>::new();
+
+        for (name, value) in items {
+            if name == "__metadata__" {
+                continue;
+            }
+
+            let invalid = || decode_error(format!("invalid header entry for {:?}", name));
+
+            let dtype = value.get("dtype").and_then(Json::as_str).ok_or_else(invalid)?;
+
+            let item_size = dtype_size(dtype).ok_or_else(|| {
+                decode_error(format!("{:?} has unknown dtype {:?}", name, dtype))
+            })?;
+
+            let dtype = dtype.to_string();
+
+            let shape = value.get("shape").and_then(Json::as_usize_vec).ok_or_else(invalid)?;
+            let offsets = value.get("data_offsets").and_then(Json::as_usize_vec).ok_or_else(invalid)?;
+
+            let [begin, end] = offsets[..] else {
+                return Err(invalid());
+            };
+
+            let size = shape.iter()
+                .try_fold(item_size, |size, dim| size.checked_mul(*dim));
+
+            if begin > end || end > data_len || Some(end - begin) != size {
+                return Err(decode_error(format!(
+                    "{:?} offsets [{}, {}] don't match shape {:?} in {} bytes",
+                    name, begin, end, shape, data_len
+                )));
+            }
+
+            entries.push(Entry { name, dtype, shape, begin, end });
+        }
+
+        // overlapping entries would alias the same mapped bytes
+        entries.sort_by_key(|entry| (entry.begin, entry.end));
+
+        for pair in entries.windows(2) {
+            if pair[0].end > pair[1].begin {
+                return Err(decode_error(format!(
+                    "{:?} offsets [{}, {}] overlap {:?} offsets [{}, {}]",
+                    pair[1].name, pair[1].begin, pair[1].end,
+                    pair[0].name, pair[0].begin, pair[0].end
+                )));
+            }
+        }
+
+        Ok(Header { entries, data_len })
+    }
+
+    // Checks every entry has T's dtype, for the single-type loaders
+    fn check_dtype<T: SafetensorsType>(&self) -> Result<(), TensorError> {
+        match self.entries.iter().find(|entry| entry.dtype != T::DTYPE) {
+            Some(entry) => Err(dtype_error::<T>(&entry.dtype)),
+            None => Ok(()),
+        }
+    }
+}
+
+// Item size of each safetensors dtype, including ones without a Rust type
+fn dtype_size(dtype: &str) -> Option<usize> {
+    match dtype {
+        "BOOL" | "U8" | "I8" | "F8_E4M3" | "F8_E5M2" => Some(1),
+        "U16" | "I16" | "F16" | "BF16" => Some(2),
+        "U32" | "I32" | "F32" => Some(4),
+        "U64" | "I64" | "F64" => Some(8),
+        _ => None,
+    }
+}
+
+fn dtype_error<T: SafetensorsType>(found: &str) -> TensorError {
+    TensorError::DType {
+        op: "safetensors",
+        expected: T::DTYPE.to_string(),
+        found: found.to_string(),
+    }
+}
+
+fn json_string(value: &str) -> String {
+    let mut out = String::from("\"");
+
+    for c in value.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            c if (c as u32) < 0x20 => out.push_str(&format!("\\u{:04x}", c as u32)),
+            c => out.push(c),
+        }
+    }
+
+    out.push('"');
+    out
+}
+
+// Minimal JSON for the header, keeping numbers as text
+enum Json {
+    Null,
+    Bool,
+    Number(String),
+    String(String),
+    Array(Vec<Json>),
+    Object(Vec<(String, Json)>),
+}
+
+impl Json {
+    fn get(&self, key: &str) -> Option<&Json> {
+        match self {
+            Json::Object(items) => items.iter().find(|(k, _)| k == key).map(|(_, v)| v),
+            _ => None,
+        }
+    }
+
+    fn as_str(&self) -> Option<&str> {
+        match self {
+            Json::String(value) => Some(value),
+            _ => None,
+        }
+    }
+
+    fn as_usize_vec(&self) -> Option<Vec<usize>> {
+        match self {
+            Json::Array(items) => items.iter().map(|item| match item {
+                Json::Number(n) => n.parse::<usize>().ok(),
+                _ => None,
+            }).collect(),
+            _ => None,
+        }
+    }
+}
+
+struct Parser<'a> {
+    bytes: &'a [u8],
+    index: usize,
+}
+
+impl Parser<'_> {
+    fn value(&mut self) -> Result<Json, TensorError> {
+        self.skip_whitespace();
+
+        match self.peek()? {
+            b'{' => {
+                self.index += 1;
+                let mut items = Vec::<(String, Json)>::new();
+
+                if self.next_is(b'}')? {
+                    return Ok(Json::Object(items));
+                }
+
+                loop {
+                    self.skip_whitespace();
+                    let key = self.string()?;
+
+                    self.expect(b':')?;
+                    items.push((key, self.value()?));
+
+                    if self.next_is(b'}')? {
+                        return Ok(Json::Object(items));
+                    }
+
+                    self.expect(b',')?;
+                }
+            }
+            b'[' => {
+                self.index += 1;
+                let mut items = Vec::<Json>::new();
+
+                if self.next_is(b']')? {
+                    return Ok(Json::Array(items));
+                }
+
+                loop {
+                    items.push(self.value()?);
+
+                    if self.next_is(b']')? {
+                        return Ok(Json::Array(items));
+                    }
+
+                    self.expect(b',')?;
+                }
+            }
+            b'"' => Ok(Json::String(self.string()?)),
+            b't' => self.literal("true", Json::Bool),
+            b'f' => self.literal("false", Json::Bool),
+            b'n' => self.literal("null", Json::Null),
+            _ => {
+                let start = self.index;
+
+                while self.index < self.bytes.len()
+                    && matches!(self.bytes[self.index], b'0'..=b'9' | b'-' | b'+' | b'.' | b'e' | b'E')
+                {
+                    self.index += 1;
+                }
+
+                if start == self.index {
+                    return Err(self.error("unexpected character"));
+                }
+
+                let number = std::str::from_utf8(&self.bytes[start..self.index]).unwrap();
+
+                Ok(Json::Number(number.to_string()))
+            }
+        }
+    }
+
+    fn string(&mut self) -> Result<String, TensorError> {
+        if self.peek()? != b'"' {
+            return Err(self.error("expected a string"));
+        }
+        self.index += 1;
+
+        let mut out = Vec::<u8>::new();
+
+        loop {
+            let c = self.peek()?;
+            self.index += 1;
+
+            match c {
+                b'"' => break,
+                b'\\' => {
+                    let escape = self.peek()?;
+                    self.index += 1;
+
+                    match escape {
+                        b'"' | b'\\' | b'/' => out.push(escape),
+                        b'b' => out.push(0x08),
+                        b'f' => out.push(0x0c),
+                        b'n' => out.push(b'\n'),
+                        b'r' => out.push(b'\r'),
+                        b't' => out.push(b'\t'),
+                        b'u' => {
+                            let mut code = self.hex4()?;
+
+                            // surrogate pair
+                            if (0xd800..0xdc00).contains(&code) && self.bytes[self.index..].starts_with(b"\\u") {
+                                self.index += 2;
+                                let low = self.hex4()?;
+                                code = 0x10000 + ((code - 0xd800) << 10) + (low.wrapping_sub(0xdc00) & 0x3ff);
+                            }
+
+                            let c = char::from_u32(code).unwrap_or(char::REPLACEMENT_CHARACTER);
+                            out.extend(c.to_string().as_bytes());
+                        }
+                        _ => return Err(self.error("invalid escape")),
+                    }
+                }
+                c => out.push(c),
+            }
+        }
+
+        String::from_utf8(out).map_err(|_| self.error("invalid utf-8 string"))
+    }
+
+    fn hex4(&mut self) -> Result<u32, TensorError> {
+        let hex = self.bytes.get(self.index..self.index + 4)
+            .and_then(|hex| std::str::from_utf8(hex).ok())
+            .and_then(|hex| u32::from_str_radix(hex, 16).ok())
+            .ok_or_else(|| self.error("invalid unicode escape"))?;
+
+        self.index += 4;
+
+        Ok(hex)
+    }
+
+    fn literal(&mut self, text: &str, value: Json) -> Result<Json, TensorError> {
+        if self.bytes[self.index..].starts_with(text.as_bytes()) {
+            self.index += text.len();
+            Ok(value)
+        } else {
+            Err(self.error("unexpected literal"))
+        }
+    }
+
+    // Consumes the byte after whitespace if it matches
+    fn next_is(&mut self, c: u8) -> Result<bool, TensorError> {
+        self.skip_whitespace();
+
+        if self.peek()? == c {
+            self.index += 1;
+            Ok(true)
+        } else {
+            Ok(false)
+        }
+    }
+
+    fn expect(&mut self, c: u8) -> Result<(), TensorError> {
+        if self.next_is(c)? {
+            Ok(())
+        } else {
+            Err(self.error("unexpected character"))
+        }
+    }
+
+    fn end(&mut self) -> Result<(), TensorError> {
+        self.skip_whitespace();
+
+        if self.index == self.bytes.len() {
+            Ok(())
+        } else {
+            Err(self.error("trailing characters"))
+        }
+    }
+
+    fn peek(&self) -> Result<u8, TensorError> {
+        self.bytes.get(self.index).copied().ok_or_else(|| self.error("unexpected end"))
+    }
+
+    fn skip_whitespace(&mut self) {
+        while self.index < self.bytes.len() && self.bytes[self.index].is_ascii_whitespace() {
+            self.index += 1;
+        }
+    }
+
+    fn error(&self, reason: &str) -> TensorError {
+        decode_error(format!("{} in header at {}", reason, self.index))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::collections::HashMap;
+
+    use crate::{io::safetensors::{self, Safetensors}, ten, tensor::{Tensor, TensorError}};
+
+    fn safetensors_bytes(header: &str, data: &[u8]) -> Vec<u8> {
+        let mut out = (header.len() as u64).to_le_bytes().to_vec();
+        out.extend(header.as_bytes());
+        out.extend(data);
+        out
+    }
+
+    fn temp_path(name: &str) -> String {
+        std::env::temp_dir()
+            .join(format!("essay-{}-{}.safetensors", name, std::process::id()))
+            .to_str().unwrap().to_string()
+    }
+
+    #[test]
+    fn safetensors_decode() {
+        let mut data = Vec::<u8>::new();
+        data.extend(1.5f32.to_le_bytes());
+        data.extend((-2f32).to_le_bytes());
+        data.extend(3f32.to_le_bytes());
+        data.extend(4f32.to_le_bytes());
+
+        let header = r#"{"__metadata__": {"format": "pt"},
+            "w": {"dtype": "F32", "shape": [2, 1], "data_offsets": [0, 8]},
+            "bé": {"dtype": "F32", "shape": [2], "data_offsets": [8, 16]}}"#;
+
+        let tensors = safetensors::decode::<f32>(&safetensors_bytes(header, &data)).unwrap();
+
+        assert_eq!(tensors.len(), 2);
+        assert_eq!(tensors["w"], ten![[1.5], [-2.]]);
+        assert_eq!(tensors["bé"], ten![3., 4.]);
+    }
+
+    #[test]
+    fn safetensors_round_trip() {
+        let mut tensors = HashMap::<String, Tensor<i64>>::new();
+        tensors.insert("a".to_string(), ten![[1i64, 2], [3, 4]]);
+        tensors.insert("\"quoted\"".to_string(), ten![5i64]);
+
+        let data = safetensors::encode(&tensors);
+        let header_len = u64::from_le_bytes(data.as_slice()[..8].try_into().unwrap());
+        assert_eq!(header_len % 8, 0);
+
+        assert_eq!(safetensors::decode::<i64>(data.as_slice()).unwrap(), tensors);
+    }
+
+    #[test]
+    fn safetensors_errors() {
+        let data = safetensors_bytes(r#"{"x": {"dtype": "F16", "shape": [1], "data_offsets": [0, 2]}}"#, &[0; 2]);
+
+        assert_eq!(
+            safetensors::decode::<f32>(&data).unwrap_err(),
+            TensorError::DType { op: "safetensors", expected: "F32".to_string(), found: "F16".to_string() }
+        );
+
+        let data = safetensors_bytes(r#"{"x": {"dtype": "F32", "shape": [2], "data_offsets": [0, 4]}}"#, &[0; 4]);
+        assert!(matches!(safetensors::decode::<f32>(&data).unwrap_err(), TensorError::Decode { .. }));
+
+        let data = safetensors_bytes(r#"{"x": {"dtype": "F32""#, &[]);
+        assert!(matches!(safetensors::decode::<f32>(&data).unwrap_err(), TensorError::Decode { .. }));
+
+        assert!(matches!(safetensors::decode::<f32>(&[1, 2]).unwrap_err(), TensorError::Decode { .. }));
+
+        assert!(matches!(
+            safetensors::load::<f32>("/nonexistent/file.safetensors").unwrap_err(),
+            TensorError::Io { .. }
+        ));
+    }
+
+    #[test]
+    fn safetensors_file() {
+        let path = temp_path("file");
+
+        let mut tensors = HashMap::<String, Tensor<bool>>::new();
+        tensors.insert("mask".to_string(), ten![[true, false], [false, true]]);
+
+        safetensors::save(path.as_str(), &tensors).unwrap();
+
+        assert_eq!(safetensors::load::<bool>(path.as_str()).unwrap(), tensors);
+        assert_eq!(safetensors::load_mmap::<bool>(path.as_str()).unwrap(), tensors);
+
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn safetensors_mmap() {
+        let path = temp_path("mmap");
+
+        let mut tensors = HashMap::<String, Tensor>::new();
+        tensors.insert("w".to_string(), ten![[1., 2., 3.], [4., 5., 6.]]);
+        tensors.insert("b".to_string(), ten![0.5, -0.5]);
+
+        safetensors::save(path.as_str(), &tensors).unwrap();
+
+        let mut loaded = safetensors::load_mmap::<f32>(path.as_str()).unwrap();
+        assert_eq!(loaded, tensors);
+
+        // mapped tensors are copy-on-write and outlive the other tensors
+        let b = loaded.remove("b").unwrap();
+        drop(loaded);
+        assert_eq!(&b + &ten![1., 1.], ten![1.5, 0.5]);
+
+        assert_eq!(safetensors::load::<f32>(path.as_str()).unwrap(), tensors);
+
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn safetensors_mmap_unaligned() {
+        let path = temp_path("unaligned");
+
+        // the f32 data starts at an odd offset, so it is copied
+        let header = r#"{"x": {"dtype": "F32", "shape": [2], "data_offsets": [1, 9]}}"#;
+        let mut data = vec![0u8];
+        data.extend(2f32.to_le_bytes());
+        data.extend(3f32.to_le_bytes());
+
+        std::fs::write(&path, safetensors_bytes(header, &data)).unwrap();
+
+        assert_eq!(safetensors::load_mmap::<f32>(path.as_str()).unwrap()["x"], ten![2., 3.]);
+
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn safetensors_mixed_dtypes() {
+        let mut data = Vec::<u8>::new();
+        data.extend(7i64.to_le_bytes());
+        data.extend(8i64.to_le_bytes());
+        data.extend(1.5f32.to_le_bytes());
+        data.extend([0u8; 2]);
+
+        let header = r#"{"w": {"dtype": "F32", "shape": [1], "data_offsets": [16, 20]},
+            "ids": {"dtype": "I64", "shape": [2], "data_offsets": [0, 16]},
+            "h": {"dtype": "F16", "shape": [1], "data_offsets": [20, 22]}}"#;
+        let data = safetensors_bytes(header, &data);
+
+        assert!(matches!(safetensors::decode::<f32>(&data).unwrap_err(), TensorError::DType { .. }));
+
+        let tensors = Safetensors::decode(&data).unwrap();
+
+        assert_eq!(tensors.len(), 3);
+        assert_eq!(tensors.names().collect::<Vec<_>>(), vec!["ids", "w", "h"]);
+        assert_eq!(tensors.dtype("h"), Some("F16"));
+        assert_eq!(tensors.shape("ids"), Some(&[2][..]));
+
+        assert_eq!(tensors.get::<f32>("w").unwrap(), ten![1.5]);
+        assert_eq!(tensors.get::<i64>("ids").unwrap(), ten![7i64, 8]);
+
+        assert_eq!(
+            tensors.get::<f32>("ids").unwrap_err(),
+            TensorError::DType { op: "safetensors", expected: "F32".to_string(), found: "I64".to_string() }
+        );
+        assert!(matches!(tensors.get::<f32>("b").unwrap_err(), TensorError::InvalidArgument { .. }));
+    }
+
+    #[test]
+    fn safetensors_size_overflow() {
+        let header = r#"{"x": {"dtype": "F32", "shape": [4611686018427387904, 4], "data_offsets": [0, 0]}}"#;
+        let data = safetensors_bytes(header, &[]);
+
+        assert!(matches!(safetensors::decode::<f32>(&data).unwrap_err(), TensorError::Decode { .. }));
+        assert!(matches!(Safetensors::decode(&data).unwrap_err(), TensorError::Decode { .. }));
+    }
+
+    #[test]
+    fn safetensors_overlap() {
+        let path = temp_path("overlap");
+
+        // "b" shares bytes with "a", which would alias two mutable tensors
+        let header = r#"{"a": {"dtype": "F32", "shape": [2], "data_offsets": [0, 8]},
+            "b": {"dtype": "F32", "shape": [2], "data_offsets": [4, 12]}}"#;
+        let data = safetensors_bytes(header, &[0; 12]);
+
+        assert!(matches!(safetensors::decode::<f32>(&data).unwrap_err(), TensorError::Decode { .. }));
+
+        std::fs::write(&path, &data).unwrap();
+
+        assert!(matches!(
+            safetensors::load_mmap::<f32>(path.as_str()).unwrap_err(),
+            TensorError::Decode { .. }
+        ));
+
+        std::fs::remove_file(&path).unwrap();
+
+        // adjacent and empty entries don't overlap
+        let header = r#"{"b": {"dtype": "F32", "shape": [1], "data_offsets": [4, 8]},
+            "a": {"dtype": "F32", "shape": [1], "data_offsets": [0, 4]},
+            "e": {"dtype": "F32", "shape": [0], "data_offsets": [8, 8]}}"#;
+        let tensors = safetensors::decode::<f32>(&safetensors_bytes(header, &[0; 8])).unwrap();
+        assert_eq!(tensors.len(), 3);
+    }
+}
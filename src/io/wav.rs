@@ -0,0 +1,53 @@
+///
+/// Sample encoding of a WAV file. PCM formats are signed, except 8-bit
+/// PCM, which is unsigned with an offset of 128.
+///
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum WavFormat {
+    Pcm8,
+    Pcm16,
+    Pcm24,
+    Pcm32,
+    Float32,
+    Float64,
+}
+
+pub(super) const WAVE_FORMAT_PCM: u16 = 0x0001;
+pub(super) const WAVE_FORMAT_IEEE_FLOAT: u16 = 0x0003;
+pub(super) const WAVE_FORMAT_EXTENSIBLE: u16 = 0xfffe;
+
+impl WavFormat {
+    pub(super) fn from_tag(tag: u16, bits_per_sample: u16) -> Option<WavFormat> {
+        match (tag, bits_per_sample) {
+            (WAVE_FORMAT_PCM, 8) => Some(WavFormat::Pcm8),
+            (WAVE_FORMAT_PCM, 16) => Some(WavFormat::Pcm16),
+            (WAVE_FORMAT_PCM, 24) => Some(WavFormat::Pcm24),
+            (WAVE_FORMAT_PCM, 32) => Some(WavFormat::Pcm32),
+            (WAVE_FORMAT_IEEE_FLOAT, 32) => Some(WavFormat::Float32),
+            (WAVE_FORMAT_IEEE_FLOAT, 64) => Some(WavFormat::Float64),
+            _ => None,
+        }
+    }
+
+    pub(super) fn tag(&self) -> u16 {
+        match self {
+            WavFormat::Float32 | WavFormat::Float64 => WAVE_FORMAT_IEEE_FLOAT,
+            _ => WAVE_FORMAT_PCM,
+        }
+    }
+
+    pub fn bits_per_sample(&self) -> u16 {
+        match self {
+            WavFormat::Pcm8 => 8,
+            WavFormat::Pcm16 => 16,
+            WavFormat::Pcm24 => 24,
+            WavFormat::Pcm32 | WavFormat::Float32 => 32,
+            WavFormat::Float64 => 64,
+        }
+    }
+
+    #[inline]
+    pub(super) fn bytes_per_sample(&self) -> usize {
+        self.bits_per_sample() as usize / 8
+    }
+}
@@ -0,0 +1,574 @@
+use crate::tensor::TensorError;
+
+const LOCAL_HEADER: u32 = 0x0403_4b50;
+const CENTRAL_HEADER: u32 = 0x0201_4b50;
+const END_OF_CENTRAL: u32 = 0x0605_4b50;
+const ZIP64_END_OF_CENTRAL: u32 = 0x0606_4b50;
+const ZIP64_LOCATOR: u32 = 0x0706_4b50;
+
+const METHOD_STORED: u16 = 0;
+const METHOD_DEFLATE: u16 = 8;
+
+// 1980-01-01, the earliest DOS date
+const DOS_DATE: u16 = 0x21;
+
+///
+/// Reads the entries of a zip archive as (name, data), supporting stored
+/// and deflate entries and zip64 sizes.
+///
+pub(super) fn read_zip(op: &'static str, data: &[u8]) -> Result<Vec<(String, Vec<u8>)>, TensorError> {
+    let reader = Reader { op, data };
+
+    let (n_entries, mut offset) = reader.central_directory()?;
+
+    // each central header takes at least 46 bytes, which bounds an
+    // untrusted count
+    let mut entries = Vec::<(String, Vec<u8>)>::with_capacity(n_entries.min(data.len() / 46));
+
+    for _ in 0..n_entries {
+        if reader.u32(offset)? != CENTRAL_HEADER {
+            return Err(reader.error(format!("invalid central directory header at {}", offset)));
+        }
+
+        let flags = reader.u16(offset + 8)?;
+        let method = reader.u16(offset + 10)?;
+        let crc = reader.u32(offset + 16)?;
+        let mut compressed_size = reader.u32(offset + 20)? as u64;
+        let mut size = reader.u32(offset + 24)? as u64;
+        let name_len = reader.u16(offset + 28)? as usize;
+        let extra_len = reader.u16(offset + 30)? as usize;
+        let comment_len = reader.u16(offset + 32)? as usize;
+        let mut local_offset = reader.u32(offset + 42)? as u64;
+
+        let name = reader.bytes(offset + 46, name_len)?;
+        let name = String::from_utf8_lossy(name).to_string();
+
+        // zip64 extra field with the 64-bit values of the saturated fields
+        let extra = offset + 46 + name_len;
+        let mut i = extra;
+        while i + 4 <= extra + extra_len {
+            let id = reader.u16(i)?;
+            let len = reader.u16(i + 2)? as usize;
+
+            if id == 0x0001 {
+                let mut field = i + 4;
+
+                for value in [&mut size, &mut compressed_size, &mut local_offset] {
+                    if *value == u32::MAX as u64 {
+                        *value = reader.u64(field)?;
+                        field += 8;
+                    }
+                }
+            }
+
+            i += 4 + len;
+        }
+
+        if flags & 0x1 != 0 {
+            return Err(reader.error(format!("{} is encrypted", name)));
+        }
+
+        let local = local_offset as usize;
+        if reader.u32(local)? != LOCAL_HEADER {
+            return Err(reader.error(format!("invalid local header for {}", name)));
+        }
+
+        let start = local + 30 + reader.u16(local + 26)? as usize + reader.u16(local + 28)? as usize;
+        let compressed = reader.bytes(start, compressed_size as usize)?;
+
+        let value = match method {
+            METHOD_STORED => compressed.to_vec(),
+            METHOD_DEFLATE => inflate(compressed, size as usize)
+                .map_err(|reason| reader.error(format!("{} {}", name, reason)))?,
+            _ => return Err(reader.error(format!("{} has unsupported method {}", name, method))),
+        };
+
+        if value.len() as u64 != size || crc32(&value) != crc {
+            return Err(reader.error(format!("{} has an invalid size or crc", name)));
+        }
+
+        entries.push((name, value));
+
+        offset = extra + extra_len + comment_len;
+    }
+
+    Ok(entries)
+}
+
+///
+/// Writes the entries as an uncompressed zip archive.
+///
+pub(super) fn write_zip(op: &'static str, entries: &[(String, Vec<u8>)]) -> Result<Vec<u8>, TensorError> {
+    let too_large = || TensorError::InvalidArgument {
+        op,
+        reason: "archive exceeds the 4GB zip limit".to_string(),
+    };
+
+    if entries.len() > u16::MAX as usize {
+        return Err(TensorError::InvalidArgument {
+            op,
+            reason: format!("{} entries exceed the zip limit", entries.len()),
+        });
+    }
+
+    let mut out = Vec::<u8>::new();
+    let mut central = Vec::<u8>::new();
+
+    for (name, value) in entries {
+        let offset = u32::try_from(out.len()).map_err(|_| too_large())?;
+        let size = u32::try_from(value.len()).map_err(|_| too_large())?;
+        let crc = crc32(value);
+
+        // fields shared by the local and central headers
+        let mut common = Vec::<u8>::new();
+        common.extend(20u16.to_le_bytes());
+        common.extend(0u16.to_le_bytes());
+        common.extend(METHOD_STORED.to_le_bytes());
+        common.extend(0u16.to_le_bytes());
+        common.extend(DOS_DATE.to_le_bytes());
+        common.extend(crc.to_le_bytes());
+        common.extend(size.to_le_bytes());
+        common.extend(size.to_le_bytes());
+        common.extend((name.len() as u16).to_le_bytes());
+        common.extend(0u16.to_le_bytes());
+
+        out.extend(LOCAL_HEADER.to_le_bytes());
+        out.extend(&common);
+        out.extend(name.as_bytes());
+        out.extend(value);
+
+        central.extend(CENTRAL_HEADER.to_le_bytes());
+        central.extend(20u16.to_le_bytes());
+        central.extend(&common);
+        // comment length, disk, internal and external attributes
+        central.extend([0; 10]);
+        central.extend(offset.to_le_bytes());
+        central.extend(name.as_bytes());
+    }
+
+    let central_offset = u32::try_from(out.len()).map_err(|_| too_large())?;
+    let central_size = central.len() as u32;
+
+    out.extend(central);
+
+    out.extend(END_OF_CENTRAL.to_le_bytes());
+    out.extend([0; 4]);
+    out.extend((entries.len() as u16).to_le_bytes());
+    out.extend((entries.len() as u16).to_le_bytes());
+    out.extend(central_size.to_le_bytes());
+    out.extend(central_offset.to_le_bytes());
+    out.extend(0u16.to_le_bytes());
+
+    u32::try_from(out.len()).map_err(|_| too_large())?;
+
+    Ok(out)
+}
+
+struct Reader<'a> {
+    op: &'static str,
+    data: &'a [u8],
+}
+
+impl<'a> Reader<'a> {
+    // Number of entries and the offset of the central directory
+    fn central_directory(&self) -> Result<(usize, usize), TensorError> {
+        // the end record is at most a 64k comment from the end
+        let min_end = self.data.len().saturating_sub(22 + u16::MAX as usize);
+
+        let end = (min_end..self.data.len().saturating_sub(21)).rev()
+            .find(|i| self.u32(*i).ok() == Some(END_OF_CENTRAL))
+            .ok_or_else(|| self.error("missing end of central directory".to_string()))?;
+
+        let n_entries = self.u16(end + 10)? as usize;
+        let offset = self.u32(end + 16)? as usize;
+
+        if n_entries != u16::MAX as usize && offset != u32::MAX as usize {
+            return Ok((n_entries, offset));
+        }
+
+        if end < 20 || self.u32(end - 20)? != ZIP64_LOCATOR {
+            return Err(self.error("missing zip64 end of central directory".to_string()));
+        }
+
+        let end64 = self.u64(end - 20 + 8)? as usize;
+
+        if self.u32(end64)? != ZIP64_END_OF_CENTRAL {
+            return Err(self.error("invalid zip64 end of central directory".to_string()));
+        }
+
+        Ok((self.u64(end64 + 32)? as usize, self.u64(end64 + 48)? as usize))
+    }
+
+    fn bytes(&self, offset: usize, len: usize) -> Result<&'a [u8], TensorError> {
+        offset.checked_add(len)
+            .and_then(|end| self.data.get(offset..end))
+            .ok_or_else(|| self.error(format!("unexpected end of data at {}", offset)))
+    }
+
+    fn u16(&self, offset: usize) -> Result<u16, TensorError> {
+        Ok(u16::from_le_bytes(self.bytes(offset, 2)?.try_into().unwrap()))
+    }
+
+    fn u32(&self, offset: usize) -> Result<u32, TensorError> {
+        Ok(u32::from_le_bytes(self.bytes(offset, 4)?.try_into().unwrap()))
+    }
+
+    fn u64(&self, offset: usize) -> Result<u64, TensorError> {
+        Ok(u64::from_le_bytes(self.bytes(offset, 8)?.try_into().unwrap()))
+    }
+
+    fn error(&self, reason: String) -> TensorError {
+        TensorError::Decode { op: self.op, reason }
+    }
+}
+
+pub(super) fn crc32(data: &[u8]) -> u32 {
+    let mut table = [0u32; 256];
+    for (i, entry) in table.iter_mut().enumerate() {
+        let mut c = i as u32;
+        for _ in 0..8 {
+            c = if c & 1 != 0 { 0xedb8_8320 ^ (c >> 1) } else { c >> 1 };
+        }
+        *entry = c;
+    }
+
+    !data.iter().fold(!0u32, |crc, b| table[((crc ^ *b as u32) & 0xff) as usize] ^ (crc >> 8))
+}
+
+const LENGTH_BASE: [u16; 29] = [
+    3, 4, 5, 6, 7, 8, 9, 10, 11, 13, 15, 17, 19, 23, 27, 31,
+    35, 43, 51, 59, 67, 83, 99, 115, 131, 163, 195, 227, 258,
+];
+
+const LENGTH_EXTRA: [u8; 29] = [
+    0, 0, 0, 0, 0, 0, 0, 0, 1, 1, 1, 1, 2, 2, 2, 2,
+    3, 3, 3, 3, 4, 4, 4, 4, 5, 5, 5, 5, 0,
+];
+
+const DIST_BASE: [u16; 30] = [
+    1, 2, 3, 4, 5, 7, 9, 13, 17, 25, 33, 49, 65, 97, 129, 193,
+    257, 385, 513, 769, 1025, 1537, 2049, 3073, 4097, 6145, 8193, 12289, 16385, 24577,
+];
+
+const DIST_EXTRA: [u8; 30] = [
+    0, 0, 0, 0, 1, 1, 2, 2, 3, 3, 4, 4, 5, 5, 6, 6,
+    7, 7, 8, 8, 9, 9, 10, 10, 11, 11, 12, 12, 13, 13,
+];
+
+// Order of the code length code lengths in a dynamic block
+const CODE_LENGTH_ORDER: [usize; 19] = [
+    16, 17, 18, 0, 8, 7, 9, 6, 10, 5, 11, 4, 12, 3, 13, 2, 14, 1, 15,
+];
+
+///
+/// Decompresses raw deflate data (RFC 1951), failing if the output
+/// exceeds the declared size.
+///
+pub(super) fn inflate(data: &[u8], size: usize) -> Result<Vec<u8>, &'static str> {
+    let mut bits = BitReader { data, index: 0, bit: 0 };
+
+    // the declared size is untrusted, so only a typical ratio is reserved
+    let mut out = Vec::<u8>::with_capacity(size.min(data.len().saturating_mul(8)));
+
+    loop {
+        let is_final = bits.read(1)? == 1;
+
+        match bits.read(2)? {
+            0 => {
+                bits.align();
+
+                let len = bits.read(16)? as usize;
+                let nlen = bits.read(16)? as usize;
+
+                if len != !nlen & 0xffff {
+                    return Err("has an invalid stored block length");
+                }
+
+                if out.len() + len > size {
+                    return Err(TOO_LARGE);
+                }
+
+                let start = bits.index;
+                let block = data.get(start..start + len).ok_or("is truncated")?;
+
+                out.extend(block);
+                bits.index += len;
+            }
+            1 => {
+                let mut lengths = [0u8; 288];
+                lengths[..144].fill(8);
+                lengths[144..256].fill(9);
+                lengths[256..280].fill(7);
+                lengths[280..].fill(8);
+
+                let lit = Huffman::new(&lengths)?;
+                let dist = Huffman::new(&[5; 30])?;
+
+                inflate_block(&mut bits, &mut out, size, &lit, &dist)?;
+            }
+            2 => {
+                let (lit, dist) = dynamic_tables(&mut bits)?;
+
+                inflate_block(&mut bits, &mut out, size, &lit, &dist)?;
+            }
+            _ => return Err("has an invalid block type"),
+        }
+
+        if is_final {
+            return Ok(out);
+        }
+    }
+}
+
+fn dynamic_tables(bits: &mut BitReader) -> Result<(Huffman, Huffman), &'static str> {
+    let n_lit = bits.read(5)? as usize + 257;
+    let n_dist = bits.read(5)? as usize + 1;
+    let n_code = bits.read(4)? as usize + 4;
+
+    let mut code_lengths = [0u8; 19];
+    for i in CODE_LENGTH_ORDER.iter().take(n_code) {
+        code_lengths[*i] = bits.read(3)? as u8;
+    }
+
+    let code = Huffman::new(&code_lengths)?;
+
+    let mut lengths = Vec::<u8>::with_capacity(n_lit + n_dist);
+
+    while lengths.len() < n_lit + n_dist {
+        let (value, repeat) = match code.decode(bits)? {
+            symbol @ 0..=15 => (symbol as u8, 1),
+            16 => (*lengths.last().ok_or("repeats a missing length")?, 3 + bits.read(2)?),
+            17 => (0, 3 + bits.read(3)?),
+            _ => (0, 11 + bits.read(7)?),
+        };
+
+        lengths.extend(std::iter::repeat_n(value, repeat as usize));
+    }
+
+    if lengths.len() > n_lit + n_dist {
+        return Err("has too many code lengths");
+    }
+
+    Ok((Huffman::new(&lengths[..n_lit])?, Huffman::new(&lengths[n_lit..])?))
+}
+
+const TOO_LARGE: &str = "is larger than its declared size";
+
+fn inflate_block(
+    bits: &mut BitReader,
+    out: &mut Vec<u8>,
+    size: usize,
+    lit: &Huffman,
+    dist: &Huffman
+) -> Result<(), &'static str> {
+    loop {
+        let symbol = lit.decode(bits)? as usize;
+
+        match symbol {
+            0..=255 if out.len() >= size => return Err(TOO_LARGE),
+            0..=255 => out.push(symbol as u8),
+            256 => return Ok(()),
+            _ => {
+                let i = symbol - 257;
+                if i >= LENGTH_BASE.len() {
+                    return Err("has an invalid length code");
+                }
+
+                let len = LENGTH_BASE[i] as usize + bits.read(LENGTH_EXTRA[i] as u32)? as usize;
+
+                let i = dist.decode(bits)? as usize;
+                if i >= DIST_BASE.len() {
+                    return Err("has an invalid distance code");
+                }
+
+                let distance = DIST_BASE[i] as usize + bits.read(DIST_EXTRA[i] as u32)? as usize;
+
+                if distance > out.len() {
+                    return Err("has a distance before the start");
+                }
+
+                if out.len() + len > size {
+                    return Err(TOO_LARGE);
+                }
+
+                // copies one byte at a time because the copy can overlap
+                let start = out.len() - distance;
+                for j in 0..len {
+                    out.push(out[start + j]);
+                }
+            }
+        }
+    }
+}
+
+struct BitReader<'a> {
+    data: &'a [u8],
+    index: usize,
+    bit: u32,
+}
+
+impl BitReader<'_> {
+    // Reads n bits, least significant first
+    fn read(&mut self, n: u32) -> Result<u32, &'static str> {
+        let mut value = 0;
+
+        for i in 0..n {
+            let byte = *self.data.get(self.index).ok_or("is truncated")?;
+
+            value |= (((byte >> self.bit) & 1) as u32) << i;
+
+            self.bit += 1;
+            if self.bit == 8 {
+                self.bit = 0;
+                self.index += 1;
+            }
+        }
+
+        Ok(value)
+    }
+
+    fn align(&mut self) {
+        if self.bit > 0 {
+            self.bit = 0;
+            self.index += 1;
+        }
+    }
+}
+
+// Canonical Huffman code as the count of codes of each length and the
+// symbols sorted by code
+struct Huffman {
+    counts: [u16; 16],
+    symbols: Vec<u16>,
+}
+
+impl Huffman {
+    fn new(lengths: &[u8]) -> Result<Self, &'static str> {
+        let mut counts = [0u16; 16];
+        for len in lengths {
+            counts[*len as usize] += 1;
+        }
+        counts[0] = 0;
+
+        let mut offsets = [0u16; 16];
+        for i in 1..15 {
+            offsets[i + 1] = offsets[i] + counts[i];
+        }
+
+        let mut symbols = vec![0u16; lengths.len()];
+        for (symbol, len) in lengths.iter().enumerate() {
+            if *len != 0 {
+                symbols[offsets[*len as usize] as usize] = symbol as u16;
+                offsets[*len as usize] += 1;
+            }
+        }
+
+        Ok(Self { counts, symbols })
+    }
+
+    fn decode(&self, bits: &mut BitReader) -> Result<u16, &'static str> {
+        // code, first code and first symbol index of the current length
+        let mut code = 0i32;
+        let mut first = 0i32;
+        let mut index = 0i32;
+
+        for len in 1..16 {
+            code |= bits.read(1)? as i32;
+
+            let count = self.counts[len] as i32;
+            if code - first < count {
+                return Ok(self.symbols[(index + code - first) as usize]);
+            }
+
+            index += count;
+            first = (first + count) << 1;
+            code <<= 1;
+        }
+
+        Err("has an invalid Huffman code")
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{
+        crc32, inflate, read_zip, write_zip, END_OF_CENTRAL, ZIP64_END_OF_CENTRAL, ZIP64_LOCATOR,
+    };
+
+    #[test]
+    fn crc32_check() {
+        assert_eq!(crc32(b"123456789"), 0xcbf4_3926);
+        assert_eq!(crc32(b""), 0);
+    }
+
+    #[test]
+    fn inflate_blocks() {
+        // zlib.compressobj(9, zlib.DEFLATED, -15) of "hello hello hello hello"
+        let fixed = [0xcb, 0x48, 0xcd, 0xc9, 0xc9, 0x57, 0xc8, 0x40, 0x27, 0x01];
+        assert_eq!(inflate(&fixed, 23).unwrap(), b"hello hello hello hello");
+
+        let stored = [0x01, 0x03, 0x00, 0xfc, 0xff, b'a', b'b', b'c'];
+        assert_eq!(inflate(&stored, usize::MAX).unwrap(), b"abc");
+
+        assert!(inflate(&[0x07], usize::MAX).is_err());
+        assert!(inflate(&fixed[..4], usize::MAX).is_err());
+
+        // output past the declared size stops the inflate
+        assert!(inflate(&fixed, 22).is_err());
+        assert!(inflate(&fixed, 6).is_err());
+        assert!(inflate(&stored, 2).is_err());
+    }
+
+    #[test]
+    fn inflate_dynamic() {
+        // zlib.compressobj(9, zlib.DEFLATED, -15) uses a dynamic block
+        let dynamic = [
+            0x2d, 0x8a, 0xc1, 0x11, 0x00, 0x40, 0x10, 0xc1, 0x6a, 0x4d, 0xf4, 0xdf,
+            0xc3, 0x0d, 0xb7, 0x0f, 0x43, 0x20, 0x42, 0x65, 0x1d, 0xd2, 0x9c, 0x79,
+            0x1b, 0x31, 0xec, 0xf0, 0xe9, 0xd6, 0x92, 0x7b, 0xaf, 0xf0, 0x01,
+        ];
+
+        assert_eq!(
+            inflate(&dynamic, usize::MAX).unwrap(),
+            b"babaaabaabbaaaaacabaacaacabbbaababcaaaababbaabaabaacaaabbabaaacaabaacb"
+        );
+    }
+
+    #[test]
+    fn zip_round_trip() {
+        let entries = vec![
+            ("a.npy".to_string(), b"first".to_vec()),
+            ("b.npy".to_string(), Vec::new()),
+        ];
+
+        let data = write_zip("test", &entries).unwrap();
+
+        assert_eq!(read_zip("test", &data).unwrap(), entries);
+        assert!(read_zip("test", &data[..data.len() - 1]).is_err());
+    }
+
+    #[test]
+    fn zip64_entry_count() {
+        // zip64 end record claiming 2^60 entries, then its locator and the
+        // end record
+        let mut data = Vec::<u8>::new();
+        data.extend(ZIP64_END_OF_CENTRAL.to_le_bytes());
+        data.extend([0; 28]);
+        data.extend((1u64 << 60).to_le_bytes());
+        data.extend([0; 16]);
+
+        data.extend(ZIP64_LOCATOR.to_le_bytes());
+        data.extend([0; 4]);
+        data.extend(0u64.to_le_bytes());
+        data.extend([0; 4]);
+
+        data.extend(END_OF_CENTRAL.to_le_bytes());
+        data.extend([0; 6]);
+        data.extend(u16::MAX.to_le_bytes());
+        data.extend([0; 4]);
+        data.extend(u32::MAX.to_le_bytes());
+        data.extend([0; 2]);
+
+        assert!(read_zip("test", &data).is_err());
+    }
+}
@@ -1,9 +1,12 @@
 pub mod array;
+pub mod autodiff;
 pub mod signal;
 pub mod io;
 pub mod init;
 pub mod linalg;
 pub mod math;
+pub mod nn;
+pub mod optim;
 pub mod random;
 pub mod stats;
 pub mod tensor;
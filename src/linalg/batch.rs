@@ -0,0 +1,57 @@
+use crate::tensor::{Tensor, TensorError, Type};
+
+///
+/// Batch dims and the [m, n] matrix dims of a [.., m, n] tensor.
+///
+pub(super) fn matrix_dims<T: Type>(
+    op: &'static str, 
+    a: &Tensor<T>
+) -> Result<(Vec<usize>, usize, usize), TensorError> {
+    if a.rank() < 2 {
+        return Err(TensorError::InvalidShape {
+            op,
+            shape: a.shape().as_vec(),
+            reason: "requires a matrix with rank >= 2",
+        });
+    }
+
+    let dims = a.shape().as_vec();
+    let rank = dims.len();
+
+    Ok((dims[..rank - 2].to_vec(), dims[rank - 2], dims[rank - 1]))
+}
+
+///
+/// Batch dims and the matrix size n of a [.., n, n] tensor.
+///
+pub(super) fn square_dims<T: Type>(
+    op: &'static str, 
+    a: &Tensor<T>
+) -> Result<(Vec<usize>, usize), TensorError> {
+    let (batch, m, n) = matrix_dims(op, a)?;
+
+    if m != n {
+        return Err(TensorError::InvalidShape {
+            op,
+            shape: a.shape().as_vec(),
+            reason: "requires a square matrix",
+        });
+    }
+
+    Ok((batch, n))
+}
+
+/// Batch dims followed by the inner dims
+pub(super) fn with_inner(batch: &[usize], inner: &[usize]) -> Vec<usize> {
+    let mut dims = batch.to_vec();
+    dims.extend_from_slice(inner);
+    dims
+}
+
+///
+/// Splits contiguous data into n_batch matrices of len items. Unlike
+/// `chunks_exact` this also works for empty [.., 0, n] matrices.
+///
+pub(super) fn matrices<T>(data: &[T], n_batch: usize, len: usize) -> impl Iterator<Item = &[T]> {
+    (0..n_batch).map(move |i| &data[i * len..(i + 1) * len])
+}
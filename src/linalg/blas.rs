@@ -1,3 +1,11 @@
+use num_complex::Complex;
+use num_traits::{One, Zero};
+
+use crate::tensor::Type;
+
+#[cfg(feature="naive")]
+use super::blas_naive::gemm_naive;
+
 //
 // C = alpha * A * B + beta * C
 // A: m by k
@@ -24,12 +32,153 @@ pub unsafe fn sgemm(
     );
     
     #[cfg(feature="naive")]
-    sgemm_naive(
+    gemm_naive(
         m, k, n,
         alpha,
-        a, rsa as usize, csa as usize,
-        b, rsb as usize, csb as usize,
+        a, rsa, csa,
+        b, rsb, csb,
         beta,
-        c, rsc as usize, csc as usize,
+        c, rsc, csc,
     );
 }
+
+///
+/// Element types with a matrix multiply kernel, used by `matmul` and
+/// `matvec`. Floats and complex numbers use matrixmultiply and integers
+/// use a naive loop.
+///
+pub trait Gemm: Type + Copy + Zero + One {
+    /// C = alpha * A * B + beta * C
+    ///
+    /// # Safety
+    ///
+    /// The pointers and strides must address m x k, k x n and m x n
+    /// matrices. When beta is zero, C may be uninitialized.
+    #[allow(clippy::too_many_arguments)]
+    unsafe fn gemm(
+        m: usize, k: usize, n: usize,
+        alpha: Self,
+        a: *const Self, rsa: usize, csa: usize,
+        b: *const Self, rsb: usize, csb: usize,
+        beta: Self,
+        c: *mut Self, rsc: usize, csc: usize
+    );
+}
+
+impl Gemm for f32 {
+    #[inline]
+    unsafe fn gemm(
+        m: usize, k: usize, n: usize,
+        alpha: Self,
+        a: *const Self, rsa: usize, csa: usize,
+        b: *const Self, rsb: usize, csb: usize,
+        beta: Self,
+        c: *mut Self, rsc: usize, csc: usize
+    ) {
+        sgemm(m, k, n, alpha, a, rsa, csa, b, rsb, csb, beta, c, rsc, csc);
+    }
+}
+
+impl Gemm for f64 {
+    #[inline]
+    unsafe fn gemm(
+        m: usize, k: usize, n: usize,
+        alpha: Self,
+        a: *const Self, rsa: usize, csa: usize,
+        b: *const Self, rsb: usize, csb: usize,
+        beta: Self,
+        c: *mut Self, rsc: usize, csc: usize
+    ) {
+        #[cfg(not(feature="naive"))]
+        matrixmultiply::dgemm(
+            m, k, n,
+            alpha,
+            a, rsa as isize, csa as isize,
+            b, rsb as isize, csb as isize,
+            beta,
+            c, rsc as isize, csc as isize  
+        );
+
+        #[cfg(feature="naive")]
+        gemm_naive(m, k, n, alpha, a, rsa, csa, b, rsb, csb, beta, c, rsc, csc);
+    }
+}
+
+// Complex<T> is repr(C) with re, im, which matches matrixmultiply's [T; 2]
+impl Gemm for Complex<f32> {
+    #[inline]
+    unsafe fn gemm(
+        m: usize, k: usize, n: usize,
+        alpha: Self,
+        a: *const Self, rsa: usize, csa: usize,
+        b: *const Self, rsb: usize, csb: usize,
+        beta: Self,
+        c: *mut Self, rsc: usize, csc: usize
+    ) {
+        #[cfg(not(feature="naive"))]
+        matrixmultiply::cgemm(
+            matrixmultiply::CGemmOption::Standard,
+            matrixmultiply::CGemmOption::Standard,
+            m, k, n,
+            [alpha.re, alpha.im],
+            a.cast(), rsa as isize, csa as isize,
+            b.cast(), rsb as isize, csb as isize,
+            [beta.re, beta.im],
+            c.cast(), rsc as isize, csc as isize  
+        );
+
+        #[cfg(feature="naive")]
+        gemm_naive(m, k, n, alpha, a, rsa, csa, b, rsb, csb, beta, c, rsc, csc);
+    }
+}
+
+impl Gemm for Complex<f64> {
+    #[inline]
+    unsafe fn gemm(
+        m: usize, k: usize, n: usize,
+        alpha: Self,
+        a: *const Self, rsa: usize, csa: usize,
+        b: *const Self, rsb: usize, csb: usize,
+        beta: Self,
+        c: *mut Self, rsc: usize, csc: usize
+    ) {
+        #[cfg(not(feature="naive"))]
+        matrixmultiply::zgemm(
+            matrixmultiply::CGemmOption::Standard,
+            matrixmultiply::CGemmOption::Standard,
+            m, k, n,
+            [alpha.re, alpha.im],
+            a.cast(), rsa as isize, csa as isize,
+            b.cast(), rsb as isize, csb as isize,
+            [beta.re, beta.im],
+            c.cast(), rsc as isize, csc as isize  
+        );
+
+        #[cfg(feature="naive")]
+        gemm_naive(m, k, n, alpha, a, rsa, csa, b, rsb, csb, beta, c, rsc, csc);
+    }
+}
+
+macro_rules! gemm_naive_types {
+    ($($ty:ty)*) => {
+        $(
+            impl Gemm for $ty {
+                #[inline]
+                unsafe fn gemm(
+                    m: usize, k: usize, n: usize,
+                    alpha: Self,
+                    a: *const Self, rsa: usize, csa: usize,
+                    b: *const Self, rsb: usize, csb: usize,
+                    beta: Self,
+                    c: *mut Self, rsc: usize, csc: usize
+                ) {
+                    super::blas_naive::gemm_naive(
+                        m, k, n, alpha, a, rsa, csa, b, rsb, csb, beta, c, rsc, csc
+                    );
+                }
+            }
+        )*
+    }
+}
+
+gemm_naive_types!(i8 i16 i32 i64 i128 isize u8 u16 u32 u64 u128 usize);
@@ -1,15 +1,24 @@
+use std::ops::{Add, Mul};
 
-#[allow(dead_code)]
-unsafe fn sgemm_naive(
-    m: usize, k: usize, n: usize,
-    alpha: f32,
-    a: *const f32, rsa: usize, csa: usize,
-    b: *const f32, rsb: usize, csb: usize,
-    beta: f32,
-    c: *mut f32, rsc: usize, csc: usize
-) {
-    assert!(beta == 0.);
+use num_traits::Zero;
 
+//
+// C = alpha * A * B + beta * C
+//
+// Reference implementation for types without an optimized kernel, and for
+// all types with the "naive" feature.
+//
+#[allow(clippy::too_many_arguments)]
+pub(super) unsafe fn gemm_naive<T>(
+    m: usize, k: usize, n: usize,
+    alpha: T,
+    a: *const T, rsa: usize, csa: usize,
+    b: *const T, rsb: usize, csb: usize,
+    beta: T,
+    c: *mut T, rsc: usize, csc: usize
+) where
+    T: Copy + Zero + Add<Output=T> + Mul<Output=T>
+{
     macro_rules! a {
         ($i:expr, $j:expr) => (a.add(rsa * $i + csa * $j));
     }
@@ -24,13 +33,18 @@ unsafe fn sgemm_naive(
 
     for m in 0..m {
         for n in 0..n {
-            let mut v: f32 = 0.;
+            let mut v = T::zero();
             
             for k in 0..k {
-                v += *a![m, k] * *b![k, n];
+                v = v + *a![m, k] * *b![k, n];
             }
 
-            *c![m, n] = alpha * v;
+            // C may be uninitialized when beta is zero
+            if beta.is_zero() {
+                c![m, n].write(alpha * v);
+            } else {
+                c![m, n].write(alpha * v + beta * *c![m, n]);
+            }
         }
     }
 }
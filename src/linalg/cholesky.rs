@@ -0,0 +1,119 @@
+use num_traits::Float;
+
+use crate::tensor::{Tensor, TensorError, Type};
+
+use super::batch::{matrices, square_dims, with_inner};
+
+impl<T: Type + Float> Tensor<T> {
+    ///
+    /// Cholesky decomposition of a symmetric positive-definite matrix,
+    /// returning the lower triangular L where A = L L^T. Only the lower
+    /// triangle of A is used.
+    ///
+    #[inline]
+    pub fn cholesky(&self) -> Tensor<T> {
+        cholesky(self)
+    }
+
+    #[inline]
+    pub fn try_cholesky(&self) -> Result<Tensor<T>, TensorError> {
+        try_cholesky(self)
+    }
+}
+
+pub fn cholesky<T: Type + Float>(a: &Tensor<T>) -> Tensor<T> {
+    try_cholesky(a).unwrap_or_else(|err| panic!("{}", err))
+}
+
+pub fn try_cholesky<T: Type + Float>(a: &Tensor<T>) -> Result<Tensor<T>, TensorError> {
+    let (batch, n) = square_dims("cholesky", a)?;
+
+    let a = a.to_contiguous();
+
+    let mut vec = Vec::<T>::with_capacity(a.size());
+
+    let n_batch: usize = batch.iter().product();
+    for a in matrices(a.as_slice(), n_batch, n * n) {
+        let offset = vec.len();
+        vec.resize(offset + n * n, T::zero());
+        let l = &mut vec[offset..];
+
+        for j in 0..n {
+            let mut sum = a[j * n + j];
+            for k in 0..j {
+                sum = sum - l[j * n + k] * l[j * n + k];
+            }
+
+            // also catches NaN
+            if sum <= T::zero() || sum.is_nan() {
+                return Err(TensorError::LinAlg {
+                    op: "cholesky",
+                    reason: "matrix is not positive definite",
+                });
+            }
+
+            let d = sum.sqrt();
+            l[j * n + j] = d;
+
+            for i in j + 1..n {
+                let mut sum = a[i * n + j];
+                for k in 0..j {
+                    sum = sum - l[i * n + k] * l[j * n + k];
+                }
+
+                l[i * n + j] = sum / d;
+            }
+        }
+    }
+
+    Ok(Tensor::from_vec(vec, with_inner(&batch, &[n, n]).as_slice()))
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{linalg::{cholesky, try_cholesky}, ten, tensor::{Tensor, TensorError}};
+
+    #[test]
+    fn cholesky_2x2() {
+        let a = ten![[4., 2.], [2., 5.]];
+        let l = cholesky(&a);
+
+        assert_eq!(l, ten![[2., 0.], [1., 2.]]);
+        assert_eq!(l.matmul(&l.transpose()), a);
+    }
+
+    #[test]
+    fn cholesky_3x3_f64() {
+        let a = ten![[25., 15., -5.], [15., 18., 0.], [-5., 0., 11.]].map(|v| *v as f64);
+        let l = a.cholesky();
+
+        assert_eq!(l, ten![[5.0f64, 0., 0.], [3., 3., 0.], [-1., 1., 3.]]);
+    }
+
+    #[test]
+    fn cholesky_batch() {
+        let a = ten![[[4., 0.], [0., 9.]], [[1., 0.], [0., 16.]]];
+
+        assert_eq!(a.cholesky(), ten![[[2., 0.], [0., 3.]], [[1., 0.], [0., 4.]]]);
+    }
+
+    #[test]
+    fn cholesky_empty() {
+        assert_eq!(Tensor::<f32>::zeros([0, 0]).cholesky().shape().as_vec(), &[0, 0]);
+        assert_eq!(try_cholesky(&Tensor::<f32>::zeros([2, 0, 0])).unwrap().shape().as_vec(), &[2, 0, 0]);
+        assert_eq!(Tensor::<f32>::zeros([0, 2, 2]).cholesky().shape().as_vec(), &[0, 2, 2]);
+    }
+
+    #[test]
+    fn cholesky_not_positive_definite() {
+        assert_eq!(
+            try_cholesky(&ten![[1., 2.], [2., 1.]]).unwrap_err(),
+            TensorError::LinAlg { op: "cholesky", reason: "matrix is not positive definite" },
+        );
+
+        assert!(matches!(
+            ten![[1., 2., 3.]].try_cholesky().unwrap_err(),
+            TensorError::InvalidShape { op: "cholesky", .. },
+        ));
+    }
+}
@@ -0,0 +1,128 @@
+use num_traits::Float;
+
+use crate::tensor::{Tensor, Type};
+
+use super::{batch::{matrices, square_dims}, lu::LuFactor};
+
+impl<T: Type + Float> Tensor<T> {
+    /// Determinant of a square matrix, batched over the leading dims.
+    #[inline]
+    pub fn det(&self) -> Tensor<T> {
+        det(self)
+    }
+
+    ///
+    /// Sign and natural log of the absolute determinant, which avoids the
+    /// overflow and underflow of `det` for large matrices. A singular matrix
+    /// has sign 0 and a log determinant of -inf.
+    ///
+    #[inline]
+    pub fn slogdet(&self) -> (Tensor<T>, Tensor<T>) {
+        slogdet(self)
+    }
+}
+
+pub fn det<T: Type + Float>(a: &Tensor<T>) -> Tensor<T> {
+    let (batch, n) = square_dims("det", a).unwrap_or_else(|err| panic!("{}", err));
+
+    let a = a.to_contiguous();
+
+    let n_batch: usize = batch.iter().product();
+    let vec: Vec<T> = matrices(a.as_slice(), n_batch, n * n).map(|a| {
+        let factor = LuFactor::new(a, n, n);
+
+        (0..n).fold(factor.sign, |det, i| det * factor.diag(i))
+    }).collect();
+
+    Tensor::from_vec(vec, batch.as_slice())
+}
+
+pub fn slogdet<T: Type + Float>(a: &Tensor<T>) -> (Tensor<T>, Tensor<T>) {
+    let (batch, n) = square_dims("slogdet", a).unwrap_or_else(|err| panic!("{}", err));
+
+    let a = a.to_contiguous();
+
+    let mut signs = Vec::<T>::new();
+    let mut logdets = Vec::<T>::new();
+
+    let n_batch: usize = batch.iter().product();
+    for a in matrices(a.as_slice(), n_batch, n * n) {
+        let factor = LuFactor::new(a, n, n);
+
+        if factor.is_singular() {
+            signs.push(T::zero());
+            logdets.push(T::neg_infinity());
+        } else {
+            let mut sign = factor.sign;
+            let mut logdet = T::zero();
+
+            for i in 0..n {
+                let d = factor.diag(i);
+
+                sign = sign * d.signum();
+                logdet = logdet + d.abs().ln();
+            }
+
+            signs.push(sign);
+            logdets.push(logdet);
+        }
+    }
+
+    (
+        Tensor::from_vec(signs, batch.as_slice()),
+        Tensor::from_vec(logdets, batch.as_slice()),
+    )
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{linalg::{det, slogdet}, ten, tensor::{scalar, Tensor}};
+
+    #[test]
+    fn det_2x2() {
+        assert_eq!(det(&ten![[1., 2.], [3., 4.]]), scalar(-2.));
+        assert_eq!(det(&ten![[1., 2.], [2., 4.]]), scalar(0.));
+        assert_eq!(ten![[2., 0.], [0., 3.]].det(), scalar(6.));
+    }
+
+    #[test]
+    fn det_3x3_f64() {
+        let a = ten![[6., 1., 1.], [4., -2., 5.], [2., 8., 7.]].map(|v| *v as f64);
+
+        assert!((a.det()[0] - -306.).abs() < 1e-10);
+    }
+
+    #[test]
+    fn det_batch() {
+        let a = ten![[[2., 0.], [0., 3.]], [[0., 1.], [1., 0.]]];
+
+        assert_eq!(a.det(), ten![6., -1.]);
+    }
+
+    #[test]
+    fn det_empty() {
+        // like NumPy, the determinant of a 0x0 matrix is 1
+        assert_eq!(Tensor::<f32>::zeros([0, 0]).det(), scalar(1.));
+        assert_eq!(Tensor::<f32>::zeros([2, 0, 0]).det(), ten![1., 1.]);
+        assert_eq!(Tensor::<f32>::zeros([0, 2, 2]).det().shape().as_vec(), &[0]);
+
+        let (sign, logdet) = Tensor::<f32>::zeros([0, 0]).slogdet();
+        assert_eq!(sign, scalar(1.));
+        assert_eq!(logdet, scalar(0.));
+        assert_eq!(Tensor::<f32>::zeros([0, 2, 2]).slogdet().0.shape().as_vec(), &[0]);
+    }
+
+    #[test]
+    fn slogdet_basic() {
+        let (sign, logdet) = slogdet(&ten![[1., 2.], [3., 4.]]);
+        assert_eq!(sign, scalar(-1.));
+        assert!((logdet[0] - 2f32.ln()).abs() < 1e-6);
+
+        let a = ten![[[2., 0.], [0., 3.]], [[1., 2.], [2., 4.]]];
+        let (sign, logdet) = a.slogdet();
+
+        assert_eq!(sign, ten![1., 0.]);
+        assert!((logdet[0] - 6f32.ln()).abs() < 1e-6);
+        assert_eq!(logdet[1], f32::NEG_INFINITY);
+    }
+}
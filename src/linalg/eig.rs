@@ -0,0 +1,359 @@
+use num_complex::Complex;
+use num_traits::{Float, One, Zero};
+
+use crate::tensor::{Tensor, TensorError, Type};
+
+use super::batch::{matrices, square_dims, with_inner};
+
+type Eig<T> = (Tensor<Complex<T>>, Tensor<Complex<T>>);
+
+impl<T: Type + Float> Tensor<T> {
+    ///
+    /// Eigen decomposition of a general square matrix, returning (w, V)
+    /// where column i of V is the unit eigenvector for the eigenvalue w[i].
+    /// The eigenvalues are not ordered.
+    ///
+    #[inline]
+    pub fn eig(&self) -> Eig<T> {
+        eig(self)
+    }
+
+    #[inline]
+    pub fn try_eig(&self) -> Result<Eig<T>, TensorError> {
+        try_eig(self)
+    }
+}
+
+pub fn eig<T: Type + Float>(a: &Tensor<T>) -> Eig<T> {
+    try_eig(a).unwrap_or_else(|err| panic!("{}", err))
+}
+
+pub fn try_eig<T: Type + Float>(a: &Tensor<T>) -> Result<Eig<T>, TensorError> {
+    let (batch, n) = square_dims("eig", a)?;
+
+    let a = a.to_contiguous();
+
+    let n_batch: usize = batch.iter().product();
+    let mut w_vec = Vec::<Complex<T>>::with_capacity(n_batch * n);
+    let mut v_vec = Vec::<Complex<T>>::with_capacity(a.size());
+
+    for a in matrices(a.as_slice(), n_batch, n * n) {
+        let mut h: Vec<Complex<T>> = a.iter().map(|x| Complex::new(*x, T::zero())).collect();
+        let mut z = identity::<T>(n);
+
+        hessenberg(&mut h, &mut z, n);
+        schur(&mut h, &mut z, n)?;
+
+        w_vec.extend((0..n).map(|i| h[i * n + i]));
+        v_vec.extend(schur_vectors(&h, &z, n));
+    }
+
+    Ok((
+        Tensor::from_vec(w_vec, with_inner(&batch, &[n]).as_slice()),
+        Tensor::from_vec(v_vec, with_inner(&batch, &[n, n]).as_slice()),
+    ))
+}
+
+fn identity<T: Float>(n: usize) -> Vec<Complex<T>> {
+    let mut z = vec![Complex::zero(); n * n];
+    for i in 0..n {
+        z[i * n + i] = Complex::one();
+    }
+    z
+}
+
+// Unitary reduction to upper Hessenberg form with Givens rotations,
+// accumulating A = Z H Z^H
+fn hessenberg<T: Float>(h: &mut [Complex<T>], z: &mut [Complex<T>], n: usize) {
+    for k in 0..n.saturating_sub(2) {
+        for i in (k + 2..n).rev() {
+            let g = Givens::new(h[(i - 1) * n + k], h[i * n + k]);
+
+            g.rows(h, n, i - 1, 0..n);
+            g.cols(h, n, i - 1, 0..n);
+            g.cols(z, n, i - 1, 0..n);
+
+            h[i * n + k] = Complex::zero();
+        }
+    }
+}
+
+// Shifted QR iteration on a Hessenberg matrix, reducing it to the upper
+// triangular Schur form T with A = Z T Z^H
+fn schur<T: Float>(
+    h: &mut [Complex<T>], 
+    z: &mut [Complex<T>], 
+    n: usize
+) -> Result<(), TensorError> {
+    let eps = T::epsilon();
+    let h_norm = h.iter().fold(T::zero(), |s, x| s + x.norm());
+
+    let mut hi = n.saturating_sub(1);
+    let mut iter = 0;
+
+    while hi > 0 {
+        // find the start of the unreduced block ending at hi
+        let mut lo = hi;
+        while lo > 0 {
+            let scale = h[(lo - 1) * n + lo - 1].norm() + h[lo * n + lo].norm();
+            let scale = if scale == T::zero() { h_norm } else { scale };
+
+            if h[lo * n + lo - 1].norm() <= eps * scale {
+                h[lo * n + lo - 1] = Complex::zero();
+                break;
+            }
+
+            lo -= 1;
+        }
+
+        if lo == hi {
+            hi -= 1;
+            iter = 0;
+            continue;
+        }
+
+        iter += 1;
+        if iter > 30 * n {
+            return Err(TensorError::LinAlg { op: "eig", reason: "did not converge" });
+        }
+
+        let mu = if iter % 10 == 0 {
+            // exceptional shift to break cycles
+            let three_quarters = T::from(0.75).unwrap();
+            h[hi * n + hi] + Complex::new(h[hi * n + hi - 1].norm() * three_quarters, T::zero())
+        } else {
+            wilkinson_shift(h, n, hi)
+        };
+
+        for i in lo..=hi {
+            h[i * n + i] = h[i * n + i] - mu;
+        }
+
+        // H - mu I = Q R, then H' = R Q + mu I
+        let mut rotations = Vec::with_capacity(hi - lo);
+        for k in lo..hi {
+            let g = Givens::new(h[k * n + k], h[(k + 1) * n + k]);
+
+            g.rows(h, n, k, k..n);
+            h[(k + 1) * n + k] = Complex::zero();
+
+            rotations.push(g);
+        }
+
+        for (k, g) in (lo..hi).zip(&rotations) {
+            g.cols(h, n, k, 0..(k + 2).min(hi + 1));
+            g.cols(z, n, k, 0..n);
+        }
+
+        for i in lo..=hi {
+            h[i * n + i] = h[i * n + i] + mu;
+        }
+    }
+
+    Ok(())
+}
+
+// Eigenvalue of the trailing 2x2 block closer to the last diagonal item
+fn wilkinson_shift<T: Float>(h: &[Complex<T>], n: usize, hi: usize) -> Complex<T> {
+    let a = h[(hi - 1) * n + hi - 1];
+    let b = h[(hi - 1) * n + hi];
+    let c = h[hi * n + hi - 1];
+    let d = h[hi * n + hi];
+
+    let two = T::one() + T::one();
+    let half_tr = (a + d).unscale(two);
+    let disc = ((a - d).unscale(two).powu(2) + b * c).sqrt();
+
+    let l1 = half_tr + disc;
+    let l2 = half_tr - disc;
+
+    if (l1 - d).norm() < (l2 - d).norm() { l1 } else { l2 }
+}
+
+// Eigenvectors of the triangular T by back substitution, mapped by Z
+fn schur_vectors<T: Float>(t: &[Complex<T>], z: &[Complex<T>], n: usize) -> Vec<Complex<T>> {
+    let t_norm = t.iter().fold(T::zero(), |s, x| s + x.norm());
+    let small = (T::epsilon() * t_norm).max(T::min_positive_value());
+
+    let mut v = vec![Complex::zero(); n * n];
+    let mut y = vec![Complex::<T>::zero(); n];
+
+    for k in 0..n {
+        let lambda = t[k * n + k];
+
+        y.fill(Complex::zero());
+        y[k] = Complex::one();
+
+        for i in (0..k).rev() {
+            let mut sum = Complex::<T>::zero();
+            for j in i + 1..=k {
+                sum = sum + t[i * n + j] * y[j];
+            }
+
+            let mut d = t[i * n + i] - lambda;
+            if d.norm() < small {
+                d = Complex::new(small, T::zero());
+            }
+
+            y[i] = -sum / d;
+        }
+
+        let mut norm = T::zero();
+        for i in 0..n {
+            let mut x = Complex::<T>::zero();
+            for j in 0..=k {
+                x = x + z[i * n + j] * y[j];
+            }
+
+            v[i * n + k] = x;
+            norm = norm.hypot(x.norm());
+        }
+
+        for i in 0..n {
+            v[i * n + k] = v[i * n + k].unscale(norm);
+        }
+    }
+
+    v
+}
+
+///
+/// Complex rotation G = [[c, s], [-conj(s), c]] with a real c, chosen so
+/// G [a, b]^T has a zero second item.
+///
+struct Givens<T> {
+    c: T,
+    s: Complex<T>,
+}
+
+impl<T: Float> Givens<T> {
+    fn new(a: Complex<T>, b: Complex<T>) -> Self {
+        let a_norm = a.norm();
+        let r = a_norm.hypot(b.norm());
+
+        if r == T::zero() {
+            Self { c: T::one(), s: Complex::zero() }
+        } else if a_norm == T::zero() {
+            Self { c: T::zero(), s: Complex::one() }
+        } else {
+            Self {
+                c: a_norm / r,
+                s: a.unscale(a_norm) * b.conj().unscale(r),
+            }
+        }
+    }
+
+    // rows p and p + 1 of x = G x
+    fn rows(&self, x: &mut [Complex<T>], n: usize, p: usize, cols: std::ops::Range<usize>) {
+        for j in cols {
+            let xp = x[p * n + j];
+            let xq = x[(p + 1) * n + j];
+
+            x[p * n + j] = xp.scale(self.c) + self.s * xq;
+            x[(p + 1) * n + j] = -self.s.conj() * xp + xq.scale(self.c);
+        }
+    }
+
+    // columns p and p + 1 of x = x G^H
+    fn cols(&self, x: &mut [Complex<T>], n: usize, p: usize, rows: std::ops::Range<usize>) {
+        for i in rows {
+            let xp = x[i * n + p];
+            let xq = x[i * n + p + 1];
+
+            x[i * n + p] = xp.scale(self.c) + xq * self.s.conj();
+            x[i * n + p + 1] = -xp * self.s + xq.scale(self.c);
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use num_complex::Complex;
+
+    use crate::{linalg::eig, ten, tensor::Tensor};
+
+    fn assert_eig(a: &Tensor<f64>, w: &Tensor<Complex<f64>>, v: &Tensor<Complex<f64>>) {
+        let a = a.map(|x| Complex::new(*x, 0.));
+        let diff = &a.matmul(v) - &(v * w);
+
+        assert!(diff.norm().reduce_max()[0] < 1e-10, "{:?}", diff);
+    }
+
+    fn sorted(w: &Tensor<Complex<f64>>) -> Vec<(f64, f64)> {
+        let mut vec: Vec<(f64, f64)> = w.iter().map(|c| (c.re, c.im)).collect();
+        vec.sort_by(|a, b| a.partial_cmp(b).unwrap());
+        vec
+    }
+
+    #[test]
+    fn eig_real() {
+        let a = ten![[2., 0.], [1., 3.]].map(|x| *x as f64);
+        let (w, v) = eig(&a);
+
+        assert_eig(&a, &w, &v);
+
+        let w = sorted(&w);
+        assert!((w[0].0 - 2.).abs() < 1e-12 && w[0].1.abs() < 1e-12);
+        assert!((w[1].0 - 3.).abs() < 1e-12 && w[1].1.abs() < 1e-12);
+    }
+
+    #[test]
+    fn eig_rotation() {
+        let a = ten![[0., -1.], [1., 0.]].map(|x| *x as f64);
+        let (w, v) = a.eig();
+
+        assert_eig(&a, &w, &v);
+
+        let w = sorted(&w);
+        assert!(w[0].0.abs() < 1e-12 && (w[0].1 + 1.).abs() < 1e-12);
+        assert!(w[1].0.abs() < 1e-12 && (w[1].1 - 1.).abs() < 1e-12);
+    }
+
+    #[test]
+    fn eig_4x4() {
+        let a = ten![
+            [4., -2., 1., 0.],
+            [3., 6., -4., 2.],
+            [2., 1., 8., -1.],
+            [0., 5., 2., 1.]
+        ].map(|x| *x as f64);
+        let (w, v) = a.eig();
+
+        assert_eig(&a, &w, &v);
+
+        let trace: f64 = w.iter().map(|c| c.re).sum();
+        assert!((trace - 19.).abs() < 1e-10);
+    }
+
+    #[test]
+    fn eig_batch_f32() {
+        let a = ten![[[1., 2.], [0., 3.]], [[0., 1.], [-2., 0.]]];
+        let (w, v) = a.eig();
+
+        assert_eq!(w.shape().as_vec(), &[2, 2]);
+        assert_eq!(v.shape().as_vec(), &[2, 2, 2]);
+
+        let a = a.map(|x| Complex::new(*x, 0.));
+        for i in 0..2 {
+            let v = v.slice(i);
+            let diff = &a.slice(i).matmul(&v) - &(&v * &w.slice(i));
+
+            assert!(diff.norm().reduce_max()[0] < 1e-5);
+        }
+    }
+
+    #[test]
+    fn eig_empty() {
+        let (w, v) = Tensor::<f64>::zeros([0, 0]).eig();
+        assert_eq!(w.shape().as_vec(), &[0]);
+        assert_eq!(v.shape().as_vec(), &[0, 0]);
+
+        let (w, v) = Tensor::<f64>::zeros([2, 0, 0]).try_eig().unwrap();
+        assert_eq!(w.shape().as_vec(), &[2, 0]);
+        assert_eq!(v.shape().as_vec(), &[2, 0, 0]);
+
+        let (w, v) = Tensor::<f64>::zeros([0, 2, 2]).eig();
+        assert_eq!(w.shape().as_vec(), &[0, 2]);
+        assert_eq!(v.shape().as_vec(), &[0, 2, 2]);
+    }
+}
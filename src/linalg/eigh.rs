@@ -0,0 +1,188 @@
+use num_traits::Float;
+
+use crate::tensor::{Tensor, Type};
+
+use super::batch::{matrices, square_dims, with_inner};
+
+impl<T: Type + Float> Tensor<T> {
+    ///
+    /// Eigen decomposition of a symmetric matrix, returning (w, V) where
+    /// the eigenvalues w are ascending and column i of V is the unit
+    /// eigenvector for w[i]. Only the lower triangle of A is used.
+    ///
+    #[inline]
+    pub fn eigh(&self) -> (Tensor<T>, Tensor<T>) {
+        eigh(self)
+    }
+}
+
+pub fn eigh<T: Type + Float>(a: &Tensor<T>) -> (Tensor<T>, Tensor<T>) {
+    let (batch, n) = square_dims("eigh", a).unwrap_or_else(|err| panic!("{}", err));
+
+    let a = a.to_contiguous();
+
+    let n_batch: usize = batch.iter().product();
+    let mut w_vec = Vec::<T>::with_capacity(n_batch * n);
+    let mut v_vec = Vec::<T>::with_capacity(a.size());
+
+    for a in matrices(a.as_slice(), n_batch, n * n) {
+        let (w, v) = jacobi_eigh(a, n);
+
+        w_vec.extend(w);
+        v_vec.extend(v);
+    }
+
+    (
+        Tensor::from_vec(w_vec, with_inner(&batch, &[n]).as_slice()),
+        Tensor::from_vec(v_vec, with_inner(&batch, &[n, n]).as_slice()),
+    )
+}
+
+///
+/// Cyclic Jacobi eigen decomposition of the symmetric matrix given by the
+/// lower triangle of the row-major [n, n] a. Returns the ascending
+/// eigenvalues and the [n, n] eigenvectors as columns.
+///
+pub(super) fn jacobi_eigh<T: Float>(a: &[T], n: usize) -> (Vec<T>, Vec<T>) {
+    let mut a = a.to_vec();
+
+    for i in 0..n {
+        for j in i + 1..n {
+            a[i * n + j] = a[j * n + i];
+        }
+    }
+
+    let mut v = vec![T::zero(); n * n];
+    for i in 0..n {
+        v[i * n + i] = T::one();
+    }
+
+    let norm2 = a.iter().fold(T::zero(), |s, x| s + *x * *x);
+    let tol = T::epsilon() * T::epsilon() * norm2;
+
+    for _ in 0..100 {
+        let mut off = T::zero();
+        for p in 0..n {
+            for q in p + 1..n {
+                off = off + a[p * n + q] * a[p * n + q];
+            }
+        }
+
+        if off <= tol {
+            break;
+        }
+
+        for p in 0..n {
+            for q in p + 1..n {
+                let apq = a[p * n + q];
+
+                if apq == T::zero() {
+                    continue;
+                }
+
+                let two = T::one() + T::one();
+                let theta = (a[q * n + q] - a[p * n + p]) / (two * apq);
+                let t = theta.signum() / (theta.abs() + (theta * theta + T::one()).sqrt());
+                let c = T::one() / (t * t + T::one()).sqrt();
+                let s = t * c;
+
+                // A' = J^T A J
+                rotate_cols(&mut a, n, p, q, c, s);
+                rotate_rows(&mut a, n, p, q, c, s);
+                rotate_cols(&mut v, n, p, q, c, s);
+            }
+        }
+    }
+
+    let mut order: Vec<usize> = (0..n).collect();
+    order.sort_by(|i, j| a[i * n + i].partial_cmp(&a[j * n + j]).unwrap_or(std::cmp::Ordering::Equal));
+
+    let w = order.iter().map(|i| a[i * n + i]).collect();
+
+    let mut v_sorted = vec![T::zero(); n * n];
+    for (j, old) in order.iter().enumerate() {
+        for i in 0..n {
+            v_sorted[i * n + j] = v[i * n + old];
+        }
+    }
+
+    (w, v_sorted)
+}
+
+fn rotate_cols<T: Float>(x: &mut [T], n: usize, p: usize, q: usize, c: T, s: T) {
+    for k in 0..x.len() / n {
+        let xp = x[k * n + p];
+        let xq = x[k * n + q];
+
+        x[k * n + p] = c * xp - s * xq;
+        x[k * n + q] = s * xp + c * xq;
+    }
+}
+
+fn rotate_rows<T: Float>(x: &mut [T], n: usize, p: usize, q: usize, c: T, s: T) {
+    for k in 0..n {
+        let xp = x[p * n + k];
+        let xq = x[q * n + k];
+
+        x[p * n + k] = c * xp - s * xq;
+        x[q * n + k] = s * xp + c * xq;
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{linalg::eigh, ten, tensor::Tensor, test::assert_close};
+
+    #[test]
+    fn eigh_2x2() {
+        let a = ten![[2., 1.], [1., 2.]];
+        let (w, v) = eigh(&a);
+
+        assert_close(&w, &ten![1., 3.], 1e-4);
+        assert_close(&a.matmul(&v), &(&v * &w), 1e-4);
+        assert_close(&v.transpose().matmul(&v), &ten![[1., 0.], [0., 1.]], 1e-4);
+    }
+
+    #[test]
+    fn eigh_lower_triangle() {
+        let (w, _) = ten![[2., 100.], [1., 2.]].eigh();
+
+        assert_close(&w, &ten![1., 3.], 1e-4);
+    }
+
+    #[test]
+    fn eigh_3x3_f64() {
+        let a = ten![[4., 1., 2.], [1., 3., 0.], [2., 0., 5.]].map(|v| *v as f64);
+        let (w, v) = a.eigh();
+
+        let diff = (&a.matmul(&v) - &(&v * &w)).abs().reduce_max()[0];
+        assert!(diff < 1e-12);
+        assert!(w[0] <= w[1] && w[1] <= w[2]);
+        assert!((w.reduce_sum()[0] - 12.).abs() < 1e-12);
+    }
+
+    #[test]
+    fn eigh_batch() {
+        let a = ten![[[2., 0.], [0., 1.]], [[0., 1.], [1., 0.]]];
+        let (w, v) = a.eigh();
+
+        assert_eq!(w.shape().as_vec(), &[2, 2]);
+        assert_eq!(v.shape().as_vec(), &[2, 2, 2]);
+        assert_close(&w, &ten![[1., 2.], [-1., 1.]], 1e-4);
+    }
+
+    #[test]
+    fn eigh_empty() {
+        let (w, v) = Tensor::<f32>::zeros([0, 0]).eigh();
+        assert_eq!(w.shape().as_vec(), &[0]);
+        assert_eq!(v.shape().as_vec(), &[0, 0]);
+
+        let (w, v) = Tensor::<f32>::zeros([2, 0, 0]).eigh();
+        assert_eq!(w.shape().as_vec(), &[2, 0]);
+        assert_eq!(v.shape().as_vec(), &[2, 0, 0]);
+
+        let (w, v) = Tensor::<f32>::zeros([0, 2, 2]).eigh();
+        assert_eq!(w.shape().as_vec(), &[0, 2]);
+        assert_eq!(v.shape().as_vec(), &[0, 2, 2]);
+    }
+}
@@ -0,0 +1,423 @@
+use std::collections::HashMap;
+
+use crate::tensor::{Tensor, TensorError, Type};
+
+use super::{blas::Gemm, matmul::try_matmul};
+
+///
+/// Einstein summation over the operands, like NumPy's `einsum`.
+///
+/// Each comma-separated term labels the axes of one operand, and the
+/// optional `->` term gives the output axes. Without `->`, the output is
+/// the labels appearing exactly once, in alphabetical order. Labels
+/// missing from the output are summed, and a label repeated within a term
+/// takes the diagonal.
+///
+/// ```text
+/// einsum("ij,jk->ik", [&a, &b])     // matmul
+/// einsum("bij,bjk->bik", [&a, &b])  // batched matmul
+/// einsum("i,j->ij", [&x, &y])       // outer product
+/// einsum("ii->", [&a])              // trace
+/// einsum("ii->i", [&a])             // diagonal
+/// einsum("bi,bij,bj->b", [&x, &a, &y]) // batched bilinear form
+/// ```
+///
+/// Operands are contracted pairwise, greedily picking the pair with the
+/// smallest result, and each pair is lowered to a batched gemm.
+///
+pub fn einsum<'a, T: Gemm>(
+    subscripts: &str, 
+    operands: impl AsRef<[&'a Tensor<T>]>
+) -> Tensor<T> {
+    try_einsum(subscripts, operands).unwrap_or_else(|err| panic!("{}", err))
+}
+
+pub fn try_einsum<'a, T: Gemm>(
+    subscripts: &str, 
+    operands: impl AsRef<[&'a Tensor<T>]>
+) -> Result<Tensor<T>, TensorError> {
+    let operands = operands.as_ref();
+
+    let (inputs, output) = parse(subscripts, operands.len())?;
+    let dims = label_dims(&inputs, operands)?;
+
+    let mut terms: Vec<Term<T>> = inputs.into_iter()
+        .zip(operands)
+        .map(|(labels, tensor)| Term { labels, tensor: tensor.to_contiguous() })
+        .collect();
+
+    // take diagonals and sum out labels used by a single term up front
+    for i in 0..terms.len() {
+        let keep = kept_labels(&terms, &output, &[i]);
+
+        if keep != terms[i].labels {
+            terms[i] = terms[i].relabel(keep, &dims);
+        }
+    }
+
+    while terms.len() > 1 {
+        let (i, j) = next_pair(&terms, &output, &dims);
+
+        let b = terms.remove(j);
+        let a = terms.remove(i);
+
+        let is_kept = |c| output.contains(&c) || terms.iter().any(|t| t.labels.contains(&c));
+        let term = contract(a, b, is_kept, &dims)?;
+
+        terms.push(term);
+    }
+
+    let term = terms.pop().unwrap();
+
+    Ok(term.relabel(output, &dims).tensor)
+}
+
+struct Term<T: Type> {
+    labels: Vec<char>,
+    tensor: Tensor<T>,
+}
+
+impl<T: Gemm> Term<T> {
+    ///
+    /// Generic loop that moves the axes to the target labels, summing the
+    /// labels missing from the target and taking the diagonal of repeated
+    /// labels.
+    ///
+    fn relabel(&self, target: Vec<char>, dims: &HashMap<char, usize>) -> Term<T> {
+        if target == self.labels {
+            return Term { labels: target, tensor: self.tensor.clone() };
+        }
+
+        let mut distinct = Vec::<char>::new();
+        for c in &self.labels {
+            if ! distinct.contains(c) {
+                distinct.push(*c);
+            }
+        }
+
+        let in_strides = row_major_strides(&self.labels, dims);
+        let out_strides = row_major_strides(&target, dims);
+
+        let loop_dims: Vec<usize> = distinct.iter().map(|c| dims[c]).collect();
+
+        let stride_of = |labels: &[char], strides: &[usize], c: char| -> usize {
+            labels.iter().zip(strides).filter(|(l, _)| **l == c).map(|(_, s)| s).sum()
+        };
+
+        let in_step: Vec<usize> = distinct.iter()
+            .map(|c| stride_of(&self.labels, &in_strides, *c))
+            .collect();
+
+        let out_step: Vec<usize> = distinct.iter()
+            .map(|c| stride_of(&target, &out_strides, *c))
+            .collect();
+
+        let out_dims: Vec<usize> = target.iter().map(|c| dims[c]).collect();
+        let out_size: usize = out_dims.iter().product();
+        let mut out = vec![T::zero(); out_size];
+
+        let input = self.tensor.as_slice();
+        let n_loop: usize = loop_dims.iter().product();
+
+        let mut index = vec![0; distinct.len()];
+        let mut in_offset = 0;
+        let mut out_offset = 0;
+
+        for _ in 0..n_loop {
+            out[out_offset] = out[out_offset] + input[in_offset];
+
+            // odometer increment, last label fastest
+            for k in (0..index.len()).rev() {
+                index[k] += 1;
+                in_offset += in_step[k];
+                out_offset += out_step[k];
+
+                if index[k] < loop_dims[k] {
+                    break;
+                }
+
+                in_offset -= in_step[k] * index[k];
+                out_offset -= out_step[k] * index[k];
+                index[k] = 0;
+            }
+        }
+
+        Term { labels: target, tensor: Tensor::from_vec(out, out_dims.as_slice()) }
+    }
+}
+
+// Contract two terms with a batched gemm over [batch, a_only, sum] x
+// [batch, sum, b_only]
+fn contract<T: Gemm>(
+    a: Term<T>, 
+    b: Term<T>, 
+    is_kept: impl Fn(char) -> bool,
+    dims: &HashMap<char, usize>,
+) -> Result<Term<T>, TensorError> {
+    let shared = |c: &&char| b.labels.contains(c);
+
+    let batch: Vec<char> = a.labels.iter().filter(shared).filter(|c| is_kept(**c)).cloned().collect();
+    let sum: Vec<char> = a.labels.iter().filter(shared).filter(|c| ! is_kept(**c)).cloned().collect();
+    let a_only: Vec<char> = a.labels.iter().filter(|c| ! b.labels.contains(c)).cloned().collect();
+    let b_only: Vec<char> = b.labels.iter().filter(|c| ! a.labels.contains(c)).cloned().collect();
+
+    let size = |labels: &[char]| -> usize { labels.iter().map(|c| dims[c]).product() };
+
+    let (n_batch, m, k, n) = (size(&batch), size(&a_only), size(&sum), size(&b_only));
+
+    let a = a.relabel([batch.as_slice(), &a_only, &sum].concat(), dims);
+    let b = b.relabel([batch.as_slice(), &sum, &b_only].concat(), dims);
+
+    let a = a.tensor.try_reshape([n_batch, m, k])?;
+    let b = b.tensor.try_reshape([n_batch, k, n])?;
+
+    let labels = [batch, a_only, b_only].concat();
+    let out_dims: Vec<usize> = labels.iter().map(|c| dims[c]).collect();
+
+    let tensor = try_matmul(&a, &b)?.try_reshape(out_dims.as_slice())?;
+
+    Ok(Term { labels, tensor })
+}
+
+// Greedy order: the pair with the smallest intermediate result
+fn next_pair<T: Type>(
+    terms: &[Term<T>], 
+    output: &[char], 
+    dims: &HashMap<char, usize>
+) -> (usize, usize) {
+    let mut best = (0, 1);
+    let mut best_size = usize::MAX;
+
+    for i in 0..terms.len() {
+        for j in i + 1..terms.len() {
+            let size = kept_labels(terms, output, &[i, j]).iter()
+                .map(|c| dims[c])
+                .product();
+
+            if size < best_size {
+                best = (i, j);
+                best_size = size;
+            }
+        }
+    }
+
+    best
+}
+
+// Distinct labels of the selected terms still needed by the output or by
+// another term
+fn kept_labels<T: Type>(terms: &[Term<T>], output: &[char], selected: &[usize]) -> Vec<char> {
+    let mut keep = Vec::<char>::new();
+
+    for i in selected {
+        for c in &terms[*i].labels {
+            let is_used = output.contains(c) || terms.iter().enumerate()
+                .any(|(j, t)| ! selected.contains(&j) && t.labels.contains(c));
+
+            if is_used && ! keep.contains(c) {
+                keep.push(*c);
+            }
+        }
+    }
+
+    keep
+}
+
+fn row_major_strides(labels: &[char], dims: &HashMap<char, usize>) -> Vec<usize> {
+    let mut strides = vec![0; labels.len()];
+    let mut stride = 1;
+
+    for (i, c) in labels.iter().enumerate().rev() {
+        strides[i] = stride;
+        stride *= dims[c];
+    }
+
+    strides
+}
+
+fn parse(subscripts: &str, n_operands: usize) -> Result<(Vec<Vec<char>>, Vec<char>), TensorError> {
+    let error = |reason: String| TensorError::InvalidArgument { op: "einsum", reason };
+
+    let subscripts: String = subscripts.chars().filter(|c| ! c.is_whitespace()).collect();
+
+    let (lhs, rhs) = match subscripts.split_once("->") {
+        Some((lhs, rhs)) => (lhs, Some(rhs)),
+        None => (subscripts.as_str(), None),
+    };
+
+    let inputs: Vec<Vec<char>> = lhs.split(',').map(|t| t.chars().collect()).collect();
+
+    if inputs.len() != n_operands {
+        return Err(error(format!(
+            "subscripts '{}' have {} terms for {} operands", subscripts, inputs.len(), n_operands
+        )));
+    }
+
+    let invalid = lhs.chars().chain(rhs.unwrap_or("").chars())
+        .find(|c| *c != ',' && ! c.is_ascii_alphabetic());
+
+    if let Some(c) = invalid {
+        return Err(error(format!("invalid subscript '{}' in '{}'", c, subscripts)));
+    }
+
+    let output: Vec<char> = match rhs {
+        Some(rhs) => {
+            let output: Vec<char> = rhs.chars().collect();
+
+            for (i, c) in output.iter().enumerate() {
+                if output[..i].contains(c) {
+                    return Err(error(format!("output subscript '{}' is repeated", c)));
+                }
+
+                if ! inputs.iter().any(|t| t.contains(c)) {
+                    return Err(error(format!("output subscript '{}' is not in the inputs", c)));
+                }
+            }
+
+            output
+        }
+        None => {
+            let mut output: Vec<char> = inputs.iter().flatten().cloned()
+                .filter(|c| inputs.iter().flatten().filter(|d| *d == c).count() == 1)
+                .collect();
+
+            output.sort();
+            output
+        }
+    };
+
+    Ok((inputs, output))
+}
+
+fn label_dims<T: Type>(
+    inputs: &[Vec<char>], 
+    operands: &[&Tensor<T>]
+) -> Result<HashMap<char, usize>, TensorError> {
+    let mut dims = HashMap::<char, (usize, usize)>::new();
+
+    for (i, (labels, tensor)) in inputs.iter().zip(operands).enumerate() {
+        if labels.len() != tensor.rank() {
+            return Err(TensorError::InvalidShape {
+                op: "einsum",
+                shape: tensor.shape().as_vec(),
+                reason: "subscripts don't match the operand rank",
+            });
+        }
+
+        for (c, dim) in labels.iter().zip(tensor.shape().as_vec()) {
+            match dims.get(c) {
+                Some((prev, j)) if *prev != dim => {
+                    return Err(TensorError::ShapeMismatch {
+                        op: "einsum",
+                        a: operands[*j].shape().as_vec(),
+                        b: tensor.shape().as_vec(),
+                    });
+                }
+                Some(_) => {}
+                None => { dims.insert(*c, (dim, i)); }
+            }
+        }
+    }
+
+    Ok(dims.into_iter().map(|(c, (dim, _))| (c, dim)).collect())
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{linalg::{einsum, try_einsum}, ten, tensor::{scalar, TensorError}};
+
+    #[test]
+    fn einsum_matmul() {
+        let a = ten![[1., 2.], [3., 4.]];
+        let b = ten![[5., 6., 7.], [8., 9., 10.]];
+
+        assert_eq!(einsum("ij,jk->ik", [&a, &b]), a.matmul(&b));
+        assert_eq!(einsum("ij,jk", [&a, &b]), a.matmul(&b));
+        assert_eq!(einsum("ij,jk->ki", [&a, &b]), a.matmul(&b).transpose());
+        assert_eq!(einsum("ij,j->i", [&a, &ten![1., 1.]]), ten![3., 7.]);
+    }
+
+    #[test]
+    fn einsum_batch_matmul() {
+        let a = ten![[[1., 2.], [3., 4.]], [[1., 0.], [0., 1.]]];
+        let b = ten![[[1., 0.], [0., 1.]], [[5., 6.], [7., 8.]]];
+
+        assert_eq!(
+            einsum("bij,bjk->bik", [&a, &b]), 
+            ten![[[1., 2.], [3., 4.]], [[5., 6.], [7., 8.]]]
+        );
+    }
+
+    #[test]
+    fn einsum_single_operand() {
+        let a = ten![[1., 2.], [3., 4.]];
+
+        assert_eq!(einsum("ii->", [&a]), scalar(5.));
+        assert_eq!(einsum("ii->i", [&a]), ten![1., 4.]);
+        assert_eq!(einsum("ij->ji", [&a]), ten![[1., 3.], [2., 4.]]);
+        assert_eq!(einsum("ij->i", [&a]), ten![3., 7.]);
+        assert_eq!(einsum("ij->", [&a]), scalar(10.));
+        assert_eq!(einsum("ij", [&a]), a);
+        assert_eq!(einsum("ji", [&a]), ten![[1., 3.], [2., 4.]]);
+    }
+
+    #[test]
+    fn einsum_outer_and_dot() {
+        let x = ten![1., 2.];
+        let y = ten![3., 4., 5.];
+
+        assert_eq!(einsum("i,j->ij", [&x, &y]), ten![[3., 4., 5.], [6., 8., 10.]]);
+        assert_eq!(einsum("i,i->", [&x, &x]), scalar(5.));
+        assert_eq!(einsum("i,i->i", [&x, &x]), ten![1., 4.]);
+    }
+
+    #[test]
+    fn einsum_bilinear() {
+        let x = ten![[1., 0.], [1., 1.]];
+        let a = ten![[[2., 1.], [1., 2.]], [[1., 0.], [0., 1.]]];
+        let y = ten![[0., 1.], [2., 3.]];
+
+        // x_b^T A_b y_b
+        assert_eq!(einsum("bi,bij,bj->b", [&x, &a, &y]), ten![1., 5.]);
+    }
+
+    #[test]
+    fn einsum_chain_i32() {
+        let a = ten![[1, 2], [3, 4]];
+        let b = ten![[0, 1], [1, 0]];
+        let c = ten![[2, 0], [0, 2]];
+
+        assert_eq!(einsum("ij,jk,kl->il", [&a, &b, &c]), a.matmul(&b).matmul(&c));
+    }
+
+    #[test]
+    fn einsum_errors() {
+        let a = ten![[1., 2.], [3., 4.]];
+        let b = ten![1., 2., 3.];
+
+        assert!(matches!(
+            try_einsum("ij,j->i", [&a, &b]),
+            Err(TensorError::ShapeMismatch { op: "einsum", .. })
+        ));
+
+        assert!(matches!(
+            try_einsum("ijk->i", [&a]),
+            Err(TensorError::InvalidShape { op: "einsum", .. })
+        ));
+
+        assert!(matches!(
+            try_einsum("ij,jk->ik", [&a]),
+            Err(TensorError::InvalidArgument { op: "einsum", .. })
+        ));
+
+        assert!(matches!(
+            try_einsum("ij->iz", [&a]),
+            Err(TensorError::InvalidArgument { op: "einsum", .. })
+        ));
+
+        assert!(matches!(
+            try_einsum("i1->i", [&a]),
+            Err(TensorError::InvalidArgument { op: "einsum", .. })
+        ));
+    }
+}
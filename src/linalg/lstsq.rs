@@ -0,0 +1,87 @@
+use num_traits::Float;
+
+use crate::tensor::{Tensor, TensorError, Type};
+
+use super::{batch::matrix_dims, matmul::try_matmul, svd::pinv, Gemm};
+
+impl<T: Type + Float + Gemm> Tensor<T> {
+    ///
+    /// Least-squares solution x minimizing ||A x - b||, where A is
+    /// [.., m, n] and b is either a vector [m] or a matrix [.., m, k].
+    /// Rank-deficient systems return the minimum-norm solution.
+    ///
+    #[inline]
+    pub fn lstsq(&self, b: &Tensor<T>) -> Tensor<T> {
+        lstsq(self, b)
+    }
+
+    #[inline]
+    pub fn try_lstsq(&self, b: &Tensor<T>) -> Result<Tensor<T>, TensorError> {
+        try_lstsq(self, b)
+    }
+}
+
+pub fn lstsq<T: Type + Float + Gemm>(a: &Tensor<T>, b: &Tensor<T>) -> Tensor<T> {
+    try_lstsq(a, b).unwrap_or_else(|err| panic!("{}", err))
+}
+
+pub fn try_lstsq<T: Type + Float + Gemm>(
+    a: &Tensor<T>, 
+    b: &Tensor<T>
+) -> Result<Tensor<T>, TensorError> {
+    let (_, m, _) = matrix_dims("lstsq", a)?;
+
+    let b_rows = if b.rank() == 1 { b.cols() } else { b.rows() };
+
+    if b.rank() == 0 || b_rows != m {
+        return Err(TensorError::ShapeMismatch {
+            op: "lstsq",
+            a: a.shape().as_vec(),
+            b: b.shape().as_vec(),
+        });
+    }
+
+    let a_pinv = pinv(a);
+
+    if b.rank() == 1 {
+        a_pinv.try_matvec(b)
+    } else {
+        try_matmul(&a_pinv, b)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{linalg::{lstsq, try_lstsq}, ten, tensor::TensorError, test::assert_close};
+
+    #[test]
+    fn lstsq_line_fit() {
+        // least-squares line y = 1.3 + 1.8 x
+        let a = ten![[1., 0.], [1., 1.], [1., 2.], [1., 3.]];
+        let y = ten![1.5, 2.5, 5.5, 6.5];
+
+        assert_close(&lstsq(&a, &y), &ten![1.3, 1.8], 1e-4);
+        assert_close(&a.lstsq(&ten![[1.], [3.], [5.], [7.]]), &ten![[1.], [2.]], 1e-4);
+    }
+
+    #[test]
+    fn lstsq_underdetermined() {
+        // minimum norm solution of x + y = 2
+        assert_close(&ten![[1., 1.]].lstsq(&ten![2.]), &ten![1., 1.], 1e-4);
+    }
+
+    #[test]
+    fn lstsq_batch() {
+        let a = ten![[[2., 0.], [0., 4.]], [[1., 0.], [0., 1.]]];
+
+        assert_close(&a.lstsq(&ten![2., 4.]), &ten![[1., 1.], [2., 4.]], 1e-4);
+    }
+
+    #[test]
+    fn lstsq_mismatch() {
+        assert!(matches!(
+            try_lstsq(&ten![[1., 0.], [0., 1.]], &ten![1., 2., 3.]).unwrap_err(),
+            TensorError::ShapeMismatch { op: "lstsq", .. },
+        ));
+    }
+}
@@ -0,0 +1,234 @@
+use num_traits::Float;
+
+use crate::tensor::{Tensor, Type};
+
+use super::batch::{matrices, matrix_dims, with_inner};
+
+impl<T: Type + Float> Tensor<T> {
+    ///
+    /// LU decomposition with partial pivoting, returning (P, L, U) where
+    /// A = P L U. For an [m, n] matrix with k = min(m, n), P is [m, m],
+    /// L is unit lower triangular [m, k] and U is upper triangular [k, n].
+    ///
+    #[inline]
+    pub fn lu(&self) -> (Tensor<T>, Tensor<T>, Tensor<T>) {
+        lu(self)
+    }
+}
+
+pub fn lu<T: Type + Float>(a: &Tensor<T>) -> (Tensor<T>, Tensor<T>, Tensor<T>) {
+    let (batch, m, n) = matrix_dims("lu", a).unwrap_or_else(|err| panic!("{}", err));
+    let k = m.min(n);
+
+    let a = a.to_contiguous();
+
+    let n_batch: usize = batch.iter().product();
+    let mut p_vec = Vec::<T>::with_capacity(n_batch * m * m);
+    let mut l_vec = Vec::<T>::with_capacity(n_batch * m * k);
+    let mut u_vec = Vec::<T>::with_capacity(n_batch * k * n);
+
+    for a in matrices(a.as_slice(), n_batch, m * n) {
+        let factor = LuFactor::new(a, m, n);
+
+        for i in 0..m {
+            for j in 0..m {
+                p_vec.push(if factor.perm[j] == i { T::one() } else { T::zero() });
+            }
+        }
+
+        for i in 0..m {
+            for j in 0..k {
+                l_vec.push(match i.cmp(&j) {
+                    std::cmp::Ordering::Greater => factor.lu[i * n + j],
+                    std::cmp::Ordering::Equal => T::one(),
+                    std::cmp::Ordering::Less => T::zero(),
+                });
+            }
+        }
+
+        for i in 0..k {
+            for j in 0..n {
+                u_vec.push(if i <= j { factor.lu[i * n + j] } else { T::zero() });
+            }
+        }
+    }
+
+    (
+        Tensor::from_vec(p_vec, with_inner(&batch, &[m, m]).as_slice()),
+        Tensor::from_vec(l_vec, with_inner(&batch, &[m, k]).as_slice()),
+        Tensor::from_vec(u_vec, with_inner(&batch, &[k, n]).as_slice()),
+    )
+}
+
+///
+/// Packed LU factors of a row-major [m, n] matrix, where L's unit diagonal
+/// is implicit and row i of L U is row perm[i] of A.
+///
+pub(super) struct LuFactor<T> {
+    pub(super) lu: Vec<T>,
+    pub(super) perm: Vec<usize>,
+    pub(super) sign: T,
+    n: usize,
+}
+
+impl<T: Float> LuFactor<T> {
+    pub(super) fn new(a: &[T], m: usize, n: usize) -> Self {
+        assert_eq!(a.len(), m * n);
+
+        let mut lu = a.to_vec();
+        let mut perm: Vec<usize> = (0..m).collect();
+        let mut sign = T::one();
+
+        for k in 0..m.min(n) {
+            let mut p = k;
+            for i in k + 1..m {
+                if lu[i * n + k].abs() > lu[p * n + k].abs() {
+                    p = i;
+                }
+            }
+
+            if p != k {
+                for j in 0..n {
+                    lu.swap(k * n + j, p * n + j);
+                }
+                perm.swap(k, p);
+                sign = -sign;
+            }
+
+            let pivot = lu[k * n + k];
+
+            if pivot != T::zero() {
+                for i in k + 1..m {
+                    let f = lu[i * n + k] / pivot;
+                    lu[i * n + k] = f;
+
+                    for j in k + 1..n {
+                        lu[i * n + j] = lu[i * n + j] - f * lu[k * n + j];
+                    }
+                }
+            }
+        }
+
+        Self { lu, perm, sign, n }
+    }
+
+    #[inline]
+    pub(super) fn diag(&self, i: usize) -> T {
+        self.lu[i * self.n + i]
+    }
+
+    pub(super) fn is_singular(&self) -> bool {
+        (0..self.n).any(|i| self.diag(i) == T::zero())
+    }
+
+    ///
+    /// Solves A x = b for a square A, where b is [n, k] row-major.
+    ///
+    pub(super) fn solve(&self, b: &[T], k: usize) -> Vec<T> {
+        let n = self.n;
+
+        let mut x = Vec::<T>::with_capacity(n * k);
+        for i in 0..n {
+            x.extend_from_slice(&b[self.perm[i] * k..(self.perm[i] + 1) * k]);
+        }
+
+        // forward substitution with the unit lower L
+        for i in 0..n {
+            for j in 0..i {
+                let l = self.lu[i * n + j];
+
+                for c in 0..k {
+                    x[i * k + c] = x[i * k + c] - l * x[j * k + c];
+                }
+            }
+        }
+
+        // back substitution with the upper U
+        for i in (0..n).rev() {
+            for j in i + 1..n {
+                let u = self.lu[i * n + j];
+
+                for c in 0..k {
+                    x[i * k + c] = x[i * k + c] - u * x[j * k + c];
+                }
+            }
+
+            let d = self.diag(i);
+            for c in 0..k {
+                x[i * k + c] = x[i * k + c] / d;
+            }
+        }
+
+        x
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{linalg::lu, ten, tensor::Tensor};
+
+    #[test]
+    fn lu_2x2() {
+        let a = ten![[1., 2.], [3., 4.]];
+
+        let (p, l, u) = lu(&a);
+
+        assert_eq!(p, ten![[0., 1.], [1., 0.]]);
+        assert_eq!(l, ten![[1., 0.], [1. / 3., 1.]]);
+        assert_eq!(u, ten![[3., 4.], [0., 2. - 4. / 3.]]);
+
+        assert_eq!(p.matmul(&l.matmul(&u)), a);
+    }
+
+    #[test]
+    fn lu_rect() {
+        let a = ten![[1., 2., 3.], [4., 5., 6.]];
+        let (p, l, u) = a.lu();
+
+        assert_eq!(p.shape().as_vec(), &[2, 2]);
+        assert_eq!(l.shape().as_vec(), &[2, 2]);
+        assert_eq!(u.shape().as_vec(), &[2, 3]);
+        assert_eq!(p.matmul(&l.matmul(&u)), a);
+
+        let a = ten![[1., 2.], [3., 4.], [5., 6.]];
+        let (p, l, u) = a.lu();
+
+        assert_eq!(p.shape().as_vec(), &[3, 3]);
+        assert_eq!(l.shape().as_vec(), &[3, 2]);
+        assert_eq!(u.shape().as_vec(), &[2, 2]);
+        assert!((&p.matmul(&l.matmul(&u)) - &a).abs().reduce_max()[0] < 1e-6);
+    }
+
+    #[test]
+    fn lu_batch() {
+        let a = ten![[[2., 0.], [0., 3.]], [[0., 1.], [1., 0.]]].map(|v| *v as f64);
+        let (p, l, u) = a.lu();
+
+        assert_eq!(p.shape().as_vec(), &[2, 2, 2]);
+        assert_eq!(p.matmul(&l.matmul(&u)), a);
+        assert_eq!(u, ten![[[2.0f64, 0.], [0., 3.]], [[1., 0.], [0., 1.]]]);
+    }
+
+    #[test]
+    fn lu_empty() {
+        let (p, l, u) = Tensor::<f32>::zeros([0, 0]).lu();
+        assert_eq!(p.shape().as_vec(), &[0, 0]);
+        assert_eq!(l.shape().as_vec(), &[0, 0]);
+        assert_eq!(u.shape().as_vec(), &[0, 0]);
+
+        let (p, l, u) = Tensor::<f32>::zeros([2, 2, 0]).lu();
+        assert_eq!(p, ten![[[1., 0.], [0., 1.]], [[1., 0.], [0., 1.]]]);
+        assert_eq!(l.shape().as_vec(), &[2, 2, 0]);
+        assert_eq!(u.shape().as_vec(), &[2, 0, 0]);
+
+        let (p, _, u) = Tensor::<f32>::zeros([0, 2, 3]).lu();
+        assert_eq!(p.shape().as_vec(), &[0, 2, 2]);
+        assert_eq!(u.shape().as_vec(), &[0, 2, 3]);
+    }
+
+    #[test]
+    #[should_panic]
+    fn lu_vector() {
+        lu(&ten![1., 2.]);
+    }
+}
@@ -1,4 +1,4 @@
-use crate::{linalg::blas::sgemm, tensor::{Tensor, unsafe_init}};
+use crate::{linalg::blas::Gemm, tensor::{Tensor, TensorError, Type, unsafe_init}};
 
 #[derive(Clone, Debug)]
 pub enum Transpose {
@@ -9,148 +9,262 @@ pub enum Transpose {
 }
 
 pub trait TransposeMatmul {
-    fn mkn(
+    fn mkn<T: Type>(
         &self, 
-        a: &Tensor,
-        b: &Tensor,
-    ) -> (usize, usize, usize);
+        a: &Tensor<T>,
+        b: &Tensor<T>,
+    ) -> Result<(usize, usize, usize), TensorError>;
 
-    unsafe fn sgemm(
+    unsafe fn gemm<T: Gemm>(
         &self, 
-        a: &Tensor, b: &Tensor,
-        a_ptr: *const f32,
-        b_ptr: *const f32,
-        o_ptr: *mut f32,
+        a: &Tensor<T>, b: &Tensor<T>,
+        a_ptr: *const T,
+        b_ptr: *const T,
+        o_ptr: *mut T,
     );
 }
 
-//#[derive(Debug, Clone)]
-//struct Matmul;
-
-impl Tensor {
-    pub fn matmul(&self, b: &Tensor) -> Tensor {
+impl<T: Gemm> Tensor<T> {
+    pub fn matmul(&self, b: &Tensor<T>) -> Tensor<T> {
         matmul_t(self, b, Transpose::None)
     }
 
-    pub fn matmul_t(&self, b: &Tensor, transpose: Transpose) -> Tensor {
+    pub fn matmul_t(&self, b: &Tensor<T>, transpose: Transpose) -> Tensor<T> {
         matmul_t(self, b, transpose)
     }
+
+    pub fn try_matmul(&self, b: &Tensor<T>) -> Result<Tensor<T>, TensorError> {
+        try_matmul_t(self, b, Transpose::None)
+    }
+
+    pub fn try_matmul_t(
+        &self, 
+        b: &Tensor<T>, 
+        transpose: Transpose
+    ) -> Result<Tensor<T>, TensorError> {
+        try_matmul_t(self, b, transpose)
+    }
 }
 
-pub fn matmul(a: &Tensor, b: &Tensor) -> Tensor {
+pub fn matmul<T: Gemm>(a: &Tensor<T>, b: &Tensor<T>) -> Tensor<T> {
     matmul_t(a, b, Transpose::None)
 }
 
-pub fn matmul_t<T: TransposeMatmul>(a: &Tensor, b: &Tensor, transpose: T) -> Tensor {
-    //assert_eq!(M, N, "matrix multiplication requires matching dim >= 2");
-    assert!(a.rank() > 1, "matrix multiplication requires rank >= 2");
-    // todo!() - re-enable this assertion
-    //assert_eq!(&a.shape().as_subslice(2..), &b.shape().as_subslice(2..), "matmul batch shape must match");
+pub fn matmul_t<T: Gemm>(
+    a: &Tensor<T>, 
+    b: &Tensor<T>, 
+    transpose: impl TransposeMatmul
+) -> Tensor<T> {
+    try_matmul_t(a, b, transpose).unwrap_or_else(|err| panic!("{}", err))
+}
 
-    let (m, _, n) = transpose.mkn(a, b);
+pub fn try_matmul<T: Gemm>(a: &Tensor<T>, b: &Tensor<T>) -> Result<Tensor<T>, TensorError> {
+    try_matmul_t(a, b, Transpose::None)
+}
+
+pub fn try_matmul_t<T: Gemm>(
+    a: &Tensor<T>, 
+    b: &Tensor<T>, 
+    transpose: impl TransposeMatmul
+) -> Result<Tensor<T>, TensorError> {
+    if a.rank() < 2 || b.rank() < 2 {
+        let shape = if a.rank() < 2 { a.shape() } else { b.shape() };
+
+        return Err(TensorError::InvalidShape {
+            op: "matmul",
+            shape: shape.as_vec(),
+            reason: "requires rank >= 2",
+        });
+    }
+    let (m, _, n) = transpose.mkn(a, b)?;
+
+    let Batch { dims, a_index, b_index } = broadcast_batch("matmul", a, 2, b, 2)?;
+
+    let a = &a.to_contiguous();
+    let b = &b.to_contiguous();
 
-    let batch_len : usize = a.shape().sublen(0, a.rank() - 2);
     let a_size = a.rows() * a.cols();
     let b_size = b.rows() * b.cols();
     let o_size = m * n;
 
-    let shape = b.shape().clone().with_cols(m).with_rows(n);
+    let mut dims = dims;
+    dims.push(m);
+    dims.push(n);
 
     unsafe {
-        unsafe_init::<f32>(o_size * batch_len, shape, |o| {
-            for batch in 0..batch_len {
-                let a_ptr = a.as_ptr().add(a_size * batch);
-                let b_ptr = b.as_ptr().add(b_size * batch);
-                let c_ptr = o.add(o_size * batch);
+        Ok(unsafe_init::<T>(o_size * a_index.len(), dims.as_slice(), |o| {
+            for (i, (a_i, b_i)) in a_index.iter().zip(&b_index).enumerate() {
+                let a_ptr = a.as_ptr().add(a_size * a_i);
+                let b_ptr = b.as_ptr().add(b_size * b_i);
+                let c_ptr = o.add(o_size * i);
         
-                transpose.sgemm(a, b, a_ptr, b_ptr, c_ptr);
+                transpose.gemm(a, b, a_ptr, b_ptr, c_ptr);
             }
-        })
+        }))
+    }
+}
+
+///
+/// Broadcast batch dims, with the a and b matrix index for each batch item.
+///
+pub(super) struct Batch {
+    pub(super) dims: Vec<usize>,
+    pub(super) a_index: Vec<usize>,
+    pub(super) b_index: Vec<usize>,
+}
+
+///
+/// Broadcasts the leading batch dims of a and b like NumPy's matmul, where
+/// the inner a_inner and b_inner dims are the matrix or vector.
+///
+pub(super) fn broadcast_batch<T: Type>(
+    op: &'static str,
+    a: &Tensor<T>,
+    a_inner: usize,
+    b: &Tensor<T>,
+    b_inner: usize,
+) -> Result<Batch, TensorError> {
+    let a_dims = a.shape().as_vec();
+    let a_dims = &a_dims[..a.rank() - a_inner];
+
+    let b_dims = b.shape().as_vec();
+    let b_dims = &b_dims[..b.rank() - b_inner];
+
+    let rank = a_dims.len().max(b_dims.len());
+    let mut batch = vec![1; rank];
+
+    for (i, dim) in batch.iter_mut().rev().enumerate() {
+        let a_dim = rdim(a_dims, i);
+        let b_dim = rdim(b_dims, i);
+
+        *dim = if a_dim == b_dim || b_dim == 1 {
+            a_dim
+        } else if a_dim == 1 {
+            b_dim
+        } else {
+            return Err(TensorError::ShapeMismatch {
+                op,
+                a: a.shape().as_vec(),
+                b: b.shape().as_vec(),
+            });
+        };
     }
+
+    let size: usize = batch.iter().product();
+
+    Ok(Batch {
+        a_index: (0..size).map(|i| batch_index(i, &batch, a_dims)).collect(),
+        b_index: (0..size).map(|i| batch_index(i, &batch, b_dims)).collect(),
+        dims: batch,
+    })
+}
+
+#[inline]
+fn rdim(dims: &[usize], i: usize) -> usize {
+    if i < dims.len() { dims[dims.len() - 1 - i] } else { 1 }
+}
+
+// Index into the operand's own batch for the broadcast batch item i,
+// where broadcast size-1 dims don't advance.
+fn batch_index(i: usize, batch: &[usize], dims: &[usize]) -> usize {
+    let mut i = i;
+    let mut index = 0;
+    let mut stride = 1;
+
+    for (j, batch_dim) in batch.iter().rev().enumerate() {
+        let dim = rdim(dims, j);
+
+        if dim != 1 {
+            index += (i % batch_dim) * stride;
+        }
+
+        i /= batch_dim;
+        stride *= dim;
+    }
+
+    index
 }
 
 impl TransposeMatmul for Transpose {
     #[inline]
-    fn mkn(
+    fn mkn<T: Type>(
         &self, 
-        a: &Tensor,
-        b: &Tensor,
-    ) -> (usize, usize, usize) {
-        match self {
+        a: &Tensor<T>,
+        b: &Tensor<T>,
+    ) -> Result<(usize, usize, usize), TensorError> {
+        let (is_match, mkn) = match self {
             Transpose::None => {
-                assert_eq!(a.cols(), b.rows(), "matmul shape does not match. A={:?} B={:?}",
-                    a.shape().as_vec(), b.shape().as_vec());
-
-                (a.rows(), a.cols(), b.cols())
+                (a.cols() == b.rows(), (a.rows(), a.cols(), b.cols()))
             },
             Transpose::TransposeA => {
-                assert_eq!(a.rows(), b.rows(), "matmul shape does not match. A={:?} B={:?} for {:?}", 
-                    a.shape().as_vec(), b.shape().as_vec(), &self);
-
-                (a.cols(), a.rows(), b.cols())
+                (a.rows() == b.rows(), (a.cols(), a.rows(), b.cols()))
             },
             Transpose::TransposeB => {
-                assert_eq!(a.cols(), b.cols(), "matmul shape does not match. A={:?} B={:?} for {:?}", 
-                    a.shape().as_vec(), b.shape().as_vec(), &self);
-
-                (a.rows(), a.rows(), b.rows())
+                (a.cols() == b.cols(), (a.rows(), a.cols(), b.rows()))
             },
             Transpose::TransposeAB => {
-                assert_eq!(a.rows(), b.cols(), "matmul shape does not match. A={:?} B={:?} for {:?}", 
-                    a.shape().as_vec(), b.shape().as_vec(), &self);
-
-                (a.cols(), a.rows(), b.rows())
+                (a.rows() == b.cols(), (a.cols(), a.rows(), b.rows()))
             },
+        };
+
+        if is_match {
+            Ok(mkn)
+        } else {
+            Err(TensorError::ShapeMismatch {
+                op: "matmul",
+                a: a.shape().as_vec(),
+                b: b.shape().as_vec(),
+            })
         }
     }
 
     #[inline]
-    unsafe fn sgemm(
+    unsafe fn gemm<T: Gemm>(
         &self, 
-        a: &Tensor,
-        b: &Tensor,
-        a_ptr: *const f32,
-        b_ptr: *const f32,
-        o_ptr: *mut f32,
+        a: &Tensor<T>,
+        b: &Tensor<T>,
+        a_ptr: *const T,
+        b_ptr: *const T,
+        o_ptr: *mut T,
     ) {
         match self {
             Transpose::None => {
-                sgemm(
+                T::gemm(
                     a.rows(), a.cols(), b.cols(),
-                    1.,
+                    T::one(),
                     a_ptr, a.cols(), 1,
                     b_ptr, b.cols(), 1,
-                    0.,
+                    T::zero(),
                     o_ptr, b.cols(), 1,
                 );
             }
             Transpose::TransposeA => {
-                sgemm(
+                T::gemm(
                     a.cols(), a.rows(), b.cols(),
-                    1.,
+                    T::one(),
                     a_ptr, 1, a.cols(),
                     b_ptr, b.cols(), 1,
-                    0.,
+                    T::zero(),
                     o_ptr, b.cols(), 1,
                 );
             }
             Transpose::TransposeB => {
-                sgemm(
+                T::gemm(
                     a.rows(), a.cols(), b.rows(),
-                    1.,
+                    T::one(),
                     a_ptr, a.cols(), 1,
                     b_ptr, 1, b.cols(),
-                    0.,
+                    T::zero(),
                     o_ptr, b.rows(), 1,
                 );
             }
             Transpose::TransposeAB => {
-                sgemm(
+                T::gemm(
                     a.cols(), a.rows(), b.rows(),
-                    1.,
+                    T::one(),
                     a_ptr, 1, a.cols(),
                     b_ptr, 1, b.cols(),
-                    0.,
+                    T::zero(),
                     o_ptr, b.rows(), 1,
                 );
             }
@@ -169,7 +283,9 @@ impl TransposeMatmul for Transpose {
 
 #[cfg(test)]
 mod test {
-    use crate::{ten, linalg::matmul::Transpose};
+    use num_complex::Complex;
+
+    use crate::{ten, linalg::matmul::Transpose, tensor::TensorError};
 
     #[test]
     fn test_matmul_1() {
@@ -286,4 +402,111 @@ mod test {
         assert_eq!(a.matmul_t(&b, Transpose::TransposeB), 
             ten!([[90., 120., 150.]]));
     }
+
+    #[test]
+    fn try_matmul_errors() {
+        let a = ten![[1., 2.]];
+        let b = ten![[10.], [20.]];
+
+        assert_eq!(a.try_matmul(&b).unwrap(), a.matmul(&b));
+
+        assert_eq!(
+            a.try_matmul(&a).unwrap_err(),
+            TensorError::ShapeMismatch { op: "matmul", a: vec![1, 2], b: vec![1, 2] }
+        );
+        assert!(matches!(
+            ten![1., 2.].try_matmul(&b).unwrap_err(),
+            TensorError::InvalidShape { op: "matmul", .. }
+        ));
+    }
+
+    #[test]
+    fn matmul_f64() {
+        let a = ten![[1., 0., 2.], [0., 1., 10.]].map(|v| *v as f64);
+        let b = ten![[1., 0.], [0., 1.], [3., 4.]].map(|v| *v as f64);
+
+        assert_eq!(a.matmul(&b), ten![[7.0f64, 8.], [30., 41.]]);
+        assert_eq!(
+            a.matmul_t(&a, Transpose::TransposeB), 
+            ten![[5.0f64, 20.], [20., 101.]]
+        );
+    }
+
+    #[test]
+    fn matmul_i32() {
+        let a = ten![[1, 0, 2], [0, 1, 10]];
+        let b = ten![[1, 0], [0, 1], [3, 4]];
+
+        assert_eq!(a.matmul(&b), ten![[7, 8], [30, 41]]);
+        assert_eq!(b.matmul(&a), ten![[1, 0, 2], [0, 1, 10], [3, 4, 46]]);
+
+        assert_eq!(a.matmul_t(&a, Transpose::TransposeA), ten![[1, 0, 2], [0, 1, 10], [2, 10, 104]]);
+        assert_eq!(a.matmul_t(&b, Transpose::TransposeAB), ten![[1, 0, 3], [0, 1, 4], [2, 10, 46]]);
+    }
+
+    #[test]
+    fn matmul_complex() {
+        let a = ten![
+            [Complex::new(1.0f32, 1.), Complex::new(0., 2.)],
+            [Complex::new(2., 0.), Complex::new(1., -1.)]
+        ];
+        let b = ten![[Complex::new(1.0f32, 0.)], [Complex::new(0., 1.)]];
+
+        assert_eq!(
+            a.matmul(&b), 
+            ten![[Complex::new(-1.0f32, 1.)], [Complex::new(3., 1.)]]
+        );
+
+        let a = a.map(|v| Complex::new(v.re as f64, v.im as f64));
+        let b = b.map(|v| Complex::new(v.re as f64, v.im as f64));
+
+        assert_eq!(
+            a.matmul(&b), 
+            ten![[Complex::new(-1.0f64, 1.)], [Complex::new(3., 1.)]]
+        );
+    }
+
+    #[test]
+    fn matmul_batch() {
+        let a = ten![[[1, 2]], [[3, 4]]];
+        let b = ten![[[1], [10]], [[100], [1000]]];
+
+        let c = a.matmul(&b);
+        assert_eq!(c.shape().as_vec(), &[2, 1, 1]);
+        assert_eq!(c, ten![[[21]], [[4300]]]);
+    }
+
+    #[test]
+    fn matmul_batch_broadcast() {
+        let a = ten![[[1, 2]], [[3, 4]]];
+        let b = ten![[1, 10], [100, 1000]];
+
+        // [B, M, K] x [K, N]
+        assert_eq!(a.matmul(&b), ten![[[201, 2010]], [[403, 4030]]]);
+
+        // [M, K] x [B, K, N]
+        let a1 = ten![[1, 2]];
+        let b1 = ten![[[1], [10]], [[100], [1000]]];
+        assert_eq!(a1.matmul(&b1), ten![[[21]], [[2100]]]);
+
+        // [1, M, K] x [B, K, N]
+        let a1 = ten![[[1, 2]]];
+        assert_eq!(a1.matmul(&b1), ten![[[21]], [[2100]]]);
+
+        // [B, 1, M, K] x [C, K, N]
+        let c = a.reshape([2, 1, 1, 2]).matmul(&b1);
+        assert_eq!(c.shape().as_vec(), &[2, 2, 1, 1]);
+        assert_eq!(c, ten![[[21], [2100]], [[43], [4300]]].reshape([2, 2, 1, 1]));
+    }
+
+    #[test]
+    fn matmul_batch_mismatch() {
+        let a = ten![[[1, 2]], [[3, 4]]];
+        let b = ten![[[1], [10]], [[100], [1000]], [[1], [1]]];
+
+        assert_eq!(
+            a.try_matmul(&b).unwrap_err(),
+            TensorError::ShapeMismatch { op: "matmul", a: vec![2, 1, 2], b: vec![3, 2, 1] }
+        );
+    }
 }
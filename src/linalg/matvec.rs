@@ -1,140 +1,175 @@
-use std::cmp;
-
 use crate::{
-    linalg::blas::sgemm, tensor::{Tensor, unsafe_init}
+    linalg::blas::Gemm, tensor::{Tensor, TensorError, Type, unsafe_init}
 };
 
-use super::matmul::Transpose;
+use super::matmul::{broadcast_batch, Batch, Transpose};
+
+impl<T: Gemm> Tensor<T> {
+    pub fn matvec(&self, b: &Tensor<T>) -> Tensor<T> {
+        matvec_t(self, b, Transpose::None)
+    }
+
+    pub fn matvec_t(&self, b: &Tensor<T>, transpose: impl TransposeMatvec) -> Tensor<T> {
+        matvec_t(self, b, transpose)
+    }
 
-impl Tensor<f32> {
-    pub fn matvec(&self, b: &Tensor<f32>) -> Tensor {
-        matvec_t_f32(&self, b, Transpose::None)
+    pub fn try_matvec(&self, b: &Tensor<T>) -> Result<Tensor<T>, TensorError> {
+        try_matvec_t(self, b, Transpose::None)
     }
 
-    pub fn matvec_t(&self, b: &Tensor<f32>, transpose: impl TransposeMatvec) -> Tensor {
-        matvec_t_f32(&self, b, transpose)
+    pub fn try_matvec_t(
+        &self, 
+        b: &Tensor<T>, 
+        transpose: impl TransposeMatvec
+    ) -> Result<Tensor<T>, TensorError> {
+        try_matvec_t(self, b, transpose)
     }
 }
 
-fn matvec_t_f32(
-    a: &Tensor<f32>,
-    x: &Tensor<f32>,
+fn matvec_t<T: Gemm>(
+    a: &Tensor<T>,
+    x: &Tensor<T>,
     transpose: impl TransposeMatvec,
-) -> Tensor<f32> {
-    assert!(a.rank() >= 2, "matrix[{}]-vector multiplication requires dim >= 2", a.rank());
-    
-    let batch : usize = a.shape().broadcast_min(2, x.shape(), cmp::min(2, x.rank()));
+) -> Tensor<T> {
+    try_matvec_t(a, x, transpose).unwrap_or_else(|err| panic!("{}", err))
+}
 
-    let a_size = a.cols() * a.rows();
+///
+/// Matrix-vector product where a is [.., M, K] and x is [.., K], with the
+/// leading batch dims broadcast like NumPy's matmul, returning [.., M].
+///
+fn try_matvec_t<T: Gemm>(
+    a: &Tensor<T>,
+    x: &Tensor<T>,
+    transpose: impl TransposeMatvec,
+) -> Result<Tensor<T>, TensorError> {
+    if a.rank() < 2 || x.rank() < 1 {
+        let shape = if a.rank() < 2 { a.shape() } else { x.shape() };
+
+        return Err(TensorError::InvalidShape {
+            op: "matvec",
+            shape: shape.as_vec(),
+            reason: "requires a matrix with rank >= 2 and a vector with rank >= 1",
+        });
+    }
 
-    let x_rows = cmp::max(1, x.rows());
-    let x_size = x.cols() * x_rows;
+    let (m, k) = transpose.mk(a, x)?;
 
-    let (o_cols, o_rows) = transpose.o_size(a, x);
-    let o_size = o_cols * o_rows;
+    let Batch { dims, a_index, b_index: x_index } = broadcast_batch("matvec", a, 2, x, 1)?;
 
+    let a = &a.to_contiguous();
+    let x = &x.to_contiguous();
+
+    let a_size = a.rows() * a.cols();
+    let n_batch = a_index.len();
+
+    let mut dims = dims;
+    dims.push(m);
 
-    let shape = x.shape().clone().with_cols(o_cols);
     unsafe {
-        unsafe_init::<f32>(o_size * batch, shape, |o| {
-            for n in 0..batch {
-                let a_ptr = a.as_wrap_ptr(n * a_size);
-                let x_ptr = x.as_wrap_ptr(n * x_size);
-                let o_ptr = o.add(n * o_size);
+        Ok(unsafe_init::<T>(m * n_batch, dims.as_slice(), |o| {
+            let is_shared_a = a_index.iter().all(|i| *i == 0);
+            let is_dense_x = x_index.iter().enumerate().all(|(i, j)| i == *j);
+
+            if is_shared_a && is_dense_x {
+                // all vectors use the same matrix, so compute as one matrix
+                transpose.gemm(a, n_batch, k, m, a.as_ptr(), x.as_ptr(), o);
+            } else {
+                for (i, (a_i, x_i)) in a_index.iter().zip(&x_index).enumerate() {
+                    let a_ptr = a.as_ptr().add(a_size * a_i);
+                    let x_ptr = x.as_ptr().add(k * x_i);
+                    let o_ptr = o.add(m * i);
 
-                transpose.sgemm(a, x, o_cols, o_rows, a_ptr, x_ptr, o_ptr);
+                    transpose.gemm(a, 1, k, m, a_ptr, x_ptr, o_ptr);
+                }
             }
-        })
+        }))
     }
 }
 
 pub trait TransposeMatvec {
-    fn o_size(&self, a: &Tensor<f32>, x: &Tensor<f32>) -> (usize, usize);
+    /// Output length m and the shared length k
+    fn mk<T: Type>(&self, a: &Tensor<T>, x: &Tensor<T>) -> Result<(usize, usize), TensorError>;
 
-    fn sgemm(
+    ///
+    /// Computes n vectors as O[n, m] = X[n, k] * op(A)^T
+    ///
+    /// # Safety
+    ///
+    /// The pointers must address a contiguous matrix, n contiguous
+    /// vectors and n contiguous outputs.
+    ///
+    #[allow(clippy::too_many_arguments)]
+    unsafe fn gemm<T: Gemm>(
         &self, 
-        a: &Tensor<f32>, 
-        x: &Tensor<f32>, 
-        o_cols: usize,
-        o_rows: usize,
-        a_ptr: *const f32, 
-        x_ptr: *const f32, 
-        o_ptr: *mut f32,
+        a: &Tensor<T>, 
+        n: usize,
+        k: usize,
+        m: usize,
+        a_ptr: *const T, 
+        x_ptr: *const T, 
+        o_ptr: *mut T,
     );
 }
 
+// A vector has no orientation, so transposing x is a no-op: TransposeB is
+// the same as None and TransposeAB is the same as TransposeA
 impl TransposeMatvec for Transpose {
-    fn o_size(&self, a: &Tensor<f32>, x: &Tensor<f32>) -> (usize, usize) {
-        match self {
-            Transpose::None => {
-                assert_eq!(a.cols(), x.cols(),
-                    "matvec shapes do not match. A={:?} x={:?}",
-                    &a.shape().as_vec(), &x.shape().as_vec());
-
-                (a.rows(), cmp::max(1, x.rows()))
-            },
-            Transpose::TransposeA => {
-                assert_eq!(a.rows(), x.cols(),
-                    "matvec shapes do not match. A={:?} x={:?} for {:?}",
-                    &a.shape().as_vec(), &x.shape().as_vec(), self);
-                    
-                (a.cols(), cmp::max(1, x.rows()))
-            },
-            Transpose::TransposeB => todo!(),
-            Transpose::TransposeAB => todo!(),
+    fn mk<T: Type>(&self, a: &Tensor<T>, x: &Tensor<T>) -> Result<(usize, usize), TensorError> {
+        let (m, k) = match self {
+            Transpose::None | Transpose::TransposeB => (a.rows(), a.cols()),
+            Transpose::TransposeA | Transpose::TransposeAB => (a.cols(), a.rows()),
+        };
+
+        if x.cols() == k {
+            Ok((m, k))
+        } else {
+            Err(TensorError::ShapeMismatch {
+                op: "matvec",
+                a: a.shape().as_vec(),
+                b: x.shape().as_vec(),
+            })
         }
     }
 
-    fn sgemm(
+    unsafe fn gemm<T: Gemm>(
         &self, 
-        a: &Tensor<f32>, 
-        x: &Tensor<f32>,
-        o_cols: usize,
-        o_rows: usize,
-        a_ptr: *const f32, 
-        x_ptr: *const f32, 
-        o_ptr: *mut f32,
+        a: &Tensor<T>, 
+        n: usize,
+        k: usize,
+        m: usize,
+        a_ptr: *const T, 
+        x_ptr: *const T, 
+        o_ptr: *mut T,
     ) {
         match self {
-            Transpose::None => {
-                unsafe {
-                    sgemm(
-                        o_rows, x.cols(), o_cols,
-                        1.,
-                        x_ptr,
-                        x.cols(), 1,
-                        a_ptr,
-                        1, a.cols(),
-                        0.,
-                        o_ptr,
-                        o_cols, 1,
-                    );
-                }
+            Transpose::None | Transpose::TransposeB => {
+                T::gemm(
+                    n, k, m,
+                    T::one(),
+                    x_ptr, k, 1,
+                    a_ptr, 1, a.cols(),
+                    T::zero(),
+                    o_ptr, m, 1,
+                );
+            }
+            Transpose::TransposeA | Transpose::TransposeAB => {
+                T::gemm(
+                    n, k, m,
+                    T::one(),
+                    x_ptr, k, 1,
+                    a_ptr, a.cols(), 1,
+                    T::zero(),
+                    o_ptr, m, 1,
+                );
             }
-            Transpose::TransposeA => {
-                unsafe {
-                    sgemm(
-                        o_rows, x.cols(), o_cols,
-                        1.,
-                        x_ptr,
-                        x.cols(), 1,
-                        a_ptr,
-                        a.cols(), 1,
-                        0.,
-                        o_ptr,
-                        o_cols, 1,
-                    );
-                }
-            },
-            Transpose::TransposeB => todo!(),
-            Transpose::TransposeAB => todo!(),
         }
     }
 }
 
 #[cfg(test)]
 mod test {
-    use crate::{ten, linalg::matmul::Transpose};
+    use crate::{ten, linalg::matmul::Transpose, tensor::TensorError};
 
     #[test]
     fn test_matvec_1_1() {
@@ -191,5 +226,50 @@ mod test {
 
         assert_eq!(a.matvec(&x), ten![[10.], [20.]]);
     }
-    
+
+    #[test]
+    fn matvec_transpose_b() {
+        let a = ten![[1., 4.], [2., 5.], [3., 6.]];
+        let b = ten![10., 20.];
+        assert_eq!(a.matvec_t(&b, Transpose::TransposeB), ten![90., 120., 150.]);
+
+        let a = ten![[1., 2., 3.], [4., 5., 6.]];
+        assert_eq!(a.matvec_t(&b, Transpose::TransposeAB), ten![90., 120., 150.]);
+    }
+
+    #[test]
+    fn matvec_batch() {
+        let a = ten![[[1, 0], [0, 1]], [[2, 0], [0, 3]]];
+
+        // [B, M, K] x [K]
+        assert_eq!(a.matvec(&ten![1, 2]), ten![[1, 2], [2, 6]]);
+
+        // [B, M, K] x [B, K]
+        assert_eq!(a.matvec(&ten![[1, 2], [3, 4]]), ten![[1, 2], [6, 12]]);
+
+        // [M, K] x [B, K]
+        let a1 = ten![[1, 2], [3, 4]];
+        assert_eq!(a1.matvec(&ten![[1, 0], [0, 1]]), ten![[1, 3], [2, 4]]);
+
+        // [1, M, K] x [B, K]
+        let a1 = ten![[[1, 2], [3, 4]]];
+        assert_eq!(a1.matvec(&ten![[1, 0], [0, 1]]), ten![[1, 3], [2, 4]]);
+
+        // [B, 1, M, K] x [C, K]
+        let x = ten![[1, 0], [0, 1], [1, 1]];
+        let o = a.reshape([2, 1, 2, 2]).matvec(&x);
+        assert_eq!(o.shape().as_vec(), &[2, 3, 2]);
+        assert_eq!(o, ten![[[1, 0], [0, 1], [1, 1]], [[2, 0], [0, 3], [2, 3]]]);
+    }
+
+    #[test]
+    fn matvec_batch_mismatch() {
+        let a = ten![[[1, 0], [0, 1]], [[2, 0], [0, 3]]];
+        let x = ten![[1, 2], [3, 4], [5, 6]];
+
+        assert_eq!(
+            a.try_matvec(&x).unwrap_err(),
+            TensorError::ShapeMismatch { op: "matvec", a: vec![2, 2, 2], b: vec![3, 2] }
+        );
+    }
 }
@@ -1,7 +1,42 @@
+mod batch;
 mod blas_naive;
 mod blas;
+mod cholesky;
+mod det;
+mod eig;
+mod eigh;
+mod einsum;
+mod lstsq;
+mod lu;
 mod matmul;
 mod matvec;
+mod norm;
+mod qr;
+mod solve;
+mod svd;
 
-pub use matmul::matmul;
+pub use blas::Gemm;
 
+pub use cholesky::{cholesky, try_cholesky};
+
+pub use det::{det, slogdet};
+
+pub use eig::{eig, try_eig};
+
+pub use eigh::eigh;
+
+pub use einsum::{einsum, try_einsum};
+
+pub use lstsq::{lstsq, try_lstsq};
+
+pub use lu::lu;
+
+pub use matmul::{matmul, try_matmul, Transpose};
+
+pub use norm::{norm, Norm};
+
+pub use qr::qr;
+
+pub use solve::{inv, solve, try_inv, try_solve};
+
+pub use svd::{matrix_rank, pinv, svd, SvdArg, SvdOpt};
@@ -0,0 +1,84 @@
+use num_traits::Float;
+
+use crate::tensor::{Tensor, Type};
+
+use super::{batch::{matrices, matrix_dims}, svd::singular_values};
+
+///
+/// Matrix norms for `norm`.
+///
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Norm {
+    /// Square root of the sum of squared items
+    Frobenius,
+    /// Sum of the singular values
+    Nuclear,
+    /// Largest singular value
+    Spectral,
+}
+
+impl<T: Type + Float> Tensor<T> {
+    ///
+    /// Norm of a matrix, batched over the leading dims. Named
+    /// `matrix_norm` because `norm` is the complex magnitude.
+    ///
+    #[inline]
+    pub fn matrix_norm(&self, ord: Norm) -> Tensor<T> {
+        norm(self, ord)
+    }
+}
+
+pub fn norm<T: Type + Float>(a: &Tensor<T>, ord: Norm) -> Tensor<T> {
+    let (batch, m, n) = matrix_dims("norm", a).unwrap_or_else(|err| panic!("{}", err));
+
+    let a = a.to_contiguous();
+    let n_batch: usize = batch.iter().product();
+
+    let vec: Vec<T> = matrices(a.as_slice(), n_batch, m * n).map(|a| {
+        match ord {
+            Norm::Frobenius => a.iter().fold(T::zero(), |s, x| s.hypot(*x)),
+            Norm::Nuclear => {
+                singular_values(a, m, n).iter().fold(T::zero(), |s, x| s + *x)
+            }
+            Norm::Spectral => {
+                singular_values(a, m, n).first().cloned().unwrap_or(T::zero())
+            }
+        }
+    }).collect();
+
+    Tensor::from_vec(vec, batch.as_slice())
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{linalg::{norm, Norm}, ten, tensor::{scalar, Tensor}};
+
+    #[test]
+    fn norm_frobenius() {
+        assert_eq!(norm(&ten![[3., 0.], [0., 4.]], Norm::Frobenius), scalar(5.));
+        assert_eq!(
+            ten![[[3., 4.]], [[0., 1.]]].matrix_norm(Norm::Frobenius),
+            ten![5., 1.]
+        );
+    }
+
+    #[test]
+    fn norm_nuclear_spectral() {
+        let a = ten![[3., 0.], [4., 5.]].map(|v| *v as f64);
+
+        let nuc = norm(&a, Norm::Nuclear)[0];
+        assert!((nuc - (45f64.sqrt() + 5f64.sqrt())).abs() < 1e-12);
+
+        let spec = a.matrix_norm(Norm::Spectral)[0];
+        assert!((spec - 45f64.sqrt()).abs() < 1e-12);
+    }
+
+    #[test]
+    fn norm_empty() {
+        for ord in [Norm::Frobenius, Norm::Nuclear, Norm::Spectral] {
+            assert_eq!(Tensor::<f32>::zeros([0, 3]).matrix_norm(ord), scalar(0.));
+            assert_eq!(Tensor::<f32>::zeros([2, 0, 0]).matrix_norm(ord), ten![0., 0.]);
+            assert_eq!(Tensor::<f32>::zeros([0, 2, 2]).matrix_norm(ord).shape().as_vec(), &[0]);
+        }
+    }
+}
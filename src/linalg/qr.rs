@@ -0,0 +1,172 @@
+use num_traits::Float;
+
+use crate::tensor::{Tensor, Type};
+
+use super::batch::{matrices, matrix_dims, with_inner};
+
+impl<T: Type + Float> Tensor<T> {
+    ///
+    /// Reduced QR decomposition using Householder reflections, returning
+    /// (Q, R) where A = Q R. For an [m, n] matrix with k = min(m, n), Q is
+    /// [m, k] with orthonormal columns and R is upper triangular [k, n].
+    ///
+    #[inline]
+    pub fn qr(&self) -> (Tensor<T>, Tensor<T>) {
+        qr(self)
+    }
+}
+
+pub fn qr<T: Type + Float>(a: &Tensor<T>) -> (Tensor<T>, Tensor<T>) {
+    let (batch, m, n) = matrix_dims("qr", a).unwrap_or_else(|err| panic!("{}", err));
+    let k = m.min(n);
+
+    let a = a.to_contiguous();
+
+    let n_batch: usize = batch.iter().product();
+    let mut q_vec = Vec::<T>::with_capacity(n_batch * m * k);
+    let mut r_vec = Vec::<T>::with_capacity(n_batch * k * n);
+
+    for a in matrices(a.as_slice(), n_batch, m * n) {
+        let (q, r) = householder_qr(a, m, n);
+
+        q_vec.extend(q);
+        r_vec.extend_from_slice(&r[..k * n]);
+    }
+
+    (
+        Tensor::from_vec(q_vec, with_inner(&batch, &[m, k]).as_slice()),
+        Tensor::from_vec(r_vec, with_inner(&batch, &[k, n]).as_slice()),
+    )
+}
+
+///
+/// Householder QR of a row-major [m, n] matrix, returning Q as [m, k] and
+/// R as [m, n] with zeros below the diagonal.
+///
+pub(super) fn householder_qr<T: Float>(a: &[T], m: usize, n: usize) -> (Vec<T>, Vec<T>) {
+    let k = m.min(n);
+
+    let mut r = a.to_vec();
+    let mut vs = Vec::<Vec<T>>::with_capacity(k);
+
+    for j in 0..k {
+        let mut v: Vec<T> = (j..m).map(|i| r[i * n + j]).collect();
+
+        let norm = v.iter().fold(T::zero(), |s, x| s.hypot(*x));
+        let alpha = if v[0] < T::zero() { norm } else { -norm };
+
+        v[0] = v[0] - alpha;
+
+        let v_norm = v.iter().fold(T::zero(), |s, x| s.hypot(*x));
+
+        if v_norm > T::zero() {
+            for x in v.iter_mut() {
+                *x = *x / v_norm;
+            }
+
+            reflect(&mut r, &v, j, n, j..n);
+        }
+
+        for i in j + 1..m {
+            r[i * n + j] = T::zero();
+        }
+
+        vs.push(v);
+    }
+
+    // Q = H_0 H_1 ... H_k-1 I[m, k]
+    let mut q = vec![T::zero(); m * k];
+    for i in 0..k {
+        q[i * k + i] = T::one();
+    }
+
+    for (j, v) in vs.iter().enumerate().rev() {
+        if v.iter().any(|x| *x != T::zero()) {
+            reflect(&mut q, v, j, k, 0..k);
+        }
+    }
+
+    (q, r)
+}
+
+// x[row.., cols] -= 2 v (v^T x[row.., cols]) for a unit v
+fn reflect<T: Float>(
+    x: &mut [T],
+    v: &[T],
+    row: usize,
+    stride: usize,
+    cols: std::ops::Range<usize>
+) {
+    let two = T::one() + T::one();
+
+    for c in cols {
+        let mut dot = T::zero();
+        for (i, vi) in v.iter().enumerate() {
+            dot = dot + *vi * x[(row + i) * stride + c];
+        }
+
+        let dot = two * dot;
+        for (i, vi) in v.iter().enumerate() {
+            let index = (row + i) * stride + c;
+            x[index] = x[index] - dot * *vi;
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{linalg::qr, ten, tensor::Tensor, test::assert_close};
+
+    #[test]
+    fn qr_square() {
+        let a = ten![[12., -51., 4.], [6., 167., -68.], [-4., 24., -41.]];
+        let (q, r) = qr(&a);
+
+        assert_close(&q.matmul(&r), &a, 1e-4);
+        assert_close(&q.transpose().matmul(&q), &ten![
+            [1., 0., 0.], [0., 1., 0.], [0., 0., 1.]
+        ], 1e-4);
+        assert_close(&r.map(|v: &f32| v.abs()), &ten![
+            [14., 21., 14.], [0., 175., 70.], [0., 0., 35.]
+        ], 1e-4);
+    }
+
+    #[test]
+    fn qr_rect() {
+        let a = ten![[1., 2.], [3., 4.], [5., 6.]];
+        let (q, r) = a.qr();
+
+        assert_eq!(q.shape().as_vec(), &[3, 2]);
+        assert_eq!(r.shape().as_vec(), &[2, 2]);
+        assert_eq!(r[(1, 0)], 0.);
+        assert_close(&q.matmul(&r), &a, 1e-4);
+
+        let a = ten![[1., 2., 3.], [4., 5., 6.]];
+        let (q, r) = a.qr();
+
+        assert_eq!(q.shape().as_vec(), &[2, 2]);
+        assert_eq!(r.shape().as_vec(), &[2, 3]);
+        assert_close(&q.matmul(&r), &a, 1e-4);
+    }
+
+    #[test]
+    fn qr_empty() {
+        let (q, r) = Tensor::<f32>::zeros([3, 0]).qr();
+        assert_eq!(q.shape().as_vec(), &[3, 0]);
+        assert_eq!(r.shape().as_vec(), &[0, 0]);
+
+        let (q, r) = Tensor::<f32>::zeros([0, 3, 2]).qr();
+        assert_eq!(q.shape().as_vec(), &[0, 3, 2]);
+        assert_eq!(r.shape().as_vec(), &[0, 2, 2]);
+    }
+
+    #[test]
+    fn qr_batch_f64() {
+        let a = ten![[[2., 1.], [1., 3.]], [[0., 1.], [1., 0.]]].map(|v| *v as f64);
+        let (q, r) = a.qr();
+
+        assert_eq!(q.shape().as_vec(), &[2, 2, 2]);
+        let diff = (&q.matmul(&r) - &a).abs().reduce_max()[0];
+        assert!(diff < 1e-12);
+    }
+}
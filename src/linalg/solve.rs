@@ -0,0 +1,206 @@
+use num_traits::Float;
+
+use crate::tensor::{Tensor, TensorError, Type};
+
+use super::{
+    batch::{matrices, square_dims, with_inner}, 
+    lu::LuFactor, 
+    matmul::{broadcast_batch, Batch},
+};
+
+impl<T: Type + Float> Tensor<T> {
+    ///
+    /// Solves A x = b, where A is [.., n, n] and b is either a vector [n]
+    /// or a matrix [.., n, k]. Batch dims broadcast like `matmul`.
+    ///
+    #[inline]
+    pub fn solve(&self, b: &Tensor<T>) -> Tensor<T> {
+        solve(self, b)
+    }
+
+    #[inline]
+    pub fn try_solve(&self, b: &Tensor<T>) -> Result<Tensor<T>, TensorError> {
+        try_solve(self, b)
+    }
+
+    ///
+    /// Matrix inverse, batched over the leading dims. Named `inverse`
+    /// because `inv` is the elementwise reciprocal.
+    ///
+    #[inline]
+    pub fn inverse(&self) -> Tensor<T> {
+        inv(self)
+    }
+
+    #[inline]
+    pub fn try_inverse(&self) -> Result<Tensor<T>, TensorError> {
+        try_inv(self)
+    }
+}
+
+pub fn solve<T: Type + Float>(a: &Tensor<T>, b: &Tensor<T>) -> Tensor<T> {
+    try_solve(a, b).unwrap_or_else(|err| panic!("{}", err))
+}
+
+pub fn try_solve<T: Type + Float>(a: &Tensor<T>, b: &Tensor<T>) -> Result<Tensor<T>, TensorError> {
+    let (a_batch, n) = square_dims("solve", a)?;
+
+    // like NumPy 2, b is a single vector only when it's rank 1
+    let (b_inner, k) = if b.rank() == 1 { (1, 1) } else { (2, b.cols()) };
+
+    let b_rows = if b.rank() == 1 { b.cols() } else { b.rows() };
+
+    if b.rank() == 0 || b_rows != n {
+        return Err(TensorError::ShapeMismatch {
+            op: "solve",
+            a: a.shape().as_vec(),
+            b: b.shape().as_vec(),
+        });
+    }
+
+    let Batch { dims, a_index, b_index } = broadcast_batch("solve", a, 2, b, b_inner)?;
+
+    let a = a.to_contiguous();
+    let b = b.to_contiguous();
+
+    let a_slice = a.as_slice();
+    let b_slice = b.as_slice();
+
+    let mut vec = Vec::<T>::with_capacity(a_index.len() * n * k);
+    let mut factors = Vec::<Option<LuFactor<T>>>::new();
+    factors.resize_with(a_batch.iter().product(), || None);
+
+    for (a_i, b_i) in a_index.iter().zip(&b_index) {
+        let factor = factors[*a_i].get_or_insert_with(|| {
+            LuFactor::new(&a_slice[a_i * n * n..(a_i + 1) * n * n], n, n)
+        });
+
+        if factor.is_singular() {
+            return Err(TensorError::LinAlg { op: "solve", reason: "matrix is singular" });
+        }
+
+        vec.extend(factor.solve(&b_slice[b_i * n * k..(b_i + 1) * n * k], k));
+    }
+
+    let inner = if b_inner == 1 { vec![n] } else { vec![n, k] };
+
+    Ok(Tensor::from_vec(vec, with_inner(&dims, &inner).as_slice()))
+}
+
+pub fn inv<T: Type + Float>(a: &Tensor<T>) -> Tensor<T> {
+    try_inv(a).unwrap_or_else(|err| panic!("{}", err))
+}
+
+pub fn try_inv<T: Type + Float>(a: &Tensor<T>) -> Result<Tensor<T>, TensorError> {
+    let (batch, n) = square_dims("inv", a)?;
+
+    let a = a.to_contiguous();
+
+    let mut eye = vec![T::zero(); n * n];
+    for i in 0..n {
+        eye[i * n + i] = T::one();
+    }
+
+    let mut vec = Vec::<T>::with_capacity(a.size());
+
+    let n_batch: usize = batch.iter().product();
+    for a in matrices(a.as_slice(), n_batch, n * n) {
+        let factor = LuFactor::new(a, n, n);
+
+        if factor.is_singular() {
+            return Err(TensorError::LinAlg { op: "inv", reason: "matrix is singular" });
+        }
+
+        vec.extend(factor.solve(&eye, n));
+    }
+
+    Ok(Tensor::from_vec(vec, with_inner(&batch, &[n, n]).as_slice()))
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        linalg::{inv, solve, try_inv}, 
+        ten, tensor::{Tensor, TensorError}, test::assert_close
+    };
+
+    #[test]
+    fn solve_vector() {
+        let a = ten![[3., 1.], [1., 2.]];
+        let b = ten![9., 8.];
+
+        assert_close(&solve(&a, &b), &ten![2., 3.], 1e-5);
+        assert_close(&a.matvec(&a.solve(&b)), &b, 1e-5);
+    }
+
+    #[test]
+    fn solve_matrix() {
+        let a = ten![[3., 1.], [1., 2.]];
+        let b = ten![[9., 3.], [8., 1.]];
+
+        let x = a.solve(&b);
+        assert_eq!(x.shape().as_vec(), &[2, 2]);
+        assert_close(&a.matmul(&x), &b, 1e-5);
+    }
+
+    #[test]
+    fn solve_batch() {
+        let a = ten![[[2., 0.], [0., 4.]], [[1., 1.], [0., 1.]]];
+
+        // [B, n, n] x [n]
+        assert_close(&a.solve(&ten![2., 4.]), &ten![[1., 1.], [-2., 4.]], 1e-5);
+
+        // [n, n] x [B, n, k]
+        let a1 = ten![[2., 0.], [0., 4.]];
+        let b = ten![[[2.], [4.]], [[4.], [8.]]];
+        assert_close(&a1.solve(&b), &ten![[[1.], [1.]], [[2.], [2.]]], 1e-5);
+    }
+
+    #[test]
+    fn solve_errors() {
+        assert_eq!(
+            ten![[1., 2.], [2., 4.]].try_solve(&ten![1., 2.]).unwrap_err(),
+            TensorError::LinAlg { op: "solve", reason: "matrix is singular" },
+        );
+
+        assert!(matches!(
+            ten![[1., 0.], [0., 1.]].try_solve(&ten![1., 2., 3.]).unwrap_err(),
+            TensorError::ShapeMismatch { op: "solve", .. },
+        ));
+    }
+
+    #[test]
+    fn inv_2x2() {
+        let a = ten![[4., 7.], [2., 6.]];
+
+        assert_close(&inv(&a), &ten![[0.6, -0.7], [-0.2, 0.4]], 1e-5);
+        assert_close(&a.matmul(&a.inverse()), &ten![[1., 0.], [0., 1.]], 1e-5);
+    }
+
+    #[test]
+    fn inv_batch_f64() {
+        let a = ten![[[2., 0.], [0., 4.]], [[0., 1.], [1., 0.]]].map(|v| *v as f64);
+
+        assert_eq!(a.inverse(), ten![[[0.5f64, 0.], [0., 0.25]], [[0., 1.], [1., 0.]]]);
+    }
+
+    #[test]
+    fn solve_empty() {
+        let a = Tensor::<f32>::zeros([0, 0]);
+        assert_eq!(a.solve(&Tensor::zeros([0])).shape().as_vec(), &[0]);
+        assert_eq!(a.solve(&Tensor::zeros([0, 2])).shape().as_vec(), &[0, 2]);
+        assert_eq!(a.inverse().shape().as_vec(), &[0, 0]);
+
+        let a = Tensor::<f32>::zeros([0, 2, 2]);
+        assert_eq!(a.solve(&ten![1., 2.]).shape().as_vec(), &[0, 2]);
+        assert_eq!(a.inverse().shape().as_vec(), &[0, 2, 2]);
+    }
+
+    #[test]
+    fn inv_singular() {
+        assert_eq!(
+            try_inv(&ten![[1., 2.], [2., 4.]]).unwrap_err(),
+            TensorError::LinAlg { op: "inv", reason: "matrix is singular" },
+        );
+    }
+}
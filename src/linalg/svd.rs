@@ -0,0 +1,418 @@
+use essay_opt::derive_opt;
+use num_traits::Float;
+
+use crate::tensor::{Tensor, Type};
+
+use super::batch::{matrices, matrix_dims, with_inner};
+
+impl<T: Type + Float> Tensor<T> {
+    ///
+    /// Singular value decomposition, returning (U, S, Vh) where
+    /// A = U diag(S) Vh and S is descending. For an [m, n] matrix with
+    /// k = min(m, n), U is [m, m] and Vh is [n, n], or [m, k] and [k, n]
+    /// with the `economy` option.
+    ///
+    #[inline]
+    pub fn svd(&self, opt: impl SvdOpt) -> (Tensor<T>, Tensor<T>, Tensor<T>) {
+        svd(self, opt)
+    }
+
+    /// Moore-Penrose pseudo-inverse using the SVD.
+    #[inline]
+    pub fn pinv(&self) -> Tensor<T> {
+        pinv(self)
+    }
+
+    /// Number of singular values above max(m, n) * eps * max(S).
+    #[inline]
+    pub fn matrix_rank(&self) -> Tensor<usize> {
+        matrix_rank(self)
+    }
+}
+
+pub fn svd<T: Type + Float>(
+    a: &Tensor<T>, 
+    opt: impl SvdOpt
+) -> (Tensor<T>, Tensor<T>, Tensor<T>) {
+    let (batch, m, n) = matrix_dims("svd", a).unwrap_or_else(|err| panic!("{}", err));
+    let opt = opt.into_arg();
+    let full = ! opt.economy;
+    let k = m.min(n);
+
+    let a = a.to_contiguous();
+    let n_batch: usize = batch.iter().product();
+
+    let mut u_vec = Vec::<T>::new();
+    let mut s_vec = Vec::<T>::new();
+    let mut vh_vec = Vec::<T>::new();
+
+    for a in matrices(a.as_slice(), n_batch, m * n) {
+        let (u, s, vh) = svd_matrix(a, m, n, full);
+
+        u_vec.extend(u);
+        s_vec.extend(s);
+        vh_vec.extend(vh);
+    }
+
+    let (u_cols, vh_rows) = if full { (m, n) } else { (k, k) };
+
+    (
+        Tensor::from_vec(u_vec, with_inner(&batch, &[m, u_cols]).as_slice()),
+        Tensor::from_vec(s_vec, with_inner(&batch, &[k]).as_slice()),
+        Tensor::from_vec(vh_vec, with_inner(&batch, &[vh_rows, n]).as_slice()),
+    )
+}
+
+#[derive_opt(SvdOpt)]
+#[derive(Default)]
+// the generated setter's ..self update is needless for the one option
+#[allow(clippy::needless_update)]
+pub struct SvdArg {
+    economy: bool,
+}
+
+pub fn pinv<T: Type + Float>(a: &Tensor<T>) -> Tensor<T> {
+    let (batch, m, n) = matrix_dims("pinv", a).unwrap_or_else(|err| panic!("{}", err));
+    let k = m.min(n);
+
+    let a = a.to_contiguous();
+    let n_batch: usize = batch.iter().product();
+
+    let mut vec = Vec::<T>::with_capacity(a.size());
+
+    for a in matrices(a.as_slice(), n_batch, m * n) {
+        let (u, s, vh) = svd_matrix(a, m, n, false);
+        let cutoff = rank_tol(&s, m, n);
+
+        // A+ = V diag(1 / S) U^T, dropping the small singular values
+        for i in 0..n {
+            for j in 0..m {
+                let mut sum = T::zero();
+
+                for l in 0..k {
+                    if s[l] > cutoff {
+                        sum = sum + vh[l * n + i] * u[j * k + l] / s[l];
+                    }
+                }
+
+                vec.push(sum);
+            }
+        }
+    }
+
+    Tensor::from_vec(vec, with_inner(&batch, &[n, m]).as_slice())
+}
+
+pub fn matrix_rank<T: Type + Float>(a: &Tensor<T>) -> Tensor<usize> {
+    let (batch, m, n) = matrix_dims("matrix_rank", a).unwrap_or_else(|err| panic!("{}", err));
+
+    let a = a.to_contiguous();
+    let n_batch: usize = batch.iter().product();
+
+    let vec: Vec<usize> = matrices(a.as_slice(), n_batch, m * n).map(|a| {
+        let s = singular_values(a, m, n);
+        let cutoff = rank_tol(&s, m, n);
+
+        s.iter().filter(|s| **s > cutoff).count()
+    }).collect();
+
+    Tensor::from_vec(vec, batch.as_slice())
+}
+
+fn rank_tol<T: Float>(s: &[T], m: usize, n: usize) -> T {
+    let s_max = s.first().cloned().unwrap_or(T::zero());
+
+    s_max * T::epsilon() * T::from(m.max(n)).unwrap()
+}
+
+///
+/// SVD of a row-major [m, n] matrix, returning U [m, m], S [k] and
+/// Vh [n, n] when full, or U [m, k] and Vh [k, n] otherwise.
+///
+pub(super) fn svd_matrix<T: Float>(
+    a: &[T], 
+    m: usize, 
+    n: usize, 
+    full: bool
+) -> (Vec<T>, Vec<T>, Vec<T>) {
+    if m >= n {
+        let (u, s, v) = jacobi_svd(a, m, n, full);
+
+        (u, s, transpose(&v, n, n))
+    } else {
+        // A^T = U' S V'^T, so A = V' S U'^T
+        let (u, s, v) = jacobi_svd(&transpose(a, m, n), n, m, full);
+        let u_cols = if full { n } else { m };
+
+        (v, s, transpose(&u, n, u_cols))
+    }
+}
+
+///
+/// Descending singular values of a row-major [m, n] matrix.
+///
+pub(super) fn singular_values<T: Float>(a: &[T], m: usize, n: usize) -> Vec<T> {
+    svd_matrix(a, m, n, false).1
+}
+
+// One-sided Jacobi SVD for m >= n, returning U [m, m or n], S [n] and V [n, n]
+fn jacobi_svd<T: Float>(a: &[T], m: usize, n: usize, full: bool) -> (Vec<T>, Vec<T>, Vec<T>) {
+    let eps = T::epsilon();
+
+    let mut u = a.to_vec();
+    let mut v = vec![T::zero(); n * n];
+    for i in 0..n {
+        v[i * n + i] = T::one();
+    }
+
+    for _ in 0..75 {
+        let mut is_rotated = false;
+
+        for p in 0..n {
+            for q in p + 1..n {
+                let mut alpha = T::zero();
+                let mut beta = T::zero();
+                let mut gamma = T::zero();
+
+                for i in 0..m {
+                    alpha = alpha + u[i * n + p] * u[i * n + p];
+                    beta = beta + u[i * n + q] * u[i * n + q];
+                    gamma = gamma + u[i * n + p] * u[i * n + q];
+                }
+
+                if gamma == T::zero() || gamma.abs() <= eps * (alpha * beta).sqrt() {
+                    continue;
+                }
+
+                is_rotated = true;
+
+                let two = T::one() + T::one();
+                let zeta = (beta - alpha) / (two * gamma);
+                let t = zeta.signum() / (zeta.abs() + (T::one() + zeta * zeta).sqrt());
+                let c = T::one() / (T::one() + t * t).sqrt();
+                let s = c * t;
+
+                for x in [&mut u, &mut v] {
+                    for i in 0..x.len() / n {
+                        let xp = x[i * n + p];
+                        let xq = x[i * n + q];
+
+                        x[i * n + p] = c * xp - s * xq;
+                        x[i * n + q] = s * xp + c * xq;
+                    }
+                }
+            }
+        }
+
+        if ! is_rotated {
+            break;
+        }
+    }
+
+    let norms: Vec<T> = (0..n).map(|j| {
+        (0..m).fold(T::zero(), |s, i| s.hypot(u[i * n + j]))
+    }).collect();
+
+    let mut order: Vec<usize> = (0..n).collect();
+    order.sort_by(|i, j| norms[*j].partial_cmp(&norms[*i]).unwrap_or(std::cmp::Ordering::Equal));
+
+    let s: Vec<T> = order.iter().map(|j| norms[*j]).collect();
+    let s_tol = s.first().cloned().unwrap_or(T::zero()) * eps * T::from(m).unwrap();
+
+    let u_cols = if full { m } else { n };
+    let mut u_out = vec![T::zero(); m * u_cols];
+    let mut is_filled = vec![false; u_cols];
+
+    for (j, old) in order.iter().enumerate() {
+        if s[j] > s_tol {
+            for i in 0..m {
+                u_out[i * u_cols + j] = u[i * n + old] / s[j];
+            }
+            is_filled[j] = true;
+        }
+    }
+
+    complete_basis(&mut u_out, m, u_cols, &mut is_filled);
+
+    let mut v_out = vec![T::zero(); n * n];
+    for (j, old) in order.iter().enumerate() {
+        for i in 0..n {
+            v_out[i * n + j] = v[i * n + old];
+        }
+    }
+
+    (u_out, s, v_out)
+}
+
+// Fills the missing columns of the orthonormal [m, cols] u by Gram-Schmidt
+// on the unit vector that is furthest from the existing columns
+fn complete_basis<T: Float>(u: &mut [T], m: usize, cols: usize, is_filled: &mut [bool]) {
+    for j in 0..cols {
+        if is_filled[j] {
+            continue;
+        }
+
+        let mut best = (T::zero(), vec![T::zero(); m]);
+
+        for e in 0..m {
+            let mut w = vec![T::zero(); m];
+            w[e] = T::one();
+
+            // twice for stability
+            for _ in 0..2 {
+                for c in (0..cols).filter(|c| is_filled[*c]) {
+                    let dot = (0..m).fold(T::zero(), |s, i| s + w[i] * u[i * cols + c]);
+
+                    for i in 0..m {
+                        w[i] = w[i] - dot * u[i * cols + c];
+                    }
+                }
+            }
+
+            let norm = w.iter().fold(T::zero(), |s, x| s.hypot(*x));
+
+            if norm > best.0 {
+                best = (norm, w);
+            }
+        }
+
+        for i in 0..m {
+            u[i * cols + j] = best.1[i] / best.0;
+        }
+        is_filled[j] = true;
+    }
+}
+
+fn transpose<T: Float>(a: &[T], m: usize, n: usize) -> Vec<T> {
+    let mut vec = Vec::with_capacity(m * n);
+
+    for j in 0..n {
+        for i in 0..m {
+            vec.push(a[i * n + j]);
+        }
+    }
+
+    vec
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{linalg::{matrix_rank, pinv, svd, SvdOpt}, ten, tensor::{Tensor, scalar}, test::assert_close};
+
+    fn assert_orthonormal(q: &Tensor) {
+        let n = q.cols();
+        let eye = Tensor::init_rindexed([n, n], |idx| if idx[0] == idx[1] { 1. } else { 0. });
+
+        assert_close(&q.transpose().matmul(q), &eye, 1e-4);
+    }
+
+    #[test]
+    fn svd_square() {
+        let a = ten![[3., 0.], [4., 5.]];
+        let (u, s, vh) = svd(&a, ());
+
+        assert_close(&s, &ten![45f32.sqrt(), 5f32.sqrt()], 1e-4);
+        assert_close(&(&u * &s).matmul(&vh), &a, 1e-4);
+        assert_orthonormal(&u);
+        assert_orthonormal(&vh.transpose());
+    }
+
+    #[test]
+    fn svd_full_and_economy() {
+        let a = ten![[1., 2.], [3., 4.], [5., 6.]];
+
+        let (u, s, vh) = a.svd(());
+        assert_eq!(u.shape().as_vec(), &[3, 3]);
+        assert_eq!(s.shape().as_vec(), &[2]);
+        assert_eq!(vh.shape().as_vec(), &[2, 2]);
+        assert_orthonormal(&u);
+
+        let (u, s, vh) = a.svd(().economy(true));
+        assert_eq!(u.shape().as_vec(), &[3, 2]);
+        assert_close(&(&u * &s).matmul(&vh), &a, 1e-4);
+
+        let a = a.transpose();
+        let (u, s, vh) = a.svd(());
+        assert_eq!(u.shape().as_vec(), &[2, 2]);
+        assert_eq!(vh.shape().as_vec(), &[3, 3]);
+        assert_orthonormal(&vh.transpose());
+
+        let (u, s2, vh) = a.svd(().economy(true));
+        assert_eq!(vh.shape().as_vec(), &[2, 3]);
+        assert_close(&s2, &s, 1e-4);
+        assert_close(&(&u * &s).matmul(&vh), &a, 1e-4);
+    }
+
+    #[test]
+    fn svd_rank_deficient() {
+        let a = ten![[1., 2.], [2., 4.], [3., 6.]];
+        let (u, s, vh) = a.svd(());
+
+        assert!(s[1] < 1e-5);
+        assert_orthonormal(&u);
+        assert_close(&(&u.slice((.., ..2)) * &s).matmul(&vh), &a, 1e-4);
+    }
+
+    #[test]
+    fn svd_batch_f64() {
+        let a = ten![[[2., 0.], [0., -3.]], [[0., 1.], [1., 0.]]].map(|v| *v as f64);
+        let (u, s, vh) = a.svd(());
+
+        assert_eq!(s, ten![[3.0f64, 2.], [1., 1.]]);
+
+        for i in 0..2 {
+            let usv = (&u.slice(i) * &s.slice(i)).matmul(&vh.slice(i));
+
+            assert!((&usv - &a.slice(i)).abs().reduce_max()[0] < 1e-12);
+        }
+    }
+
+    #[test]
+    fn pinv_matrix() {
+        let a = ten![[1., 2.], [3., 4.], [5., 6.]];
+        let p = pinv(&a);
+
+        assert_eq!(p.shape().as_vec(), &[2, 3]);
+        assert_close(&p.matmul(&a), &ten![[1., 0.], [0., 1.]], 1e-4);
+        assert_close(&a.matmul(&p).matmul(&a), &a, 1e-4);
+
+        let a = ten![[1., 2.], [2., 4.]];
+        assert_close(&a.pinv(), &ten![[0.04, 0.08], [0.08, 0.16]], 1e-4);
+    }
+
+    #[test]
+    fn matrix_rank_basic() {
+        assert_eq!(matrix_rank(&ten![[1., 2.], [3., 4.]]), scalar(2));
+        assert_eq!(matrix_rank(&ten![[1., 2.], [2., 4.], [3., 6.]]), scalar(1));
+        assert_eq!(ten![[[0., 0.], [0., 0.]], [[1., 0.], [0., 1.]]].matrix_rank(), ten![0, 2]);
+    }
+
+    #[test]
+    fn svd_empty() {
+        let (u, s, vh) = Tensor::<f32>::zeros([0, 3]).svd(());
+        assert_eq!(u.shape().as_vec(), &[0, 0]);
+        assert_eq!(s.shape().as_vec(), &[0]);
+        assert_eq!(vh, ten![[1., 0., 0.], [0., 1., 0.], [0., 0., 1.]]);
+
+        let (u, s, vh) = Tensor::<f32>::zeros([0, 3]).svd(().economy(true));
+        assert_eq!(u.shape().as_vec(), &[0, 0]);
+        assert_eq!(s.shape().as_vec(), &[0]);
+        assert_eq!(vh.shape().as_vec(), &[0, 3]);
+
+        let (u, s, vh) = Tensor::<f32>::zeros([0, 0]).svd(());
+        assert_eq!(u.shape().as_vec(), &[0, 0]);
+        assert_eq!(s.shape().as_vec(), &[0]);
+        assert_eq!(vh.shape().as_vec(), &[0, 0]);
+
+        let (u, s, vh) = Tensor::<f32>::zeros([0, 2, 3]).svd(());
+        assert_eq!(u.shape().as_vec(), &[0, 2, 2]);
+        assert_eq!(s.shape().as_vec(), &[0, 2]);
+        assert_eq!(vh.shape().as_vec(), &[0, 3, 3]);
+
+        assert_eq!(Tensor::<f32>::zeros([3, 0]).pinv().shape().as_vec(), &[0, 3]);
+        assert_eq!(Tensor::<f32>::zeros([0, 2, 2]).pinv().shape().as_vec(), &[0, 2, 2]);
+
+        assert_eq!(Tensor::<f32>::zeros([0, 3]).matrix_rank(), scalar(0));
+        assert_eq!(Tensor::<f32>::zeros([0, 2, 2]).matrix_rank().shape().as_vec(), &[0]);
+    }
+}
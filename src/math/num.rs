@@ -253,6 +253,65 @@ tensor_ops2!(
     i8 i16 i32 i64 i128 isize u8 u16 u32 u64 u128 usize    
 );
 
+//
+// In-place operations: AddAssign, SubAssign, MulAssign, DivAssign, RemAssign,
+// BitAndAssign, BitOrAssign, BitXorAssign, ShlAssign, ShrAssign
+//
+// The tensor keeps its shape, so a tensor rhs must broadcast to it. Shared
+// storage is cloned before the update by make_mut.
+//
+
+macro_rules! tensor_ops_assign {
+    ($op:ident, $fun:ident) => {
+        impl<T, U> ops::$op<Tensor<U>> for Tensor<T>
+        where
+            T: ops::$op<U> + Type + Clone,
+            U: Type + Clone,
+        {
+            fn $fun(&mut self, rhs: Tensor<U>) {
+                self.$fun(&rhs)
+            }
+        }
+
+        impl<T, U> ops::$op<&Tensor<U>> for Tensor<T>
+        where
+            T: ops::$op<U> + Type + Clone,
+            U: Type + Clone,
+        {
+            fn $fun(&mut self, rhs: &Tensor<U>) {
+                let rhs = rhs.broadcast_to(self.shape().clone());
+
+                for (x, y) in self.as_mut_slice().iter_mut().zip(rhs.iter()) {
+                    x.$fun(y.clone());
+                }
+            }
+        }
+
+        impl<T, U> ops::$op<U> for Tensor<T>
+        where
+            T: ops::$op<U> + Type + Clone,
+            U: Type + Clone,
+        {
+            fn $fun(&mut self, rhs: U) {
+                for x in self.as_mut_slice() {
+                    x.$fun(rhs.clone());
+                }
+            }
+        }
+    }
+}
+
+tensor_ops_assign!(AddAssign, add_assign);
+tensor_ops_assign!(SubAssign, sub_assign);
+tensor_ops_assign!(MulAssign, mul_assign);
+tensor_ops_assign!(DivAssign, div_assign);
+tensor_ops_assign!(RemAssign, rem_assign);
+tensor_ops_assign!(BitAndAssign, bitand_assign);
+tensor_ops_assign!(BitOrAssign, bitor_assign);
+tensor_ops_assign!(BitXorAssign, bitxor_assign);
+tensor_ops_assign!(ShlAssign, shl_assign);
+tensor_ops_assign!(ShrAssign, shr_assign);
+
 #[cfg(test)]
 mod test {
     use std::ops;
@@ -529,4 +588,71 @@ mod test {
         assert!(ten![1] >= ten![1]);
         assert!(! (ten![0] >= ten![1]));
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn assign_patterns() {
+        let mut t = ten![2, 20];
+        t += ten![3, 30];
+        assert_eq!(t, ten![5, 50]);
+
+        t -= &ten![1, 10];
+        assert_eq!(t, ten![4, 40]);
+
+        t *= 2;
+        assert_eq!(t, ten![8, 80]);
+
+        t /= scalar(4);
+        assert_eq!(t, ten![2, 20]);
+
+        t %= 3;
+        assert_eq!(t, ten![2, 2]);
+
+        t <<= 2;
+        assert_eq!(t, ten![8, 8]);
+
+        t >>= ten![1, 2];
+        assert_eq!(t, ten![4, 2]);
+
+        t |= 0x1;
+        assert_eq!(t, ten![5, 3]);
+
+        t &= 0x6;
+        assert_eq!(t, ten![4, 2]);
+
+        t ^= 0x3;
+        assert_eq!(t, ten![7, 1]);
+    }
+
+    #[test]
+    fn assign_broadcast() {
+        let mut t = ten![[1., 2.], [3., 4.]];
+        t += ten![10., 20.];
+        assert_eq!(t, ten![[11., 22.], [13., 24.]]);
+
+        t *= ten![[2.], [3.]];
+        assert_eq!(t, ten![[22., 44.], [39., 72.]]);
+    }
+
+    #[test]
+    #[should_panic]
+    fn assign_broadcast_mismatch() {
+        let mut t = ten![1., 2.];
+        t += ten![[1., 2.], [3., 4.]];
+    }
+
+    #[test]
+    fn assign_shared_copy_on_write() {
+        let a = ten![1., 2., 3.];
+        let mut b = a.clone();
+        b += 1.;
+
+        assert_eq!(a, ten![1., 2., 3.]);
+        assert_eq!(b, ten![2., 3., 4.]);
+
+        let a = ten![[1., 2.], [3., 4.]];
+        let mut c = a.transpose();
+        c -= &a;
+        assert_eq!(c, ten![[0., 1.], [-1., 0.]]);
+        assert_eq!(a, ten![[1., 2.], [3., 4.]]);
+    }
+}
@@ -0,0 +1,217 @@
+use num_traits::Float;
+
+use crate::tensor::{Tensor, Type};
+
+impl<T: Type + Float> Tensor<T> {
+    #[inline]
+    pub fn relu(&self) -> Tensor<T> {
+        relu(self)
+    }
+
+    #[inline]
+    pub fn leaky_relu(&self, alpha: T) -> Tensor<T> {
+        leaky_relu(self, alpha)
+    }
+
+    #[inline]
+    pub fn gelu(&self) -> Tensor<T> {
+        gelu(self)
+    }
+
+    #[inline]
+    pub fn gelu_tanh(&self) -> Tensor<T> {
+        gelu_tanh(self)
+    }
+
+    #[inline]
+    pub fn silu(&self) -> Tensor<T> {
+        silu(self)
+    }
+
+    #[inline]
+    pub fn sigmoid(&self) -> Tensor<T> {
+        sigmoid(self)
+    }
+
+    #[inline]
+    pub fn softplus(&self) -> Tensor<T> {
+        softplus(self)
+    }
+
+    #[inline]
+    pub fn elu(&self, alpha: T) -> Tensor<T> {
+        elu(self, alpha)
+    }
+}
+
+pub fn relu<T: Type + Float>(x: &Tensor<T>) -> Tensor<T> {
+    // unlike Float::max, the comparison propagates NaN
+    x.map(|x| if *x < T::zero() { T::zero() } else { *x })
+}
+
+pub fn leaky_relu<T: Type + Float>(x: &Tensor<T>, alpha: T) -> Tensor<T> {
+    x.map(|x| if *x >= T::zero() { *x } else { alpha * *x })
+}
+
+///
+/// GELU with the exact Gaussian CDF, x Φ(x).
+///
+pub fn gelu<T: Type + Float>(x: &Tensor<T>) -> Tensor<T> {
+    x.map(|x| {
+        let v = x.to_f64().unwrap();
+
+        T::from(0.5 * v * (1. + erf(v * std::f64::consts::FRAC_1_SQRT_2))).unwrap()
+    })
+}
+
+///
+/// GELU with the tanh approximation used by GPT-2 and BERT.
+///
+pub fn gelu_tanh<T: Type + Float>(x: &Tensor<T>) -> Tensor<T> {
+    let half = T::from(0.5).unwrap();
+    let c = T::from((2. / std::f64::consts::PI).sqrt()).unwrap();
+    let k = T::from(0.044715).unwrap();
+
+    x.map(|x| half * *x * (T::one() + (c * (*x + k * x.powi(3))).tanh()))
+}
+
+pub fn silu<T: Type + Float>(x: &Tensor<T>) -> Tensor<T> {
+    x.map(|x| *x * sigmoid_item(*x))
+}
+
+pub fn sigmoid<T: Type + Float>(x: &Tensor<T>) -> Tensor<T> {
+    x.map(|x| sigmoid_item(*x))
+}
+
+///
+/// ln(1 + e^x), without overflow for large x.
+///
+pub fn softplus<T: Type + Float>(x: &Tensor<T>) -> Tensor<T> {
+    x.map(|x| softplus_item(*x))
+}
+
+pub fn elu<T: Type + Float>(x: &Tensor<T>, alpha: T) -> Tensor<T> {
+    x.map(|x| if *x > T::zero() { *x } else { alpha * x.exp_m1() })
+}
+
+// only exponentiates non-positive values so neither branch overflows
+pub(super) fn sigmoid_item<T: Float>(x: T) -> T {
+    if x >= T::zero() {
+        (T::one() + (-x).exp()).recip()
+    } else {
+        let e = x.exp();
+
+        e / (T::one() + e)
+    }
+}
+
+pub(super) fn softplus_item<T: Float>(x: T) -> T {
+    x.max(T::zero()) + (-x.abs()).exp().ln_1p()
+}
+
+// W. J. Cody's rational approximations, accurate to about 1e-16
+fn erf(x: f64) -> f64 {
+    if x < 0. {
+        return - erf(- x);
+    }
+
+    if x < 0.46875 {
+        let p = [3.2093775891384694e3, 3.77485237685302e2,
+            1.1386415415105016e2, 3.1611237438705655e0,
+            1.8577770618460315e-1];
+        let q = [2.844236833439171e3, 1.2826165260773723e3,
+            2.4402463793444417e2, 2.3601290952344122e1, 1.];
+
+        let z = x * x;
+        let num = p.iter().rev().fold(0., |acc, c| acc * z + c);
+        let den = q.iter().rev().fold(0., |acc, c| acc * z + c);
+
+        x * num / den
+    } else {
+        1. - erfc_large(x)
+    }
+}
+
+fn erfc_large(x: f64) -> f64 {
+    if x < 4. {
+        let p = [1.2303393547979972e3, 2.0510783778260716e3,
+            1.7120476126340707e3, 8.81952221241769e2,
+            2.986351381974001e2, 6.611919063714163e1,
+            8.883149794388377e0, 5.641884969886701e-1,
+            2.1531153547440383e-8];
+        let q = [1.2303393548037495e3, 3.4393676741437216e3,
+            4.362619090143247e3, 3.2907992357334597e3,
+            1.6213895745666903e3, 5.371811018620099e2,
+            1.176939508913125e2, 1.5744926110709835e1, 1.];
+
+        let num = p.iter().rev().fold(0., |acc, c| acc * x + c);
+        let den = q.iter().rev().fold(0., |acc, c| acc * x + c);
+
+        (- x * x).exp() * num / den
+    } else if x < 27. {
+        let p = [-6.587491615298378e-4, -1.6083785148742275e-2,
+            -1.2578172611122926e-1, -3.6034489994980445e-1,
+            -3.0532663496123236e-1, -1.6315387137302097e-2];
+        let q = [2.3352049762686918e-3, 6.051834131244132e-2,
+            5.279051029514285e-1, 1.8729528499234673e0,
+            2.568520192289822e0, 1.];
+
+        let z = (x * x).recip();
+        let num = p.iter().rev().fold(0., |acc, c| acc * z + c);
+        let den = q.iter().rev().fold(0., |acc, c| acc * z + c);
+
+        (- x * x).exp() / x * (std::f64::consts::FRAC_2_SQRT_PI / 2. + z * num / den)
+    } else {
+        0.
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{nn::activation::erf, ten, test::assert_close};
+
+    #[test]
+    fn erf_values() {
+        // scipy.special.erf
+        for (x, y) in [
+            (0., 0.),
+            (0.1, 0.1124629160182849),
+            (0.5, 0.5204998778130465),
+            (1., 0.8427007929497149),
+            (2., 0.9953222650189527),
+            (-3., -0.9999779095030014),
+            (5., 0.9999999999984626),
+        ] {
+            assert!((erf(x) - y).abs() < 1e-14, "erf({}) = {} != {}", x, erf(x), y);
+        }
+    }
+
+    #[test]
+    fn relu_leaky_elu() {
+        let x = ten![[-2., -0.5], [0., 3.]];
+
+        assert_eq!(x.relu(), ten![[0., 0.], [0., 3.]]);
+        assert!(ten![f32::NAN].relu()[0].is_nan());
+        assert_eq!(x.leaky_relu(0.1), ten![[-0.2, -0.05], [0., 3.]]);
+        assert_close(&x.elu(1.), &ten![[(-2f32).exp_m1(), (-0.5f32).exp_m1()], [0., 3.]], 1e-5);
+    }
+
+    #[test]
+    fn sigmoid_silu_softplus() {
+        let x = ten![-1000., -1., 0., 1., 1000.];
+
+        assert_close(&x.sigmoid(), &ten![0., 0.26894142, 0.5, 0.7310586, 1.], 1e-5);
+        assert_close(&x.silu(), &ten![0., -0.26894142, 0., 0.7310586, 1000.], 1e-5);
+        assert_close(&x.softplus(), &ten![0., 0.31326169, 0.69314718, 1.31326169, 1000.], 1e-5);
+    }
+
+    #[test]
+    fn gelu_values() {
+        let x = ten![-3., -1., 0., 0.5, 2.];
+
+        // x Φ(x) and its tanh approximation
+        assert_close(&x.gelu(), &ten![-0.00404969, -0.15865525, 0., 0.34573123, 1.95449974], 1e-5);
+        assert_close(&x.gelu_tanh(), &ten![-0.00363739, -0.15880801, 0., 0.34571401, 1.95459769], 1e-5);
+        assert_eq!(ten![1f64].gelu().shape().as_vec(), &[1]);
+    }
+}
@@ -0,0 +1,813 @@
+use essay_opt::derive_opt;
+
+use crate::{
+    linalg::Gemm,
+    tensor::{unsafe_init, Shape, Tensor, TensorError, Type},
+};
+
+///
+/// Memory layout of an image batch. NHWC kernels are [KH, KW, C_in, C_out]
+/// like TensorFlow and NCHW kernels are [C_out, C_in, KH, KW] like PyTorch,
+/// where C_in is the channels per group.
+///
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum DataFormat {
+    #[default]
+    Nhwc,
+    Nchw,
+}
+
+///
+/// Zero padding of the height and width for convolution and pooling.
+///
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum Padding {
+    /// No padding, so the window stays inside the image
+    #[default]
+    Valid,
+    /// Pads so the output size is ceil(input / stride), with any odd
+    /// padding after the image
+    Same,
+    /// Explicit (height, width) padding on both sides
+    Explicit(usize, usize),
+}
+
+impl From<usize> for Padding {
+    fn from(pad: usize) -> Self {
+        Padding::Explicit(pad, pad)
+    }
+}
+
+impl From<[usize; 2]> for Padding {
+    fn from(pad: [usize; 2]) -> Self {
+        Padding::Explicit(pad[0], pad[1])
+    }
+}
+
+#[derive_opt(Conv2dOpt)]
+#[derive(Default)]
+pub struct Conv2dArg {
+    /// (height, width) stride, defaulting to [1, 1]
+    stride: Option<[usize; 2]>,
+    padding: Option<Padding>,
+    /// (height, width) kernel dilation, defaulting to [1, 1]
+    dilation: Option<[usize; 2]>,
+    /// Channel groups, where each group of output channels only sees
+    /// its group of input channels
+    groups: Option<usize>,
+    format: Option<DataFormat>,
+}
+
+impl<T: Gemm> Tensor<T> {
+    #[inline]
+    pub fn conv2d(&self, kernel: &Tensor<T>, opt: impl Conv2dOpt) -> Tensor<T> {
+        conv2d(self, kernel, opt)
+    }
+
+    #[inline]
+    pub fn conv2d_transpose(&self, kernel: &Tensor<T>, opt: impl Conv2dOpt) -> Tensor<T> {
+        conv2d_transpose(self, kernel, opt)
+    }
+}
+
+pub fn conv2d<T: Gemm>(x: &Tensor<T>, kernel: &Tensor<T>, opt: impl Conv2dOpt) -> Tensor<T> {
+    try_conv2d(x, kernel, opt).unwrap_or_else(|err| panic!("{}", err))
+}
+
+///
+/// 2-D convolution (cross-correlation) of a rank-4 image batch, computed
+/// by im2col and a matrix multiply for each image and group.
+///
+pub fn try_conv2d<T: Gemm>(
+    x: &Tensor<T>,
+    kernel: &Tensor<T>,
+    opt: impl Conv2dOpt
+) -> Result<Tensor<T>, TensorError> {
+    let conv = Conv::new("conv2d", x, kernel, opt.into_arg())?;
+
+    if x.dim(conv.x_channels()) != conv.c_in * conv.groups {
+        return Err(TensorError::ShapeMismatch {
+            op: "conv2d",
+            a: x.shape().as_vec(),
+            b: kernel.shape().as_vec(),
+        });
+    }
+
+    let image = Image::new("conv2d", conv.format, x)?;
+    let height = conv.height.out_dim("conv2d", image.h)?;
+    let width = conv.width.out_dim("conv2d", image.w)?;
+
+    let out = Image { h: height.out, w: width.out, c: conv.c_out, ..image };
+
+    let x = x.to_contiguous();
+    let kernel = kernel.to_contiguous();
+
+    let pixels = out.h * out.w;
+    let k_len = conv.k_len();
+    let c_group = conv.c_out / conv.groups;
+
+    let mut cols = vec![T::zero(); pixels * k_len];
+
+    unsafe {
+        Ok(unsafe_init::<T>(out.size(), out.shape(), |o| {
+            for n in 0..image.n {
+                for g in 0..conv.groups {
+                    im2col(&conv, &image, &x, n, g, &height, &width, &mut cols);
+
+                    let (k_offset, rsk, csk) = conv.kernel_matrix(g);
+
+                    T::gemm(
+                        pixels, k_len, c_group,
+                        T::one(),
+                        cols.as_ptr(), k_len, 1,
+                        kernel.as_ptr().add(k_offset), rsk, csk,
+                        T::zero(),
+                        o.add(n * out.stride_n() + g * c_group * out.stride_c()),
+                        out.stride_w(), out.stride_c(),
+                    );
+                }
+            }
+        }))
+    }
+}
+
+pub fn conv2d_transpose<T: Gemm>(
+    x: &Tensor<T>,
+    kernel: &Tensor<T>,
+    opt: impl Conv2dOpt
+) -> Tensor<T> {
+    try_conv2d_transpose(x, kernel, opt).unwrap_or_else(|err| panic!("{}", err))
+}
+
+///
+/// Transposed 2-D convolution, the adjoint of `conv2d` with the same
+/// kernel. The kernel's in and out channels are swapped, so NHWC kernels
+/// are [KH, KW, C_out, C_in] and NCHW kernels are [C_in, C_out, KH, KW],
+/// with C_out as the channels per group.
+///
+pub fn try_conv2d_transpose<T: Gemm>(
+    x: &Tensor<T>,
+    kernel: &Tensor<T>,
+    opt: impl Conv2dOpt
+) -> Result<Tensor<T>, TensorError> {
+    let conv = Conv::new("conv2d_transpose", x, kernel, opt.into_arg())?;
+
+    if x.dim(conv.x_channels()) != conv.c_out {
+        return Err(TensorError::ShapeMismatch {
+            op: "conv2d_transpose",
+            a: x.shape().as_vec(),
+            b: kernel.shape().as_vec(),
+        });
+    }
+
+    let image = Image::new("conv2d_transpose", conv.format, x)?;
+    let height = conv.height.transpose_dim("conv2d_transpose", image.h)?;
+    let width = conv.width.transpose_dim("conv2d_transpose", image.w)?;
+
+    let out = Image { h: height.out, w: width.out, c: conv.c_in * conv.groups, ..image };
+
+    let x = x.to_contiguous();
+    let kernel = kernel.to_contiguous();
+
+    let pixels = image.h * image.w;
+    let k_len = conv.k_len();
+    let c_group = conv.c_out / conv.groups;
+
+    let mut cols = vec![T::zero(); pixels * k_len];
+    let mut vec = vec![T::zero(); out.size()];
+
+    for n in 0..image.n {
+        for g in 0..conv.groups {
+            let (k_offset, rsk, csk) = conv.kernel_matrix(g);
+
+            unsafe {
+                T::gemm(
+                    pixels, c_group, k_len,
+                    T::one(),
+                    x.as_ptr().add(n * image.stride_n() + g * c_group * image.stride_c()),
+                    image.stride_w(), image.stride_c(),
+                    kernel.as_ptr().add(k_offset), csk, rsk,
+                    T::zero(),
+                    cols.as_mut_ptr(), k_len, 1,
+                );
+            }
+
+            col2im(&conv, &image, &out, n, g, &height, &width, &cols, &mut vec);
+        }
+    }
+
+    Ok(Tensor::from_vec(vec, out.shape()))
+}
+
+///
+/// Kernel geometry and options shared by conv2d and conv2d_transpose,
+/// where c_in and c_out are the channels of the forward convolution.
+///
+struct Conv {
+    format: DataFormat,
+    groups: usize,
+    kh: usize,
+    kw: usize,
+    c_in: usize,
+    c_out: usize,
+    height: Window,
+    width: Window,
+}
+
+impl Conv {
+    fn new(
+        op: &'static str,
+        x: &Tensor<impl Type>,
+        kernel: &Tensor<impl Type>,
+        opt: Conv2dArg
+    ) -> Result<Self, TensorError> {
+        let format = opt.format.unwrap_or_default();
+        let groups = opt.groups.unwrap_or(1);
+        let stride = opt.stride.unwrap_or([1, 1]);
+        let dilation = opt.dilation.unwrap_or([1, 1]);
+
+        if groups == 0 || stride.contains(&0) || dilation.contains(&0) {
+            return Err(TensorError::InvalidArgument {
+                op,
+                reason: "groups, stride and dilation must be positive".to_string(),
+            });
+        }
+
+        if x.rank() != 4 || kernel.rank() != 4 {
+            let shape = if x.rank() != 4 { x.shape() } else { kernel.shape() };
+
+            return Err(TensorError::InvalidShape {
+                op,
+                shape: shape.as_vec(),
+                reason: "requires rank-4 image and kernel",
+            });
+        }
+
+        let (kh, kw, c_in, c_out) = match format {
+            DataFormat::Nhwc => (kernel.dim(0), kernel.dim(1), kernel.dim(2), kernel.dim(3)),
+            DataFormat::Nchw => (kernel.dim(2), kernel.dim(3), kernel.dim(1), kernel.dim(0)),
+        };
+
+        if kh == 0 || kw == 0 || ! c_out.is_multiple_of(groups) {
+            return Err(TensorError::InvalidShape {
+                op,
+                shape: kernel.shape().as_vec(),
+                reason: "requires a non-empty kernel with out channels divisible by groups",
+            });
+        }
+
+        let padding = opt.padding.unwrap_or_default();
+
+        Ok(Self {
+            format,
+            groups,
+            kh,
+            kw,
+            c_in,
+            c_out,
+            height: Window::new(kh, stride[0], dilation[0], padding, 0),
+            width: Window::new(kw, stride[1], dilation[1], padding, 1),
+        })
+    }
+
+    fn x_channels(&self) -> usize {
+        match self.format {
+            DataFormat::Nhwc => 3,
+            DataFormat::Nchw => 1,
+        }
+    }
+
+    fn k_len(&self) -> usize {
+        self.kh * self.kw * self.c_in
+    }
+
+    // im2col column of the kernel tap, matching the kernel's layout
+    #[inline]
+    fn col(&self, i: usize, j: usize, c: usize) -> usize {
+        match self.format {
+            DataFormat::Nhwc => (i * self.kw + j) * self.c_in + c,
+            DataFormat::Nchw => (c * self.kh + i) * self.kw + j,
+        }
+    }
+
+    // Group g of the kernel as a [k_len, c_out / groups] matrix, as the
+    // offset, row stride and column stride
+    fn kernel_matrix(&self, g: usize) -> (usize, usize, usize) {
+        let c_group = self.c_out / self.groups;
+
+        match self.format {
+            DataFormat::Nhwc => (g * c_group, self.c_out, 1),
+            DataFormat::Nchw => (g * c_group * self.k_len(), 1, self.k_len()),
+        }
+    }
+}
+
+///
+/// One spatial dimension of a sliding window.
+///
+pub(super) struct Window {
+    size: usize,
+    stride: usize,
+    dilation: usize,
+    padding: Padding,
+    // 0 for height, 1 for width
+    dim: usize,
+}
+
+///
+/// Output size and the padding before the input.
+///
+pub(super) struct WindowDim {
+    pub(super) out: usize,
+    pub(super) pad: usize,
+}
+
+impl Window {
+    pub(super) fn new(
+        size: usize,
+        stride: usize,
+        dilation: usize,
+        padding: Padding,
+        dim: usize
+    ) -> Self {
+        Self { size, stride, dilation, padding, dim }
+    }
+
+    fn extent(&self) -> usize {
+        (self.size - 1) * self.dilation + 1
+    }
+
+    fn explicit(&self) -> usize {
+        match self.padding {
+            Padding::Explicit(h, w) => if self.dim == 0 { h } else { w },
+            _ => 0,
+        }
+    }
+
+    pub(super) fn out_dim(&self, op: &'static str, len: usize) -> Result<WindowDim, TensorError> {
+        let extent = self.extent();
+
+        let (padded, pad) = match self.padding {
+            Padding::Valid => (len, 0),
+            Padding::Same => {
+                let out = len.div_ceil(self.stride);
+                let total = ((out.max(1) - 1) * self.stride + extent).saturating_sub(len);
+
+                (len + total, total / 2)
+            }
+            Padding::Explicit(..) => (len + 2 * self.explicit(), self.explicit()),
+        };
+
+        if padded < extent || len == 0 {
+            return Err(TensorError::InvalidArgument {
+                op,
+                reason: format!("window of {} doesn't fit the padded size {}", extent, padded),
+            });
+        }
+
+        Ok(WindowDim { out: (padded - extent) / self.stride + 1, pad })
+    }
+
+    fn transpose_dim(&self, op: &'static str, len: usize) -> Result<WindowDim, TensorError> {
+        let full = len.saturating_sub(1) * self.stride + self.extent();
+
+        let (out, pad) = match self.padding {
+            Padding::Valid => (full, 0),
+            Padding::Same => {
+                let out = len * self.stride;
+
+                (out, full.saturating_sub(out) / 2)
+            }
+            Padding::Explicit(..) => {
+                let pad = self.explicit();
+
+                (full.saturating_sub(2 * pad), pad)
+            }
+        };
+
+        if out == 0 || len == 0 {
+            return Err(TensorError::InvalidArgument {
+                op,
+                reason: format!("padding leaves no output from size {}", len),
+            });
+        }
+
+        Ok(WindowDim { out, pad })
+    }
+
+    ///
+    /// The input position of tap k for output position i, if it's inside
+    /// the input rather than the padding.
+    ///
+    #[inline]
+    pub(super) fn input(&self, dim: &WindowDim, i: usize, k: usize, len: usize) -> Option<usize> {
+        (i * self.stride + k * self.dilation)
+            .checked_sub(dim.pad)
+            .filter(|pos| *pos < len)
+    }
+}
+
+///
+/// Image batch dimensions in either format.
+///
+#[derive(Clone, Copy, Debug)]
+pub(super) struct Image {
+    pub(super) format: DataFormat,
+    pub(super) n: usize,
+    pub(super) h: usize,
+    pub(super) w: usize,
+    pub(super) c: usize,
+}
+
+impl Image {
+    pub(super) fn new(
+        op: &'static str,
+        format: DataFormat,
+        x: &Tensor<impl Type>
+    ) -> Result<Self, TensorError> {
+        if x.rank() != 4 {
+            return Err(TensorError::InvalidShape {
+                op,
+                shape: x.shape().as_vec(),
+                reason: "requires a rank-4 image batch",
+            });
+        }
+
+        let (n, h, w, c) = match format {
+            DataFormat::Nhwc => (x.dim(0), x.dim(1), x.dim(2), x.dim(3)),
+            DataFormat::Nchw => (x.dim(0), x.dim(2), x.dim(3), x.dim(1)),
+        };
+
+        Ok(Self { format, n, h, w, c })
+    }
+
+    pub(super) fn size(&self) -> usize {
+        self.n * self.h * self.w * self.c
+    }
+
+    pub(super) fn shape(&self) -> Shape {
+        match self.format {
+            DataFormat::Nhwc => Shape::from([self.n, self.h, self.w, self.c]),
+            DataFormat::Nchw => Shape::from([self.n, self.c, self.h, self.w]),
+        }
+    }
+
+    #[inline]
+    pub(super) fn stride_n(&self) -> usize {
+        self.h * self.w * self.c
+    }
+
+    #[inline]
+    pub(super) fn stride_h(&self) -> usize {
+        self.w * self.stride_w()
+    }
+
+    #[inline]
+    pub(super) fn stride_w(&self) -> usize {
+        match self.format {
+            DataFormat::Nhwc => self.c,
+            DataFormat::Nchw => 1,
+        }
+    }
+
+    #[inline]
+    pub(super) fn stride_c(&self) -> usize {
+        match self.format {
+            DataFormat::Nhwc => 1,
+            DataFormat::Nchw => self.h * self.w,
+        }
+    }
+
+    #[inline]
+    pub(super) fn offset(&self, n: usize, h: usize, w: usize, c: usize) -> usize {
+        n * self.stride_n() + h * self.stride_h() + w * self.stride_w() + c * self.stride_c()
+    }
+}
+
+// Fills cols with the [out_h * out_w, k_len] input patches of group g
+#[allow(clippy::too_many_arguments)]
+fn im2col<T: Gemm>(
+    conv: &Conv,
+    image: &Image,
+    x: &Tensor<T>,
+    n: usize,
+    g: usize,
+    height: &WindowDim,
+    width: &WindowDim,
+    cols: &mut [T],
+) {
+    let x = x.as_slice();
+    let k_len = conv.k_len();
+
+    for oh in 0..height.out {
+        for ow in 0..width.out {
+            let row = &mut cols[(oh * width.out + ow) * k_len..][..k_len];
+
+            for i in 0..conv.kh {
+                let ih = conv.height.input(height, oh, i, image.h);
+
+                for j in 0..conv.kw {
+                    let iw = conv.width.input(width, ow, j, image.w);
+
+                    for c in 0..conv.c_in {
+                        row[conv.col(i, j, c)] = match (ih, iw) {
+                            (Some(ih), Some(iw)) => {
+                                x[image.offset(n, ih, iw, g * conv.c_in + c)]
+                            }
+                            _ => T::zero(),
+                        };
+                    }
+                }
+            }
+        }
+    }
+}
+
+// Adds the [in_h * in_w, k_len] cols of group g into their output
+// positions, the adjoint of im2col
+#[allow(clippy::too_many_arguments)]
+fn col2im<T: Gemm>(
+    conv: &Conv,
+    image: &Image,
+    out: &Image,
+    n: usize,
+    g: usize,
+    height: &WindowDim,
+    width: &WindowDim,
+    cols: &[T],
+    vec: &mut [T],
+) {
+    let k_len = conv.k_len();
+
+    for ih in 0..image.h {
+        for iw in 0..image.w {
+            let row = &cols[(ih * image.w + iw) * k_len..][..k_len];
+
+            for i in 0..conv.kh {
+                let Some(oh) = conv.height.input(height, ih, i, out.h) else {
+                    continue;
+                };
+
+                for j in 0..conv.kw {
+                    let Some(ow) = conv.width.input(width, iw, j, out.w) else {
+                        continue;
+                    };
+
+                    for c in 0..conv.c_in {
+                        let v = &mut vec[out.offset(n, oh, ow, g * conv.c_in + c)];
+
+                        *v = *v + row[conv.col(i, j, c)];
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        nn::{conv2d, conv2d_transpose, try_conv2d, Conv2dOpt, DataFormat, Padding},
+        ten, tensor::{Tensor, TensorError}, test::assert_close,
+    };
+
+    // init_rindexed with the index in shape order
+    fn init(shape: [usize; 4], f: impl Fn(usize, usize, usize, usize) -> f32) -> Tensor {
+        Tensor::init_rindexed(shape, |i| f(i[3], i[2], i[1], i[0]))
+    }
+
+    fn value(seed: usize) -> f32 {
+        ((seed * 7919 + 13) % 101) as f32 / 50. - 1.
+    }
+
+    // [n, h, w, c] image and its NCHW copy
+    fn image(n: usize, h: usize, w: usize, c: usize) -> (Tensor, Tensor) {
+        let f = move |n: usize, h: usize, w: usize, c: usize| value(((n * 31 + h) * 37 + w) * 41 + c);
+
+        (
+            init([n, h, w, c], f),
+            init([n, c, h, w], move |n, c, h, w| f(n, h, w, c)),
+        )
+    }
+
+    // [kh, kw, c_in, c_out] kernel and its [c_out, c_in, kh, kw] copy
+    fn kernel(kh: usize, kw: usize, c_in: usize, c_out: usize) -> (Tensor, Tensor) {
+        let f = move |h: usize, w: usize, i: usize, o: usize| value(((h * 5 + w) * 7 + i) * 11 + o + 1000);
+
+        (
+            init([kh, kw, c_in, c_out], f),
+            init([c_out, c_in, kh, kw], move |o, i, h, w| f(h, w, i, o)),
+        )
+    }
+
+    fn nhwc_to_nchw(x: &Tensor) -> Tensor {
+        let (n, h, w, c) = (x.dim(0), x.dim(1), x.dim(2), x.dim(3));
+        let x = x.to_contiguous();
+
+        init([n, c, h, w], |b, ci, y, z| x.as_slice()[((b * h + y) * w + z) * c + ci])
+    }
+
+    // direct NHWC convolution with explicit padding
+    fn conv_direct(
+        x: &Tensor, k: &Tensor,
+        stride: [usize; 2], pad: [usize; 2], dilation: [usize; 2], groups: usize
+    ) -> Tensor {
+        let (n, h, w, c) = (x.dim(0), x.dim(1), x.dim(2), x.dim(3));
+        let (kh, kw, c_in, c_out) = (k.dim(0), k.dim(1), k.dim(2), k.dim(3));
+        let c_group = c_out / groups;
+
+        let oh = (h + 2 * pad[0] - (kh - 1) * dilation[0] - 1) / stride[0] + 1;
+        let ow = (w + 2 * pad[1] - (kw - 1) * dilation[1] - 1) / stride[1] + 1;
+
+        let x = x.to_contiguous();
+        let k = k.to_contiguous();
+        let (x, k) = (x.as_slice(), k.as_slice());
+
+        assert_eq!(c, c_in * groups);
+
+        init([n, oh, ow, c_out], |b, y, z, o| {
+            let g = o / c_group;
+            let mut sum = 0.;
+
+            for i in 0..kh {
+                for j in 0..kw {
+                    let ih = (y * stride[0] + i * dilation[0]) as isize - pad[0] as isize;
+                    let iw = (z * stride[1] + j * dilation[1]) as isize - pad[1] as isize;
+
+                    if ih < 0 || iw < 0 || ih >= h as isize || iw >= w as isize {
+                        continue;
+                    }
+
+                    for ci in 0..c_in {
+                        let xv = x[((b * h + ih as usize) * w + iw as usize) * c + g * c_in + ci];
+                        let kv = k[((i * kw + j) * c_in + ci) * c_out + o];
+
+                        sum += xv * kv;
+                    }
+                }
+            }
+
+            sum
+        })
+    }
+
+    #[test]
+    fn conv2d_basic() {
+        let x = ten![[[[1.], [2.], [3.]], [[4.], [5.], [6.]], [[7.], [8.], [9.]]]];
+        let k = ten![[[[1.]], [[0.]]], [[[0.]], [[-1.]]]];
+
+        assert_eq!(conv2d(&x, &k, ()), ten![[[[-4.], [-4.]], [[-4.], [-4.]]]]);
+
+        assert_eq!(
+            conv2d(&x, &k, ().padding(Padding::Same)).shape().as_vec(),
+            &[1, 3, 3, 1]
+        );
+
+        assert_eq!(
+            conv2d(&x, &k, ().padding(1).stride([2, 2])),
+            ten![[[[-1.], [-3.]], [[-7.], [-4.]]]]
+        );
+    }
+
+    #[test]
+    fn conv2d_options() {
+        let cases = [
+            // stride, pad, dilation, groups
+            ([1, 1], [0, 0], [1, 1], 1),
+            ([2, 1], [1, 2], [1, 1], 1),
+            ([1, 2], [1, 0], [2, 1], 1),
+            ([2, 2], [2, 2], [2, 2], 1),
+            ([1, 1], [1, 1], [1, 1], 2),
+            ([2, 1], [0, 1], [1, 2], 3),
+        ];
+
+        for (stride, pad, dilation, groups) in cases {
+            let (x, x_nchw) = image(2, 7, 6, 3 * groups);
+            let (k, k_nchw) = kernel(3, 2, 3, 2 * groups);
+
+            let expected = conv_direct(&x, &k, stride, pad, dilation, groups);
+
+            let opt = ().stride(stride).padding(pad).dilation(dilation).groups(groups);
+            assert_close(&conv2d(&x, &k, opt), &expected, 1e-4);
+
+            let opt = ().stride(stride).padding(pad).dilation(dilation).groups(groups)
+                .format(DataFormat::Nchw);
+            assert_close(&conv2d(&x_nchw, &k_nchw, opt), &nhwc_to_nchw(&expected), 1e-4);
+        }
+    }
+
+    #[test]
+    fn conv2d_same() {
+        for (stride, h, w) in [(1, 5, 6), (2, 5, 6), (3, 7, 4)] {
+            let (x, _) = image(1, h, w, 2);
+            let (k, _) = kernel(3, 4, 2, 3);
+
+            let y = conv2d(&x, &k, ().stride([stride, stride]).padding(Padding::Same));
+
+            assert_eq!(
+                y.shape().as_vec(),
+                &[1, h.div_ceil(stride), w.div_ceil(stride), 3]
+            );
+        }
+
+        // odd total padding goes after the image, like TensorFlow
+        let x = ten![[[[1.], [2.]], [[3.], [4.]]]];
+        let k = ten![[[[1.]], [[1.]]], [[[1.]], [[1.]]]];
+
+        assert_eq!(
+            conv2d(&x, &k, ().padding(Padding::Same)),
+            ten![[[[10.], [6.]], [[7.], [4.]]]]
+        );
+    }
+
+    #[test]
+    fn conv2d_errors() {
+        let (x, _) = image(1, 4, 4, 3);
+        let (k, _) = kernel(2, 2, 2, 2);
+
+        assert!(matches!(
+            try_conv2d(&x, &k, ()).unwrap_err(),
+            TensorError::ShapeMismatch { op: "conv2d", .. }
+        ));
+
+        let (k, _) = kernel(5, 2, 3, 2);
+        assert!(matches!(
+            try_conv2d(&x, &k, ()).unwrap_err(),
+            TensorError::InvalidArgument { op: "conv2d", .. }
+        ));
+
+        let (k, _) = kernel(2, 2, 1, 2);
+        assert!(matches!(
+            try_conv2d(&x, &k, ().groups(3)).unwrap_err(),
+            TensorError::InvalidShape { op: "conv2d", .. }
+        ));
+
+        assert!(matches!(
+            try_conv2d(&ten![[1., 2.]], &k, ()).unwrap_err(),
+            TensorError::InvalidShape { op: "conv2d", .. }
+        ));
+    }
+
+    #[test]
+    fn conv2d_transpose_adjoint() {
+        // <conv2d(x, k), y> == <x, conv2d_transpose(y, k)>
+        let cases = [
+            // size, stride, padding, dilation, groups
+            ([8, 7], [1, 1], Padding::Valid, [1, 1], 1),
+            ([9, 8], [2, 2], Padding::Valid, [1, 1], 1),
+            ([9, 7], [2, 1], Padding::Explicit(1, 2), [1, 2], 1),
+            ([10, 8], [3, 2], Padding::Explicit(1, 0), [1, 1], 2),
+            ([8, 8], [2, 2], Padding::Same, [1, 1], 1),
+        ];
+
+        for ([h, w], stride, padding, dilation, groups) in cases {
+            for format in [DataFormat::Nhwc, DataFormat::Nchw] {
+                let (x, x_nchw) = image(2, h, w, 2 * groups);
+                let (k, k_nchw) = kernel(3, 2, 2, 3 * groups);
+
+                let (x, k) = match format {
+                    DataFormat::Nhwc => (x, k),
+                    DataFormat::Nchw => (x_nchw, k_nchw),
+                };
+
+                let opt = || ().stride(stride).padding(padding).dilation(dilation)
+                    .groups(groups).format(format);
+
+                let y = conv2d(&x, &k, opt()).map(|v| v.sin());
+
+                let xt = conv2d_transpose(&y, &k, opt());
+                assert_eq!(xt.shape(), x.shape());
+
+                let lhs = (conv2d(&x, &k, opt()) * &y).reduce_sum()[0];
+                let rhs = (&x * &xt).reduce_sum()[0];
+
+                assert!((lhs - rhs).abs() < 1e-3, "{} != {}", lhs, rhs);
+            }
+        }
+    }
+
+    #[test]
+    fn conv2d_transpose_shape() {
+        let (x, _) = image(1, 3, 4, 2);
+        let (k, _) = kernel(3, 3, 5, 2);
+
+        assert_eq!(conv2d_transpose(&x, &k, ()).shape().as_vec(), &[1, 5, 6, 5]);
+        assert_eq!(
+            conv2d_transpose(&x, &k, ().stride([2, 2]).padding(Padding::Same)).shape().as_vec(),
+            &[1, 6, 8, 5]
+        );
+        assert_eq!(
+            conv2d_transpose(&x, &k, ().stride([2, 2]).padding(1)).shape().as_vec(),
+            &[1, 5, 7, 5]
+        );
+
+        // stride 2 upsampling with a 1x1 kernel leaves gaps
+        let x = ten![[[[1.], [2.]]]];
+        let k = ten![[[[3.]]]];
+
+        assert_eq!(
+            conv2d_transpose(&x, &k, ().stride([1, 2]).padding(Padding::Same)),
+            ten![[[[3.], [0.], [6.], [0.]]]]
+        );
+    }
+}
@@ -0,0 +1,183 @@
+use crate::tensor::{Tensor, TensorError};
+
+use super::activation::softplus_item;
+
+///
+/// Softmax cross-entropy of logits [..., C] with the class labels [...],
+/// as the loss for each label.
+///
+pub fn cross_entropy(logits: &Tensor, labels: &Tensor<usize>) -> Tensor {
+    try_cross_entropy(logits, labels).unwrap_or_else(|err| panic!("{}", err))
+}
+
+pub fn try_cross_entropy(
+    logits: &Tensor,
+    labels: &Tensor<usize>
+) -> Result<Tensor, TensorError> {
+    let rank = logits.rank();
+
+    if rank == 0 || labels.shape().as_vec() != logits.shape().as_vec()[..rank - 1] {
+        return Err(TensorError::ShapeMismatch {
+            op: "cross_entropy",
+            a: logits.shape().as_vec(),
+            b: labels.shape().as_vec(),
+        });
+    }
+
+    let n_classes = logits.dim(rank - 1);
+
+    if let Some(label) = labels.iter().find(|label| **label >= n_classes) {
+        return Err(TensorError::InvalidArgument {
+            op: "cross_entropy",
+            reason: format!("label {} is out of range for {} classes", label, n_classes),
+        });
+    }
+
+    let log_probs = logits.log_softmax(-1).to_contiguous();
+
+    let vec: Vec<f32> = log_probs.as_slice()
+        .chunks(n_classes.max(1))
+        .zip(labels.iter())
+        .map(|(log_p, label)| - log_p[*label])
+        .collect();
+
+    Ok(Tensor::from_vec(vec, labels.shape().clone()))
+}
+
+///
+/// Softmax cross-entropy of logits [..., C] with target probabilities
+/// [..., C], such as one-hot or smoothed labels, summed over the classes.
+///
+pub fn cross_entropy_one_hot(logits: &Tensor, targets: &Tensor) -> Tensor {
+    targets.map2(&logits.log_softmax(-1), |t, log_p| {
+        if *t == 0. { 0. } else { - t * log_p }
+    }).reduce_sum_axis(-1)
+}
+
+///
+/// Binary cross-entropy of logits with targets in [0, 1], computed as
+/// softplus(x) - x t so large logits don't overflow, averaged over the
+/// last axis.
+///
+pub fn binary_cross_entropy_with_logits(logits: &Tensor, targets: &Tensor) -> Tensor {
+    logits.map2(targets, |x, t| softplus_item(*x) - x * t).reduce_mean_axis(-1)
+}
+
+///
+/// Mean squared error over the last axis.
+///
+pub fn mse(pred: &Tensor, target: &Tensor) -> Tensor {
+    pred.map2(target, |p, t| (p - t) * (p - t)).reduce_mean_axis(-1)
+}
+
+///
+/// Huber loss over the last axis, quadratic within delta of the target
+/// and linear beyond it.
+///
+pub fn huber(pred: &Tensor, target: &Tensor, delta: f32) -> Tensor {
+    pred.map2(target, |p, t| {
+        let d = (p - t).abs();
+
+        if d <= delta { 0.5 * d * d } else { delta * (d - 0.5 * delta) }
+    }).reduce_mean_axis(-1)
+}
+
+///
+/// KL divergence sum(p ln(p / q)) of the distribution q from p over the
+/// last axis, where zero p terms contribute nothing.
+///
+pub fn kl_div(p: &Tensor, q: &Tensor) -> Tensor {
+    p.map2(q, |p, q| {
+        if *p == 0. { 0. } else { p * (p / q).ln() }
+    }).reduce_sum_axis(-1)
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        nn::{
+            binary_cross_entropy_with_logits, cross_entropy, cross_entropy_one_hot,
+            huber, kl_div, mse, try_cross_entropy,
+        },
+        ten, tensor::{Tensor, TensorError}, test::assert_close,
+    };
+
+    #[test]
+    fn cross_entropy_labels() {
+        let logits = ten![[[1., 2., 3.], [0., 0., 0.]], [[0., 0., 1000.], [5., 0., 0.]]];
+        let labels = Tensor::from_vec(vec![2usize, 0, 2, 1], [2, 2]);
+
+        let lse = (1f32.exp() + 2f32.exp() + 3f32.exp()).ln();
+
+        let loss = cross_entropy(&logits, &labels);
+        assert_close(&loss, &ten![
+            [lse - 3., 3f32.ln()],
+            [0., (5f32.exp() + 2.).ln()]
+        ], 1e-5);
+
+        let targets = ten![
+            [[0., 0., 1.], [1., 0., 0.]],
+            [[0., 0., 1.], [0., 1., 0.]]
+        ];
+        assert_close(&cross_entropy_one_hot(&logits, &targets), &loss, 1e-5);
+
+        // smoothed labels
+        assert_close(
+            &cross_entropy_one_hot(&ten![0., 0.], &ten![0.25, 0.75]),
+            &Tensor::from_scalar(2f32.ln()), 1e-5
+        );
+
+        assert_close(
+            &cross_entropy(&ten![0., 0.], &Tensor::from_scalar(1usize)),
+            &Tensor::from_scalar(2f32.ln()), 1e-5
+        );
+    }
+
+    #[test]
+    fn cross_entropy_errors() {
+        let logits = ten![[1., 2.], [3., 4.]];
+
+        assert!(matches!(
+            try_cross_entropy(&logits, &Tensor::from(vec![0usize, 1, 0])).unwrap_err(),
+            TensorError::ShapeMismatch { op: "cross_entropy", .. }
+        ));
+        assert!(matches!(
+            try_cross_entropy(&logits, &Tensor::from(vec![0usize, 2])).unwrap_err(),
+            TensorError::InvalidArgument { op: "cross_entropy", .. }
+        ));
+    }
+
+    #[test]
+    fn bce_with_logits() {
+        let logits = ten![[0., 2., -1000.], [1000., -1., 3.]];
+        let targets = ten![[1., 0., 0.], [1., 1., 0.5]];
+
+        let bce = |x: f32, t: f32| {
+            let p = 1. / (1. + (-x).exp());
+            - t * p.ln() - (1. - t) * (1. - p).ln()
+        };
+
+        assert_close(&binary_cross_entropy_with_logits(&logits, &targets), &ten![
+            (bce(0., 1.) + bce(2., 0.)) / 3.,
+            (bce(-1., 1.) + bce(3., 0.5)) / 3.
+        ], 1e-5);
+    }
+
+    #[test]
+    fn regression_losses() {
+        let pred = ten![[1., 2., 3.], [0., 0., 10.]];
+        let target = ten![[1., 3., 5.], [0.5, 0., 0.]];
+
+        assert_close(&mse(&pred, &target), &ten![5. / 3., (0.25 + 100.) / 3.], 1e-5);
+        assert_close(&huber(&pred, &target, 1.), &ten![(0.5 + 1.5) / 3., (0.125 + 9.5) / 3.], 1e-5);
+        assert_close(&mse(&ten![1., 2.], &ten![0., 0.]), &Tensor::from_scalar(2.5), 1e-5);
+    }
+
+    #[test]
+    fn kl_divergence() {
+        let p = ten![[0.5, 0.5, 0.], [0.25, 0.25, 0.5]];
+        let q = ten![[0.25, 0.25, 0.5], [0.25, 0.25, 0.5]];
+
+        assert_close(&kl_div(&p, &q), &ten![2f32.ln(), 0.], 1e-5);
+    }
+}
@@ -0,0 +1,30 @@
+mod activation;
+mod conv;
+mod loss;
+mod norm;
+mod pool;
+mod softmax;
+
+pub use activation::{elu, gelu, gelu_tanh, leaky_relu, relu, sigmoid, silu, softplus};
+
+pub use conv::{
+    conv2d, conv2d_transpose, try_conv2d, try_conv2d_transpose,
+    Conv2dArg, Conv2dOpt, DataFormat, Padding,
+};
+
+pub use loss::{
+    binary_cross_entropy_with_logits, cross_entropy, cross_entropy_one_hot, huber, kl_div,
+    mse, try_cross_entropy,
+};
+
+pub use norm::{
+    group_norm, layer_norm, rms_norm, try_group_norm,
+    BatchNorm, BatchNormArg, BatchNormOpt, NormArg, NormOpt,
+};
+
+pub use pool::{
+    avg_pool2d, global_avg_pool, max_pool2d, try_avg_pool2d, try_max_pool2d,
+    Pool2dArg, Pool2dOpt,
+};
+
+pub use softmax::{log_softmax, logsumexp, softmax};
@@ -0,0 +1,414 @@
+use essay_opt::derive_opt;
+
+use crate::tensor::{FoldState, Tensor, TensorError};
+
+#[derive_opt(NormOpt)]
+#[derive(Default)]
+pub struct NormArg {
+    /// per-channel scale (gamma), broadcast over the trailing axes
+    scale: Option<Tensor>,
+    /// per-channel shift (beta), broadcast over the trailing axes
+    shift: Option<Tensor>,
+    /// added to the variance before the square root, defaulting to 1e-5
+    eps: Option<f32>,
+}
+
+impl Tensor {
+    #[inline]
+    pub fn layer_norm(&self, opt: impl NormOpt) -> Tensor {
+        layer_norm(self, opt)
+    }
+
+    #[inline]
+    pub fn rms_norm(&self, opt: impl NormOpt) -> Tensor {
+        rms_norm(self, opt)
+    }
+
+    #[inline]
+    pub fn group_norm(&self, groups: usize, opt: impl NormOpt) -> Tensor {
+        group_norm(self, groups, opt)
+    }
+}
+
+///
+/// Normalizes each item to zero mean and unit variance over the last axis.
+///
+pub fn layer_norm(x: &Tensor, opt: impl NormOpt) -> Tensor {
+    let opt = opt.into_arg();
+    let eps = opt.eps.unwrap_or(EPS);
+
+    let y = x.normalize(-1,
+        Moments::default(),
+        |s, v| s.update(*v),
+        |s| (s.mean, (s.variance() + eps).sqrt().recip()),
+        |(mean, inv_std), v| (*v - *mean) * *inv_std
+    );
+
+    affine(y, opt.scale.as_ref(), opt.shift.as_ref())
+}
+
+///
+/// Scales each item by the reciprocal root mean square over the last
+/// axis, without centering.
+///
+pub fn rms_norm(x: &Tensor, opt: impl NormOpt) -> Tensor {
+    let opt = opt.into_arg();
+    let eps = opt.eps.unwrap_or(EPS);
+
+    let y = x.normalize(-1,
+        MeanSquare::default(),
+        |s, v| s.update(*v),
+        |s| (s.mean + eps).sqrt().recip(),
+        |inv_rms, v| *v * *inv_rms
+    );
+
+    affine(y, opt.scale.as_ref(), opt.shift.as_ref())
+}
+
+pub fn group_norm(x: &Tensor, groups: usize, opt: impl NormOpt) -> Tensor {
+    try_group_norm(x, groups, opt).unwrap_or_else(|err| panic!("{}", err))
+}
+
+///
+/// Normalizes channels-last [N, ..., C] over each sample's channel groups
+/// and all of its inner axes. One group is layer normalization over the
+/// whole sample, and C groups is instance normalization.
+///
+pub fn try_group_norm(
+    x: &Tensor,
+    groups: usize,
+    opt: impl NormOpt
+) -> Result<Tensor, TensorError> {
+    let rank = x.rank();
+
+    if rank < 2 {
+        return Err(TensorError::InvalidShape {
+            op: "group_norm",
+            shape: x.shape().as_vec(),
+            reason: "group_norm expects [N, ..., C]",
+        });
+    }
+
+    let (n, c) = (x.dim(0), x.dim(rank - 1));
+
+    if groups == 0 || ! c.is_multiple_of(groups) {
+        return Err(TensorError::InvalidArgument {
+            op: "group_norm",
+            reason: format!("{} channels can't be split into {} groups", c, groups),
+        });
+    }
+
+    let opt = opt.into_arg();
+    let eps = opt.eps.unwrap_or(EPS);
+
+    let inner = x.shape().size() / n.max(1) / c.max(1);
+    let group_len = c / groups;
+
+    // gathers each group into a contiguous lane of [N, G, inner * C/G]
+    let group_index = |i: usize| {
+        let (sample, rest) = (i / (inner * c), i % (inner * c));
+        let (k, ch) = (rest / c, rest % c);
+        let (g, j) = (ch / group_len, ch % group_len);
+
+        (sample * groups + g) * inner * group_len + k * group_len + j
+    };
+
+    let x = x.to_contiguous();
+    let data = x.as_slice();
+
+    let mut grouped = vec![0.; data.len()];
+    for (i, v) in data.iter().enumerate() {
+        grouped[group_index(i)] = *v;
+    }
+
+    let grouped = Tensor::from_vec(grouped, [n, groups, inner * group_len])
+        .normalize(-1,
+            Moments::default(),
+            |s, v| s.update(*v),
+            |s| (s.mean, (s.variance() + eps).sqrt().recip()),
+            |(mean, inv_std), v| (*v - *mean) * *inv_std
+        );
+    let grouped = grouped.as_slice();
+
+    let vec = (0..data.len()).map(|i| grouped[group_index(i)]).collect();
+
+    let y = Tensor::from_vec(vec, x.shape().clone());
+
+    Ok(affine(y, opt.scale.as_ref(), opt.shift.as_ref()))
+}
+
+#[derive_opt(BatchNormOpt)]
+#[derive(Default)]
+pub struct BatchNormArg {
+    /// weight of each batch in the running statistics, defaulting to 0.1
+    momentum: Option<f32>,
+    scale: Option<Tensor>,
+    shift: Option<Tensor>,
+    eps: Option<f32>,
+}
+
+///
+/// Batch normalization of channels-last [..., C], normalizing each channel
+/// over all the other axes.
+///
+/// Training normalizes by the batch statistics and folds them into the
+/// running mean and variance, which inference uses instead.
+///
+pub struct BatchNorm {
+    running_mean: Tensor,
+    running_var: Tensor,
+    momentum: f32,
+    eps: f32,
+    scale: Option<Tensor>,
+    shift: Option<Tensor>,
+}
+
+impl BatchNorm {
+    pub fn new(channels: usize, opt: impl BatchNormOpt) -> Self {
+        let opt = opt.into_arg();
+
+        Self {
+            running_mean: Tensor::zeros([channels]),
+            running_var: Tensor::ones([channels]),
+            momentum: opt.momentum.unwrap_or(0.1),
+            eps: opt.eps.unwrap_or(EPS),
+            scale: opt.scale,
+            shift: opt.shift,
+        }
+    }
+
+    #[inline]
+    pub fn running_mean(&self) -> &Tensor {
+        &self.running_mean
+    }
+
+    #[inline]
+    pub fn running_var(&self) -> &Tensor {
+        &self.running_var
+    }
+
+    ///
+    /// Normalizes by the batch statistics and updates the running
+    /// statistics, using the unbiased batch variance for the running
+    /// variance.
+    ///
+    pub fn train(&mut self, x: &Tensor) -> Tensor {
+        let c = self.check(x);
+        let len = x.shape().size() / c.max(1);
+
+        let moments = x.clone().reshape([len, c])
+            .fold_axis(0, Moments::default(), |s, v| s.update(*v));
+
+        let mean = moments.map(|(mean, _)| *mean);
+        let var = moments.map(|(_, var)| *var);
+
+        let bessel = len as f32 / (len.max(2) - 1) as f32;
+        let momentum = self.momentum;
+
+        self.running_mean = self.running_mean.map2(&mean, |r, m| {
+            (1. - momentum) * r + momentum * m
+        });
+        self.running_var = self.running_var.map2(&var, |r, v| {
+            (1. - momentum) * r + momentum * v * bessel
+        });
+
+        self.apply(x, &mean, &var)
+    }
+
+    ///
+    /// Normalizes by the running statistics.
+    ///
+    pub fn eval(&self, x: &Tensor) -> Tensor {
+        self.check(x);
+
+        self.apply(x, &self.running_mean, &self.running_var)
+    }
+
+    fn check(&self, x: &Tensor) -> usize {
+        let c = self.running_mean.dim(0);
+
+        assert!(
+            x.rank() > 0 && x.dim(x.rank() - 1) == c,
+            "batch_norm expects [..., {}] but got {:?}", c, x.shape().as_vec()
+        );
+
+        c
+    }
+
+    fn apply(&self, x: &Tensor, mean: &Tensor, var: &Tensor) -> Tensor {
+        let eps = self.eps;
+        let inv_std = var.map(|v| (v + eps).sqrt().recip());
+
+        let y = x.map2(mean, |v, m| v - m).map2(&inv_std, |v, s| v * s);
+
+        affine(y, self.scale.as_ref(), self.shift.as_ref())
+    }
+}
+
+const EPS: f32 = 1e-5;
+
+fn affine(y: Tensor, scale: Option<&Tensor>, shift: Option<&Tensor>) -> Tensor {
+    let y = match scale {
+        Some(scale) => y.map2(scale, |v, s| v * s),
+        None => y,
+    };
+
+    match shift {
+        Some(shift) => y.map2(shift, |v, b| v + b),
+        None => y,
+    }
+}
+
+// Welford's running mean and variance, stable for large offsets
+#[derive(Clone, Debug, Default)]
+struct Moments {
+    n: usize,
+    mean: f32,
+    m2: f32,
+}
+
+impl Moments {
+    fn update(self, x: f32) -> Self {
+        let n = self.n + 1;
+        let delta = x - self.mean;
+        let mean = self.mean + delta / n as f32;
+
+        Self { n, mean, m2: self.m2 + delta * (x - mean) }
+    }
+
+    fn variance(&self) -> f32 {
+        if self.n > 0 { self.m2 / self.n as f32 } else { 0. }
+    }
+}
+
+impl FoldState for Moments {
+    type Out = (f32, f32);
+
+    fn into_result(self) -> Self::Out {
+        let variance = self.variance();
+
+        (self.mean, variance)
+    }
+}
+
+// running mean of the squares, which can't overflow the way a sum can
+#[derive(Clone, Debug, Default)]
+struct MeanSquare {
+    n: usize,
+    mean: f32,
+}
+
+impl MeanSquare {
+    fn update(self, x: f32) -> Self {
+        let n = self.n + 1;
+
+        Self { n, mean: self.mean + (x * x - self.mean) / n as f32 }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        nn::{group_norm, layer_norm, rms_norm, try_group_norm, BatchNorm},
+        ten, tensor::{Tensor, TensorError}, test::assert_close,
+    };
+
+    #[test]
+    fn layer_norm_rows() {
+        use crate::nn::NormOpt;
+
+        let x = ten![[1., 2., 3., 4.], [-2., 0., 2., 4.]];
+        let z = 1.3416355;
+
+        assert_close(&x.layer_norm(()), &ten![[-z, -z / 3., z / 3., z], [-z, -z / 3., z / 3., z]], 1e-4);
+
+        let y = layer_norm(&x, ().scale(ten![1., 2., 1., 2.]).shift(ten![0., 0., 1., 1.]));
+        let row = [-z, -2. * z / 3., 1. + z / 3., 1. + 2. * z];
+        assert_close(&y, &Tensor::from(vec![row, row]), 1e-4);
+
+        // Welford keeps the variance of a large offset
+        let y = ten![[10000., 10001., 10002., 10003.]].layer_norm(());
+        assert_close(&y, &ten![[-z, -z / 3., z / 3., z]], 1e-4);
+
+        // a constant row is all zeros rather than NaN
+        assert_eq!(ten![[3., 3.]].layer_norm(()), ten![[0., 0.]]);
+    }
+
+    #[test]
+    fn rms_norm_rows() {
+        use crate::nn::NormOpt;
+
+        let x = ten![[3., 4.], [0., -2.]];
+        let r = (12.5f32 + 1e-5).sqrt();
+        let s = (2f32 + 1e-5).sqrt();
+
+        assert_close(&rms_norm(&x, ()), &ten![[3. / r, 4. / r], [0., -2. / s]], 1e-4);
+        let r = 12.5f32.sqrt();
+        assert_close(
+            &x.rms_norm(().scale(ten![2., 1.]).eps(0.)),
+            &ten![[6. / r, 4. / r], [0., -(2f32).sqrt()]], 1e-4
+        );
+    }
+
+    #[test]
+    fn group_norm_groups() {
+        use crate::nn::NormOpt;
+
+        // [1, 2, 4] with two channels of different scale
+        let x = ten![[[0., 10.], [1., 20.], [2., 30.], [3., 40.]]];
+        let z = 1.3416355;
+
+        // one group per channel is instance normalization
+        assert_close(
+            &group_norm(&x, 2, ()),
+            &ten![[[-z, -z], [-z / 3., -z / 3.], [z / 3., z / 3.], [z, z]]], 1e-4
+        );
+
+        // one group normalizes the whole sample
+        let y = x.group_norm(1, ());
+        let flat = x.clone().reshape([1, 8]).layer_norm(()).reshape([1, 4, 2]);
+        assert_close(&y, &flat, 1e-4);
+
+        let y = x.group_norm(2, ().scale(ten![1., 2.]).shift(ten![0., 1.]));
+        assert_close(&y, &ten![[
+            [-z, 1. - 2. * z], [-z / 3., 1. - 2. * z / 3.],
+            [z / 3., 1. + 2. * z / 3.], [z, 1. + 2. * z]
+        ]], 1e-4);
+
+        assert!(matches!(
+            try_group_norm(&x, 3, ()).unwrap_err(),
+            TensorError::InvalidArgument { op: "group_norm", .. }
+        ));
+        assert!(matches!(
+            try_group_norm(&ten![1., 2.], 1, ()).unwrap_err(),
+            TensorError::InvalidShape { op: "group_norm", .. }
+        ));
+    }
+
+    #[test]
+    fn batch_norm_modes() {
+        use crate::nn::BatchNormOpt;
+
+        let mut bn = BatchNorm::new(2, ().momentum(0.5));
+
+        let x = ten![[1., 10.], [3., 10.], [5., 10.], [7., 10.]];
+        let z = 1.3416355;
+
+        let y = bn.train(&x);
+        assert_close(&y, &ten![[-z, 0.], [-z / 3., 0.], [z / 3., 0.], [z, 0.]], 1e-4);
+
+        // the running variance is unbiased: 20 / 3 for the first channel
+        assert_close(bn.running_mean(), &ten![2., 5.], 1e-4);
+        assert_close(bn.running_var(), &ten![0.5 + 0.5 * 20. / 3., 0.5], 1e-4);
+
+        let y = bn.eval(&ten![[2., 5.]]);
+        let s = (0.5f32 + 10. / 3. + 1e-5).sqrt();
+        assert_close(&y, &ten![[0., 0.]], 1e-4);
+
+        let y = bn.eval(&ten![[2. + s, 5.]]);
+        assert_close(&y, &ten![[1., 0.]], 1e-4);
+
+        let bn = BatchNorm::new(2, ().scale(ten![2., 1.]).shift(ten![1., -1.]));
+        assert_close(&bn.eval(&ten![[[1., 0.]]]), &ten![[[1. + 2. / (1f32 + 1e-5).sqrt(), -1.]]], 1e-4);
+    }
+}
@@ -0,0 +1,261 @@
+use essay_opt::derive_opt;
+use num_traits::Float;
+
+use crate::tensor::{Tensor, TensorError, Type};
+
+use super::conv::{DataFormat, Image, Padding, Window};
+
+#[derive_opt(Pool2dOpt)]
+#[derive(Default)]
+pub struct Pool2dArg {
+    /// (height, width) stride, defaulting to the window size
+    stride: Option<[usize; 2]>,
+    padding: Option<Padding>,
+    format: Option<DataFormat>,
+}
+
+impl<T: Type + Float> Tensor<T> {
+    #[inline]
+    pub fn max_pool2d(&self, window: [usize; 2], opt: impl Pool2dOpt) -> Tensor<T> {
+        max_pool2d(self, window, opt)
+    }
+
+    #[inline]
+    pub fn avg_pool2d(&self, window: [usize; 2], opt: impl Pool2dOpt) -> Tensor<T> {
+        avg_pool2d(self, window, opt)
+    }
+
+    #[inline]
+    pub fn global_avg_pool(&self, format: DataFormat) -> Tensor<T> {
+        global_avg_pool(self, format)
+    }
+}
+
+pub fn max_pool2d<T: Type + Float>(
+    x: &Tensor<T>,
+    window: [usize; 2],
+    opt: impl Pool2dOpt
+) -> Tensor<T> {
+    try_max_pool2d(x, window, opt).unwrap_or_else(|err| panic!("{}", err))
+}
+
+///
+/// Maximum over each window of a rank-4 image batch, where padding never
+/// wins.
+///
+pub fn try_max_pool2d<T: Type + Float>(
+    x: &Tensor<T>,
+    window: [usize; 2],
+    opt: impl Pool2dOpt
+) -> Result<Tensor<T>, TensorError> {
+    pool2d("max_pool2d", x, window, opt.into_arg(), |values| {
+        // unlike Float::max, a NaN item wins so it propagates
+        values.fold(T::neg_infinity(), |max, v| if v > max || v.is_nan() { v } else { max })
+    })
+}
+
+pub fn avg_pool2d<T: Type + Float>(
+    x: &Tensor<T>,
+    window: [usize; 2],
+    opt: impl Pool2dOpt
+) -> Tensor<T> {
+    try_avg_pool2d(x, window, opt).unwrap_or_else(|err| panic!("{}", err))
+}
+
+///
+/// Mean over each window of a rank-4 image batch, counting only the items
+/// inside the image.
+///
+pub fn try_avg_pool2d<T: Type + Float>(
+    x: &Tensor<T>,
+    window: [usize; 2],
+    opt: impl Pool2dOpt
+) -> Result<Tensor<T>, TensorError> {
+    pool2d("avg_pool2d", x, window, opt.into_arg(), |values| {
+        let (sum, count) = values.fold((T::zero(), 0), |(sum, n), v| (sum + v, n + 1));
+
+        sum / T::from(count.max(1)).unwrap()
+    })
+}
+
+///
+/// Mean over the height and width of a rank-4 image batch, as [N, C].
+///
+pub fn global_avg_pool<T: Type + Float>(x: &Tensor<T>, format: DataFormat) -> Tensor<T> {
+    let image = Image::new("global_avg_pool", format, x)
+        .unwrap_or_else(|err| panic!("{}", err));
+
+    let x = x.to_contiguous();
+    let x = x.as_slice();
+
+    let len = T::from((image.h * image.w).max(1)).unwrap();
+
+    Tensor::init_rindexed([image.n, image.c], |index| {
+        let (n, c) = (index[1], index[0]);
+        let mut sum = T::zero();
+
+        for h in 0..image.h {
+            for w in 0..image.w {
+                sum = sum + x[image.offset(n, h, w, c)];
+            }
+        }
+
+        sum / len
+    })
+}
+
+fn pool2d<T: Type + Float>(
+    op: &'static str,
+    x: &Tensor<T>,
+    window: [usize; 2],
+    opt: Pool2dArg,
+    mut f: impl FnMut(&mut dyn Iterator<Item = T>) -> T,
+) -> Result<Tensor<T>, TensorError> {
+    let format = opt.format.unwrap_or_default();
+    let stride = opt.stride.unwrap_or(window);
+    let padding = opt.padding.unwrap_or_default();
+
+    if window.contains(&0) || stride.contains(&0) {
+        return Err(TensorError::InvalidArgument {
+            op,
+            reason: "window and stride must be positive".to_string(),
+        });
+    }
+
+    let image = Image::new(op, format, x)?;
+
+    let rows = Window::new(window[0], stride[0], 1, padding, 0);
+    let cols = Window::new(window[1], stride[1], 1, padding, 1);
+
+    let height = rows.out_dim(op, image.h)?;
+    let width = cols.out_dim(op, image.w)?;
+
+    let out = Image { h: height.out, w: width.out, ..image };
+
+    let x = x.to_contiguous();
+    let x = x.as_slice();
+
+    let mut vec = vec![T::zero(); out.size()];
+
+    for n in 0..image.n {
+        for oh in 0..out.h {
+            for ow in 0..out.w {
+                for c in 0..image.c {
+                    let mut values = (0..window[0])
+                        .filter_map(|i| rows.input(&height, oh, i, image.h))
+                        .flat_map(|h| {
+                            (0..window[1])
+                                .filter_map(|j| cols.input(&width, ow, j, image.w))
+                                .map(move |w| (h, w))
+                        })
+                        .map(|(h, w)| x[image.offset(n, h, w, c)]);
+
+                    vec[out.offset(n, oh, ow, c)] = f(&mut values);
+                }
+            }
+        }
+    }
+
+    Ok(Tensor::from_vec(vec, out.shape()))
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        nn::{
+            avg_pool2d, global_avg_pool, max_pool2d, try_max_pool2d,
+            DataFormat, Padding, Pool2dOpt,
+        },
+        ten, tensor::{Tensor, TensorError},
+    };
+
+    fn x_nhwc() -> Tensor {
+        // [1, 4, 4, 2] with values 0..16 and a second channel of negatives
+        Tensor::init_rindexed([1, 4, 4, 2], |i| {
+            let v = (i[2] * 4 + i[1]) as f32;
+
+            if i[0] == 0 { v } else { - v }
+        })
+    }
+
+    #[test]
+    fn max_pool() {
+        let x = x_nhwc();
+
+        assert_eq!(
+            max_pool2d(&x, [2, 2], ()),
+            ten![[[[5., 0.], [7., -2.]], [[13., -8.], [15., -10.]]]]
+        );
+
+        assert_eq!(
+            max_pool2d(&x, [3, 3], ().stride([1, 1])).shape().as_vec(),
+            &[1, 2, 2, 2]
+        );
+
+        // padding never wins, even over negative values
+        assert_eq!(
+            max_pool2d(&x, [2, 2], ().padding(1)),
+            ten![[
+                [[0., 0.], [2., -1.], [3., -3.]],
+                [[8., -4.], [10., -5.], [11., -7.]],
+                [[12., -12.], [14., -13.], [15., -15.]]
+            ]]
+        );
+
+        let x = ten![[[[1.], [f32::NAN]], [[3.], [4.]]]];
+        assert!(max_pool2d(&x, [2, 2], ())[0].is_nan());
+        assert!(max_pool2d(&x, [2, 1], ())[(0, 0, 1, 0)].is_nan());
+        assert_eq!(max_pool2d(&x, [2, 1], ())[(0, 0, 0, 0)], 3.);
+    }
+
+    #[test]
+    fn avg_pool() {
+        let x = x_nhwc();
+
+        assert_eq!(
+            avg_pool2d(&x, [2, 2], ()),
+            ten![[[[2.5, -2.5], [4.5, -4.5]], [[10.5, -10.5], [12.5, -12.5]]]]
+        );
+
+        // Same pads after the image and excludes the padding from the mean
+        assert_eq!(
+            avg_pool2d(&x, [3, 3], ().stride([2, 2]).padding(Padding::Same)),
+            ten![[[[5., -5.], [6.5, -6.5]], [[11., -11.], [12.5, -12.5]]]]
+        );
+    }
+
+    #[test]
+    fn pool_nchw() {
+        let x = x_nhwc();
+        let x_nchw = Tensor::init_rindexed([1, 2, 4, 4], |i| x[(0, i[1], i[0], i[2])]);
+
+        let y = max_pool2d(&x_nchw, [2, 2], ().format(DataFormat::Nchw));
+        assert_eq!(y, ten![[[[5., 7.], [13., 15.]], [[0., -2.], [-8., -10.]]]]);
+
+        let y = avg_pool2d(&x_nchw, [2, 4], ().stride([2, 1]).format(DataFormat::Nchw));
+        assert_eq!(y, ten![[[[3.5], [11.5]], [[-3.5], [-11.5]]]]);
+    }
+
+    #[test]
+    fn global_avg() {
+        let x = x_nhwc();
+
+        assert_eq!(global_avg_pool(&x, DataFormat::Nhwc), ten![[7.5, -7.5]]);
+        assert_eq!(
+            global_avg_pool(&ten![[[[1., 2.], [3., 4.]]], [[[5., 6.], [7., 8.]]]], DataFormat::Nchw),
+            ten![[2.5], [6.5]]
+        );
+    }
+
+    #[test]
+    fn pool_errors() {
+        assert!(matches!(
+            try_max_pool2d(&x_nhwc(), [5, 1], ()).unwrap_err(),
+            TensorError::InvalidArgument { op: "max_pool2d", .. }
+        ));
+        assert!(matches!(
+            try_max_pool2d(&ten![[1., 2.]], [1, 1], ()).unwrap_err(),
+            TensorError::InvalidShape { op: "max_pool2d", .. }
+        ));
+    }
+}
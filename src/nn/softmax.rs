@@ -0,0 +1,149 @@
+use num_traits::Float;
+
+use crate::tensor::{Axis, FoldState, Tensor, Type};
+
+impl<T: Type + Float> Tensor<T> {
+    #[inline]
+    pub fn softmax(&self, axis: impl Into<Axis>) -> Tensor<T> {
+        softmax(self, axis)
+    }
+
+    #[inline]
+    pub fn log_softmax(&self, axis: impl Into<Axis>) -> Tensor<T> {
+        log_softmax(self, axis)
+    }
+
+    #[inline]
+    pub fn logsumexp(&self, axis: impl Into<Axis>) -> Tensor<T> {
+        logsumexp(self, axis)
+    }
+}
+
+///
+/// exp(x) / sum(exp(x)) along the axis, shifted by the maximum so large
+/// values don't overflow.
+///
+pub fn softmax<T: Type + Float>(x: &Tensor<T>, axis: impl Into<Axis>) -> Tensor<T> {
+    x.normalize(axis,
+        LogSumExp::default(),
+        |s, v| s.update(*v),
+        |s| s,
+        |s, v| (*v - s.max).exp() / s.sum
+    )
+}
+
+pub fn log_softmax<T: Type + Float>(x: &Tensor<T>, axis: impl Into<Axis>) -> Tensor<T> {
+    x.normalize(axis,
+        LogSumExp::default(),
+        |s, v| s.update(*v),
+        |s| (s.max, s.sum.ln()),
+        |(max, ln_sum), v| (*v - *max) - *ln_sum
+    )
+}
+
+///
+/// ln(sum(exp(x))) along the axis, removing the axis like `reduce_sum_axis`.
+///
+pub fn logsumexp<T: Type + Float>(x: &Tensor<T>, axis: impl Into<Axis>) -> Tensor<T> {
+    x.fold_axis(axis, LogSumExp::default(), |s, v| s.update(*v))
+}
+
+///
+/// Running maximum and the sum of exp(x - max), rescaled as the maximum
+/// grows, so the exponentials never overflow.
+///
+#[derive(Clone, Debug)]
+struct LogSumExp<T: Float> {
+    max: T,
+    sum: T,
+}
+
+impl<T: Float> LogSumExp<T> {
+    fn update(self, x: T) -> Self {
+        if x == T::neg_infinity() {
+            self
+        } else if x > self.max {
+            Self {
+                max: x,
+                sum: self.sum * (self.max - x).exp() + T::one(),
+            }
+        } else {
+            Self {
+                max: self.max,
+                sum: self.sum + (x - self.max).exp(),
+            }
+        }
+    }
+}
+
+impl<T: Float> Default for LogSumExp<T> {
+    fn default() -> Self {
+        Self { 
+            max: T::neg_infinity(),
+            sum: T::zero(),
+        }
+    }
+}
+
+impl<T: Float> FoldState for LogSumExp<T> {
+    type Out = T;
+
+    fn into_result(self) -> Self::Out {
+        self.max + self.sum.ln()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{ten, tensor::Tensor, test::assert_close};
+
+    #[test]
+    fn softmax_last_axis() {
+        let x = ten![[1., 2., 3.], [1000., 1000., 1000.]];
+
+        let e = [1f32.exp(), 2f32.exp(), 3f32.exp()];
+        let sum = e[0] + e[1] + e[2];
+        let third = 1. / 3.;
+
+        assert_close(
+            &x.softmax(-1),
+            &ten![[e[0] / sum, e[1] / sum, e[2] / sum], [third, third, third]], 1e-5
+        );
+
+        assert_close(
+            &x.log_softmax(-1),
+            &ten![
+                [1. - sum.ln(), 2. - sum.ln(), 3. - sum.ln()],
+                [third.ln(), third.ln(), third.ln()]
+            ], 1e-5
+        );
+
+        assert_close(&x.logsumexp(-1), &ten![sum.ln(), 1000. + 3f32.ln()], 1e-5);
+    }
+
+    #[test]
+    fn softmax_inner_axis() {
+        // the axis with leading and trailing batch dims
+        let x = ten![[[0., 1.], [0., 2.]], [[-1., 0.], [1., -1000.]]];
+
+        let s = x.softmax(1);
+        let sig = |x: f32| 1. / (1. + (-x).exp());
+
+        assert_close(&s, &ten![
+            [[0.5, sig(-1.)], [0.5, sig(1.)]],
+            [[sig(-2.), 1.], [sig(2.), 0.]]
+        ], 1e-5);
+
+        assert_close(&s.reduce_sum_axis(1), &ten![[1., 1.], [1., 1.]], 1e-5);
+        assert_close(&x.logsumexp(1), &ten![[2f32.ln(), 1. + 1f32.exp().ln_1p()], [2f32.exp().ln_1p() - 1., 0.]], 1e-5);
+    }
+
+    #[test]
+    fn softmax_all() {
+        let x = ten![[0., 0.], [0., f32::NEG_INFINITY]];
+
+        assert_close(&x.softmax(None), &ten![[1. / 3., 1. / 3.], [1. / 3., 0.]], 1e-5);
+        assert_close(&x.logsumexp(None), &Tensor::from_scalar(3f32.ln()), 1e-5);
+        assert_close(&ten![2f64].softmax(-1).map(|v| *v as f32), &ten![1.], 1e-5);
+    }
+}
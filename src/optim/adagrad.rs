@@ -0,0 +1,121 @@
+use essay_opt::derive_opt;
+
+use crate::tensor::Tensor;
+
+use super::{check_step, init_state, Optimizer, Schedule};
+
+#[derive_opt(AdagradOpt)]
+#[derive(Default)]
+pub struct AdagradArg {
+    /// Base learning rate, defaulting to 0.01
+    lr: Option<f32>,
+    eps: Option<f32>,
+    /// Starting value of the squared gradient sums
+    initial_accumulator: Option<f32>,
+    /// L2 penalty added to the gradient
+    weight_decay: Option<f32>,
+    schedule: Option<Schedule>,
+}
+
+///
+/// Adagrad, scaling the gradient by its accumulated root sum of squares.
+///
+pub struct Adagrad {
+    lr: f32,
+    eps: f32,
+    initial_accumulator: f32,
+    weight_decay: f32,
+    schedule: Schedule,
+
+    steps: usize,
+    sum: Vec<Tensor>,
+}
+
+impl Adagrad {
+    pub fn new(opt: impl AdagradOpt) -> Self {
+        let opt = opt.into_arg();
+
+        Self {
+            lr: opt.lr.unwrap_or(0.01),
+            eps: opt.eps.unwrap_or(1e-10),
+            initial_accumulator: opt.initial_accumulator.unwrap_or(0.),
+            weight_decay: opt.weight_decay.unwrap_or(0.),
+            schedule: opt.schedule.unwrap_or_default(),
+
+            steps: 0,
+            sum: Vec::new(),
+        }
+    }
+}
+
+impl Optimizer for Adagrad {
+    fn step(&mut self, params: &mut [Tensor], grads: &[Tensor]) {
+        check_step("adagrad", params, grads);
+
+        let lr = self.lr();
+
+        init_state("adagrad", &mut self.sum, params, self.initial_accumulator, self.steps);
+
+        for (i, (param, grad)) in params.iter_mut().zip(grads).enumerate() {
+            let mut grad = grad.clone();
+
+            if self.weight_decay != 0. {
+                grad += &*param * self.weight_decay;
+            }
+
+            self.sum[i] = &self.sum[i] + grad.square();
+
+            let eps = self.eps;
+            let update = grad.map2(&self.sum[i], |g, s| g / (s.sqrt() + eps));
+
+            *param = &*param - update * lr;
+        }
+
+        self.steps += 1;
+    }
+
+    fn lr(&self) -> f32 {
+        self.schedule.lr(self.lr, self.steps)
+    }
+
+    fn steps(&self) -> usize {
+        self.steps
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{optim::{Adagrad, AdagradOpt, Optimizer}, ten, test::assert_close};
+
+    #[test]
+    fn adagrad_steps() {
+        let mut adagrad = Adagrad::new(().lr(0.1));
+        let mut params = [ten![1., 2.]];
+
+        adagrad.step(&mut params, &[ten![3., -4.]]);
+        assert_close(&params[0], &ten![0.9, 2.1], 1e-5);
+
+        // sum = [9 + 16, 16 + 9]
+        adagrad.step(&mut params, &[ten![4., -3.]]);
+        assert_close(&params[0], &ten![0.9 - 0.08, 2.1 + 0.06], 1e-5);
+
+        let mut adagrad = Adagrad::new(().lr(0.1).initial_accumulator(16.));
+        let mut params = [ten![0.]];
+
+        adagrad.step(&mut params, &[ten![3.]]);
+        assert_close(&params[0], &ten![-0.06], 1e-5);
+    }
+
+    #[test]
+    fn adagrad_quadratic() {
+        let mut adagrad = Adagrad::new(().lr(0.5));
+        let mut params = [ten![0., 0.]];
+
+        for _ in 0..2000 {
+            let grad = (&params[0] - ten![3., -1.]) * 2.;
+            adagrad.step(&mut params, &[grad]);
+        }
+
+        assert!((&params[0] - ten![3., -1.]).abs().reduce_max()[0] < 1e-3);
+    }
+}
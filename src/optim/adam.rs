@@ -0,0 +1,196 @@
+use essay_opt::derive_opt;
+
+use crate::tensor::Tensor;
+
+use super::{check_step, init_state, Optimizer, Schedule};
+
+#[derive_opt(AdamOpt)]
+#[derive(Default)]
+pub struct AdamArg {
+    /// Base learning rate, defaulting to 1e-3
+    lr: Option<f32>,
+    /// Decay of the gradient mean, defaulting to 0.9
+    beta1: Option<f32>,
+    /// Decay of the squared gradient mean, defaulting to 0.999
+    beta2: Option<f32>,
+    eps: Option<f32>,
+    /// L2 penalty for Adam, or decoupled decay for AdamW
+    weight_decay: Option<f32>,
+    /// Uses the maximum of the squared gradient means
+    amsgrad: bool,
+    schedule: Option<Schedule>,
+}
+
+///
+/// Adam with bias-corrected moment estimates, or AdamW with the weight
+/// decay decoupled from the gradient.
+///
+pub struct Adam {
+    lr: f32,
+    beta1: f32,
+    beta2: f32,
+    eps: f32,
+    weight_decay: f32,
+    is_decoupled: bool,
+    amsgrad: bool,
+    schedule: Schedule,
+
+    steps: usize,
+    m: Vec<Tensor>,
+    v: Vec<Tensor>,
+    v_max: Vec<Tensor>,
+}
+
+impl Adam {
+    pub fn new(opt: impl AdamOpt) -> Self {
+        Self::with_arg(opt.into_arg(), 0., false)
+    }
+
+    ///
+    /// AdamW, where the weight decay defaults to 0.01.
+    ///
+    pub fn adamw(opt: impl AdamOpt) -> Self {
+        Self::with_arg(opt.into_arg(), 0.01, true)
+    }
+
+    fn with_arg(opt: AdamArg, weight_decay: f32, is_decoupled: bool) -> Self {
+        Self {
+            lr: opt.lr.unwrap_or(1e-3),
+            beta1: opt.beta1.unwrap_or(0.9),
+            beta2: opt.beta2.unwrap_or(0.999),
+            eps: opt.eps.unwrap_or(1e-8),
+            weight_decay: opt.weight_decay.unwrap_or(weight_decay),
+            is_decoupled,
+            amsgrad: opt.amsgrad,
+            schedule: opt.schedule.unwrap_or_default(),
+
+            steps: 0,
+            m: Vec::new(),
+            v: Vec::new(),
+            v_max: Vec::new(),
+        }
+    }
+}
+
+impl Optimizer for Adam {
+    fn step(&mut self, params: &mut [Tensor], grads: &[Tensor]) {
+        check_step("adam", params, grads);
+
+        let lr = self.lr();
+
+        init_state("adam", &mut self.m, params, 0., self.steps);
+        init_state("adam", &mut self.v, params, 0., self.steps);
+
+        if self.amsgrad {
+            init_state("adam", &mut self.v_max, params, 0., self.steps);
+        }
+
+        let t = (self.steps + 1) as i32;
+        let bias1 = 1. - self.beta1.powi(t);
+        let bias2 = 1. - self.beta2.powi(t);
+
+        for (i, (param, grad)) in params.iter_mut().zip(grads).enumerate() {
+            let mut grad = grad.clone();
+
+            if self.weight_decay != 0. {
+                if self.is_decoupled {
+                    *param = &*param * (1. - lr * self.weight_decay);
+                } else {
+                    grad += &*param * self.weight_decay;
+                }
+            }
+
+            self.m[i] = &self.m[i] * self.beta1 + &grad * (1. - self.beta1);
+            self.v[i] = &self.v[i] * self.beta2 + grad.square() * (1. - self.beta2);
+
+            let v = if self.amsgrad {
+                self.v_max[i] = self.v_max[i].map2(&self.v[i], |a, b| a.max(*b));
+
+                &self.v_max[i]
+            } else {
+                &self.v[i]
+            };
+
+            let eps = self.eps;
+            let update = self.m[i].map2(v, |m, v| {
+                (m / bias1) / ((v / bias2).sqrt() + eps)
+            });
+
+            *param = &*param - update * lr;
+        }
+
+        self.steps += 1;
+    }
+
+    fn lr(&self) -> f32 {
+        self.schedule.lr(self.lr, self.steps)
+    }
+
+    fn steps(&self) -> usize {
+        self.steps
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{optim::{Adam, AdamOpt, Optimizer}, ten, test::assert_close};
+
+    #[test]
+    fn adam_first_step() {
+        // the bias-corrected first step moves each item by lr
+        let mut adam = Adam::new(().lr(0.1));
+        let mut params = [ten![1., 2., 3.]];
+
+        adam.step(&mut params, &[ten![0.5, -20., 1e-3]]);
+        assert_close(&params[0], &ten![0.9, 2.1, 2.9], 1e-5);
+    }
+
+    #[test]
+    fn adam_weight_decay() {
+        let mut adam = Adam::new(().lr(0.1).weight_decay(1.));
+        let mut params = [ten![1.]];
+
+        // the L2 gradient cancels the gradient, so the step is zero
+        adam.step(&mut params, &[ten![-1.]]);
+        assert_close(&params[0], &ten![1.], 1e-5);
+
+        let mut adamw = Adam::adamw(().lr(0.1).weight_decay(1.));
+        let mut params = [ten![1.]];
+
+        adamw.step(&mut params, &[ten![-1.]]);
+        assert_close(&params[0], &ten![1.], 1e-5);
+
+        let mut adamw = Adam::adamw(().lr(0.1));
+        let mut params = [ten![2.]];
+
+        adamw.step(&mut params, &[ten![1.]]);
+        assert_close(&params[0], &ten![2. * (1. - 0.1 * 0.01) - 0.1], 1e-5);
+    }
+
+    #[test]
+    fn adam_quadratic() {
+        for amsgrad in [false, true] {
+            let mut adam = Adam::new(().lr(0.05).amsgrad(amsgrad));
+            let mut params = [ten![0., 0.], ten![[5.]]];
+
+            for _ in 0..2000 {
+                let grads = [
+                    (&params[0] - ten![3., -1.]) * 2.,
+                    &params[1] * 2.,
+                ];
+                adam.step(&mut params, &grads);
+            }
+
+            assert!((&params[0] - ten![3., -1.]).abs().reduce_max()[0] < 1e-3);
+            assert!(params[1].abs().reduce_max()[0] < 1e-3);
+        }
+    }
+    #[test]
+    #[should_panic(expected = "adam params changed after the first step")]
+    fn adam_params_changed() {
+        let mut adam = Adam::new(());
+
+        adam.step(&mut [ten![1.]], &[ten![1.]]);
+        adam.step(&mut [ten![1., 2.]], &[ten![1., 1.]]);
+    }
+}
@@ -0,0 +1,65 @@
+mod adagrad;
+mod adam;
+mod rmsprop;
+mod schedule;
+mod sgd;
+
+pub use adagrad::{Adagrad, AdagradArg, AdagradOpt};
+pub use adam::{Adam, AdamArg, AdamOpt};
+pub use rmsprop::{RmsProp, RmsPropArg, RmsPropOpt};
+pub use schedule::Schedule;
+pub use sgd::{Sgd, SgdArg, SgdOpt};
+
+use crate::tensor::Tensor;
+
+///
+/// Updates parameters in place from their gradients, keeping any
+/// per-parameter state between steps.
+///
+pub trait Optimizer {
+    fn step(&mut self, params: &mut [Tensor], grads: &[Tensor]);
+
+    /// The learning rate of the next step, after the schedule.
+    fn lr(&self) -> f32;
+
+    /// The number of steps taken.
+    fn steps(&self) -> usize;
+}
+
+fn check_step(op: &'static str, params: &[Tensor], grads: &[Tensor]) {
+    assert_eq!(
+        params.len(), grads.len(),
+        "{} has {} params but {} grads", op, params.len(), grads.len()
+    );
+
+    for (param, grad) in params.iter().zip(grads) {
+        assert_eq!(
+            param.shape(), grad.shape(),
+            "{} param and grad shapes must match", op
+        );
+    }
+}
+
+///
+/// Fills the per-parameter state with `init` on the first step. Later steps
+/// must pass the same params, because the state and the step count, which
+/// drives bias correction and momentum seeding, belong to them.
+///
+fn init_state(
+    op: &'static str,
+    state: &mut Vec<Tensor>,
+    params: &[Tensor],
+    init: f32,
+    steps: usize,
+) {
+    if steps == 0 {
+        *state = params.iter()
+            .map(|p| Tensor::fill(p.shape().clone(), init))
+            .collect();
+    } else {
+        let is_match = state.len() == params.len()
+            && state.iter().zip(params).all(|(s, p)| s.shape() == p.shape());
+
+        assert!(is_match, "{} params changed after the first step", op);
+    }
+}
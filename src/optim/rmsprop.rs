@@ -0,0 +1,159 @@
+use essay_opt::derive_opt;
+
+use crate::tensor::Tensor;
+
+use super::{check_step, init_state, Optimizer, Schedule};
+
+#[derive_opt(RmsPropOpt)]
+#[derive(Default)]
+pub struct RmsPropArg {
+    /// Base learning rate, defaulting to 0.01
+    lr: Option<f32>,
+    /// Decay of the squared gradient mean, defaulting to 0.99
+    alpha: Option<f32>,
+    eps: Option<f32>,
+    momentum: Option<f32>,
+    /// Normalizes by the gradient variance instead of the squared mean
+    centered: bool,
+    /// L2 penalty added to the gradient
+    weight_decay: Option<f32>,
+    schedule: Option<Schedule>,
+}
+
+///
+/// RMSProp, scaling the gradient by a running root mean square.
+///
+pub struct RmsProp {
+    lr: f32,
+    alpha: f32,
+    eps: f32,
+    momentum: f32,
+    centered: bool,
+    weight_decay: f32,
+    schedule: Schedule,
+
+    steps: usize,
+    square_avg: Vec<Tensor>,
+    grad_avg: Vec<Tensor>,
+    velocity: Vec<Tensor>,
+}
+
+impl RmsProp {
+    pub fn new(opt: impl RmsPropOpt) -> Self {
+        let opt = opt.into_arg();
+
+        Self {
+            lr: opt.lr.unwrap_or(0.01),
+            alpha: opt.alpha.unwrap_or(0.99),
+            eps: opt.eps.unwrap_or(1e-8),
+            momentum: opt.momentum.unwrap_or(0.),
+            centered: opt.centered,
+            weight_decay: opt.weight_decay.unwrap_or(0.),
+            schedule: opt.schedule.unwrap_or_default(),
+
+            steps: 0,
+            square_avg: Vec::new(),
+            grad_avg: Vec::new(),
+            velocity: Vec::new(),
+        }
+    }
+}
+
+impl Optimizer for RmsProp {
+    fn step(&mut self, params: &mut [Tensor], grads: &[Tensor]) {
+        check_step("rmsprop", params, grads);
+
+        let lr = self.lr();
+
+        init_state("rmsprop", &mut self.square_avg, params, 0., self.steps);
+
+        if self.centered {
+            init_state("rmsprop", &mut self.grad_avg, params, 0., self.steps);
+        }
+
+        if self.momentum > 0. {
+            init_state("rmsprop", &mut self.velocity, params, 0., self.steps);
+        }
+
+        for (i, (param, grad)) in params.iter_mut().zip(grads).enumerate() {
+            let mut grad = grad.clone();
+
+            if self.weight_decay != 0. {
+                grad += &*param * self.weight_decay;
+            }
+
+            let alpha = self.alpha;
+            self.square_avg[i] = &self.square_avg[i] * alpha + grad.square() * (1. - alpha);
+
+            let mut variance = self.square_avg[i].clone();
+
+            if self.centered {
+                self.grad_avg[i] = &self.grad_avg[i] * alpha + &grad * (1. - alpha);
+                variance -= self.grad_avg[i].square();
+            }
+
+            let eps = self.eps;
+            let update = grad.map2(&variance, |g, v| g / (v.sqrt() + eps));
+
+            let update = if self.momentum > 0. {
+                self.velocity[i] = &self.velocity[i] * self.momentum + update;
+
+                self.velocity[i].clone()
+            } else {
+                update
+            };
+
+            *param = &*param - update * lr;
+        }
+
+        self.steps += 1;
+    }
+
+    fn lr(&self) -> f32 {
+        self.schedule.lr(self.lr, self.steps)
+    }
+
+    fn steps(&self) -> usize {
+        self.steps
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{optim::{Optimizer, RmsProp, RmsPropOpt}, ten, test::assert_close};
+
+    #[test]
+    fn rmsprop_first_step() {
+        // square_avg = 0.01 g^2, so the first step is lr / sqrt(0.01)
+        let mut rmsprop = RmsProp::new(().lr(0.01));
+        let mut params = [ten![1., 2.]];
+
+        rmsprop.step(&mut params, &[ten![3., -0.5]]);
+        assert_close(&params[0], &ten![0.9, 2.1], 1e-4);
+
+        let mut rmsprop = RmsProp::new(().lr(0.01).alpha(0.75).momentum(0.5));
+        let mut params = [ten![0.]];
+
+        rmsprop.step(&mut params, &[ten![1.]]);
+        assert_close(&params[0], &ten![-0.02], 1e-4);
+
+        // square_avg = 0.4375, velocity = 0.5 * 2 + 1 / sqrt(0.4375)
+        rmsprop.step(&mut params, &[ten![1.]]);
+        assert_close(&params[0], &ten![-0.02 - 0.01 * (1. + 1. / 0.4375f32.sqrt())], 1e-4);
+    }
+
+    #[test]
+    fn rmsprop_quadratic() {
+        for centered in [false, true] {
+            let mut rmsprop = RmsProp::new(().lr(0.01).centered(centered));
+            let mut params = [ten![0., 0.]];
+
+            for _ in 0..2000 {
+                let grad = (&params[0] - ten![3., -1.]) * 2.;
+                rmsprop.step(&mut params, &[grad]);
+            }
+
+            assert!((&params[0] - ten![3., -1.]).abs().reduce_max()[0] < 1e-2);
+        }
+    }
+}
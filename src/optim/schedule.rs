@@ -0,0 +1,93 @@
+use std::f32::consts::PI;
+
+///
+/// A learning-rate schedule, as a multiple of the optimizer's base rate
+/// by step.
+///
+#[derive(Clone, Debug, Default, PartialEq)]
+pub enum Schedule {
+    #[default]
+    Constant,
+    /// Decays by gamma every step_size steps
+    Step { step_size: usize, gamma: f32 },
+    /// Cosine annealing down to min_lr over total_steps, then constant
+    Cosine { total_steps: usize, min_lr: f32 },
+    /// Linear warmup from lr / steps up to lr, followed by the schedule
+    Warmup { steps: usize, then: Box<Schedule> },
+}
+
+impl Schedule {
+    pub fn step(step_size: usize, gamma: f32) -> Self {
+        assert!(step_size > 0, "step schedule requires step_size > 0");
+
+        Schedule::Step { step_size, gamma }
+    }
+
+    pub fn cosine(total_steps: usize, min_lr: f32) -> Self {
+        assert!(total_steps > 0, "cosine schedule requires total_steps > 0");
+
+        Schedule::Cosine { total_steps, min_lr }
+    }
+
+    pub fn warmup(steps: usize, then: Schedule) -> Self {
+        Schedule::Warmup { steps, then: Box::new(then) }
+    }
+
+    /// The learning rate at the zero-based step.
+    pub fn lr(&self, base_lr: f32, step: usize) -> f32 {
+        match self {
+            Schedule::Constant => base_lr,
+            Schedule::Step { step_size, gamma } => {
+                base_lr * gamma.powi((step / step_size) as i32)
+            }
+            Schedule::Cosine { total_steps, min_lr } => {
+                let t = step.min(*total_steps) as f32 / *total_steps as f32;
+
+                min_lr + 0.5 * (base_lr - min_lr) * (1. + (PI * t).cos())
+            }
+            Schedule::Warmup { steps, then } => {
+                if step < *steps {
+                    base_lr * (step + 1) as f32 / *steps as f32
+                } else {
+                    then.lr(base_lr, step - steps)
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{optim::Schedule, test::assert_close};
+
+    #[test]
+    fn schedule_constant_step() {
+        assert_eq!(Schedule::Constant.lr(0.1, 1000), 0.1);
+
+        let s = Schedule::step(10, 0.5);
+        assert_eq!(s.lr(0.1, 0), 0.1);
+        assert_eq!(s.lr(0.1, 9), 0.1);
+        assert_eq!(s.lr(0.1, 10), 0.05);
+        assert_eq!(s.lr(0.1, 25), 0.025);
+    }
+
+    #[test]
+    fn schedule_cosine() {
+        let s = Schedule::cosine(100, 0.01);
+
+        assert_close(s.lr(0.1, 0), 0.1, 1e-6);
+        assert_close(s.lr(0.1, 50), 0.055, 1e-6);
+        assert_close(s.lr(0.1, 100), 0.01, 1e-6);
+        assert_close(s.lr(0.1, 200), 0.01, 1e-6);
+    }
+
+    #[test]
+    fn schedule_warmup() {
+        let s = Schedule::warmup(4, Schedule::step(2, 0.1));
+
+        assert_close(s.lr(1., 0), 0.25, 1e-6);
+        assert_close(s.lr(1., 3), 1., 1e-6);
+        assert_close(s.lr(1., 4), 1., 1e-6);
+        assert_close(s.lr(1., 6), 0.1, 1e-6);
+    }
+}
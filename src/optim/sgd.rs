@@ -0,0 +1,183 @@
+use essay_opt::derive_opt;
+
+use crate::tensor::Tensor;
+
+use super::{check_step, init_state, Optimizer, Schedule};
+
+#[derive_opt(SgdOpt)]
+#[derive(Default)]
+pub struct SgdArg {
+    /// Base learning rate, defaulting to 0.01
+    lr: Option<f32>,
+    momentum: Option<f32>,
+    /// Dampening of the gradient added to the momentum
+    dampening: Option<f32>,
+    nesterov: bool,
+    /// L2 penalty added to the gradient
+    weight_decay: Option<f32>,
+    schedule: Option<Schedule>,
+}
+
+///
+/// Stochastic gradient descent with optional momentum and Nesterov
+/// momentum, following PyTorch's update.
+///
+pub struct Sgd {
+    lr: f32,
+    momentum: f32,
+    dampening: f32,
+    nesterov: bool,
+    weight_decay: f32,
+    schedule: Schedule,
+
+    steps: usize,
+    velocity: Vec<Tensor>,
+}
+
+impl Sgd {
+    pub fn new(opt: impl SgdOpt) -> Self {
+        let opt = opt.into_arg();
+
+        let sgd = Self {
+            lr: opt.lr.unwrap_or(0.01),
+            momentum: opt.momentum.unwrap_or(0.),
+            dampening: opt.dampening.unwrap_or(0.),
+            nesterov: opt.nesterov,
+            weight_decay: opt.weight_decay.unwrap_or(0.),
+            schedule: opt.schedule.unwrap_or_default(),
+
+            steps: 0,
+            velocity: Vec::new(),
+        };
+
+        assert!(
+            ! sgd.nesterov || sgd.momentum > 0. && sgd.dampening == 0.,
+            "sgd nesterov requires momentum and zero dampening"
+        );
+
+        sgd
+    }
+}
+
+impl Optimizer for Sgd {
+    fn step(&mut self, params: &mut [Tensor], grads: &[Tensor]) {
+        check_step("sgd", params, grads);
+
+        let lr = self.lr();
+
+        if self.momentum > 0. {
+            init_state("sgd", &mut self.velocity, params, 0., self.steps);
+        }
+
+        for (i, (param, grad)) in params.iter_mut().zip(grads).enumerate() {
+            let mut grad = grad.clone();
+
+            if self.weight_decay != 0. {
+                grad += &*param * self.weight_decay;
+            }
+
+            if self.momentum > 0. {
+                let velocity = if self.steps == 0 {
+                    grad.clone()
+                } else {
+                    &self.velocity[i] * self.momentum + &grad * (1. - self.dampening)
+                };
+
+                grad = if self.nesterov {
+                    grad + &velocity * self.momentum
+                } else {
+                    velocity.clone()
+                };
+
+                self.velocity[i] = velocity;
+            }
+
+            *param = &*param - grad * lr;
+        }
+
+        self.steps += 1;
+    }
+
+    fn lr(&self) -> f32 {
+        self.schedule.lr(self.lr, self.steps)
+    }
+
+    fn steps(&self) -> usize {
+        self.steps
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{optim::{Optimizer, Schedule, Sgd, SgdOpt}, ten};
+
+    #[test]
+    fn sgd_plain() {
+        let mut sgd = Sgd::new(().lr(0.1));
+        let mut params = [ten![1., 2.]];
+
+        sgd.step(&mut params, &[ten![1., -2.]]);
+        assert_eq!(params[0], ten![0.9, 2.2]);
+        assert_eq!(sgd.steps(), 1);
+
+        let mut sgd = Sgd::new(().lr(0.1).weight_decay(0.5));
+        sgd.step(&mut params, &[ten![0., 0.]]);
+        assert!((&params[0] - ten![0.855, 2.09]).abs().reduce_max()[0] < 1e-6);
+    }
+
+    #[test]
+    fn sgd_momentum() {
+        let mut sgd = Sgd::new(().lr(1.).momentum(0.5));
+        let mut params = [ten![0.]];
+
+        sgd.step(&mut params, &[ten![1.]]);
+        assert_eq!(params[0], ten![-1.]);
+
+        sgd.step(&mut params, &[ten![1.]]);
+        assert_eq!(params[0], ten![-2.5]);
+
+        let mut sgd = Sgd::new(().lr(1.).momentum(0.5).nesterov(true));
+        let mut params = [ten![0.]];
+
+        sgd.step(&mut params, &[ten![1.]]);
+        assert_eq!(params[0], ten![-1.5]);
+
+        sgd.step(&mut params, &[ten![1.]]);
+        assert_eq!(params[0], ten![-3.25]);
+    }
+
+    #[test]
+    fn sgd_schedule() {
+        let mut sgd = Sgd::new(().lr(1.).schedule(Schedule::step(1, 0.5)));
+        let mut params = [ten![0.]];
+
+        assert_eq!(sgd.lr(), 1.);
+        sgd.step(&mut params, &[ten![1.]]);
+        assert_eq!(sgd.lr(), 0.5);
+        sgd.step(&mut params, &[ten![1.]]);
+
+        assert_eq!(params[0], ten![-1.5]);
+    }
+
+    #[test]
+    fn sgd_quadratic() {
+        // minimize (x - 3)^2 + (y + 1)^2
+        let mut sgd = Sgd::new(().lr(0.1).momentum(0.9));
+        let mut params = [ten![0., 0.]];
+
+        for _ in 0..200 {
+            let grad = (&params[0] - ten![3., -1.]) * 2.;
+            sgd.step(&mut params, &[grad]);
+        }
+
+        assert!((&params[0] - ten![3., -1.]).abs().reduce_max()[0] < 1e-4);
+    }
+
+    #[test]
+    #[should_panic]
+    fn sgd_shape_mismatch() {
+        let mut sgd = Sgd::new(());
+
+        sgd.step(&mut [ten![1., 2.]], &[ten![1.]]);
+    }
+}
@@ -0,0 +1,385 @@
+use essay_opt::derive_opt;
+use num_complex::Complex;
+use num_traits::Zero;
+use rustfft::FftDirection;
+
+use crate::tensor::Tensor;
+
+use super::fft::plan_fft;
+
+///
+/// Output size of a convolution, like SciPy's `mode`.
+///
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum ConvMode {
+    /// Every point where the signal and kernel overlap, n + k - 1
+    #[default]
+    Full,
+    /// The size of the signal, centered on the full output
+    Same,
+    /// Only points where one input fully overlaps the other,
+    /// max(n, k) - min(n, k) + 1
+    Valid,
+}
+
+///
+/// Convolution algorithm. `Auto` uses the direct sum for short kernels
+/// and the FFT when both inputs are long.
+///
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum ConvMethod {
+    #[default]
+    Auto,
+    Direct,
+    Fft,
+}
+
+#[derive_opt(ConvolveOpt)]
+#[derive(Default)]
+pub struct ConvolveArg {
+    mode: Option<ConvMode>,
+    method: Option<ConvMethod>,
+}
+
+// Direct is faster until both sides are longer than this
+const DIRECT_MAX: usize = 64;
+
+///
+/// Convolution of each lane of the last axis of x with a 1-d kernel.
+///
+pub fn convolve(x: impl Into<Tensor>, kernel: impl Into<Tensor>, opt: impl ConvolveOpt) -> Tensor {
+    let kernel = kernel.into().to_contiguous();
+    assert_eq!(kernel.rank(), 1, "convolve requires a 1-d kernel");
+
+    convolve_1d(x.into(), kernel.as_slice(), opt.into_arg())
+}
+
+///
+/// Cross-correlation of each lane of the last axis of x with a 1-d
+/// kernel, which is the convolution with the reversed kernel.
+///
+pub fn correlate(x: impl Into<Tensor>, kernel: impl Into<Tensor>, opt: impl ConvolveOpt) -> Tensor {
+    let kernel = kernel.into().to_contiguous();
+    assert_eq!(kernel.rank(), 1, "correlate requires a 1-d kernel");
+
+    let reversed: Vec<f32> = kernel.as_slice().iter().rev().cloned().collect();
+
+    convolve_1d(x.into(), &reversed, opt.into_arg())
+}
+
+///
+/// 2-d convolution of each [h, w] image in the last two axes of x with a
+/// [kh, kw] kernel.
+///
+pub fn convolve2d(
+    x: impl Into<Tensor>, 
+    kernel: impl Into<Tensor>, 
+    opt: impl ConvolveOpt
+) -> Tensor {
+    let kernel = kernel.into().to_contiguous();
+    assert_eq!(kernel.rank(), 2, "convolve2d requires a 2-d kernel");
+
+    convolve_2d(x.into(), &kernel, opt.into_arg())
+}
+
+/// 2-d cross-correlation, the convolution with the flipped kernel.
+pub fn correlate2d(
+    x: impl Into<Tensor>, 
+    kernel: impl Into<Tensor>, 
+    opt: impl ConvolveOpt
+) -> Tensor {
+    let kernel = kernel.into().to_contiguous();
+    assert_eq!(kernel.rank(), 2, "correlate2d requires a 2-d kernel");
+
+    let flipped = Tensor::from_vec(
+        kernel.as_slice().iter().rev().cloned().collect(), 
+        kernel.shape().clone()
+    );
+
+    convolve_2d(x.into(), &flipped, opt.into_arg())
+}
+
+fn convolve_1d(x: Tensor, kernel: &[f32], opt: ConvolveArg) -> Tensor {
+    assert!(x.rank() > 0, "convolve requires rank > 0");
+
+    let x = x.to_contiguous();
+    let n = x.cols();
+    let k = kernel.len();
+    let full = n + k - 1;
+
+    let (start, len) = mode_range(opt.mode.unwrap_or_default(), n, k);
+
+    let is_fft = match opt.method.unwrap_or_default() {
+        ConvMethod::Auto => n.min(k) > DIRECT_MAX,
+        ConvMethod::Direct => false,
+        ConvMethod::Fft => true,
+    };
+
+    let mut vec = Vec::<f32>::with_capacity(x.size() / n * len);
+
+    if is_fft {
+        let nfft = full.next_power_of_two();
+        let kernel_fft = forward_fft(kernel, nfft);
+        let inverse = plan_fft(nfft, FftDirection::Inverse);
+
+        for lane in x.as_slice().chunks_exact(n) {
+            let mut buffer = forward_fft(lane, nfft);
+
+            for (b, k) in buffer.iter_mut().zip(&kernel_fft) {
+                *b *= k;
+            }
+
+            inverse.process(&mut buffer);
+
+            let scale = 1. / nfft as f32;
+            vec.extend(buffer[start..start + len].iter().map(|c| c.re * scale));
+        }
+    } else {
+        for lane in x.as_slice().chunks_exact(n) {
+            for i in start..start + len {
+                let j_min = (i + 1).saturating_sub(n);
+                let j_max = i.min(k - 1);
+
+                let mut sum = 0.;
+                for j in j_min..=j_max {
+                    sum += lane[i - j] * kernel[j];
+                }
+
+                vec.push(sum);
+            }
+        }
+    }
+
+    let mut dims = x.shape().as_vec();
+    *dims.last_mut().unwrap() = len;
+
+    Tensor::from_vec(vec, dims.as_slice())
+}
+
+fn convolve_2d(x: Tensor, kernel: &Tensor, opt: ConvolveArg) -> Tensor {
+    assert!(x.rank() >= 2, "convolve2d requires rank >= 2");
+
+    let x = x.to_contiguous();
+    let (h, w) = (x.rows(), x.cols());
+    let (kh, kw) = (kernel.rows(), kernel.cols());
+    let (full_h, full_w) = (h + kh - 1, w + kw - 1);
+
+    let mode = opt.mode.unwrap_or_default();
+    let (start_i, len_h) = mode_range(mode, h, kh);
+    let (start_j, len_w) = mode_range(mode, w, kw);
+
+    let is_fft = match opt.method.unwrap_or_default() {
+        ConvMethod::Auto => (h * w).min(kh * kw) > DIRECT_MAX * DIRECT_MAX / 4,
+        ConvMethod::Direct => false,
+        ConvMethod::Fft => true,
+    };
+
+    let kernel = kernel.as_slice();
+    let mut vec = Vec::<f32>::with_capacity(x.size() / (h * w) * len_h * len_w);
+
+    if is_fft {
+        let (nfft_h, nfft_w) = (full_h.next_power_of_two(), full_w.next_power_of_two());
+
+        let kernel_fft = forward_fft2(kernel, kh, kw, nfft_h, nfft_w);
+
+        for image in x.as_slice().chunks_exact(h * w) {
+            let mut buffer = forward_fft2(image, h, w, nfft_h, nfft_w);
+
+            for (b, k) in buffer.iter_mut().zip(&kernel_fft) {
+                *b *= k;
+            }
+
+            fft2_in_place(&mut buffer, nfft_h, nfft_w, FftDirection::Inverse);
+
+            let scale = 1. / (nfft_h * nfft_w) as f32;
+            for i in start_i..start_i + len_h {
+                let row = &buffer[i * nfft_w + start_j..i * nfft_w + start_j + len_w];
+                vec.extend(row.iter().map(|c| c.re * scale));
+            }
+        }
+    } else {
+        for image in x.as_slice().chunks_exact(h * w) {
+            for i in start_i..start_i + len_h {
+                for j in start_j..start_j + len_w {
+                    let mut sum = 0.;
+
+                    for a in (i + 1).saturating_sub(h)..=i.min(kh - 1) {
+                        for b in (j + 1).saturating_sub(w)..=j.min(kw - 1) {
+                            sum += image[(i - a) * w + j - b] * kernel[a * kw + b];
+                        }
+                    }
+
+                    vec.push(sum);
+                }
+            }
+        }
+    }
+
+    let mut dims = x.shape().as_vec();
+    let rank = dims.len();
+    dims[rank - 2] = len_h;
+    dims[rank - 1] = len_w;
+
+    Tensor::from_vec(vec, dims.as_slice())
+}
+
+// Start and length of the mode's output within the full convolution
+fn mode_range(mode: ConvMode, n: usize, k: usize) -> (usize, usize) {
+    match mode {
+        ConvMode::Full => (0, n + k - 1),
+        ConvMode::Same => ((k - 1) / 2, n),
+        ConvMode::Valid => (n.min(k) - 1, n.max(k) - n.min(k) + 1),
+    }
+}
+
+fn forward_fft(x: &[f32], nfft: usize) -> Vec<Complex<f32>> {
+    let mut buffer = vec![Complex::zero(); nfft];
+
+    for (b, x) in buffer.iter_mut().zip(x) {
+        b.re = *x;
+    }
+
+    plan_fft(nfft, FftDirection::Forward).process(&mut buffer);
+
+    buffer
+}
+
+fn forward_fft2(x: &[f32], h: usize, w: usize, nfft_h: usize, nfft_w: usize) -> Vec<Complex<f32>> {
+    let mut buffer = vec![Complex::zero(); nfft_h * nfft_w];
+
+    for i in 0..h {
+        for j in 0..w {
+            buffer[i * nfft_w + j].re = x[i * w + j];
+        }
+    }
+
+    fft2_in_place(&mut buffer, nfft_h, nfft_w, FftDirection::Forward);
+
+    buffer
+}
+
+fn fft2_in_place(buffer: &mut [Complex<f32>], rows: usize, cols: usize, direction: FftDirection) {
+    // rustfft transforms each consecutive chunk of the plan's length
+    plan_fft(cols, direction).process(buffer);
+
+    let column_fft = plan_fft(rows, direction);
+    let mut column = vec![Complex::zero(); rows];
+
+    for j in 0..cols {
+        for i in 0..rows {
+            column[i] = buffer[i * cols + j];
+        }
+
+        column_fft.process(&mut column);
+
+        for i in 0..rows {
+            buffer[i * cols + j] = column[i];
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        signal::{convolve, convolve2d, correlate, correlate2d, ConvMethod, ConvMode, ConvolveOpt},
+        ten, tensor::Tensor, test::assert_close,
+    };
+
+    fn signal(len: usize) -> Tensor {
+        Tensor::from_vec((0..len).map(|i| ((i * 7919) % 101) as f32 / 50. - 1.).collect(), [len])
+    }
+
+    #[test]
+    fn convolve_modes() {
+        let x = ten![1., 2., 3.];
+        let k = ten![0., 1., 0.5];
+
+        assert_eq!(convolve(&x, &k, ()), ten![0., 1., 2.5, 4., 1.5]);
+        assert_eq!(convolve(&x, &k, ().mode(ConvMode::Same)), ten![1., 2.5, 4.]);
+        assert_eq!(convolve(&x, &k, ().mode(ConvMode::Valid)), ten![2.5]);
+
+        // valid is symmetric when the kernel is longer
+        assert_eq!(convolve(ten![1., 2.], ten![1., 1., 1.], ().mode(ConvMode::Valid)), ten![3., 3.]);
+    }
+
+    #[test]
+    fn correlate_modes() {
+        let x = ten![1., 2., 3.];
+        let k = ten![0., 1., 0.5];
+
+        assert_eq!(correlate(&x, &k, ()), ten![0.5, 2., 3.5, 3., 0.]);
+        assert_eq!(correlate(&x, &k, ().mode(ConvMode::Same)), ten![2., 3.5, 3.]);
+    }
+
+    #[test]
+    fn convolve_batch() {
+        let x = ten![[1., 2., 3.], [0., 1., 0.]];
+
+        assert_eq!(
+            convolve(&x, ten![1., 1.], ()),
+            ten![[1., 3., 5., 3.], [0., 1., 1., 0.]]
+        );
+    }
+
+    #[test]
+    fn convolve_fft_matches_direct() {
+        let x = signal(300);
+        let k = signal(100).map(|v| v * 0.5);
+
+        for mode in [ConvMode::Full, ConvMode::Same, ConvMode::Valid] {
+            let direct = convolve(&x, &k, ().mode(mode).method(ConvMethod::Direct));
+            let fft = convolve(&x, &k, ().mode(mode).method(ConvMethod::Fft));
+            let auto = convolve(&x, &k, ().mode(mode));
+
+            assert_close(&fft, &direct, 1e-3);
+            assert_close(&auto, &direct, 1e-3);
+        }
+
+        let small = convolve(ten![1., 2., 3.], ten![0., 1., 0.5], ().method(ConvMethod::Fft));
+        assert_close(&small, &ten![0., 1., 2.5, 4., 1.5], 1e-3);
+    }
+
+    #[test]
+    fn convolve2d_modes() {
+        let x = ten![[1., 2., 3.], [4., 5., 6.], [7., 8., 9.]];
+        let k = ten![[1., 1.], [1., 1.]];
+
+        assert_eq!(convolve2d(&x, &k, ()), ten![
+            [1., 3., 5., 3.],
+            [5., 12., 16., 9.],
+            [11., 24., 28., 15.],
+            [7., 15., 17., 9.],
+        ]);
+        assert_eq!(
+            convolve2d(&x, &k, ().mode(ConvMode::Same)), 
+            ten![[1., 3., 5.], [5., 12., 16.], [11., 24., 28.]]
+        );
+        assert_eq!(
+            convolve2d(&x, &k, ().mode(ConvMode::Valid)), 
+            ten![[12., 16.], [24., 28.]]
+        );
+
+        let k = ten![[1., 0.], [0., -1.]];
+        assert_eq!(correlate2d(&x, &k, ().mode(ConvMode::Valid)), ten![[-4., -4.], [-4., -4.]]);
+    }
+
+    #[test]
+    fn convolve2d_fft_and_batch() {
+        let x = signal(2 * 20 * 30).reshape([2, 20, 30]);
+        let k = signal(35).reshape([5, 7]);
+
+        for mode in [ConvMode::Full, ConvMode::Same, ConvMode::Valid] {
+            let direct = convolve2d(&x, &k, ().mode(mode).method(ConvMethod::Direct));
+            let fft = convolve2d(&x, &k, ().mode(mode).method(ConvMethod::Fft));
+
+            assert_close(&fft, &direct, 1e-3);
+        }
+
+        let y = convolve2d(&x, &k, ().mode(ConvMode::Same));
+        assert_eq!(y.shape().as_vec(), &[2, 20, 30]);
+        assert_close(
+            &y.slice(1), 
+            &convolve2d(x.slice(1), &k, ().mode(ConvMode::Same)), 1e-3
+        );
+    }
+}
@@ -0,0 +1,66 @@
+use std::f32::consts::PI;
+
+use crate::tensor::Tensor;
+
+use super::fft::FftNorm;
+
+///
+/// DCT-II over the last axis, computed as a matmul with the cosine basis.
+/// `Backward` is unnormalized, y[k] = 2 sum x[n] cos(pi k (2n + 1) / 2N),
+/// `Forward` scales by 1 / 2N, and `Ortho` makes the transform orthonormal.
+///
+pub fn dct(x: impl Into<Tensor>, norm: FftNorm) -> Tensor {
+    let x = x.into();
+    assert!(x.rank() > 0, "dct requires rank > 0");
+
+    let n = x.cols();
+
+    let basis = Tensor::from_vec((0..n).flat_map(|i| {
+        (0..n).map(move |k| {
+            let scale = match norm {
+                FftNorm::Backward => 2.,
+                FftNorm::Forward => 1. / n as f32,
+                FftNorm::Ortho if k == 0 => (1. / n as f32).sqrt(),
+                FftNorm::Ortho => (2. / n as f32).sqrt(),
+            };
+
+            scale * (PI * k as f32 * (2 * i + 1) as f32 / (2 * n) as f32).cos()
+        })
+    }).collect(), [n, n]);
+
+    if x.rank() == 1 {
+        x.reshape([1, n]).matmul(&basis).reshape([n])
+    } else {
+        x.matmul(&basis)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{signal::{dct, FftNorm}, ten, test::assert_close};
+
+    #[test]
+    fn dct_constant() {
+        let x = ten![1., 1., 1., 1.];
+
+        assert_close(&dct(&x, FftNorm::Backward), &ten![8., 0., 0., 0.], 1e-5);
+        assert_close(&dct(&x, FftNorm::Ortho), &ten![2., 0., 0., 0.], 1e-5);
+        assert_close(&dct(&x, FftNorm::Forward), &ten![1., 0., 0., 0.], 1e-5);
+    }
+
+    #[test]
+    fn dct_ortho_energy() {
+        let x = ten![[1., -2., 0.5], [3., 0., 1.]];
+        let y = dct(&x, FftNorm::Ortho);
+
+        assert_eq!(y.shape().as_vec(), &[2, 3]);
+
+        let energy_x = (&x * &x).reduce_sum_axis(-1);
+        let energy_y = (&y * &y).reduce_sum_axis(-1);
+        assert!((&energy_x - &energy_y).abs().reduce_max()[0] < 1e-5);
+
+        // y[1] = 2 sum x[n] cos(pi (2n + 1) / 6) for the first row
+        let y = dct(ten![1., -2., 0.5], FftNorm::Backward);
+        assert!((y[1] - 3f32.sqrt() * 0.5).abs() < 1e-5);
+    }
+}
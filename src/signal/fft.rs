@@ -0,0 +1,471 @@
+use std::{cell::RefCell, sync::Arc};
+
+use essay_opt::derive_opt;
+use num_complex::Complex;
+use num_traits::Zero;
+use rustfft::{Fft, FftDirection, FftPlanner};
+
+use crate::tensor::{Axis, Tensor, Type};
+
+use super::window::Window;
+
+thread_local! {
+    // FftPlanner caches its plans, so one planner per thread reuses them
+    // across calls
+    static PLANNER: RefCell<FftPlanner<f32>> = RefCell::new(FftPlanner::new());
+}
+
+pub(super) fn plan_fft(len: usize, direction: FftDirection) -> Arc<dyn Fft<f32>> {
+    PLANNER.with(|planner| planner.borrow_mut().plan_fft(len, direction))
+}
+
+///
+/// Scaling of the forward and inverse transforms, like NumPy's `norm`.
+///
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum FftNorm {
+    /// The inverse transform is scaled by 1/n
+    #[default]
+    Backward,
+    /// Both transforms are scaled by 1/sqrt(n)
+    Ortho,
+    /// The forward transform is scaled by 1/n
+    Forward,
+}
+
+impl FftNorm {
+    fn scale(&self, n: usize, direction: FftDirection) -> f32 {
+        match (self, direction) {
+            (FftNorm::Backward, FftDirection::Forward) => 1.,
+            (FftNorm::Backward, FftDirection::Inverse) => 1. / n as f32,
+            (FftNorm::Ortho, _) => 1. / (n as f32).sqrt(),
+            (FftNorm::Forward, FftDirection::Forward) => 1. / n as f32,
+            (FftNorm::Forward, FftDirection::Inverse) => 1.,
+        }
+    }
+}
+
+#[derive_opt(FftOpt)]
+#[derive(Default)]
+pub struct FftArg {
+    pub(super) fft_length: Option<usize>,
+    pub(super) axis: Option<Axis>,
+    pub(super) norm: Option<FftNorm>,
+    /// Only used by `rfft_norm`
+    pub(super) window: Option<Window>,
+}
+
+#[derive_opt(FftnOpt)]
+#[derive(Default)]
+pub struct FftnArg {
+    shape: Option<Vec<usize>>,
+    axes: Option<Vec<isize>>,
+    norm: Option<FftNorm>,
+}
+
+///
+/// Complex FFT along an axis, defaulting to the last. The `fft_length`
+/// option zero-pads or truncates the input along the axis.
+///
+pub fn fft(x: &Tensor<Complex<f32>>, opt: impl FftOpt) -> Tensor<Complex<f32>> {
+    let opt = opt.into_arg();
+
+    fft_axis(x, opt.axis, opt.fft_length, opt.norm, FftDirection::Forward)
+}
+
+/// Inverse of `fft`.
+pub fn ifft(x: &Tensor<Complex<f32>>, opt: impl FftOpt) -> Tensor<Complex<f32>> {
+    let opt = opt.into_arg();
+
+    fft_axis(x, opt.axis, opt.fft_length, opt.norm, FftDirection::Inverse)
+}
+
+///
+/// FFT of a real input, returning the n / 2 + 1 non-negative frequencies
+/// along the axis.
+///
+pub fn rfft(x: &Tensor<f32>, opt: impl FftOpt) -> Tensor<Complex<f32>> {
+    let opt = opt.into_arg();
+    let axis = lane_axis("rfft", x, opt.axis);
+    let n = fft_length("rfft", x.dim(axis), opt.fft_length);
+    let scale = opt.norm.unwrap_or_default().scale(n, FftDirection::Forward);
+
+    let fft = plan_fft(n, FftDirection::Forward);
+    let mut buffer = vec![Complex::zero(); n];
+
+    map_lanes(x, axis, n / 2 + 1, |lane, out| {
+        buffer.fill(Complex::zero());
+        for (b, x) in buffer.iter_mut().zip(lane) {
+            *b = Complex::new(*x, 0.);
+        }
+
+        fft.process(&mut buffer);
+
+        for (o, b) in out.iter_mut().zip(&buffer) {
+            *o = b * scale;
+        }
+    })
+}
+
+///
+/// Inverse of `rfft`, treating the input as the non-negative half of a
+/// Hermitian spectrum. The output length defaults to 2 (m - 1) for m
+/// input frequencies.
+///
+pub fn irfft(x: &Tensor<Complex<f32>>, opt: impl FftOpt) -> Tensor<f32> {
+    let opt = opt.into_arg();
+    let axis = lane_axis("irfft", x, opt.axis);
+    let m = x.dim(axis);
+    let n = fft_length("irfft", 2 * m.saturating_sub(1), opt.fft_length);
+    let scale = opt.norm.unwrap_or_default().scale(n, FftDirection::Inverse);
+
+    let fft = plan_fft(n, FftDirection::Inverse);
+    let mut buffer = vec![Complex::zero(); n];
+
+    map_lanes(x, axis, n, |lane, out| {
+        buffer.fill(Complex::zero());
+
+        let half = (n / 2 + 1).min(lane.len());
+        buffer[..half].copy_from_slice(&lane[..half]);
+        buffer[0].im = 0.;
+
+        for k in 1..half {
+            if n - k != k {
+                buffer[n - k] = lane[k].conj();
+            }
+        }
+
+        fft.process(&mut buffer);
+
+        for (o, b) in out.iter_mut().zip(&buffer) {
+            *o = b.re * scale;
+        }
+    })
+}
+
+///
+/// FFT over several axes, defaulting to all axes, or to the last
+/// `shape.len()` axes when `shape` is given. `shape` zero-pads or truncates
+/// each transformed axis.
+///
+pub fn fftn(x: &Tensor<Complex<f32>>, opt: impl FftnOpt) -> Tensor<Complex<f32>> {
+    fftn_axes(x, opt.into_arg(), None, FftDirection::Forward)
+}
+
+/// Inverse of `fftn`.
+pub fn ifftn(x: &Tensor<Complex<f32>>, opt: impl FftnOpt) -> Tensor<Complex<f32>> {
+    fftn_axes(x, opt.into_arg(), None, FftDirection::Inverse)
+}
+
+/// `fftn` over the last two axes by default.
+pub fn fft2(x: &Tensor<Complex<f32>>, opt: impl FftnOpt) -> Tensor<Complex<f32>> {
+    fftn_axes(x, opt.into_arg(), Some(vec![-2, -1]), FftDirection::Forward)
+}
+
+/// Inverse of `fft2`.
+pub fn ifft2(x: &Tensor<Complex<f32>>, opt: impl FftnOpt) -> Tensor<Complex<f32>> {
+    fftn_axes(x, opt.into_arg(), Some(vec![-2, -1]), FftDirection::Inverse)
+}
+
+///
+/// Sample frequencies of an n-point FFT with sample spacing d, with the
+/// negative frequencies in the second half like `fft`.
+///
+pub fn fftfreq(n: usize, d: f32) -> Tensor<f32> {
+    let scale = 1. / (n as f32 * d);
+
+    let vec: Vec<f32> = (0..n).map(|i| {
+        let k = if i < n.div_ceil(2) { i as isize } else { i as isize - n as isize };
+
+        k as f32 * scale
+    }).collect();
+
+    Tensor::from_vec(vec, [n])
+}
+
+/// Sample frequencies of `rfft`, the non-negative half of `fftfreq`.
+pub fn rfftfreq(n: usize, d: f32) -> Tensor<f32> {
+    let scale = 1. / (n as f32 * d);
+
+    let vec: Vec<f32> = (0..n / 2 + 1).map(|k| k as f32 * scale).collect();
+
+    Tensor::from_vec(vec, [n / 2 + 1])
+}
+
+///
+/// Moves the zero frequency to the center of every axis.
+///
+pub fn fftshift<T: Type + Clone>(x: &Tensor<T>) -> Tensor<T> {
+    let shifts = x.shape().as_vec().iter().map(|dim| dim / 2).collect();
+
+    roll(x, shifts)
+}
+
+/// Inverse of `fftshift`.
+pub fn ifftshift<T: Type + Clone>(x: &Tensor<T>) -> Tensor<T> {
+    let shifts = x.shape().as_vec().iter().map(|dim| dim - dim / 2).collect();
+
+    roll(x, shifts)
+}
+
+fn roll<T: Type + Clone>(x: &Tensor<T>, shifts: Vec<usize>) -> Tensor<T> {
+    let x = x.to_contiguous();
+    let dims = x.shape().as_vec();
+    let data = x.as_slice();
+
+    let mut vec = Vec::<T>::with_capacity(x.size());
+
+    for index in 0..x.size() {
+        // source of the output index, one axis at a time from the last
+        let mut rest = index;
+        let mut src = 0;
+        let mut stride = 1;
+
+        for (dim, shift) in dims.iter().zip(&shifts).rev() {
+            let i = rest % dim;
+            rest /= dim;
+
+            src += ((i + dim - shift % dim) % dim) * stride;
+            stride *= dim;
+        }
+
+        vec.push(data[src].clone());
+    }
+
+    Tensor::from_vec(vec, dims.as_slice())
+}
+
+fn fft_axis(
+    x: &Tensor<Complex<f32>>,
+    axis: Option<Axis>,
+    n: Option<usize>,
+    norm: Option<FftNorm>,
+    direction: FftDirection,
+) -> Tensor<Complex<f32>> {
+    let axis = lane_axis("fft", x, axis);
+    let n = fft_length("fft", x.dim(axis), n);
+    let scale = norm.unwrap_or_default().scale(n, direction);
+
+    let fft = plan_fft(n, direction);
+    let mut buffer = vec![Complex::zero(); n];
+
+    map_lanes(x, axis, n, |lane, out| {
+        buffer.fill(Complex::zero());
+        let len = lane.len().min(n);
+        buffer[..len].copy_from_slice(&lane[..len]);
+
+        fft.process(&mut buffer);
+
+        for (o, b) in out.iter_mut().zip(&buffer) {
+            *o = b * scale;
+        }
+    })
+}
+
+fn fftn_axes(
+    x: &Tensor<Complex<f32>>,
+    opt: FftnArg,
+    default_axes: Option<Vec<isize>>,
+    direction: FftDirection,
+) -> Tensor<Complex<f32>> {
+    let rank = x.rank() as isize;
+
+    let axes = match (opt.axes, &opt.shape, default_axes) {
+        (Some(axes), _, _) => axes,
+        (None, Some(shape), _) => (rank - shape.len() as isize..rank).collect(),
+        (None, None, Some(axes)) => axes,
+        (None, None, None) => (0..rank).collect(),
+    };
+
+    if let Some(shape) = &opt.shape {
+        assert_eq!(shape.len(), axes.len(), "fftn shape {:?} and axes {:?} must have the same length", shape, axes);
+    }
+
+    let mut y = x.clone();
+
+    for (i, axis) in axes.iter().enumerate() {
+        let n = opt.shape.as_ref().map(|shape| shape[i]);
+
+        y = fft_axis(&y, Some(Axis::axis(*axis)), n, opt.norm, direction);
+    }
+
+    y
+}
+
+pub(super) fn lane_axis<T: Type>(op: &'static str, x: &Tensor<T>, axis: Option<Axis>) -> usize {
+    axis.unwrap_or(Axis::axis(-1))
+        .try_axis_from_rank(op, x.rank())
+        .unwrap_or_else(|err| panic!("{}", err))
+}
+
+fn fft_length(op: &'static str, default: usize, n: Option<usize>) -> usize {
+    let n = n.unwrap_or(default);
+    assert!(n > 0, "{} requires an fft_length > 0", op);
+    n
+}
+
+///
+/// Applies f to each 1-d lane along the axis, where f writes len_out
+/// items for the output lane.
+///
+pub(super) fn map_lanes<T: Type + Clone, U: Type + Clone + Zero>(
+    x: &Tensor<T>,
+    axis: usize,
+    len_out: usize,
+    mut f: impl FnMut(&[T], &mut [U]),
+) -> Tensor<U> {
+    let x = x.to_contiguous();
+
+    let mut dims = x.shape().as_vec();
+    let dim = dims[axis];
+    let outer: usize = dims[..axis].iter().product();
+    let inner: usize = dims[axis + 1..].iter().product();
+
+    dims[axis] = len_out;
+
+    let data = x.as_slice();
+    let mut vec = vec![U::zero(); outer * len_out * inner];
+
+    let mut lane = Vec::<T>::with_capacity(dim);
+    let mut lane_out = vec![U::zero(); len_out];
+
+    for o in 0..outer {
+        for i in 0..inner {
+            lane.clear();
+            lane.extend((0..dim).map(|j| data[(o * dim + j) * inner + i].clone()));
+
+            f(&lane, &mut lane_out);
+
+            for (j, value) in lane_out.iter().enumerate() {
+                vec[(o * len_out + j) * inner + i] = value.clone();
+            }
+        }
+    }
+
+    Tensor::from_vec(vec, dims.as_slice())
+}
+
+#[cfg(test)]
+mod test {
+    use num_complex::Complex;
+
+    use crate::{
+        signal::{
+            fft, fft2, fftfreq, fftn, fftshift, ifft, ifft2, ifftshift, irfft, rfft, rfftfreq,
+            FftNorm, FftOpt,
+        },
+        ten, tensor::Tensor, test::assert_close,
+    };
+
+    fn c(x: &Tensor) -> Tensor<Complex<f32>> {
+        x.map(|v| Complex::new(*v, 0.))
+    }
+
+    #[test]
+    fn fft_impulse() {
+        assert_close(&fft(&c(&ten![1., 0., 0., 0.]), ()), &c(&ten![1., 1., 1., 1.]), 1e-5);
+
+        let y = fft(&c(&ten![0., 1., 0., 0.]), ());
+        let expect = Tensor::from_vec(vec![
+            Complex::new(1., 0.), Complex::new(0., -1.), 
+            Complex::new(-1., 0.), Complex::new(0., 1.),
+        ], [4]);
+        assert_close(&y, &expect, 1e-5);
+    }
+
+    #[test]
+    fn fft_inverse() {
+        let x = c(&ten![1., 2., -3., 4., 0.5]);
+
+        assert_close(&ifft(&fft(&x, ()), ()), &x, 1e-5);
+
+        let opt = || ().norm(FftNorm::Ortho);
+        assert_close(&ifft(&fft(&x, opt()), opt()), &x, 1e-5);
+
+        let opt = || ().norm(FftNorm::Forward);
+        assert_close(&ifft(&fft(&x, opt()), opt()), &x, 1e-5);
+    }
+
+    #[test]
+    fn fft_norm_modes() {
+        let x = c(&ten![1., 1., 1., 1.]);
+
+        assert_close(&fft(&x, ()), &c(&ten![4., 0., 0., 0.]), 1e-5);
+        assert_close(&fft(&x, ().norm(FftNorm::Ortho)), &c(&ten![2., 0., 0., 0.]), 1e-5);
+        assert_close(&fft(&x, ().norm(FftNorm::Forward)), &c(&ten![1., 0., 0., 0.]), 1e-5);
+        assert_close(&ifft(&x, ()), &c(&ten![1., 0., 0., 0.]), 1e-5);
+    }
+
+    #[test]
+    fn fft_axis_and_length() {
+        let x = c(&ten![[1., 2.], [3., 4.]]);
+
+        assert_close(&fft(&x, ()), &c(&ten![[3., -1.], [7., -1.]]), 1e-5);
+        assert_close(&fft(&x, ().axis(0)), &c(&ten![[4., 6.], [-2., -2.]]), 1e-5);
+
+        // zero padded and truncated
+        assert_close(&fft(&c(&ten![1., 1.]), ().fft_length(4)).slice(0), &c(&ten![2.]).slice(0), 1e-5);
+        assert_eq!(fft(&c(&ten![1., 1.]), ().fft_length(4)).shape().as_vec(), &[4]);
+        assert_close(&fft(&c(&ten![1., 2., 3.]), ().fft_length(1)), &c(&ten![1.]), 1e-5);
+    }
+
+    #[test]
+    fn rfft_irfft() {
+        let y = rfft(&ten![1., 2., 3., 4.], ());
+        let expect = Tensor::from_vec(vec![
+            Complex::new(10., 0.), Complex::new(-2., 2.), Complex::new(-2., 0.),
+        ], [3]);
+        assert_close(&y, &expect, 1e-5);
+
+        let x = ten![1., 2., 3., 4.];
+        assert!((&irfft(&y, ()) - &x).abs().reduce_max()[0] < 1e-5);
+
+        // odd lengths need fft_length to round trip
+        let x = ten![[1., -2., 3., 0.5, 2.], [0., 1., 0., 1., 0.]];
+        let y = rfft(&x, ());
+        assert_eq!(y.shape().as_vec(), &[2, 3]);
+        assert!((&irfft(&y, ().fft_length(5)) - &x).abs().reduce_max()[0] < 1e-5);
+
+        let y = rfft(&x, ().axis(0).norm(FftNorm::Ortho));
+        assert_eq!(y.shape().as_vec(), &[2, 5]);
+        let x2 = irfft(&y, ().axis(0).fft_length(2).norm(FftNorm::Ortho));
+        assert!((&x2 - &x).abs().reduce_max()[0] < 1e-5);
+    }
+
+    #[test]
+    fn fft2_fftn() {
+        use crate::signal::FftnOpt;
+
+        let x = c(&ten![[1., 2.], [3., 4.]]);
+
+        assert_close(&fft2(&x, ()), &c(&ten![[10., -2.], [-4., 0.]]), 1e-5);
+        assert_close(&fftn(&x, ()), &c(&ten![[10., -2.], [-4., 0.]]), 1e-5);
+        assert_close(&ifft2(&fft2(&x, ()), ()), &x, 1e-5);
+
+        assert_close(&fftn(&x, ().axes([0])), &c(&ten![[4., 6.], [-2., -2.]]), 1e-5);
+        assert_eq!(fftn(&x, ().shape([4])).shape().as_vec(), &[2, 4]);
+        assert_eq!(fft2(&x, ().shape([3, 4])).shape().as_vec(), &[3, 4]);
+
+        let x = c(&ten![[[1., 0.], [0., 0.]], [[0., 0.], [0., 0.]]]);
+        assert_close(&fftn(&x, ()), &c(&ten![[[1., 1.], [1., 1.]], [[1., 1.], [1., 1.]]]), 1e-5);
+    }
+
+    #[test]
+    fn freqs() {
+        assert_eq!(fftfreq(4, 1.), ten![0., 0.25, -0.5, -0.25]);
+        assert_eq!(fftfreq(5, 0.1), ten![0., 2., 4., -4., -2.]);
+        assert_eq!(rfftfreq(4, 1.), ten![0., 0.25, 0.5]);
+        assert_eq!(rfftfreq(5, 0.1), ten![0., 2., 4.]);
+    }
+
+    #[test]
+    fn shift() {
+        assert_eq!(fftshift(&ten![0., 1., 2., -2., -1.]), ten![-2., -1., 0., 1., 2.]);
+        assert_eq!(fftshift(&ten![0., 1., -2., -1.]), ten![-2., -1., 0., 1.]);
+        assert_eq!(ifftshift(&ten![-2., -1., 0., 1., 2.]), ten![0., 1., 2., -2., -1.]);
+
+        let x = ten![[0, 1, 2], [3, 4, 5]];
+        assert_eq!(fftshift(&x), ten![[5, 3, 4], [2, 0, 1]]);
+        assert_eq!(ifftshift(&fftshift(&x)), x);
+    }
+}
@@ -0,0 +1,404 @@
+use essay_opt::derive_opt;
+
+use crate::{linalg, tensor::{Axis, Tensor}};
+
+use super::fft::{lane_axis, map_lanes};
+
+#[derive_opt(FilterOpt)]
+#[derive(Default)]
+pub struct FilterArg {
+    /// Initial filter state, either one state for every lane or a state
+    /// per lane in the order of the lanes
+    zi: Option<Tensor>,
+    /// Filtered axis, defaulting to the last
+    axis: Option<Axis>,
+    /// Odd extension length for `filtfilt`, defaulting to 3 times the
+    /// number of coefficients
+    padlen: Option<usize>,
+}
+
+///
+/// Filters each lane of x along the axis with the rational transfer
+/// function b(z) / a(z), using the direct form II transposed structure.
+/// The coefficients are normalized by a[0].
+///
+pub fn lfilter(b: &Tensor, a: &Tensor, x: impl Into<Tensor>, opt: impl FilterOpt) -> Tensor {
+    let (b, a) = normalize_ba(b, a);
+    let opt = opt.into_arg();
+
+    let x = x.into();
+    let axis = lane_axis("lfilter", &x, opt.axis);
+    let order = b.len() - 1;
+
+    let mut zi = lane_states("lfilter", opt.zi, n_lanes(&x, axis), order);
+    let mut states = zi.chunks_exact_mut(order.max(1));
+
+    map_lanes(&x, axis, x.dim(axis), |lane, out| {
+        let z = if order > 0 { states.next().unwrap() } else { &mut [] };
+
+        lfilter_lane(&b, &a, lane, out, z);
+    })
+}
+
+///
+/// Initial state of `lfilter` for the steady state of a unit step, so a
+/// constant input scaled by zi has no startup transient.
+///
+pub fn lfilter_zi(b: &Tensor, a: &Tensor) -> Tensor {
+    let (b, a) = normalize_ba(b, a);
+    let n = b.len() - 1;
+
+    Tensor::from_vec(steady_state(&b, &a), [n].as_slice())
+}
+
+///
+/// Zero-phase filtering by running `lfilter` forward and backward over
+/// an odd extension of each lane, with `lfilter_zi` initial states.
+///
+pub fn filtfilt(b: &Tensor, a: &Tensor, x: impl Into<Tensor>, opt: impl FilterOpt) -> Tensor {
+    let (b, a) = normalize_ba(b, a);
+    let opt = opt.into_arg();
+
+    let x = x.into();
+    let axis = lane_axis("filtfilt", &x, opt.axis);
+    let padlen = opt.padlen.unwrap_or(3 * b.len());
+
+    let zi = steady_state(&b, &a);
+    let mut z = vec![0.; zi.len()];
+
+    map_lanes(&x, axis, x.dim(axis), |lane, out| {
+        zero_phase(lane, out, padlen, |x, y, is_first| {
+            for (z, zi) in z.iter_mut().zip(&zi) {
+                *z = zi * is_first;
+            }
+
+            lfilter_lane(&b, &a, x, y, &mut z);
+        });
+    })
+}
+
+///
+/// Filters each lane of x with a cascade of second-order sections, where
+/// each row of the [n_sections, 6] sos is [b0, b1, b2, a0, a1, a2].
+/// The state `zi` is [n_sections, 2] per lane.
+///
+pub fn sosfilt(sos: &Tensor, x: impl Into<Tensor>, opt: impl FilterOpt) -> Tensor {
+    let sos = normalize_sos(sos);
+    let opt = opt.into_arg();
+
+    let x = x.into();
+    let axis = lane_axis("sosfilt", &x, opt.axis);
+
+    let mut zi = lane_states("sosfilt", opt.zi, n_lanes(&x, axis), 2 * sos.len());
+    let mut states = zi.chunks_exact_mut(2 * sos.len());
+
+    map_lanes(&x, axis, x.dim(axis), |lane, out| {
+        sosfilt_lane(&sos, lane, out, states.next().unwrap());
+    })
+}
+
+///
+/// Zero-phase filtering with second-order sections, like `filtfilt`.
+///
+pub fn sosfiltfilt(sos: &Tensor, x: impl Into<Tensor>, opt: impl FilterOpt) -> Tensor {
+    let sos = normalize_sos(sos);
+    let opt = opt.into_arg();
+
+    let x = x.into();
+    let axis = lane_axis("sosfiltfilt", &x, opt.axis);
+
+    // the extra zeros of the sections at z = 0 don't count towards the order
+    let n_trivial = sos.iter().map(|s| (s[2] == 0. && s[5] == 0.) as usize).sum::<usize>();
+    let padlen = opt.padlen.unwrap_or(3 * (2 * sos.len() + 1 - n_trivial));
+
+    let zi: Vec<f32> = sos_steady_state(&sos);
+    let mut z = vec![0.; zi.len()];
+
+    map_lanes(&x, axis, x.dim(axis), |lane, out| {
+        zero_phase(lane, out, padlen, |x, y, is_first| {
+            for (z, zi) in z.iter_mut().zip(&zi) {
+                *z = zi * is_first;
+            }
+
+            sosfilt_lane(&sos, x, y, &mut z);
+        });
+    })
+}
+
+// Runs filter forward and backward over the odd extension of the lane,
+// where filter(x, y, x[0]) filters x into y starting from the state for x[0]
+fn zero_phase(lane: &[f32], out: &mut [f32], padlen: usize, mut filter: impl FnMut(&[f32], &mut [f32], f32)) {
+    let n = lane.len();
+    assert!(n > padlen, "filtfilt requires a lane length {} > padlen {}", n, padlen);
+
+    let mut ext = Vec::<f32>::with_capacity(n + 2 * padlen);
+    ext.extend((1..=padlen).rev().map(|i| 2. * lane[0] - lane[i]));
+    ext.extend_from_slice(lane);
+    ext.extend((1..=padlen).map(|i| 2. * lane[n - 1] - lane[n - 1 - i]));
+
+    let mut forward = vec![0.; ext.len()];
+    filter(&ext, &mut forward, ext[0]);
+
+    forward.reverse();
+
+    let mut backward = vec![0.; ext.len()];
+    filter(&forward, &mut backward, forward[0]);
+
+    backward.reverse();
+
+    out.copy_from_slice(&backward[padlen..padlen + n]);
+}
+
+fn lfilter_lane(b: &[f32], a: &[f32], x: &[f32], y: &mut [f32], z: &mut [f32]) {
+    let order = z.len();
+
+    for (x, y) in x.iter().zip(y.iter_mut()) {
+        let out = b[0] * x + z.first().unwrap_or(&0.);
+
+        for j in 0..order {
+            let next = if j + 1 < order { z[j + 1] } else { 0. };
+
+            z[j] = b[j + 1] * x + next - a[j + 1] * out;
+        }
+
+        *y = out;
+    }
+}
+
+fn sosfilt_lane(sos: &[[f32; 6]], x: &[f32], y: &mut [f32], z: &mut [f32]) {
+    for (i, x) in x.iter().enumerate() {
+        let mut value = *x;
+
+        for (s, z) in sos.iter().zip(z.chunks_exact_mut(2)) {
+            let out = s[0] * value + z[0];
+
+            z[0] = s[1] * value + z[1] - s[4] * out;
+            z[1] = s[2] * value - s[5] * out;
+
+            value = out;
+        }
+
+        y[i] = value;
+    }
+}
+
+// Solves (I - A^T) zi = b[1..] - a[1..] b[0] for the companion matrix A of a
+fn steady_state(b: &[f32], a: &[f32]) -> Vec<f32> {
+    let n = b.len() - 1;
+
+    if n == 0 {
+        return Vec::new();
+    }
+
+    let mut i_minus_a = vec![0f32; n * n];
+    for i in 0..n {
+        i_minus_a[i * n + i] = 1.;
+    }
+
+    // companion has -a[1..] in its first row and ones below the diagonal,
+    // so the transpose has -a[1..] in the first column
+    for i in 0..n {
+        i_minus_a[i * n] += a[i + 1];
+    }
+    for i in 0..n - 1 {
+        i_minus_a[i * n + i + 1] -= 1.;
+    }
+
+    let rhs: Vec<f32> = (0..n).map(|i| b[i + 1] - a[i + 1] * b[0]).collect();
+
+    let zi = linalg::solve(
+        &Tensor::from_vec(i_minus_a, [n, n]), 
+        &Tensor::from_vec(rhs, [n])
+    );
+
+    zi.as_slice().to_vec()
+}
+
+// Steady state of each section, scaled by the DC gain of the sections before it
+fn sos_steady_state(sos: &[[f32; 6]]) -> Vec<f32> {
+    let mut zi = Vec::<f32>::with_capacity(2 * sos.len());
+    let mut scale = 1.;
+
+    for s in sos {
+        let state = steady_state(&s[0..3], &s[3..6]);
+
+        zi.extend(state.iter().map(|z| z * scale));
+
+        scale *= (s[0] + s[1] + s[2]) / (s[3] + s[4] + s[5]);
+    }
+
+    zi
+}
+
+fn normalize_ba(b: &Tensor, a: &Tensor) -> (Vec<f32>, Vec<f32>) {
+    assert!(b.rank() == 1 && a.rank() == 1, "filter coefficients b and a must be 1-d");
+
+    let b = b.to_contiguous();
+    let a = a.to_contiguous();
+
+    let a0 = a[0];
+    assert!(a0 != 0., "filter coefficient a[0] must not be zero");
+
+    let n = b.size().max(a.size());
+
+    let mut b_vec = vec![0.; n];
+    let mut a_vec = vec![0.; n];
+
+    for (i, v) in b.as_slice().iter().enumerate() {
+        b_vec[i] = v / a0;
+    }
+
+    for (i, v) in a.as_slice().iter().enumerate() {
+        a_vec[i] = v / a0;
+    }
+
+    (b_vec, a_vec)
+}
+
+fn normalize_sos(sos: &Tensor) -> Vec<[f32; 6]> {
+    assert!(
+        sos.rank() == 2 && sos.cols() == 6, 
+        "sos must have shape [n_sections, 6], found {:?}", sos.shape().as_vec()
+    );
+
+    let sos = sos.to_contiguous();
+
+    sos.as_slice().chunks_exact(6).map(|s| {
+        assert!(s[3] != 0., "sos a0 must not be zero");
+
+        let mut section = [0.; 6];
+        for (v, c) in section.iter_mut().zip(s) {
+            *v = c / s[3];
+        }
+        section
+    }).collect()
+}
+
+// Initial state of every lane, from zero, a shared state or a state per lane
+// the lanes are counted from the other dims, since the axis may be empty
+fn n_lanes(x: &Tensor, axis: usize) -> usize {
+    let dims = x.shape().as_vec();
+
+    dims[..axis].iter().chain(&dims[axis + 1..]).product()
+}
+
+fn lane_states(op: &str, zi: Option<Tensor>, n_lanes: usize, order: usize) -> Vec<f32> {
+    match zi {
+        None => vec![0.; n_lanes * order],
+        Some(zi) if zi.size() == order => {
+            zi.to_contiguous().as_slice().repeat(n_lanes)
+        }
+        Some(zi) => {
+            assert_eq!(
+                zi.size(), n_lanes * order,
+                "{} zi {:?} must have {} items or {} per lane",
+                op, zi.shape().as_vec(), order, order
+            );
+
+            zi.to_contiguous().as_slice().to_vec()
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        signal::{filtfilt, lfilter, lfilter_zi, sosfilt, sosfiltfilt, FilterOpt},
+        ten, tensor::Tensor, test::assert_close
+    };
+
+    #[test]
+    fn lfilter_fir_iir() {
+        let x = ten![1., 2., 3., 4.];
+
+        assert_eq!(
+            lfilter(&ten![0.5, 0.5], &ten![1.], &x, ()),
+            ten![0.5, 1.5, 2.5, 3.5]
+        );
+
+        // y[n] = x[n] - 0.5 y[n - 1]
+        assert_eq!(
+            lfilter(&ten![1.], &ten![1., 0.5], &ten![1., 0., 0., 0.], ()),
+            ten![1., -0.5, 0.25, -0.125]
+        );
+
+        // a[0] normalizes both b and a
+        assert_eq!(
+            lfilter(&ten![2.], &ten![2., 1.], &ten![1., 0., 0., 0.], ()),
+            ten![1., -0.5, 0.25, -0.125]
+        );
+    }
+
+    #[test]
+    fn lfilter_axis_and_zi() {
+        let x = ten![[1., 0.], [0., 1.], [0., 0.]];
+        let y = lfilter(&ten![1.], &ten![1., -0.5], &x, ().axis(0));
+
+        assert_eq!(y, ten![[1., 0.], [0.5, 1.], [0.25, 0.5]]);
+
+        let y = lfilter(&ten![1.], &ten![1., -0.5], &ten![0., 0.], ().zi(ten![2.]));
+        assert_eq!(y, ten![2., 1.]);
+
+        let x = ten![[0., 0.], [0., 0.]];
+        let y = lfilter(&ten![1.], &ten![1., -0.5], &x, ().zi(ten![[2.], [4.]]));
+        assert_eq!(y, ten![[2., 1.], [4., 2.]]);
+    }
+
+    #[test]
+    fn lfilter_zi_steady_state() {
+        let b = ten![0.2, 0.3, 0.1];
+        let a = ten![1., -0.5, 0.1];
+
+        let zi = lfilter_zi(&b, &a);
+        assert_eq!(zi.shape().as_vec(), &[2]);
+
+        // a unit step starting from zi has no transient
+        let gain = (0.2 + 0.3 + 0.1) / (1. - 0.5 + 0.1);
+        let y = lfilter(&b, &a, &Tensor::ones([8]), ().zi(zi));
+
+        assert_close(&y, &Tensor::fill([8], gain), 1e-5);
+    }
+
+    #[test]
+    fn filtfilt_zero_phase() {
+        let b = ten![0.2, 0.3, 0.1];
+        let a = ten![1., -0.5, 0.1];
+
+        let x = Tensor::fill([20], 3.);
+        assert_close(&filtfilt(&b, &a, &x, ()), &Tensor::fill([20], 3.), 1e-4);
+
+        // a symmetric input stays symmetric without a phase shift
+        let x: Vec<f32> = (0..21).map(|i| (-((i as f32 - 10.) / 3.).powi(2)).exp()).collect();
+        let y = filtfilt(&b, &a, Tensor::from(x), ());
+
+        for i in 0..21 {
+            assert!((y[i] - y[20 - i]).abs() < 1e-4, "{:?}", y);
+        }
+    }
+
+    #[test]
+    fn sosfilt_cascade() {
+        // two first-order sections multiply into b = [1, 1, 0.25], a = [1, -0.25, -0.125]
+        let sos = ten![[1., 0.5, 0., 1., -0.5, 0.], [1., 0.5, 0., 1., 0.25, 0.]];
+        let (b, a) = (ten![1., 1., 0.25], ten![1., -0.25, -0.125]);
+
+        let x = ten![1., 0., 0., 0., 2., -1.];
+
+        assert_close(&sosfilt(&sos, &x, ()), &lfilter(&b, &a, &x, ()), 1e-6);
+        assert_close(&sosfiltfilt(&sos, &x, ().padlen(4)), &filtfilt(&b, &a, &x, ().padlen(4)), 1e-4);
+    }
+    #[test]
+    fn filter_empty() {
+        let (b, a) = (ten![1.], ten![1., -0.5]);
+        let sos = ten![[1., 0.5, 0., 1., -0.5, 0.]];
+
+        let x = Tensor::<f32>::zeros([2, 0]);
+        assert_eq!(lfilter(&b, &a, &x, ()).shape().as_vec(), &[2, 0]);
+        assert_eq!(lfilter(&b, &a, &x, ().zi(ten![[2.], [4.]])).shape().as_vec(), &[2, 0]);
+        assert_eq!(sosfilt(&sos, &x, ()).shape().as_vec(), &[2, 0]);
+
+        let x = Tensor::<f32>::zeros([0, 3]);
+        assert_eq!(lfilter(&b, &a, &x, ().axis(0)).shape().as_vec(), &[0, 3]);
+        assert_eq!(sosfilt(&sos, &x, ().axis(0)).shape().as_vec(), &[0, 3]);
+    }
+}
@@ -0,0 +1,548 @@
+use std::f64::consts::PI;
+
+use essay_opt::derive_opt;
+use rustfft::num_complex::Complex;
+
+use crate::tensor::Tensor;
+
+use super::window::{Window, WindowOpt};
+
+type C64 = Complex<f64>;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum BandType {
+    Lowpass,
+    Highpass,
+    Bandpass,
+    Bandstop,
+}
+
+#[derive_opt(FilterDesignOpt)]
+#[derive(Default)]
+pub struct FilterDesignArg {
+    /// Band type, defaulting to lowpass for one cutoff and bandpass for two
+    btype: Option<BandType>,
+    /// Sample rate of the cutoffs, which are otherwise relative to Nyquist
+    fs: Option<f32>,
+    /// Window of `firwin`, defaulting to Hamming
+    window: Option<Window>,
+}
+
+///
+/// FIR filter taps by the windowed-sinc method. The taps are scaled to a
+/// unit gain at the center of the first passband. Highpass and bandstop
+/// filters pass Nyquist and need an odd number of taps.
+///
+pub fn firwin(numtaps: usize, cutoff: &[f32], opt: impl FilterDesignOpt) -> Tensor {
+    assert!(numtaps > 0, "firwin requires numtaps > 0");
+
+    let opt = opt.into_arg();
+    let btype = band_type("firwin", cutoff, opt.btype);
+    let cutoff = normalize_cutoff("firwin", cutoff, opt.fs);
+
+    let pass_zero = matches!(btype, BandType::Lowpass | BandType::Bandstop);
+    let pass_nyquist = matches!(btype, BandType::Highpass | BandType::Bandstop);
+
+    assert!(
+        ! pass_nyquist || ! numtaps.is_multiple_of(2),
+        "firwin {:?} filter requires an odd numtaps, found {}", btype, numtaps
+    );
+
+    let mut edges = Vec::<f64>::new();
+    if pass_zero {
+        edges.push(0.);
+    }
+    edges.extend(&cutoff);
+    if pass_nyquist {
+        edges.push(1.);
+    }
+
+    let alpha = 0.5 * (numtaps - 1) as f64;
+    let m: Vec<f64> = (0..numtaps).map(|i| i as f64 - alpha).collect();
+
+    let mut h = vec![0f64; numtaps];
+    for band in edges.chunks_exact(2) {
+        let (left, right) = (band[0], band[1]);
+
+        for (h, m) in h.iter_mut().zip(&m) {
+            *h += right * sinc(right * m) - left * sinc(left * m);
+        }
+    }
+
+    let window = opt.window.as_ref()
+        .unwrap_or(&Window::Hamming)
+        .to_tensor::<f64>(numtaps, ().symmetric(true));
+
+    for (h, w) in h.iter_mut().zip(window.iter()) {
+        *h *= w;
+    }
+
+    let (left, right) = (edges[0], edges[1]);
+    let scale_frequency = if left == 0. {
+        0.
+    } else if right == 1. {
+        1.
+    } else {
+        0.5 * (left + right)
+    };
+
+    let scale: f64 = h.iter().zip(&m)
+        .map(|(h, m)| h * (PI * m * scale_frequency).cos())
+        .sum();
+
+    Tensor::from_vec(h.iter().map(|h| (h / scale) as f32).collect(), [numtaps])
+}
+
+///
+/// Butterworth digital filter of the given order, returning the transfer
+/// function coefficients (b, a) for `lfilter`.
+///
+pub fn butter(order: usize, cutoff: &[f32], opt: impl FilterDesignOpt) -> (Tensor, Tensor) {
+    zpk_to_tf(iir_design("butter", buttap(order), cutoff, opt.into_arg()))
+}
+
+///
+/// Butterworth digital filter as [n_sections, 6] second-order sections for
+/// `sosfilt`, which are more stable than (b, a) for high orders.
+///
+pub fn butter_sos(order: usize, cutoff: &[f32], opt: impl FilterDesignOpt) -> Tensor {
+    zpk_to_sos(iir_design("butter_sos", buttap(order), cutoff, opt.into_arg()))
+}
+
+///
+/// Chebyshev type I digital filter with rp dB of passband ripple,
+/// returning the transfer function coefficients (b, a).
+///
+pub fn cheby1(
+    order: usize,
+    rp: f32,
+    cutoff: &[f32],
+    opt: impl FilterDesignOpt
+) -> (Tensor, Tensor) {
+    zpk_to_tf(iir_design("cheby1", cheb1ap(order, rp), cutoff, opt.into_arg()))
+}
+
+///
+/// Chebyshev type I digital filter as [n_sections, 6] second-order sections.
+///
+pub fn cheby1_sos(order: usize, rp: f32, cutoff: &[f32], opt: impl FilterDesignOpt) -> Tensor {
+    zpk_to_sos(iir_design("cheby1_sos", cheb1ap(order, rp), cutoff, opt.into_arg()))
+}
+
+// Zeros, poles and gain of a filter
+struct Zpk {
+    z: Vec<C64>,
+    p: Vec<C64>,
+    k: f64,
+}
+
+fn iir_design(op: &str, prototype: Zpk, cutoff: &[f32], opt: FilterDesignArg) -> Zpk {
+    assert!(! prototype.p.is_empty(), "{} requires an order > 0", op);
+
+    let btype = band_type(op, cutoff, opt.btype);
+    let cutoff = normalize_cutoff(op, cutoff, opt.fs);
+
+    // prewarp for the bilinear transform with fs = 2
+    let warped: Vec<f64> = cutoff.iter().map(|w| 4. * (PI * w / 2.).tan()).collect();
+
+    let analog = match btype {
+        BandType::Lowpass => lp2lp(prototype, warped[0]),
+        BandType::Highpass => lp2hp(prototype, warped[0]),
+        BandType::Bandpass => {
+            lp2bp(prototype, (warped[0] * warped[1]).sqrt(), warped[1] - warped[0])
+        }
+        BandType::Bandstop => {
+            lp2bs(prototype, (warped[0] * warped[1]).sqrt(), warped[1] - warped[0])
+        }
+    };
+
+    bilinear(analog, 2.)
+}
+
+fn band_type(op: &str, cutoff: &[f32], btype: Option<BandType>) -> BandType {
+    let btype = btype.unwrap_or(match cutoff.len() {
+        2 => BandType::Bandpass,
+        _ => BandType::Lowpass,
+    });
+
+    let n_cutoff = match btype {
+        BandType::Lowpass | BandType::Highpass => 1,
+        BandType::Bandpass | BandType::Bandstop => 2,
+    };
+
+    assert_eq!(
+        cutoff.len(), n_cutoff,
+        "{} {:?} filter requires {} cutoff frequencies", op, btype, n_cutoff
+    );
+
+    btype
+}
+
+// Cutoffs relative to Nyquist, checking they are increasing in (0, 1)
+fn normalize_cutoff(op: &str, cutoff: &[f32], fs: Option<f32>) -> Vec<f64> {
+    let nyquist = fs.map_or(1., |fs| 0.5 * fs as f64);
+
+    let cutoff: Vec<f64> = cutoff.iter().map(|c| *c as f64 / nyquist).collect();
+
+    for (i, c) in cutoff.iter().enumerate() {
+        assert!(
+            0. < *c && *c < 1. && (i == 0 || cutoff[i - 1] < *c),
+            "{} cutoffs must be increasing between 0 and Nyquist, found {:?}", op, cutoff
+        );
+    }
+
+    cutoff
+}
+
+fn sinc(x: f64) -> f64 {
+    if x == 0. {
+        1.
+    } else {
+        (PI * x).sin() / (PI * x)
+    }
+}
+
+// Analog Butterworth lowpass prototype with a cutoff of 1 rad/s
+fn buttap(order: usize) -> Zpk {
+    let n = order as f64;
+
+    let p = (0..order).map(|i| {
+        let m = 2. * i as f64 - n + 1.;
+
+        -C64::from_polar(1., PI * m / (2. * n))
+    }).collect();
+
+    Zpk { z: Vec::new(), p, k: 1. }
+}
+
+// Analog Chebyshev type I lowpass prototype with rp dB of ripple
+fn cheb1ap(order: usize, rp: f32) -> Zpk {
+    assert!(rp > 0., "cheby1 requires a passband ripple rp > 0");
+
+    let n = order as f64;
+    let eps = (10f64.powf(0.1 * rp as f64) - 1.).sqrt();
+    let mu = (1. / eps).asinh() / n;
+
+    let p: Vec<C64> = (0..order).map(|i| {
+        let theta = PI * (2. * i as f64 - n + 1.) / (2. * n);
+
+        -C64::new(mu, theta).sinh()
+    }).collect();
+
+    let mut k = p.iter().fold(C64::new(1., 0.), |k, p| k * -p).re;
+
+    if order.is_multiple_of(2) {
+        k /= (1. + eps * eps).sqrt();
+    }
+
+    Zpk { z: Vec::new(), p, k }
+}
+
+fn lp2lp(zpk: Zpk, wo: f64) -> Zpk {
+    let degree = zpk.p.len() - zpk.z.len();
+
+    Zpk {
+        z: zpk.z.iter().map(|z| z * wo).collect(),
+        p: zpk.p.iter().map(|p| p * wo).collect(),
+        k: zpk.k * wo.powi(degree as i32),
+    }
+}
+
+fn lp2hp(zpk: Zpk, wo: f64) -> Zpk {
+    let degree = zpk.p.len() - zpk.z.len();
+
+    let mut z: Vec<C64> = zpk.z.iter().map(|z| wo / z).collect();
+    z.resize(z.len() + degree, C64::new(0., 0.));
+
+    Zpk {
+        z,
+        p: zpk.p.iter().map(|p| wo / p).collect(),
+        k: zpk.k * (prod_neg(&zpk.z) / prod_neg(&zpk.p)).re,
+    }
+}
+
+fn lp2bp(zpk: Zpk, wo: f64, bw: f64) -> Zpk {
+    let degree = zpk.p.len() - zpk.z.len();
+
+    let mut z = split_band(&zpk.z, |z| z * (bw / 2.), wo);
+    z.resize(z.len() + degree, C64::new(0., 0.));
+
+    Zpk {
+        z,
+        p: split_band(&zpk.p, |p| p * (bw / 2.), wo),
+        k: zpk.k * bw.powi(degree as i32),
+    }
+}
+
+fn lp2bs(zpk: Zpk, wo: f64, bw: f64) -> Zpk {
+    let degree = zpk.p.len() - zpk.z.len();
+
+    let mut z = split_band(&zpk.z, |z| (bw / 2.) / z, wo);
+    for _ in 0..degree {
+        z.push(C64::new(0., wo));
+        z.push(C64::new(0., -wo));
+    }
+
+    Zpk {
+        z,
+        p: split_band(&zpk.p, |p| (bw / 2.) / p, wo),
+        k: zpk.k * (prod_neg(&zpk.z) / prod_neg(&zpk.p)).re,
+    }
+}
+
+// Each root r maps to the two roots of s^2 - 2 f(r) s + wo^2
+fn split_band(roots: &[C64], f: impl Fn(C64) -> C64, wo: f64) -> Vec<C64> {
+    let roots: Vec<C64> = roots.iter().map(|r| f(*r)).collect();
+
+    let mut split: Vec<C64> = roots.iter()
+        .map(|r| r + (r * r - wo * wo).sqrt())
+        .collect();
+
+    split.extend(roots.iter().map(|r| r - (r * r - wo * wo).sqrt()));
+
+    split
+}
+
+// Bilinear transform of an analog filter to a digital filter at sample rate fs
+fn bilinear(zpk: Zpk, fs: f64) -> Zpk {
+    let degree = zpk.p.len() - zpk.z.len();
+    let fs2 = 2. * fs;
+
+    let mut z: Vec<C64> = zpk.z.iter().map(|z| (fs2 + z) / (fs2 - z)).collect();
+    z.resize(z.len() + degree, C64::new(-1., 0.));
+
+    let k_num = zpk.z.iter().fold(C64::new(1., 0.), |k, z| k * (fs2 - z));
+    let k_den = zpk.p.iter().fold(C64::new(1., 0.), |k, p| k * (fs2 - p));
+
+    Zpk {
+        z,
+        p: zpk.p.iter().map(|p| (fs2 + p) / (fs2 - p)).collect(),
+        k: zpk.k * (k_num / k_den).re,
+    }
+}
+
+fn prod_neg(roots: &[C64]) -> C64 {
+    roots.iter().fold(C64::new(1., 0.), |k, r| k * -r)
+}
+
+// Real coefficients of the monic polynomial with the given roots
+fn poly(roots: &[C64]) -> Vec<f64> {
+    let mut coeff = vec![C64::new(1., 0.)];
+
+    for r in roots {
+        coeff.push(C64::new(0., 0.));
+
+        for i in (1..coeff.len()).rev() {
+            coeff[i] = coeff[i] - r * coeff[i - 1];
+        }
+    }
+
+    coeff.iter().map(|c| c.re).collect()
+}
+
+fn zpk_to_tf(zpk: Zpk) -> (Tensor, Tensor) {
+    let b: Vec<f32> = poly(&zpk.z).iter().map(|b| (b * zpk.k) as f32).collect();
+    let a: Vec<f32> = poly(&zpk.p).iter().map(|a| *a as f32).collect();
+
+    let (n_b, n_a) = (b.len(), a.len());
+
+    (Tensor::from_vec(b, [n_b]), Tensor::from_vec(a, [n_a]))
+}
+
+///
+/// Groups the poles into conjugate or real pairs, pairs each with the
+/// nearest zero pair, and orders the sections so the poles closest to the
+/// unit circle come last. The gain is applied to the first section.
+///
+fn zpk_to_sos(zpk: Zpk) -> Tensor {
+    let n_sections = zpk.p.len().max(zpk.z.len()).div_ceil(2);
+
+    let mut z = zpk.z;
+    let mut p = zpk.p;
+    z.resize(2 * n_sections, C64::new(0., 0.));
+    p.resize(2 * n_sections, C64::new(0., 0.));
+
+    let mut p_pairs = root_pairs(&p);
+    let mut z_pairs = root_pairs(&z);
+
+    // closest to the unit circle first
+    p_pairs.sort_by(|a, b| {
+        (1. - a[0].norm()).abs().total_cmp(&(1. - b[0].norm()).abs())
+    });
+
+    let mut sections = Vec::<[f32; 6]>::with_capacity(n_sections);
+
+    for p in &p_pairs {
+        let (i, _) = z_pairs.iter().enumerate()
+            .map(|(i, z)| (i, (z[0] - p[0]).norm()))
+            .min_by(|a, b| a.1.total_cmp(&b.1))
+            .unwrap();
+
+        let z = z_pairs.swap_remove(i);
+
+        let b = poly(&z);
+        let a = poly(p);
+
+        sections.push([
+            b[0] as f32, b[1] as f32, b[2] as f32,
+            a[0] as f32, a[1] as f32, a[2] as f32,
+        ]);
+    }
+
+    sections.reverse();
+
+    for b in &mut sections[0][0..3] {
+        *b = (*b as f64 * zpk.k) as f32;
+    }
+
+    let n = sections.len();
+
+    Tensor::from_vec(sections.concat(), [n, 6])
+}
+
+// Conjugate pairs from the roots with positive imaginary parts, then the
+// real roots paired in order of magnitude
+fn root_pairs(roots: &[C64]) -> Vec<[C64; 2]> {
+    const EPS: f64 = 1e-10;
+
+    let mut pairs: Vec<[C64; 2]> = roots.iter()
+        .filter(|r| r.im > EPS)
+        .map(|r| [*r, r.conj()])
+        .collect();
+
+    let mut real: Vec<C64> = roots.iter()
+        .filter(|r| r.im.abs() <= EPS)
+        .map(|r| C64::new(r.re, 0.))
+        .collect();
+
+    real.sort_by(|a, b| b.norm().total_cmp(&a.norm()));
+
+    for r in real.chunks_exact(2) {
+        pairs.push([r[0], r[1]]);
+    }
+
+    pairs
+}
+
+#[cfg(test)]
+mod test {
+    use std::f64::consts::PI;
+
+    use rustfft::num_complex::Complex;
+
+    use crate::{
+        signal::{
+            butter, butter_sos, cheby1, firwin, lfilter, sosfilt,
+            BandType, FilterDesignOpt
+        },
+        ten, tensor::Tensor, test::assert_close
+    };
+
+    // |H(e^jw)| of b / a at w in (0, pi) radians per sample
+    fn gain(b: &Tensor, a: &Tensor, w: f64) -> f64 {
+        let eval = |c: &Tensor| c.iter().enumerate().fold(Complex::new(0., 0.), |s, (i, c)| {
+            s + Complex::from_polar(*c as f64, -w * i as f64)
+        });
+
+        (eval(b) / eval(a)).norm()
+    }
+
+    #[test]
+    fn butter_lowpass() {
+        let (b, a) = butter(2, &[0.5], ());
+
+        assert_close(&b, &ten![0.29289322, 0.58578644, 0.29289322], 1e-6);
+        assert_close(&a, &ten![1., 0., 0.17157288], 1e-6);
+
+        let (b, a) = butter(4, &[100.], ().fs(1000.));
+        assert!((gain(&b, &a, 0.) - 1.).abs() < 1e-5);
+        assert!((gain(&b, &a, 0.2 * PI) - 0.5f64.sqrt()).abs() < 1e-5);
+    }
+
+    #[test]
+    fn butter_highpass() {
+        let (b, a) = butter(3, &[0.3], ().btype(BandType::Highpass));
+
+        assert!(gain(&b, &a, 0.) < 1e-6);
+        assert!((gain(&b, &a, PI) - 1.).abs() < 1e-5);
+        assert!((gain(&b, &a, 0.3 * PI) - 0.5f64.sqrt()).abs() < 1e-5);
+    }
+
+    #[test]
+    fn butter_bandpass_sos() {
+        let sos = butter_sos(4, &[0.2, 0.4], ());
+        assert_eq!(sos.shape().as_vec(), &[4, 6]);
+
+        let (b, a) = butter(4, &[0.2, 0.4], ());
+        assert_eq!(b.size(), 9);
+        assert!(gain(&b, &a, 0.) < 1e-6);
+        assert!((gain(&b, &a, 0.2 * PI) - 0.5f64.sqrt()).abs() < 1e-4);
+        assert!((gain(&b, &a, 0.4 * PI) - 0.5f64.sqrt()).abs() < 1e-4);
+
+        let mut impulse = vec![0.; 64];
+        impulse[0] = 1.;
+        let impulse = Tensor::from(impulse);
+
+        assert_close(&sosfilt(&sos, &impulse, ()), &lfilter(&b, &a, &impulse, ()), 1e-4);
+    }
+
+    #[test]
+    fn butter_bandstop() {
+        let (b, a) = butter(2, &[0.2, 0.4], ().btype(BandType::Bandstop));
+
+        assert!((gain(&b, &a, 0.) - 1.).abs() < 1e-5);
+        assert!((gain(&b, &a, PI) - 1.).abs() < 1e-5);
+
+        let center = (0.2 * PI / 2.).tan() * (0.4 * PI / 2.).tan();
+        assert!(gain(&b, &a, 2. * center.sqrt().atan()) < 1e-4);
+    }
+
+    #[test]
+    fn cheby1_ripple() {
+        let rp = 1.;
+        let ripple = 10f64.powf(-rp / 20.);
+
+        let (b, a) = cheby1(4, rp as f32, &[0.3], ());
+        assert!((gain(&b, &a, 0.) - ripple).abs() < 1e-5);
+        assert!((gain(&b, &a, 0.3 * PI) - ripple).abs() < 1e-5);
+        assert!(gain(&b, &a, 0.6 * PI) < 0.05);
+
+        let (b, a) = cheby1(3, rp as f32, &[0.3], ());
+        assert!((gain(&b, &a, 0.) - 1.).abs() < 1e-5);
+        assert!((gain(&b, &a, 0.3 * PI) - ripple).abs() < 1e-5);
+    }
+
+    #[test]
+    fn firwin_lowpass() {
+        let h = firwin(31, &[0.3], ());
+
+        assert_eq!(h.shape().as_vec(), &[31]);
+        for i in 0..31 {
+            assert!((h[i] - h[30 - i]).abs() < 1e-6);
+        }
+
+        let a = ten![1.];
+        assert!((gain(&h, &a, 0.) - 1.).abs() < 1e-5);
+        assert!(gain(&h, &a, 0.8 * PI) < 1e-2);
+    }
+
+    #[test]
+    fn firwin_bands() {
+        let a = ten![1.];
+
+        let h = firwin(31, &[0.3], ().btype(BandType::Highpass));
+        assert!((gain(&h, &a, PI) - 1.).abs() < 1e-5);
+        assert!(gain(&h, &a, 0.) < 1e-2);
+
+        let h = firwin(51, &[100., 200.], ().fs(1000.));
+        assert!((gain(&h, &a, 0.3 * PI) - 1.).abs() < 1e-2);
+        assert!(gain(&h, &a, 0.) < 1e-2);
+        assert!(gain(&h, &a, 0.8 * PI) < 1e-2);
+    }
+
+    #[test]
+    #[should_panic]
+    fn firwin_highpass_even() {
+        firwin(30, &[0.3], ().btype(BandType::Highpass));
+    }
+}
@@ -1,46 +1,120 @@
-use crate::tensor::Tensor;
+use essay_opt::derive_opt;
 
-pub fn frame(x: impl Into<Tensor>, len: usize, step: usize) -> Tensor {
-    let x = x.into();
-    let pad_end = false;
+use crate::tensor::{Axis, Tensor};
+
+///
+/// Splits the axis, defaulting to the last, into overlapping frames of len
+/// items every step items. The axis is replaced by [n_frames, len]. With
+/// `pad_end`, the end is padded with `pad_value` so every item is in a
+/// frame.
+///
+pub fn frame(x: impl Into<Tensor>, len: usize, step: usize, opt: impl FrameOpt) -> Tensor {
+    let x = x.into().to_contiguous();
+    let opt = opt.into_arg();
+
+    assert!(len > 0 && step > 0, "frame len={} and step={} must be > 0", len, step);
+
+    let axis = opt.axis.unwrap_or(Axis::axis(-1))
+        .try_axis_from_rank("frame", x.rank())
+        .unwrap_or_else(|err| panic!("{}", err));
+
+    let pad_end = opt.pad_end;
+    let pad_value = opt.pad_value.unwrap_or(0.);
+
+    let mut dims = x.shape().as_vec();
+    let dim = dims[axis];
 
     let n_frames = if pad_end {
-        - (- (x.dim(0) as isize) / step as isize)
+        dim.div_ceil(step)
+    } else if dim >= len {
+        1 + (dim - len) / step
     } else {
-        1 + (x.dim(0) as isize - len as isize) / step as isize
-    } as usize;
+        0
+    };
+
+    let outer: usize = dims[..axis].iter().product();
+    let inner: usize = dims[axis + 1..].iter().product();
 
-    let mut vec = Vec::<Tensor>::new();
-    for i in 0..n_frames {
-        vec.push(x.subslice(i * step, len))
+    let data = x.as_slice();
+    let mut vec = Vec::<f32>::with_capacity(outer * n_frames * len * inner);
+
+    for o in 0..outer {
+        for f in 0..n_frames {
+            for j in 0..len {
+                let k = f * step + j;
+
+                for i in 0..inner {
+                    vec.push(if k < dim { data[(o * dim + k) * inner + i] } else { pad_value });
+                }
+            }
+        }
     }
 
-    todo!();
-    /*
-    let mut shape = Vec::from(x.shape().as_slice());
-    shape[0] = len;
-    shape.insert(0, n_frames);
+    dims[axis] = len;
+    dims.insert(axis, n_frames);
+
+    Tensor::from_vec(vec, dims.as_slice())
+}
 
-    Tensor::from_merge(&vec, shape)
-    */
+#[derive_opt(FrameOpt)]
+#[derive(Default)]
+pub struct FrameArg {
+    pad_end: bool,
+    pad_value: Option<f32>,
+    axis: Option<Axis>,
 }
 
 #[cfg(test)]
 mod test {
-    use crate::{signal::frame, ten};
+    use crate::{signal::{frame, FrameOpt}, ten};
 
     #[test]
     fn test_frame_2_2() {
-        let x = ten!([1., 2., 3., 4., 5., 6.]);
-        assert_eq!(frame(&x, 2, 2), ten!([[1., 2.], [3., 4.], [5., 6.]]));
+        let x = ten![1., 2., 3., 4., 5., 6.];
+        assert_eq!(frame(&x, 2, 2, ()), ten![[1., 2.], [3., 4.], [5., 6.]]);
 
-        let x = ten!([1., 2., 3., 4., 5., 6.]);
-        assert_eq!(frame(&x, 3, 3), ten!([[1., 2., 3.], [4., 5., 6.]]));
+        let x = ten![1., 2., 3., 4., 5., 6.];
+        assert_eq!(frame(&x, 3, 3, ()), ten![[1., 2., 3.], [4., 5., 6.]]);
     }
 
     #[test]
     fn test_frame_3_2() {
-        let x = ten!([1., 2., 3., 4., 5., 6.]);
-        assert_eq!(frame(&x, 3, 2), ten!([[1., 2., 3.], [3., 4., 5.]]));
+        let x = ten![1., 2., 3., 4., 5., 6.];
+        assert_eq!(frame(&x, 3, 2, ()), ten![[1., 2., 3.], [3., 4., 5.]]);
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn frame_pad_end() {
+        let x = ten![1., 2., 3., 4., 5., 6.];
+
+        assert_eq!(
+            frame(&x, 3, 2, ().pad_end(true)), 
+            ten![[1., 2., 3.], [3., 4., 5.], [5., 6., 0.]]
+        );
+
+        assert_eq!(
+            frame(&x, 4, 4, ().pad_end(true).pad_value(-1.)), 
+            ten![[1., 2., 3., 4.], [5., 6., -1., -1.]]
+        );
+
+        assert_eq!(frame(&x, 8, 2, ()).shape().as_vec(), &[0, 8]);
+    }
+
+    #[test]
+    fn frame_batch_and_axis() {
+        let x = ten![[1., 2., 3., 4.], [5., 6., 7., 8.]];
+
+        assert_eq!(
+            frame(&x, 2, 2, ()), 
+            ten![[[1., 2.], [3., 4.]], [[5., 6.], [7., 8.]]]
+        );
+
+        // frames along axis 0 keep the trailing channel axis
+        let x = ten![[1., 10.], [2., 20.], [3., 30.]];
+
+        assert_eq!(
+            frame(&x, 2, 1, ().axis(0)), 
+            ten![[[1., 10.], [2., 20.]], [[2., 20.], [3., 30.]]]
+        );
+    }
+}
@@ -0,0 +1,307 @@
+use essay_opt::derive_opt;
+
+use crate::tensor::Tensor;
+
+use super::{dct::dct, fft::FftNorm, stft::stft, window::Window};
+
+///
+/// Frequency to mel conversion for the mel filterbank.
+///
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum MelScale {
+    /// 2595 log10(1 + f / 700)
+    #[default]
+    Htk,
+    /// Linear below 1 kHz and logarithmic above, like Slaney's Auditory
+    /// Toolbox and librosa's default
+    Slaney,
+}
+
+const SLANEY_F_SP: f32 = 200. / 3.;
+const SLANEY_MIN_LOG_HZ: f32 = 1000.;
+const SLANEY_MIN_LOG_MEL: f32 = SLANEY_MIN_LOG_HZ / SLANEY_F_SP;
+
+impl MelScale {
+    pub fn hz_to_mel(&self, hz: f32) -> f32 {
+        match self {
+            MelScale::Htk => 2595. * (1. + hz / 700.).log10(),
+            MelScale::Slaney => {
+                if hz < SLANEY_MIN_LOG_HZ {
+                    hz / SLANEY_F_SP
+                } else {
+                    SLANEY_MIN_LOG_MEL + (hz / SLANEY_MIN_LOG_HZ).ln() / slaney_log_step()
+                }
+            }
+        }
+    }
+
+    pub fn mel_to_hz(&self, mel: f32) -> f32 {
+        match self {
+            MelScale::Htk => 700. * (10f32.powf(mel / 2595.) - 1.),
+            MelScale::Slaney => {
+                if mel < SLANEY_MIN_LOG_MEL {
+                    mel * SLANEY_F_SP
+                } else {
+                    SLANEY_MIN_LOG_HZ * (slaney_log_step() * (mel - SLANEY_MIN_LOG_MEL)).exp()
+                }
+            }
+        }
+    }
+}
+
+fn slaney_log_step() -> f32 {
+    6.4f32.ln() / 27.
+}
+
+///
+/// Options for the mel functions. `linear_to_mel_weight_matrix` uses the
+/// filterbank options; the others also use the STFT options, and `mfcc`
+/// uses `num_mfcc`.
+///
+#[derive_opt(MelOpt)]
+#[derive(Default)]
+pub struct MelArg {
+    /// Mel bands, defaulting to 80
+    num_mel_bins: Option<usize>,
+    /// Lowest band edge, defaulting to 0 Hz
+    lower_edge_hertz: Option<f32>,
+    /// Highest band edge, defaulting to the Nyquist frequency
+    upper_edge_hertz: Option<f32>,
+    scale: Option<MelScale>,
+    /// Scales each triangle by 2 / bandwidth for constant energy per band
+    area_norm: bool,
+
+    /// STFT frame length, defaulting to 1024 samples
+    frame_length: Option<usize>,
+    /// STFT hop, defaulting to frame_length / 4
+    frame_step: Option<usize>,
+    /// Defaults to frame_length
+    fft_length: Option<usize>,
+    /// Defaults to a periodic Hann window
+    window: Option<Window>,
+    /// Exponent of the magnitude spectrogram, defaulting to 2 for power
+    power: Option<f32>,
+
+    /// Offset added before the log in `log_mel`, defaulting to 1e-6
+    log_offset: Option<f32>,
+    /// Coefficients kept by `mfcc`, defaulting to 13
+    num_mfcc: Option<usize>,
+}
+
+///
+/// Matrix [num_spectrogram_bins, num_mel_bins] that maps a linear
+/// spectrogram with bins evenly spaced from 0 Hz to sample_rate / 2 to
+/// mel bands. Each band is a triangle between its neighbors' centers,
+/// which are evenly spaced on the mel scale.
+///
+pub fn linear_to_mel_weight_matrix(
+    num_spectrogram_bins: usize, 
+    sample_rate: f32, 
+    opt: impl MelOpt
+) -> Tensor {
+    let opt = opt.into_arg();
+
+    mel_weights(num_spectrogram_bins, sample_rate, &opt)
+}
+
+fn mel_weights(num_spectrogram_bins: usize, sample_rate: f32, opt: &MelArg) -> Tensor {
+    let num_mel_bins = opt.num_mel_bins.unwrap_or(80);
+    let nyquist = sample_rate / 2.;
+    let lower = opt.lower_edge_hertz.unwrap_or(0.);
+    let upper = opt.upper_edge_hertz.unwrap_or(nyquist);
+    let scale = opt.scale.unwrap_or_default();
+
+    assert!(num_spectrogram_bins > 1, "mel weights require more than 1 spectrogram bin");
+    assert!(num_mel_bins > 0, "mel weights require num_mel_bins > 0");
+    assert!(
+        0. <= lower && lower < upper && upper <= nyquist,
+        "mel edges must have 0 <= lower {} < upper {} <= nyquist {}", lower, upper, nyquist
+    );
+
+    let mel_lower = scale.hz_to_mel(lower);
+    let mel_upper = scale.hz_to_mel(upper);
+    let mel_step = (mel_upper - mel_lower) / (num_mel_bins + 1) as f32;
+
+    let edges: Vec<f32> = (0..num_mel_bins + 2)
+        .map(|i| scale.mel_to_hz(mel_lower + i as f32 * mel_step))
+        .collect();
+
+    let bin_step = nyquist / (num_spectrogram_bins - 1) as f32;
+
+    let mut vec = Vec::<f32>::with_capacity(num_spectrogram_bins * num_mel_bins);
+
+    for k in 0..num_spectrogram_bins {
+        let hz = k as f32 * bin_step;
+
+        for m in 0..num_mel_bins {
+            let (left, center, right) = (edges[m], edges[m + 1], edges[m + 2]);
+
+            let lower_slope = (hz - left) / (center - left);
+            let upper_slope = (right - hz) / (right - center);
+            let weight = lower_slope.min(upper_slope).max(0.);
+
+            let norm = if opt.area_norm { 2. / (right - left) } else { 1. };
+
+            vec.push(weight * norm);
+        }
+    }
+
+    Tensor::from_vec(vec, [num_spectrogram_bins, num_mel_bins])
+}
+
+///
+/// Mel spectrogram of audio samples in the last axis, returning
+/// [.., n_frames, num_mel_bins].
+///
+pub fn mel_spectrogram(x: impl Into<Tensor>, sample_rate: f32, opt: impl MelOpt) -> Tensor {
+    mel_power(x.into(), sample_rate, &opt.into_arg())
+}
+
+///
+/// Natural log of the mel spectrogram plus `log_offset`.
+///
+pub fn log_mel(x: impl Into<Tensor>, sample_rate: f32, opt: impl MelOpt) -> Tensor {
+    let opt = opt.into_arg();
+
+    log_mel_arg(x.into(), sample_rate, &opt)
+}
+
+///
+/// Mel-frequency cepstral coefficients, the orthonormal DCT-II of the log
+/// mel spectrogram truncated to `num_mfcc`, returning
+/// [.., n_frames, num_mfcc].
+///
+pub fn mfcc(x: impl Into<Tensor>, sample_rate: f32, opt: impl MelOpt) -> Tensor {
+    let opt = opt.into_arg();
+
+    let log_mel = log_mel_arg(x.into(), sample_rate, &opt);
+    let num_mel_bins = log_mel.cols();
+    let num_mfcc = opt.num_mfcc.unwrap_or(13);
+
+    assert!(
+        num_mfcc <= num_mel_bins, 
+        "mfcc num_mfcc {} must be <= num_mel_bins {}", num_mfcc, num_mel_bins
+    );
+
+    dct(log_mel, FftNorm::Ortho).slice_axis(-1, 0, num_mfcc, 1).to_contiguous()
+}
+
+fn log_mel_arg(x: Tensor, sample_rate: f32, opt: &MelArg) -> Tensor {
+    let log_offset = opt.log_offset.unwrap_or(1e-6);
+
+    mel_power(x, sample_rate, opt).map(|v| (v + log_offset).ln())
+}
+
+fn mel_power(x: Tensor, sample_rate: f32, opt: &MelArg) -> Tensor {
+    let frame_length = opt.frame_length.unwrap_or(1024);
+    let frame_step = opt.frame_step.unwrap_or((frame_length / 4).max(1));
+    let fft_length = opt.fft_length.unwrap_or(frame_length);
+    let window = opt.window.clone().unwrap_or(Window::Hann);
+    let power = opt.power.unwrap_or(2.);
+
+    let spectrum = stft(x, frame_length, frame_step, fft_length, window);
+
+    let magnitude = if power == 2. {
+        spectrum.norm_sqr()
+    } else {
+        spectrum.norm().map(|v| v.powf(power))
+    };
+
+    let weights = mel_weights(fft_length / 2 + 1, sample_rate, opt);
+
+    // batch dims broadcast against the single weight matrix
+    magnitude.matmul(&weights)
+}
+
+#[cfg(test)]
+mod test {
+    use std::f32::consts::PI;
+
+    use crate::{
+        signal::{linear_to_mel_weight_matrix, log_mel, mel_spectrogram, mfcc, MelOpt, MelScale},
+        tensor::Tensor,
+    };
+
+    fn tone(hz: f32, sample_rate: f32, len: usize) -> Tensor {
+        Tensor::from_vec(
+            (0..len).map(|i| (2. * PI * hz * i as f32 / sample_rate).sin()).collect(), 
+            [len]
+        )
+    }
+
+    #[test]
+    fn mel_scales() {
+        assert!((MelScale::Htk.hz_to_mel(1000.) - 1000.).abs() < 0.1);
+        assert!((MelScale::Htk.mel_to_hz(MelScale::Htk.hz_to_mel(440.)) - 440.).abs() < 1e-2);
+
+        assert!((MelScale::Slaney.hz_to_mel(500.) - 7.5).abs() < 1e-5);
+        assert!((MelScale::Slaney.hz_to_mel(1000.) - 15.).abs() < 1e-5);
+        assert!((MelScale::Slaney.hz_to_mel(6400.) - 42.).abs() < 1e-4);
+        assert!((MelScale::Slaney.mel_to_hz(42.) - 6400.).abs() < 1e-1);
+    }
+
+    #[test]
+    fn weight_matrix() {
+        let w = linear_to_mel_weight_matrix(5, 8., ().num_mel_bins(1));
+
+        // one triangle from 0 Hz to 4 Hz, bins every 1 Hz
+        assert_eq!(w.shape().as_vec(), &[5, 1]);
+        assert!(w[(0, 0)] < 1e-5 && w[(4, 0)] < 1e-5);
+        assert!(w[(1, 0)] > 0. && w[(2, 0)] > w[(1, 0)] && w[(2, 0)] > w[(3, 0)]);
+
+        let w = linear_to_mel_weight_matrix(257, 16000., ().num_mel_bins(40).scale(MelScale::Slaney));
+        assert_eq!(w.shape().as_vec(), &[257, 40]);
+        assert!(w.iter().all(|v| *v >= 0. && *v <= 1.));
+
+        // every band has weight
+        for m in 0..40 {
+            assert!((0..257).any(|k| w[(k, m)] > 0.), "band {}", m);
+        }
+
+        let w_norm = linear_to_mel_weight_matrix(257, 16000., ().num_mel_bins(40).area_norm(true));
+        assert!(w_norm[(1, 0)] > 0.);
+    }
+
+    #[test]
+    fn mel_spectrogram_tone() {
+        let x = tone(1000., 8000., 4096);
+        let mel = mel_spectrogram(&x, 8000., ().frame_length(256).num_mel_bins(20));
+
+        assert_eq!(mel.shape().as_vec(), &[61, 20]);
+
+        // the peak band's center is near 1 kHz
+        let frame: Vec<f32> = (0..20).map(|m| mel[(30, m)]).collect();
+        let peak = (0..20).max_by(|a, b| frame[*a].partial_cmp(&frame[*b]).unwrap()).unwrap();
+
+        let scale = MelScale::Htk;
+        let mel_step = scale.hz_to_mel(4000.) / 21.;
+        let center = scale.mel_to_hz((peak + 1) as f32 * mel_step);
+        assert!((center - 1000.).abs() < 150., "center {}", center);
+    }
+
+    #[test]
+    fn mfcc_shape_and_constant() {
+        let x = Tensor::from_vec(vec![0f32; 2048], [2, 1024]);
+
+        let log = log_mel(&x, 16000., ().frame_length(256).log_offset(1.));
+        assert_eq!(log.shape().as_vec(), &[2, 13, 80]);
+        assert!(log.iter().all(|v| *v == 0.));
+
+        let c = mfcc(&x, 16000., ().frame_length(256).log_offset(1e-6));
+        assert_eq!(c.shape().as_vec(), &[2, 13, 13]);
+
+        // a constant log mel only has energy in the first coefficient
+        assert!(c[(0, 0, 0)] < 0.);
+        assert!(c.slice((.., .., 1..)).abs().reduce_max()[0] < 1e-3);
+    }
+    #[test]
+    fn mel_short_clip() {
+        // shorter than frame_length, so there are no frames
+        let x = tone(1000., 8000., 100);
+
+        let mel = mel_spectrogram(&x, 8000., ().frame_length(256).num_mel_bins(20));
+        assert_eq!(mel.shape().as_vec(), &[0, 20]);
+        assert_eq!(log_mel(&x, 8000., ().frame_length(256)).shape().as_vec(), &[0, 80]);
+        assert_eq!(mfcc(&x, 8000., ().frame_length(256)).shape().as_vec(), &[0, 13]);
+    }
+}
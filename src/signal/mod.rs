@@ -1,6 +1,43 @@
+mod convolve;
+mod dct;
+mod fft;
+mod filter;
+mod filter_design;
 mod frame;
+mod mel;
+mod resample;
 mod rfft;
+mod stft;
+pub mod window;
+
+pub use convolve::{
+    convolve, correlate, convolve2d, correlate2d, ConvMode, ConvMethod, ConvolveArg, ConvolveOpt,
+};
+
+pub use dct::dct;
+
+pub use fft::{
+    fft, ifft, rfft, irfft, fft2, ifft2, fftn, ifftn, fftfreq, rfftfreq, 
+    fftshift, ifftshift, FftNorm, FftArg, FftOpt, FftnArg, FftnOpt,
+};
+
+pub use filter::{filtfilt, lfilter, lfilter_zi, sosfilt, sosfiltfilt, FilterArg, FilterOpt};
+
+pub use filter_design::{
+    butter, butter_sos, cheby1, cheby1_sos, firwin,
+    BandType, FilterDesignArg, FilterDesignOpt,
+};
+
+pub use frame::{frame, FrameArg, FrameOpt};
+
+pub use mel::{
+    linear_to_mel_weight_matrix, mel_spectrogram, log_mel, mfcc, MelArg, MelOpt, MelScale,
+};
+
+pub use resample::{resample, resample_poly, ResampleArg, ResampleOpt};
 
-pub use frame::frame;
 pub use rfft::rfft_norm;
 
+pub use stft::{stft, istft};
+
+pub use window::Window;
@@ -0,0 +1,204 @@
+use essay_opt::derive_opt;
+use num_complex::Complex;
+use rustfft::FftDirection;
+
+use crate::tensor::{Axis, Tensor};
+
+use super::{
+    fft::{lane_axis, map_lanes, plan_fft},
+    filter_design::{firwin, FilterDesignOpt},
+    window::Window,
+};
+
+#[derive_opt(ResampleOpt)]
+#[derive(Default)]
+pub struct ResampleArg {
+    /// Resampled axis, defaulting to the last
+    axis: Option<Axis>,
+    /// Anti-aliasing FIR window of `resample_poly`, defaulting to Kaiser(5)
+    window: Option<Window>,
+}
+
+///
+/// Resamples x by up / down along the axis, upsampling by zero insertion,
+/// applying a zero-phase anti-aliasing FIR and downsampling. The output
+/// has ceil(n * up / down) samples.
+///
+pub fn resample_poly(
+    x: impl Into<Tensor>,
+    up: usize,
+    down: usize,
+    opt: impl ResampleOpt
+) -> Tensor {
+    assert!(up > 0 && down > 0, "resample_poly requires up > 0 and down > 0");
+
+    let x = x.into();
+    let opt = opt.into_arg();
+    let axis = lane_axis("resample_poly", &x, opt.axis);
+
+    let g = gcd(up, down);
+    let (up, down) = (up / g, down / g);
+
+    if up == 1 && down == 1 {
+        return x;
+    }
+
+    let max_rate = up.max(down);
+    let half_len = 10 * max_rate;
+
+    let window = opt.window.unwrap_or(Window::Kaiser(5.));
+    let h = firwin(2 * half_len + 1, &[1. / max_rate as f32], FilterDesignOpt::window((), window));
+    let h: Vec<f32> = h.iter().map(|h| h * up as f32).collect();
+
+    let n_in = x.dim(axis);
+    let n_out = (n_in * up).div_ceil(down);
+
+    map_lanes(&x, axis, n_out, |lane, out| {
+        for (m, y) in out.iter_mut().enumerate() {
+            // output m is centered on upsampled sample m * down
+            let center = m * down + half_len;
+
+            let lo = (center + 1).saturating_sub(h.len()).div_ceil(up);
+            let hi = (center / up).min(n_in - 1);
+
+            *y = (lo..=hi).map(|i| lane[i] * h[center - i * up]).sum();
+        }
+    })
+}
+
+///
+/// Resamples x to num samples along the axis using the FFT, which assumes
+/// the signal is periodic.
+///
+pub fn resample(x: impl Into<Tensor>, num: usize, opt: impl ResampleOpt) -> Tensor {
+    assert!(num > 0, "resample requires num > 0");
+
+    let x = x.into();
+    let opt = opt.into_arg();
+    let axis = lane_axis("resample", &x, opt.axis);
+
+    let n_in = x.dim(axis);
+    assert!(n_in > 0, "resample requires a non-empty axis");
+
+    let n = n_in.min(num);
+
+    let forward = plan_fft(n_in, FftDirection::Forward);
+    let inverse = plan_fft(num, FftDirection::Inverse);
+
+    let mut x_buf = vec![Complex::new(0f32, 0.); n_in];
+    let mut y_buf = vec![Complex::new(0f32, 0.); num];
+
+    map_lanes(&x, axis, num, |lane, out| {
+        for (x, v) in x_buf.iter_mut().zip(lane) {
+            *x = Complex::new(*v, 0.);
+        }
+
+        forward.process(&mut x_buf);
+
+        // half spectrum of the output, keeping the n / 2 + 1 lowest bins
+        y_buf.fill(Complex::new(0., 0.));
+        y_buf[..n / 2 + 1].copy_from_slice(&x_buf[..n / 2 + 1]);
+
+        // the shared Nyquist bin of an even n is split or merged
+        if n.is_multiple_of(2) && num < n_in {
+            y_buf[n / 2] *= 2.;
+        } else if n.is_multiple_of(2) && num > n_in {
+            y_buf[n / 2] *= 0.5;
+        }
+
+        if num.is_multiple_of(2) {
+            y_buf[num / 2].im = 0.;
+        }
+
+        for k in 1..num.div_ceil(2) {
+            y_buf[num - k] = y_buf[k].conj();
+        }
+
+        inverse.process(&mut y_buf);
+
+        for (y, v) in out.iter_mut().zip(&y_buf) {
+            *y = v.re / n_in as f32;
+        }
+    })
+}
+
+fn gcd(a: usize, b: usize) -> usize {
+    if b == 0 { a } else { gcd(b, a % b) }
+}
+
+#[cfg(test)]
+mod test {
+    use std::f32::consts::PI;
+
+    use crate::{
+        array::stack_axis, signal::{resample, resample_poly, ResampleOpt}, ten, tensor::Tensor, test::assert_close
+    };
+
+    fn sine(n: usize, cycles: f32) -> Tensor {
+        Tensor::from((0..n).map(|i| (2. * PI * cycles * i as f32 / n as f32).sin()).collect::<Vec<f32>>())
+    }
+
+    #[test]
+    fn resample_fft() {
+        // a band-limited periodic signal is resampled exactly
+        assert_close(&resample(sine(16, 2.), 32, ()), &sine(32, 2.), 1e-5);
+        assert_close(&resample(sine(32, 2.), 12, ()), &sine(12, 2.), 1e-5);
+        assert_close(&resample(sine(15, 3.), 25, ()), &sine(25, 3.), 1e-5);
+
+        assert_close(&resample(Tensor::fill([5], 2.), 8, ()), &Tensor::fill([8], 2.), 1e-6);
+    }
+
+    #[test]
+    fn resample_fft_nyquist() {
+        // [1, -1, ...] is all Nyquist and splits across the positive and
+        // negative bins when upsampling
+        let y = resample(ten![1., -1., 1., -1.], 8, ());
+
+        assert_close(&y, &ten![1., 0., -1., 0., 1., 0., -1., 0.], 1e-6);
+    }
+
+    #[test]
+    fn resample_poly_rates() {
+        let x = sine(200, 4.);
+
+        let y = resample_poly(&x, 3, 2, ());
+        assert_eq!(y.shape().as_vec(), &[300]);
+
+        // edges have a filter transient, but the middle matches
+        let expected = sine(300, 4.);
+        for i in 60..240 {
+            assert!((y[i] - expected[i]).abs() < 1e-2, "{} {} {}", i, y[i], expected[i]);
+        }
+
+        let y = resample_poly(&x, 2, 4, ());
+        assert_eq!(y.shape().as_vec(), &[100]);
+
+        let expected = sine(100, 4.);
+        for i in 20..80 {
+            assert!((y[i] - expected[i]).abs() < 1e-2);
+        }
+
+        assert_eq!(resample_poly(&x, 3, 3, ()), x);
+        assert_eq!(resample_poly(ten![1., 2., 3.], 1, 2, ()).shape().as_vec(), &[2]);
+    }
+
+    #[test]
+    fn resample_axis() {
+        // [samples, channels] as decoded from a wav file
+        let x = stack_axis(-1, [sine(16, 2.), sine(16, 2.).map(|v| 2. * v)]);
+
+        let y = resample(&x, 32, ().axis(0));
+        assert_eq!(y.shape().as_vec(), &[32, 2]);
+        assert_close(&y.transpose().slice(0), &sine(32, 2.), 1e-5);
+        assert_close(&y.transpose().slice(1), &sine(32, 2.).map(|v| 2. * v), 1e-5);
+
+        let y = resample_poly(&x, 2, 1, ().axis(0));
+        assert_eq!(y.shape().as_vec(), &[32, 2]);
+        assert_close(&y.transpose().slice(1), &y.transpose().slice(0).map(|v| 2. * v), 1e-5);
+    }
+    #[test]
+    #[should_panic(expected = "resample requires a non-empty axis")]
+    fn resample_empty() {
+        resample(Tensor::<f32>::zeros([2, 0]), 4, ());
+    }
+}
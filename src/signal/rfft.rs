@@ -1,23 +1,28 @@
-use std::{cmp, f32::consts::PI};
+use std::cmp;
 
-use essay_opt::derive_opt;
-use rustfft::{FftPlanner, num_complex::Complex};
+use rustfft::{FftDirection, num_complex::Complex};
 
 use crate::tensor::{Tensor, unsafe_init};
 
+use super::{fft::{plan_fft, FftOpt}, window::Window};
+
+///
+/// Magnitude of the windowed real FFT over the last axis. Only the
+/// `fft_length` and `window` options are used, and the window defaults
+/// to a periodic Hann.
+///
 pub fn rfft_norm(tensor: impl Into<Tensor>, opt: impl FftOpt) -> Tensor {
-    let tensor = tensor.into();
+    let tensor = tensor.into().to_contiguous();
     let opt = opt.into_arg();
     let len = tensor.cols();
     let batch = tensor.len() / len;
 
-    let mut planner = FftPlanner::<f32>::new();
-    let fft_fwd = planner.plan_fft_forward(len);
+    let fft_fwd = plan_fft(len, FftDirection::Forward);
 
     let mut buffer = Vec::<Complex<f32>>::new();
     buffer.resize(len, Complex { re: 0., im: 0. });
 
-    let window = hann_window(len);
+    let window = opt.window.as_ref().unwrap_or(&Window::Hann).to_tensor::<f32>(len, ());
 
     let fft_out = (len / 2) + 1;
     let len_out = match opt.fft_length {
@@ -53,25 +58,9 @@ pub fn rfft_norm(tensor: impl Into<Tensor>, opt: impl FftOpt) -> Tensor {
     }
 }
 
-fn hann_window(len: usize) -> Tensor {
-    let step : f32 = PI / len as f32;
-    
-    Tensor::init_rindexed([len], |idx| {
-        let tmp = (step * idx[0] as f32).sin();
-        tmp * tmp
-    })
-}
-
-#[derive_opt(FftOpt)]
-#[derive(Default)]
-pub struct FftArg {
-    fft_length: Option<usize>,
-}
-
 #[cfg(test)]
 mod test {
-    use crate::{signal::rfft_norm, ten};
-    use super::FftOpt;
+    use crate::{signal::{rfft_norm, FftOpt}, ten};
 
     #[test]
     fn test_fft_norm() {
@@ -0,0 +1,159 @@
+use num_complex::Complex;
+
+use crate::tensor::Tensor;
+
+use super::{fft::{irfft, rfft, FftOpt}, frame::frame, window::Window};
+
+///
+/// Short-time Fourier transform over the last axis. Frames of
+/// frame_length samples every frame_step samples are multiplied by the
+/// window, which is either a periodic window or a custom window tensor,
+/// and transformed by a real FFT of fft_length, returning
+/// [.., n_frames, fft_length / 2 + 1].
+///
+pub fn stft(
+    x: impl Into<Tensor>,
+    frame_length: usize,
+    frame_step: usize,
+    fft_length: usize,
+    window: impl Into<Window>,
+) -> Tensor<Complex<f32>> {
+    let window = window.into().to_tensor::<f32>(frame_length, ());
+
+    let frames = frame(x, frame_length, frame_step, ());
+
+    // a signal shorter than frame_length has no frames
+    if frames.size() == 0 {
+        let mut dims = frames.shape().as_vec();
+        *dims.last_mut().unwrap() = fft_length / 2 + 1;
+
+        return Tensor::from_vec(vec![], dims.as_slice());
+    }
+
+    rfft(&(&frames * &window), ().fft_length(fft_length))
+}
+
+///
+/// Inverse of `stft` by weighted overlap-add. Each inverse frame is
+/// multiplied by the window, and the sum is divided by the overlapping
+/// squared window, so samples where the windows sum to zero are zero.
+/// Returns [.., (n_frames - 1) * frame_step + frame_length], or an empty
+/// [.., 0] signal for zero frames.
+///
+pub fn istft(
+    x: &Tensor<Complex<f32>>,
+    frame_length: usize,
+    frame_step: usize,
+    fft_length: usize,
+    window: impl Into<Window>,
+) -> Tensor {
+    assert!(x.rank() >= 2, "istft requires [.., n_frames, fft_length / 2 + 1]");
+    assert!(frame_length <= fft_length, "istft frame_length must be <= fft_length");
+
+    let mut dims = x.shape().as_vec();
+    let n_frames = dims[dims.len() - 2];
+    dims.pop();
+
+    if n_frames == 0 {
+        *dims.last_mut().unwrap() = 0;
+
+        return Tensor::from_vec(vec![], dims.as_slice());
+    }
+
+    let frames = irfft(x, ().fft_length(fft_length)).to_contiguous();
+
+    let len = (n_frames - 1) * frame_step + frame_length;
+    *dims.last_mut().unwrap() = len;
+
+    let window = window.into().to_tensor::<f32>(frame_length, ());
+    let window = window.as_slice();
+
+    let mut norm = vec![0f32; len];
+    for f in 0..n_frames {
+        for (j, w) in window.iter().enumerate() {
+            norm[f * frame_step + j] += w * w;
+        }
+    }
+
+    let n_batch = frames.size() / (n_frames * fft_length).max(1);
+    let mut vec = vec![0f32; n_batch * len];
+
+    for (frames, out) in frames.as_slice().chunks_exact(n_frames * fft_length).zip(vec.chunks_exact_mut(len)) {
+        for (f, frame) in frames.chunks_exact(fft_length).enumerate() {
+            for (j, w) in window.iter().enumerate() {
+                out[f * frame_step + j] += w * frame[j];
+            }
+        }
+
+        for (y, norm) in out.iter_mut().zip(&norm) {
+            if *norm > f32::EPSILON {
+                *y /= norm;
+            }
+        }
+    }
+
+    Tensor::from_vec(vec, dims.as_slice())
+}
+
+#[cfg(test)]
+mod test {
+    use num_complex::Complex;
+
+    use crate::{signal::{istft, stft, Window}, ten, tensor::Tensor};
+
+    #[test]
+    fn stft_shape_and_values() {
+        let x = ten![1., 0., 0., 0., 1., 0., 0., 0.];
+        let y = stft(&x, 4, 4, 4, &ten![1., 1., 1., 1.]);
+
+        assert_eq!(y.shape().as_vec(), &[2, 3]);
+        assert_eq!(y.norm(), ten![[1., 1., 1.], [1., 1., 1.]]);
+
+        let y = stft(&x, 4, 2, 8, Window::Hann);
+        assert_eq!(y.shape().as_vec(), &[3, 5]);
+    }
+
+    #[test]
+    fn stft_batch() {
+        let x = ten![[1., 2., 3., 4.], [0., 1., 0., 1.]];
+        let y = stft(&x, 2, 2, 2, &ten![1., 1.]);
+
+        assert_eq!(y.shape().as_vec(), &[2, 2, 2]);
+        assert_eq!(y.re(), ten![[[3., -1.], [7., -1.]], [[1., -1.], [1., -1.]]]);
+    }
+
+    #[test]
+    fn istft_round_trip() {
+        let x = Tensor::from_vec((0..32).map(|i| (i as f32 * 0.7).sin()).collect(), [32]);
+
+        let ones = ten![1., 1., 1., 1., 1., 1., 1., 1.];
+        let y = istft(&stft(&x, 8, 8, 8, &ones), 8, 8, 8, &ones);
+        assert!((&y - &x).abs().reduce_max()[0] < 1e-5);
+
+        // hann is zero at the first sample, so only the interior round trips
+        let y = istft(&stft(&x, 8, 2, 16, Window::Hann), 8, 2, 16, Window::Hann);
+
+        assert_eq!(y.shape().as_vec(), &[32]);
+        for i in 1..32 {
+            assert!((y[i] - x[i]).abs() < 1e-5, "{}: {} != {}", i, y[i], x[i]);
+        }
+    }
+
+    #[test]
+    fn stft_no_frames() {
+        let x = Tensor::<f32>::zeros([100]);
+        assert_eq!(stft(&x, 256, 128, 512, Window::Hann).shape().as_vec(), &[0, 257]);
+
+        let x = Tensor::<f32>::zeros([2, 3]);
+        assert_eq!(stft(&x, 4, 2, 4, Window::Hann).shape().as_vec(), &[2, 0, 3]);
+    }
+
+    #[test]
+    fn istft_no_frames() {
+        let x = Tensor::<Complex<f32>>::zeros([0, 5]);
+        assert_eq!(istft(&x, 8, 2, 8, Window::Hann).shape().as_vec(), &[0]);
+
+        let x = Tensor::<Complex<f32>>::zeros([3, 0, 5]);
+        assert_eq!(istft(&x, 8, 2, 8, Window::Hann).shape().as_vec(), &[3, 0]);
+    }
+}
@@ -0,0 +1,287 @@
+//!
+//! Window functions for spectral analysis.
+//!
+//! Each window has a periodic variant for FFT frames, the default, and a
+//! symmetric variant for filter design with the `symmetric` option. A
+//! periodic window of len items is the symmetric window of len + 1 items
+//! without its last item.
+//!
+
+use std::f64::consts::PI;
+
+use essay_opt::derive_opt;
+use num_traits::Float;
+
+use crate::tensor::{Tensor, Type};
+
+///
+/// A window choice for options like `stft` and `rfft_norm`, or a custom
+/// window tensor.
+///
+#[derive(Clone, Debug)]
+pub enum Window {
+    Hann,
+    Hamming,
+    Blackman,
+    BlackmanHarris,
+    FlatTop,
+    Bartlett,
+    /// Kaiser window with the shape parameter beta
+    Kaiser(f64),
+    /// Tukey window with the taper fraction alpha
+    Tukey(f64),
+    /// Gaussian window with the standard deviation in samples
+    Gaussian(f64),
+    /// A custom window, which must have the requested length
+    Tensor(Tensor),
+}
+
+impl Window {
+    /// The window as a tensor of len items.
+    pub fn to_tensor<T: Type + Float>(&self, len: usize, opt: impl WindowOpt) -> Tensor<T> {
+        let opt = opt.into_arg();
+
+        match self {
+            Window::Hann => hann(len, opt),
+            Window::Hamming => hamming(len, opt),
+            Window::Blackman => blackman(len, opt),
+            Window::BlackmanHarris => blackman_harris(len, opt),
+            Window::FlatTop => flat_top(len, opt),
+            Window::Bartlett => bartlett(len, opt),
+            Window::Kaiser(beta) => kaiser(len, *beta, opt),
+            Window::Tukey(alpha) => tukey(len, *alpha, opt),
+            Window::Gaussian(std) => gaussian(len, *std, opt),
+            Window::Tensor(tensor) => {
+                assert_eq!(
+                    tensor.shape().as_vec(), &[len], 
+                    "window tensor must have shape [{}]", len
+                );
+
+                tensor.map(|v| T::from(*v).unwrap())
+            }
+        }
+    }
+}
+
+impl From<Tensor> for Window {
+    fn from(tensor: Tensor) -> Self {
+        Window::Tensor(tensor)
+    }
+}
+
+impl From<&Tensor> for Window {
+    fn from(tensor: &Tensor) -> Self {
+        Window::Tensor(tensor.clone())
+    }
+}
+
+#[derive_opt(WindowOpt)]
+#[derive(Default)]
+// the generated setter's ..self update is needless for the one option
+#[allow(clippy::needless_update)]
+pub struct WindowArg {
+    symmetric: bool,
+}
+
+pub fn hann<T: Type + Float>(len: usize, opt: impl WindowOpt) -> Tensor<T> {
+    cosine_sum(len, &[0.5, 0.5], opt)
+}
+
+pub fn hamming<T: Type + Float>(len: usize, opt: impl WindowOpt) -> Tensor<T> {
+    cosine_sum(len, &[0.54, 0.46], opt)
+}
+
+pub fn blackman<T: Type + Float>(len: usize, opt: impl WindowOpt) -> Tensor<T> {
+    cosine_sum(len, &[0.42, 0.5, 0.08], opt)
+}
+
+/// Four-term Blackman-Harris window with -92 dB sidelobes.
+pub fn blackman_harris<T: Type + Float>(len: usize, opt: impl WindowOpt) -> Tensor<T> {
+    cosine_sum(len, &[0.35875, 0.48829, 0.14128, 0.01168], opt)
+}
+
+/// Flat-top window for amplitude-accurate measurements.
+pub fn flat_top<T: Type + Float>(len: usize, opt: impl WindowOpt) -> Tensor<T> {
+    cosine_sum(len, &[0.21557895, 0.41663158, 0.277263158, 0.083578947, 0.006947368], opt)
+}
+
+/// Triangular window that is zero at both ends of the symmetric variant.
+pub fn bartlett<T: Type + Float>(len: usize, opt: impl WindowOpt) -> Tensor<T> {
+    window_fn(len, opt, |n, m| 1. - (2. * n / m - 1.).abs())
+}
+
+///
+/// Kaiser window, where a larger beta trades a wider main lobe for lower
+/// sidelobes. beta = 0 is rectangular and beta near 8.6 is like Blackman.
+///
+pub fn kaiser<T: Type + Float>(len: usize, beta: f64, opt: impl WindowOpt) -> Tensor<T> {
+    let i0_beta = bessel_i0(beta);
+
+    window_fn(len, opt, |n, m| {
+        let r = 2. * n / m - 1.;
+
+        bessel_i0(beta * (1. - r * r).max(0.).sqrt()) / i0_beta
+    })
+}
+
+///
+/// Tukey window, a rectangle with cosine tapers over the alpha fraction of
+/// the window. alpha = 0 is rectangular and alpha = 1 is Hann.
+///
+pub fn tukey<T: Type + Float>(len: usize, alpha: f64, opt: impl WindowOpt) -> Tensor<T> {
+    if alpha <= 0. {
+        return window_fn(len, opt, |_, _| 1.);
+    } else if alpha >= 1. {
+        return hann(len, opt);
+    }
+
+    window_fn(len, opt, |n, m| {
+        let width = alpha * m / 2.;
+
+        if n < width {
+            0.5 * (1. + (PI * (n / width - 1.)).cos())
+        } else if n > m - width {
+            0.5 * (1. + (PI * ((n - m) / width + 1.)).cos())
+        } else {
+            1.
+        }
+    })
+}
+
+/// Gaussian window with the standard deviation std in samples.
+pub fn gaussian<T: Type + Float>(len: usize, std: f64, opt: impl WindowOpt) -> Tensor<T> {
+    assert!(std > 0., "gaussian window std must be > 0");
+
+    window_fn(len, opt, |n, m| {
+        let x = (n - m / 2.) / std;
+
+        (-0.5 * x * x).exp()
+    })
+}
+
+// sum of a[k] (-1)^k cos(2 pi k n / m)
+fn cosine_sum<T: Type + Float>(len: usize, a: &[f64], opt: impl WindowOpt) -> Tensor<T> {
+    window_fn(len, opt, |n, m| {
+        a.iter().enumerate().fold(0., |w, (k, a)| {
+            let sign = if k % 2 == 0 { 1. } else { -1. };
+
+            w + sign * a * (2. * PI * k as f64 * n / m).cos()
+        })
+    })
+}
+
+// Evaluates f(n, m) for n in 0..len, where m is len - 1 for a symmetric
+// window and len for a periodic one
+fn window_fn<T: Type + Float>(
+    len: usize, 
+    opt: impl WindowOpt, 
+    f: impl Fn(f64, f64) -> f64
+) -> Tensor<T> {
+    let opt = opt.into_arg();
+
+    if len == 0 {
+        return Tensor::from_vec(vec![], [0]);
+    } else if len == 1 {
+        return Tensor::from_vec(vec![T::one()], [1]);
+    }
+
+    let m = if opt.symmetric { len - 1 } else { len } as f64;
+
+    let vec: Vec<T> = (0..len)
+        .map(|n| T::from(f(n as f64, m)).unwrap())
+        .collect();
+
+    Tensor::from_vec(vec, [len].as_slice())
+}
+
+// Modified Bessel function of the first kind, order 0, by its power series
+fn bessel_i0(x: f64) -> f64 {
+    let half_x = x / 2.;
+
+    let mut sum = 1.;
+    let mut term = 1.;
+
+    for k in 1..500 {
+        term *= half_x / k as f64;
+        let term2 = term * term;
+        sum += term2;
+
+        if term2 < sum * 1e-17 {
+            break;
+        }
+    }
+
+    sum
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{signal::window::{self, Window, WindowOpt}, ten, test::assert_close};
+
+    #[test]
+    fn hann_periodic_and_symmetric() {
+        assert_close(&window::hann(4, ()), [0., 0.5, 1., 0.5], 1e-8);
+        assert_close(&window::hann(5, ().symmetric(true)), [0., 0.5, 1., 0.5, 0.], 1e-8);
+        assert_eq!(window::hann::<f32>(4, ()), ten![0., 0.5, 1., 0.5]);
+        assert_eq!(window::hann::<f32>(1, ()), ten![1.]);
+    }
+
+    #[test]
+    fn cosine_windows() {
+        assert_close(&window::hamming(3, ().symmetric(true)), [0.08, 1., 0.08], 1e-8);
+        assert_close(&window::blackman(3, ().symmetric(true)), [0., 1., 0.], 1e-8);
+        assert_close(&window::blackman_harris(3, ().symmetric(true)), [6e-5, 1., 6e-5], 1e-8);
+
+        let w = window::flat_top::<f64>(5, ().symmetric(true));
+        assert!((w[2] - 1.).abs() < 1e-8);
+        assert!(w[0] < 0.);
+    }
+
+    #[test]
+    fn bartlett_window() {
+        assert_close(&window::bartlett(5, ().symmetric(true)), [0., 0.5, 1., 0.5, 0.], 1e-8);
+        assert_close(&window::bartlett(4, ()), [0., 0.5, 1., 0.5], 1e-8);
+    }
+
+    #[test]
+    fn kaiser_window() {
+        assert_close(&window::kaiser(4, 0., ()), [1., 1., 1., 1.], 1e-8);
+
+        // I0(5 sqrt(1 - r^2)) / I0(5)
+        assert_close(
+            &window::kaiser(5, 5., ().symmetric(true)), 
+            [0.036710892, 0.552851770, 1., 0.552851770, 0.036710892], 1e-8
+        );
+    }
+
+    #[test]
+    fn tukey_window() {
+        assert_close(&window::tukey(4, 0., ()), [1., 1., 1., 1.], 1e-8);
+        assert_close(&window::tukey(4, 1., ()), [0., 0.5, 1., 0.5], 1e-8);
+        assert_close(&window::tukey(5, 0.5, ().symmetric(true)), [0., 1., 1., 1., 0.], 1e-8);
+    }
+
+    #[test]
+    fn gaussian_window() {
+        let w = window::gaussian::<f64>(5, 1., ().symmetric(true));
+
+        assert_close(&w, [(-2f64).exp(), (-0.5f64).exp(), 1., (-0.5f64).exp(), (-2f64).exp()], 1e-8);
+    }
+
+    #[test]
+    fn window_empty() {
+        assert_eq!(window::hann::<f32>(0, ().symmetric(true)).shape().as_vec(), &[0]);
+        assert_eq!(window::kaiser::<f64>(0, 5., ()).shape().as_vec(), &[0]);
+        assert_eq!(window::hann::<f32>(1, ().symmetric(true)), ten![1.]);
+    }
+
+    #[test]
+    fn window_enum() {
+        assert_eq!(Window::Hann.to_tensor::<f32>(4, ()), window::hann::<f32>(4, ()));
+        assert_eq!(
+            Window::Kaiser(3.).to_tensor::<f64>(6, ().symmetric(true)), 
+            window::kaiser(6, 3., ().symmetric(true))
+        );
+        assert_eq!(Window::from(ten![1., 2.]).to_tensor::<f64>(2, ()), ten![1f64, 2.]);
+    }
+}
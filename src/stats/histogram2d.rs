@@ -1,7 +1,7 @@
 use crate::{tensor::Tensor, init::linspace};
 
 pub fn histogram2d(data: impl Into<Tensor>, args: impl Into<Hist2Args>) -> (Tensor, Tensor, Tensor) {
-    let data : Tensor = data.into();
+    let data : Tensor = data.into().to_contiguous();
     assert!(data.rank() == 2, "histogram2d requires a rank 2 tensor {:?}", data.shape().as_vec());
     assert!(data.cols() == 2, "histogram2d requires 2D tensor {:?}", data.shape().as_vec());
 
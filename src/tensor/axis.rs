@@ -1,4 +1,4 @@
-use crate::tensor::Shape;
+use crate::tensor::{Shape, TensorError};
 
 #[derive(Default)]
 pub struct Axis {
@@ -25,6 +25,24 @@ impl Axis {
         }
     }
     
+    ///
+    /// Like `axis_from_rank`, but returns an error instead of wrapping when
+    /// the axis is outside -rank..rank.
+    ///
+    pub fn try_axis_from_rank(
+        &self, 
+        op: &'static str, 
+        rank: usize
+    ) -> Result<usize, TensorError> {
+        match self.axis {
+            Some(axis) if -(rank as isize) <= axis && axis < rank as isize => {
+                Ok((axis + rank as isize) as usize % rank)
+            }
+            Some(axis) => Err(TensorError::AxisOutOfRange { op, axis, rank }),
+            None => Ok(0),
+        }
+    }
+
     pub(crate) fn _axis_with_shape(&self, shape: &Shape) -> usize {
         match self.axis {
             Some(axis) => {
@@ -1,6 +1,6 @@
 use core::slice;
 use std::{
-    alloc::{self, Layout}, mem, ptr::NonNull
+    alloc::{self, Layout}, any::Any, mem, ptr::NonNull, sync::Arc
 };
 
 use crate::tensor::Tensor;
@@ -10,12 +10,15 @@ use super::{Shape, Type};
 pub(super) struct TensorData<T: Type> {
     data: NonNull<T>,
     len: usize,
+    /// Owner of borrowed data like a memory map, or None when the data is
+    /// an owned boxed slice
+    owner: Option<Arc<dyn Any + Send + Sync>>,
 }
 
 impl<T: Type> TensorData<T> {
     #[inline]
     fn new(len: usize, data: NonNull<T>, shape: Shape) -> Tensor<T> {
-        Tensor::new(Self { len, data }, shape)
+        Tensor::new(Self { len, data, owner: None }, shape)
     }
 
     #[inline]
@@ -58,10 +61,22 @@ impl<T: Type> TensorData<T> {
     pub(super) unsafe fn as_ptr(&self) -> *const T {
         self.data.as_ptr()
     }
+
+    /// Mutable access is only safe through a unique `&mut` from 
+    /// `Arc::get_mut`, which `Tensor::make_mut` guarantees.
+    #[inline(always)]
+    pub(super) unsafe fn as_mut_ptr(&mut self) -> *mut T {
+        self.data.as_ptr()
+    }
 }
 
 impl<T: Type> Drop for TensorData<T> {
     fn drop(&mut self) {
+        // borrowed data is released when the owner drops
+        if self.owner.is_some() {
+            return;
+        }
+
         unsafe {
             let len = self.len;
             let slice = slice::from_raw_parts_mut(self.data.as_ptr(), len); 
@@ -70,7 +85,8 @@ impl<T: Type> Drop for TensorData<T> {
     }
 }
 
-// unsafe: TensorData is read-only
+// unsafe: TensorData is read-only while shared, and only mutated through
+// a unique Arc
 unsafe impl<T: Type + Send> Send for TensorData<T> {}
 unsafe impl<T: Type + Sync> Sync for TensorData<T> {}
 
@@ -96,3 +112,18 @@ pub unsafe fn unsafe_init<T: Type>(
 
     TensorData::<T>::new(len, *data, shape)
 }
+
+/// Creates a tensor over data borrowed from the owner, like a memory-mapped
+/// file, without copying. The owner is kept alive with the tensor's data.
+///
+/// This is unsafe because the caller must guarantee that data points to
+/// len aligned and valid items that live as long as the owner, and that
+/// only the tensor mutates them.
+pub(crate) unsafe fn unsafe_from_owner<T: Type>(
+    data: NonNull<T>,
+    len: usize,
+    shape: impl Into<Shape>,
+    owner: Arc<dyn Any + Send + Sync>,
+) -> Tensor<T> {
+    Tensor::new(TensorData { data, len, owner: Some(owner) }, shape)
+}
@@ -0,0 +1,93 @@
+use std::{error, fmt};
+
+///
+/// Errors from the fallible `try_` operations. The panicking versions of
+/// the operations panic with the same message.
+///
+#[derive(Clone, Debug, PartialEq)]
+pub enum TensorError {
+    /// Two shapes are incompatible for the operation.
+    ShapeMismatch {
+        op: &'static str,
+        a: Vec<usize>,
+        b: Vec<usize>,
+    },
+
+    /// A single shape is invalid for the operation, like a rank 1 matmul.
+    InvalidShape {
+        op: &'static str,
+        shape: Vec<usize>,
+        reason: &'static str,
+    },
+
+    /// The axis is outside of -rank..rank.
+    AxisOutOfRange {
+        op: &'static str,
+        axis: isize,
+        rank: usize,
+    },
+
+    /// An argument other than a shape or axis is invalid.
+    InvalidArgument {
+        op: &'static str,
+        reason: String,
+    },
+
+    /// The item type doesn't match the expected type.
+    DType {
+        op: &'static str,
+        expected: String,
+        found: String,
+    },
+
+    /// A numerical failure, like solving with a singular matrix.
+    LinAlg {
+        op: &'static str,
+        reason: &'static str,
+    },
+
+    /// Encoded data like a WAV file is malformed or unsupported.
+    Decode {
+        op: &'static str,
+        reason: String,
+    },
+
+    /// Reading or writing a file failed.
+    Io {
+        op: &'static str,
+        reason: String,
+    },
+}
+
+impl fmt::Display for TensorError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TensorError::ShapeMismatch { op, a, b } => {
+                write!(f, "{} shapes do not match {:?} {:?}", op, a, b)
+            }
+            TensorError::InvalidShape { op, shape, reason } => {
+                write!(f, "{} {} {:?}", op, reason, shape)
+            }
+            TensorError::AxisOutOfRange { op, axis, rank } => {
+                write!(f, "{} axis {} is out of range for rank {}", op, axis, rank)
+            }
+            TensorError::InvalidArgument { op, reason } => {
+                write!(f, "{} {}", op, reason)
+            }
+            TensorError::DType { op, expected, found } => {
+                write!(f, "{} expected type {} but found {}", op, expected, found)
+            }
+            TensorError::LinAlg { op, reason } => {
+                write!(f, "{} {}", op, reason)
+            }
+            TensorError::Decode { op, reason } => {
+                write!(f, "{} {}", op, reason)
+            }
+            TensorError::Io { op, reason } => {
+                write!(f, "{} {}", op, reason)
+            }
+        }
+    }
+}
+
+impl error::Error for TensorError {}
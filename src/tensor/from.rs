@@ -319,7 +319,7 @@ impl<T: Type + Copy + 'static> From<&[Tensor<T>]> for Tensor<T> {
         }
 
         let data : Vec<T> = values.iter().flat_map(|tensor|
-            tensor.as_slice().iter().map(|v| v.clone())
+            tensor.iter().copied()
         ).collect();
 
         Tensor::from_vec(data, shape.push(n)) 
@@ -1,9 +1,8 @@
-use core::fmt;
-use std::ops::Index;
+use std::ops::{Index, IndexMut};
 
 use crate::tensor::Tensor;
 
-use super::Type;
+use super::{Shape, Type};
 
 
 impl<T: Type> Index<usize> for Tensor<T> {
@@ -12,109 +11,65 @@ impl<T: Type> Index<usize> for Tensor<T> {
     fn index(&self, index: usize) -> &Self::Output {
         assert!(index < self.size(), "tensor[{}] is larger than size={}", index, self.size);
 
-        unsafe {
-            self.as_ptr().add(index).as_ref().unwrap()
-        }
+        self.get(index).unwrap()
     }
 }
 
-impl<T: Type + fmt::Debug> Index<(usize, usize)> for Tensor<T> {
-    type Output = T;
-
-    fn index(&self, index: (usize, usize)) -> &Self::Output {
-        let shape = self.shape();
-        let len = shape.rank();
-
-        assert!(len > 1);
-        assert!(index.0 < shape.rdim(1), "Index={:?} is invalid for {:?}", index, self);
-        assert!(index.1 < shape.rdim(0), "Index={:?} is invalid for {:?}", index, self);
-
-        let offset = index.1 + shape.rdim(0) * index.0;
-
-        assert!(offset < self.size());
+impl<T: Type + Clone> IndexMut<usize> for Tensor<T> {
+    fn index_mut(&mut self, index: usize) -> &mut Self::Output {
+        assert!(index < self.size(), "tensor[{}] is larger than size={}", index, self.size);
 
-        &self[offset]
+        &mut self.as_mut_slice()[index]
     }
 }
 
-impl<T: Type> Index<(usize, usize, usize)> for Tensor<T> {
-    type Output = T;
+macro_rules! index_tuple {
+    ($ty:ty, $($id:ident),*) => {
+        impl<T: Type> Index<$ty> for Tensor<T> {
+            type Output = T;
 
-    fn index(&self, index: (usize, usize, usize)) -> &Self::Output {
-        let shape = self.shape();
-        let len = shape.size();
+            fn index(&self, index: $ty) -> &Self::Output {
+                let ($($id,)*) = index;
 
-        assert!(len > 2);
-        assert!(index.0 < shape.rdim(2));
-        assert!(index.1 < shape.rdim(1));
-        assert!(index.2 < shape.rdim(0));
-
-        let offset =
-            index.2 + shape.rdim(0) * (
-                index.1 + shape.rdim(1) * index.0
-            );
+                &self[tuple_offset(self.shape(), &[$($id),*])]
+            }
+        }
 
-        // assert!(offset < self.len());
+        impl<T: Type + Clone> IndexMut<$ty> for Tensor<T> {
+            fn index_mut(&mut self, index: $ty) -> &mut Self::Output {
+                let ($($id,)*) = index;
+                let offset = tuple_offset(self.shape(), &[$($id),*]);
 
-        &self[offset]
+                &mut self.as_mut_slice()[offset]
+            }
+        }
     }
 }
 
-impl<T: Type> Index<(usize, usize, usize, usize)> for Tensor<T> {
-    type Output = T;
+index_tuple!((usize, usize), i, j);
+index_tuple!((usize, usize, usize), i, j, k);
+index_tuple!((usize, usize, usize, usize), i, j, k, l);
+index_tuple!((usize, usize, usize, usize, usize), i, j, k, l, m);
 
-    fn index(&self, index: (usize, usize, usize, usize)) -> &Self::Output {
-        let shape = self.shape();
-        let len = shape.size();
+// Row-major offset of a tuple index into the innermost dims, so a pair
+// indexes the first matrix of a rank 3 tensor.
+fn tuple_offset(shape: &Shape, index: &[usize]) -> usize {
+    let n = index.len();
 
-        assert!(len > 3);
-        assert!(index.0 < shape.rdim(3));
-        assert!(index.1 < shape.rdim(2));
-        assert!(index.2 < shape.rdim(1));
-        assert!(index.3 < shape.rdim(0));
+    assert!(n <= shape.rank(), "Index={:?} is invalid for {:?}", index, shape.as_vec());
 
-        let offset =
-            index.3 + shape.rdim(0) * (
-                index.2 + shape.rdim(1) * (
-                    index.1 + shape.rdim(2) * index.0
-                )
-            );
+    let mut offset = 0;
 
-        // assert!(offset < self.len());
+    for (i, value) in index.iter().enumerate() {
+        let dim = shape.rdim(n - 1 - i);
 
-        &self[offset]
-    }
-}
+        assert!(*value < dim, "Index={:?} is invalid for {:?}", index, shape.as_vec());
 
-impl<T: Type> Index<(usize, usize, usize, usize, usize)> for Tensor<T> {
-    type Output = T;
-
-    fn index(&self, index: (usize, usize, usize, usize, usize)) -> &Self::Output {
-        let shape = self.shape();
-        let len = shape.rank();
-
-        assert!(len > 4);
-        assert!(index.0 < shape.rdim(4));
-        assert!(index.1 < shape.rdim(3));
-        assert!(index.2 < shape.rdim(2));
-        assert!(index.3 < shape.rdim(1));
-        assert!(index.4 < shape.rdim(0));
-
-        let offset =
-            index.4 + shape.rdim(0) * (
-                index.3 + shape.rdim(1) * (
-                    index.2 + shape.rdim(2) * (
-                        index.1 + shape.rdim(3) * index.0
-                    )
-                )
-            );
-
-        // assert!(offset < self.len());
-
-        &self[offset]
+        offset = offset * dim + value;
     }
-}
 
+    offset
+}
 
 #[cfg(test)]
 mod test {
@@ -158,4 +113,46 @@ mod test {
         assert_eq!(t.slice(2)[0], 3.);
         assert_eq!(t.slice(3)[0], 4.);
     }
+
+    #[test]
+    fn index_rank_3() {
+        let t = ten![[[1, 2], [3, 4]], [[5, 6], [7, 8]]];
+
+        assert_eq!(t[(0, 1, 0)], 3);
+        assert_eq!(t[(1, 0, 1)], 6);
+        assert_eq!(t[(1, 1, 1)], 8);
+    }
+
+    #[test]
+    #[should_panic]
+    fn index_rank_2_out_of_bounds() {
+        let t = ten![[1, 2], [3, 4]];
+
+        t[(0, 2)];
+    }
+
+    #[test]
+    fn index_mut() {
+        let mut t = ten![[1, 2], [3, 4]];
+        let t0 = t.clone();
+
+        t[1] = 20;
+        t[(1, 0)] = 30;
+
+        assert_eq!(t, ten![[1, 20], [30, 4]]);
+        assert_eq!(t0, ten![[1, 2], [3, 4]]);
+
+        let mut t = ten![[[1, 2], [3, 4]], [[5, 6], [7, 8]]];
+        t[(1, 1, 0)] = 70;
+        assert_eq!(t, ten![[[1, 2], [3, 4]], [[5, 6], [70, 8]]]);
+    }
+
+    #[test]
+    fn index_mut_view() {
+        let mut t = ten![[1, 2], [3, 4]].transpose();
+
+        t[(0, 1)] = 30;
+
+        assert_eq!(t, ten![[1, 30], [2, 4]]);
+    }
 }
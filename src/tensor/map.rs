@@ -2,7 +2,7 @@ use std::ptr;
 
 use crate::tensor::scalar;
 
-use super::{unsafe_init, Axis, Shape, Tensor, Type};
+use super::{unsafe_init, view::Items, Axis, Shape, Tensor, Type};
 
 impl<T: Type> Tensor<T> {
     pub fn init<F>(shape: impl Into<Shape>, f: F) -> Self
@@ -33,7 +33,10 @@ impl<T: Type> Tensor<T> {
     pub fn map_row<const N: usize, U: Type + Clone>(
         &self, 
         f: impl FnMut(&[T]) -> [U; N]
-    ) -> Tensor<U> {
+    ) -> Tensor<U>
+    where
+        T: Clone
+    {
         let shape = if N == 1 && self.shape().rank() > 1 {
             self.shape().clone().rremove(0)
         } else {
@@ -133,6 +136,7 @@ impl<T: Type> Tensor<T> {
 
     pub fn fold_row<const N: usize, S, F, V>(&self, axis: impl Into<Axis>, init: S, f: F) -> Tensor<V>
     where
+        T: Clone,
         S: Clone + FoldState<Out=[V; N]>,
         F: FnMut(S, &[T]) -> S,
         V: Type,
@@ -218,20 +222,21 @@ fn map<U: Type, V: Type>(
     
     unsafe {
         unsafe_init::<V>(len, shape, |o| {
-            let a = a.as_slice();
-        
-            for i in 0..len {
-                o.add(i).write((f)(&a[i]));
+            for (i, a) in a.iter().enumerate() {
+                o.add(i).write((f)(a));
             }
         })
     }
 }
 
-fn map_row<const N: usize, U: Type, V: Type + Clone>(
+fn map_row<const N: usize, U: Type + Clone, V: Type + Clone>(
     shape: Shape,
     a: &Tensor<U>, 
     mut f: impl FnMut(&[U]) -> [V; N]
 ) -> Tensor<V> {
+    // rows of a view like transpose() aren't contiguous in memory
+    let a = a.to_contiguous();
+
     let a_cols = a.cols();
     let len = a.size() / a_cols;
 
@@ -263,10 +268,8 @@ fn map_expand<const N: usize, U: Type, V: Type>(
 
     unsafe {
         unsafe_init::<V>(N * len, shape, |o| {
-            let a = a.as_slice();
-
-            for i in 0..len {
-                let value = (f)(&a[i]);
+            for (i, a) in a.iter().enumerate() {
+                let value = (f)(a);
 
                 for (j, value) in value.into_iter().enumerate() {
                     o.add(i * N + j).write(value);
@@ -299,14 +302,16 @@ where
     
     unsafe {
         unsafe_init::<V>(size, shape, |o| {
+            let a = Items::new(a);
+            let b = Items::new(b);
+
             for n in 0..batch {
                 let offset = n * inner;
 
-                let a = a.as_wrap_slice(offset);
-                let b = b.as_wrap_slice(offset);
-
                 for k in 0..inner {
-                    o.add(offset + k).write(f(&a[k], &b[k]));
+                    let i = offset + k;
+
+                    o.add(i).write(f(a.wrap(i), b.wrap(i)));
                 }
             }
         })
@@ -322,11 +327,14 @@ pub(super) fn _map2_row<const L: usize , const M: usize, const N: usize, T, U, V
     mut f: F
 ) -> Tensor<V>
 where
-    T: Type,
-    U: Type,
+    T: Type + Clone,
+    U: Type + Clone,
     V: Type,
     F: FnMut(&[T], &[U]) -> [V; N]
 {
+    let a = a.to_contiguous();
+    let b = b.to_contiguous();
+
     let a_size = a.size() / a_cols;
     assert!(a_size * a_cols == a.size());
     let b_size = b.size() / b_cols;
@@ -384,15 +392,17 @@ where
     
     unsafe {
         unsafe_init::<W>(size, shape, |o| {
+            let a = Items::new(a);
+            let b = Items::new(b);
+            let c = Items::new(c);
+
             for n in 0..batch {
                 let offset = n * inner;
 
-                let a = a.as_wrap_slice(offset);
-                let b = b.as_wrap_slice(offset);
-                let c = c.as_wrap_slice(offset);
-
                 for k in 0..inner {
-                    o.add(offset + k).write(f(&a[k], &b[k], &c[k]));
+                    let i = offset + k;
+
+                    o.add(i).write(f(a.wrap(i), b.wrap(i), c.wrap(i)));
                 }
             }
         })
@@ -410,14 +420,10 @@ where
     V: Type,
     F: FnMut(S, &U) -> S,
 {
-    let len = tensor.size();
-
-    let a = tensor.as_slice();
-
     let mut value = init.clone();
 
-    for i in 0..len {
-        value = (f)(value, &a[i]);
+    for v in tensor.iter() {
+        value = (f)(value, v);
     }
 
     Tensor::from(value.into_result())
@@ -441,14 +447,14 @@ where
 
     unsafe {
         unsafe_init::<V>(o_shape.size(), o_shape, |o| {
-            let a = tensor.as_slice();
+            let a = Items::new(tensor);
 
             for n in 0..batch {
                 for i in 0..inner {
                     let mut state = init.clone();
 
                     for k in 0..a_len {
-                        let v = &a[(n * a_len + k) * inner + i];
+                        let v = a.get((n * a_len + k) * inner + i);
 
                         state = (f)(state, v);
                     }
@@ -467,12 +473,13 @@ pub(super) fn fold_row<const N: usize, T, V, S, F>(
     mut f: F,
 ) -> Tensor<V> 
 where
-    T: Type,
+    T: Type + Clone,
     S: Clone + FoldState<Out=[V; N]>,
     F: FnMut(S, &[T]) -> S,
     V: Type,
 {
     let axis = axis.into();
+    let tensor = tensor.to_contiguous();
 
     let (o_shape, batch, a_len, inner) = axis.reduce_row(tensor.shape(), N);
     let cols = tensor.cols();
@@ -531,12 +538,12 @@ where
     T: Type + Clone,
     F: FnMut(T, T) -> T,
 {
-    let a = tensor.as_slice();
+    let mut iter = tensor.iter();
 
-    let mut value = a[0].clone();
+    let mut value = iter.next().unwrap().clone();
 
-    for i in 1..tensor.size() {
-        value = (f)(value, a[i].clone());
+    for v in iter {
+        value = (f)(value, v.clone());
     }
 
     scalar(value)
@@ -557,14 +564,14 @@ where
 
     unsafe {
         unsafe_init::<T>(o_shape.size(), o_shape, |o| {
-            let a = tensor.as_slice();
+            let a = Items::new(tensor);
 
             for n in 0..batch {
                 for i in 0..inner {
-                    let mut state = a[n * a_len * inner + i + 0 * inner].clone();
+                    let mut state = a.get(n * a_len * inner + i).clone();
 
                     for k in 1..a_len {
-                        let v = a[n * a_len * inner + i + k * inner].clone();
+                        let v = a.get(n * a_len * inner + i + k * inner).clone();
 
                         state = (f)(state, v);
                     }
@@ -596,20 +603,19 @@ where
     let axis = axis.into();
 
     let (_o_shape, batch, a_len, inner) = axis.reduce(tensor.shape());
-    // not axis.reduce, but normalize
 
     let shape = tensor.shape().clone();
     
     unsafe {
         unsafe_init::<V>(shape.size(), shape, |o| {
-            let a = tensor.as_slice();
+            let a = Items::new(tensor);
 
             for n in 0..batch {
                 for i in 0..inner {
                     let mut state = init.clone();
 
                     for k in 0..a_len {
-                        let v = &a[(n * a_len + k) * inner + i];
+                        let v = a.get((n * a_len + k) * inner + i);
 
                         state = (accum)(state, v);
                     }
@@ -617,9 +623,9 @@ where
                     let state = (complete)(state);
 
                     for k in 0..a_len {
-                        let v = &a[(n * a_len + k) * inner + i];
+                        let v = a.get((n * a_len + k) * inner + i);
 
-                        o.add((n * inner + i) * a_len + k)
+                        o.add((n * a_len + k) * inner + i)
                             .write((norm)(&state, v));
                     }
                 }
@@ -722,6 +728,24 @@ mod test {
         assert_eq!(v, ten![T2(10009), T2(11200)]);
     }
 
+    #[test]
+    fn normalize() {
+        let a = ten![[1., 2.], [3., 6.]];
+        let sum = |a: &Tensor, axis: isize| {
+            a.normalize(axis, 0., |s, v| s + v, |s| s, |s, v| v / s)
+        };
+
+        assert_eq!(sum(&a, -1), ten![[1. / 3., 2. / 3.], [1. / 3., 2. / 3.]]);
+        assert_eq!(sum(&a, 0), ten![[0.25, 0.25], [0.75, 0.75]]);
+
+        // an inner axis writes each item back to its own position
+        let a = ten![[[1., 2.], [3., 6.]], [[1., 1.], [3., 7.]]];
+        assert_eq!(sum(&a, 1), ten![
+            [[0.25, 0.25], [0.75, 0.75]],
+            [[0.25, 0.125], [0.75, 0.875]],
+        ]);
+    }
+
     #[derive(Clone, Debug)]
     struct S(usize);
 
@@ -3,17 +3,22 @@ mod map;
 // mod math;
 mod axis;
 mod data;
+mod error;
 mod index;
 mod slice;
 mod shape;
 mod tensor;
+mod tensor_mut;
+mod view;
 
 #[cfg(test)]
 mod test;
 
 pub use axis::Axis;
 
-pub(crate) use data::unsafe_init;
+pub use error::TensorError;
+
+pub(crate) use data::{unsafe_from_owner, unsafe_init};
 
 pub use from::IntoTensorList;
 
@@ -21,6 +26,12 @@ pub use map::FoldState;
 
 pub use shape::Shape;
 
+pub use slice::{
+    TensorSlice, SliceIndex, IntoSliceIndex, SliceStep, NewAxis, Ellipsis,
+};
+
 pub use tensor::{
-    Type, Tensor, scalar,
+    Type, Tensor, Iter, IterRow, scalar,
 };
+
+pub use tensor_mut::TensorMut;
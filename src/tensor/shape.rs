@@ -1,7 +1,7 @@
 use core::fmt;
 use std::cmp;
 
-use super::{Axis, Tensor, Type};
+use super::{view, Axis, Tensor, TensorError, Type};
 
 #[derive(Clone, PartialEq)]
 pub struct Shape {
@@ -180,19 +180,25 @@ impl Shape {
     }
 
     pub fn broadcast_to(&self, b: &Shape) -> Self {
+        self.try_broadcast_to(b).unwrap_or_else(|err| panic!("{}", err))
+    }
+
+    pub fn try_broadcast_to(&self, b: &Shape) -> Result<Self, TensorError> {
         let min_rank = cmp::min(self.rank(), b.rank());
         for i in 0..min_rank {
-            assert_eq!(
-                self.rdim(i), b.rdim(i), 
-                "broadcast ranks must match a={:?} b={:?}",
-                self.as_vec(), b.as_vec(),
-            );
+            if self.rdim(i) != b.rdim(i) {
+                return Err(TensorError::ShapeMismatch {
+                    op: "broadcast", 
+                    a: self.as_vec(), 
+                    b: b.as_vec(),
+                });
+            }
         }
 
         if self.rank() < b.rank() {
-            b.clone()
+            Ok(b.clone())
         } else {
-            self.clone()
+            Ok(self.clone())
         }
     }
 
@@ -520,17 +526,37 @@ impl<T: Type> Tensor<T> {
     }
 }
 
-impl<T: Type> Tensor<T> {
+impl<T: Type + Clone> Tensor<T> {
     #[inline]
     #[must_use]
     pub fn reshape(self, shape: impl Into<Shape>) -> Tensor<T> {
+        self.try_reshape(shape).unwrap_or_else(|err| panic!("{}", err))
+    }
+
+    pub fn try_reshape(self, shape: impl Into<Shape>) -> Result<Tensor<T>, TensorError> {
         let shape = shape.into();
 
-        assert_eq!(shape.size(), self.size(), "shape size must match {:?} new={:?}", 
-            self.shape().as_vec(), shape.as_vec()
-        );
+        if shape.size() != self.size() {
+            return Err(TensorError::ShapeMismatch {
+                op: "reshape",
+                a: self.shape().as_vec(),
+                b: shape.as_vec(),
+            });
+        }
+
+        if self.is_contiguous() {
+            return Ok(Self { stride: view::contiguous_stride(&shape), shape, ..self });
+        }
 
-        Self { shape, ..self }
+        match self.reshape_stride(&shape) {
+            Some(stride) => Ok(Self { shape, stride, ..self }),
+            None => {
+                // like NumPy, copies when no view of the strides has the shape
+                let tensor = self.to_contiguous();
+
+                Ok(Self { stride: view::contiguous_stride(&shape), shape, ..tensor })
+            }
+        }
     }
 
     #[must_use]
@@ -1,10 +1,17 @@
+use std::ops::{Range, RangeFrom, RangeFull, RangeInclusive, RangeTo, RangeToInclusive};
+
 use crate::tensor::Tensor;
 
-use super::Type;
+use super::{Shape, Type};
 
-impl<T: Type + Clone> Tensor<T> {
+impl<T: Type> Tensor<T> {
+    ///
+    /// NumPy-style basic indexing, returning a view that shares the
+    /// tensor's data. The index is an integer, a range, `NewAxis`, 
+    /// `Ellipsis`, or a tuple of those.
+    ///
     pub fn slice<S: TensorSlice>(&self, index: S) -> Tensor<T> {
-        S::slice(index, &self)
+        S::slice(index, self)
     }
 }
 
@@ -12,39 +19,283 @@ pub trait TensorSlice {
     fn slice<T: Type>(self, tensor: &Tensor<T>) -> Tensor<T>;
 }
 
-impl TensorSlice for usize {
-    fn slice<T: Type>(self, tensor: &Tensor<T>) -> Tensor<T> {
-        assert!(tensor.rank() > 0);
-        assert!(self < tensor.dim(0));
+#[derive(Clone, Debug, PartialEq)]
+pub enum SliceIndex {
+    /// Selects one item, removing the axis. Negative values count from the end.
+    Index(isize),
+    /// Python-style start:end:step range, where None is the default bound.
+    Range(Option<isize>, Option<isize>, isize),
+    /// Inserts a new axis of length 1.
+    NewAxis,
+    /// Expands to full ranges for all unspecified axes.
+    Ellipsis,
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct NewAxis;
+
+#[derive(Clone, Copy, Debug)]
+pub struct Ellipsis;
+
+pub trait IntoSliceIndex {
+    fn into_index(self) -> SliceIndex;
+
+    /// Converts a range with a step, where only ranges allow a step.
+    fn into_step_index(self, step: isize) -> SliceIndex
+    where
+        Self: Sized
+    {
+        match self.into_index() {
+            SliceIndex::Range(start, end, 1) => SliceIndex::Range(start, end, step),
+            index => panic!("slice step requires a range {:?}", index),
+        }
+    }
+}
+
+impl IntoSliceIndex for SliceIndex {
+    fn into_index(self) -> SliceIndex {
+        self
+    }
+}
+
+impl IntoSliceIndex for NewAxis {
+    fn into_index(self) -> SliceIndex {
+        SliceIndex::NewAxis
+    }
+}
+
+impl IntoSliceIndex for Ellipsis {
+    fn into_index(self) -> SliceIndex {
+        SliceIndex::Ellipsis
+    }
+}
+
+impl IntoSliceIndex for RangeFull {
+    fn into_index(self) -> SliceIndex {
+        SliceIndex::Range(None, None, 1)
+    }
+}
+
+macro_rules! slice_index {
+    ($($ty:ty)*) => {
+        $(
+            impl IntoSliceIndex for $ty {
+                fn into_index(self) -> SliceIndex {
+                    SliceIndex::Index(self as isize)
+                }
+            }
+
+            impl IntoSliceIndex for Range<$ty> {
+                fn into_index(self) -> SliceIndex {
+                    SliceIndex::Range(Some(self.start as isize), Some(self.end as isize), 1)
+                }
+            }
+
+            impl IntoSliceIndex for RangeFrom<$ty> {
+                fn into_index(self) -> SliceIndex {
+                    SliceIndex::Range(Some(self.start as isize), None, 1)
+                }
+            }
+
+            impl IntoSliceIndex for RangeTo<$ty> {
+                fn into_index(self) -> SliceIndex {
+                    SliceIndex::Range(None, Some(self.end as isize), 1)
+                }
+            }
 
-        //let shape = tensor.shape().slice(1..);
-        //let len : usize = shape.size();
+            impl IntoSliceIndex for RangeInclusive<$ty> {
+                fn into_index(self) -> SliceIndex {
+                    self.into_step_index(1)
+                }
 
-        //tensor.subslice_flat(self * len, len, shape)
-        todo!()
+                fn into_step_index(self, step: isize) -> SliceIndex {
+                    let (start, end) = self.into_inner();
+
+                    SliceIndex::Range(Some(start as isize), inclusive_end(end as isize, step), step)
+                }
+            }
+
+            impl IntoSliceIndex for RangeToInclusive<$ty> {
+                fn into_index(self) -> SliceIndex {
+                    self.into_step_index(1)
+                }
+
+                fn into_step_index(self, step: isize) -> SliceIndex {
+                    SliceIndex::Range(None, inclusive_end(self.end as isize, step), step)
+                }
+            }
+        )*
+    }
+}
+
+slice_index!(usize isize i32);
+
+// The exclusive end past an inclusive end in the step's direction. ..=-1
+// includes the last item and a reversed ..=0 the first, so neither has an
+// exclusive end.
+fn inclusive_end(end: isize, step: isize) -> Option<isize> {
+    if step > 0 {
+        if end == -1 { None } else { Some(end + 1) }
+    } else if end == 0 {
+        None
+    } else {
+        Some(end - 1)
+    }
+}
+
+///
+/// Adds a step to a range, as in NumPy's `start:end:step`, so `(..).step(-1)`
+/// reverses an axis.
+///
+pub trait SliceStep {
+    fn step(self, step: isize) -> SliceIndex;
+}
+
+impl<R: IntoSliceIndex> SliceStep for R {
+    fn step(self, step: isize) -> SliceIndex {
+        assert!(step != 0, "slice step must not be zero");
+
+        self.into_step_index(step)
     }
 }
 
-impl TensorSlice for (usize, usize) {
+impl<S: IntoSliceIndex> TensorSlice for S {
     fn slice<T: Type>(self, tensor: &Tensor<T>) -> Tensor<T> {
-        assert!(tensor.rank() > 1);
-        assert!(self.0 < tensor.dim(0));
-        assert!(self.1 < tensor.dim(1));
+        slice(tensor, &[self.into_index()])
+    }
+}
 
-        /*
-        let shape = tensor.shape().slice(2..);
-        let len : usize = shape.size();
-        let offset = (self.0 * tensor.dim(1) + self.1) * len;
+macro_rules! slice_tuple {
+    ($($id:ident),*) => {
+        #[allow(non_snake_case)]
+        impl<$($id: IntoSliceIndex),*> TensorSlice for ($($id,)*) {
+            fn slice<T: Type>(self, tensor: &Tensor<T>) -> Tensor<T> {
+                let ($($id,)*) = self;
 
-        tensor.subslice_flat(offset, len, shape)
-        */
-        todo!()
+                slice(tensor, &[$($id.into_index()),*])
+            }
+        }
+    }
+}
+
+slice_tuple!(P0);
+slice_tuple!(P0, P1);
+slice_tuple!(P0, P1, P2);
+slice_tuple!(P0, P1, P2, P3);
+slice_tuple!(P0, P1, P2, P3, P4);
+slice_tuple!(P0, P1, P2, P3, P4, P5);
+
+fn slice<T: Type>(tensor: &Tensor<T>, indices: &[SliceIndex]) -> Tensor<T> {
+    let rank = tensor.rank();
+
+    let n_axes = indices.iter()
+        .filter(|index| matches!(index, SliceIndex::Index(_) | SliceIndex::Range(..)))
+        .count();
+
+    let n_ellipsis = indices.iter().filter(|i| **i == SliceIndex::Ellipsis).count();
+
+    assert!(n_ellipsis <= 1, "slice can only have a single ellipsis {:?}", indices);
+    assert!(n_axes <= rank, 
+        "slice {:?} has too many indices for shape {:?}", indices, tensor.shape().as_vec()
+    );
+
+    let dims = tensor.shape().as_vec();
+    let strides = tensor.strides();
+
+    let mut o_dims = Vec::<usize>::new();
+    let mut o_strides = Vec::<isize>::new();
+    let mut offset = tensor.offset() as isize;
+
+    let mut axis = 0;
+
+    for index in indices {
+        match index {
+            SliceIndex::Index(i) => {
+                let dim = dims[axis] as isize;
+                let i = if *i < 0 { *i + dim } else { *i };
+
+                assert!(0 <= i && i < dim,
+                    "slice index {:?} is out of bounds for axis {} of {:?}",
+                    index, axis, dims
+                );
+
+                offset += i * strides[axis];
+                axis += 1;
+            }
+            SliceIndex::Range(start, end, step) => {
+                let (start, len) = range_bounds(*start, *end, *step, dims[axis]);
+
+                if len > 0 {
+                    offset += start * strides[axis];
+                }
+
+                o_dims.push(len);
+                o_strides.push(strides[axis] * step);
+                axis += 1;
+            }
+            SliceIndex::NewAxis => {
+                o_dims.push(1);
+                o_strides.push(0);
+            }
+            SliceIndex::Ellipsis => {
+                for _ in 0..rank - n_axes {
+                    o_dims.push(dims[axis]);
+                    o_strides.push(strides[axis]);
+                    axis += 1;
+                }
+            }
+        }
+    }
+
+    for i in axis..rank {
+        o_dims.push(dims[i]);
+        o_strides.push(strides[i]);
+    }
+
+    assert!(o_dims.len() <= Shape::MAX_RANK,
+        "slice result rank {} is larger than the maximum {}", o_dims.len(), Shape::MAX_RANK
+    );
+
+    tensor.view(&o_dims, &o_strides, offset)
+}
+
+// Python's slice.indices() normalization, returning the start and length
+fn range_bounds(
+    start: Option<isize>, 
+    end: Option<isize>, 
+    step: isize, 
+    dim: usize
+) -> (isize, usize) {
+    assert!(step != 0, "slice step must not be zero");
+
+    let dim = dim as isize;
+
+    let norm = |v: isize, lower: isize, upper: isize| {
+        let v = if v < 0 { v + dim } else { v };
+
+        v.clamp(lower, upper)
+    };
+
+    if step > 0 {
+        let start = start.map_or(0, |v| norm(v, 0, dim));
+        let end = end.map_or(dim, |v| norm(v, 0, dim));
+
+        let len = if start < end { (end - start + step - 1) / step } else { 0 };
+
+        (start, len as usize)
+    } else {
+        let start = start.map_or(dim - 1, |v| norm(v, -1, dim - 1));
+        let end = end.map_or(-1, |v| norm(v, -1, dim - 1));
+
+        let len = if end < start { (start - end - step - 1) / -step } else { 0 };
+
+        (start, len as usize)
     }
 }
 
 #[cfg(test)]
 mod test {
-    use crate::ten;
+    use crate::{ten, tensor::{scalar, Ellipsis, NewAxis, SliceIndex, SliceStep}};
 
     #[test]
     fn slice_usize() {
@@ -66,7 +317,7 @@ mod test {
 
     #[test]
     fn slice_usize_rank1() {
-        let t = ten!([1., 2., 3., 4.]);
+        let t = ten![1., 2., 3., 4.];
 
         let t1 = t.slice(0);
         assert_eq!(t1.shape().as_vec(), &[]);
@@ -91,10 +342,10 @@ mod test {
 
     #[test]
     fn slice_usize_usize() {
-        let t = ten!([
+        let t = ten![
             [[1., 2.], [3., 4.], [5., 6.]],
             [[10., 20.], [30., 40.], [50., 60.]],
-        ]);
+        ];
         assert_eq!(t.shape().as_vec(), &[2, 3, 2]);
 
         let t1 = t.slice((0, 1));
@@ -108,13 +359,99 @@ mod test {
 
     #[test]
     fn slice_with_broadcast() {
-        let t = ten!([1., 2., 3., 4.]);
+        let t = ten![1., 2., 3., 4.];
         assert_eq!(t.shape().as_vec(), &[4]);
 
         let t1 = t.slice(1);
         assert_eq!(t1.shape().as_vec(), &[]);
         assert_eq!(t1.as_slice(), &[2.]);
 
-        assert_eq!(&t1 + ten!([[1.], [2.]]), ten!([[3.], [4.]]));
+        assert_eq!(&t1 + ten![[1.], [2.]], ten![[3.], [4.]]);
+    }
+
+    #[test]
+    fn slice_ranges() {
+        let t = ten![[1, 2, 3, 4], [5, 6, 7, 8], [9, 10, 11, 12]];
+
+        assert_eq!(t.slice(..), t);
+        assert_eq!(t.slice(1..), ten![[5, 6, 7, 8], [9, 10, 11, 12]]);
+        assert_eq!(t.slice(..1), ten![[1, 2, 3, 4]]);
+        assert_eq!(t.slice(..=1), ten![[1, 2, 3, 4], [5, 6, 7, 8]]);
+        assert_eq!(t.slice((.., 1..3)), ten![[2, 3], [6, 7], [10, 11]]);
+        assert_eq!(t.slice((0..=1, 2)), ten![3, 7]);
+        assert_eq!(t.slice((1, 1..)), ten![6, 7, 8]);
+
+        let t1 = t.slice((1..3, 1..3));
+        assert_eq!(t1, ten![[6, 7], [10, 11]]);
+        assert_eq!(t1.offset(), 5);
+        assert_eq!(t1.strides(), vec![4, 1]);
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn slice_negative() {
+        let t = ten![[1, 2, 3, 4], [5, 6, 7, 8], [9, 10, 11, 12]];
+
+        assert_eq!(t.slice(-1), ten![9, 10, 11, 12]);
+        assert_eq!(t.slice((-2, -1)), scalar(8));
+        assert_eq!(t.slice((.., -2..)), ten![[3, 4], [7, 8], [11, 12]]);
+        assert_eq!(t.slice((.., ..-3)), ten![[1], [5], [9]]);
+        assert_eq!(t.slice((.., ..=-2)), ten![[1, 2, 3], [5, 6, 7], [9, 10, 11]]);
+        assert_eq!(t.slice((.., -10..10)), t);
+    }
+
+    #[test]
+    fn slice_step() {
+        let t = ten![[1, 2, 3, 4], [5, 6, 7, 8], [9, 10, 11, 12]];
+
+        assert_eq!(t.slice((.., (..).step(2))), ten![[1, 3], [5, 7], [9, 11]]);
+        assert_eq!(t.slice((.., (1..).step(2))), ten![[2, 4], [6, 8], [10, 12]]);
+        assert_eq!(t.slice((..).step(-1)), ten![[9, 10, 11, 12], [5, 6, 7, 8], [1, 2, 3, 4]]);
+        assert_eq!(t.slice((0, (..).step(-2))), ten![4, 2]);
+        assert_eq!(t.slice((0, (2..0).step(-1))), ten![3, 2]);
+        assert_eq!(t.slice((0, SliceIndex::Range(Some(-1), None, -3))), ten![4, 1]);
+        assert_eq!(t.slice((0, (1..1).step(1))).size(), 0);
+    }
+
+    #[test]
+    fn slice_step_inclusive() {
+        let t = ten![1, 2, 3, 4];
+
+        assert_eq!(t.slice((3..=1).step(-1)), ten![4, 3, 2]);
+        assert_eq!(t.slice((3..=0).step(-1)), ten![4, 3, 2, 1]);
+        assert_eq!(t.slice((2..=0).step(-2)), ten![3, 1]);
+        assert_eq!(t.slice((-1..=-3).step(-1)), ten![4, 3, 2]);
+        assert_eq!(t.slice((..=1).step(-1)), ten![4, 3, 2]);
+        assert_eq!(t.slice((..=0).step(-1)), ten![4, 3, 2, 1]);
+        assert_eq!(t.slice((0..=2).step(2)), ten![1, 3]);
+        assert_eq!(t.slice((1..=-1).step(2)), ten![2, 4]);
+        assert_eq!(t.slice((1..=3).step(-1)).size(), 0);
+    }
+
+    #[test]
+    fn slice_newaxis_ellipsis() {
+        let t = ten![[1, 2], [3, 4]];
+
+        assert_eq!(t.slice(NewAxis).shape().as_vec(), vec![1, 2, 2]);
+        assert_eq!(t.slice((.., NewAxis)), ten![[[1, 2]], [[3, 4]]]);
+        assert_eq!(t.slice((Ellipsis, NewAxis)), ten![[[1], [2]], [[3], [4]]]);
+        assert_eq!(t.slice((Ellipsis, 1)), ten![2, 4]);
+        assert_eq!(t.slice((1, Ellipsis)), ten![3, 4]);
+        assert_eq!(t.slice((Ellipsis, 0, 1)), scalar(2));
+
+        let t = ten![[[1, 2], [3, 4]], [[5, 6], [7, 8]]];
+        assert_eq!(t.slice((0, Ellipsis, 1)), ten![2, 4]);
+        assert_eq!(t.slice((Ellipsis, (..).step(-1))), ten![[[2, 1], [4, 3]], [[6, 5], [8, 7]]]);
+    }
+
+    #[test]
+    #[should_panic]
+    fn slice_out_of_bounds() {
+        ten![1, 2, 3].slice(3);
+    }
+
+    #[test]
+    #[should_panic]
+    fn slice_too_many_indices() {
+        ten![1, 2, 3].slice((0, 0));
+    }
+}
@@ -1,10 +1,10 @@
 use core::fmt;
-use std::{any::type_name, ops::Deref, ptr, slice, sync::Arc};
+use std::{any::type_name, borrow::Cow, ops::{Deref, Range}, ptr, slice, sync::Arc};
 
 use num_complex::Complex;
 
 use super::{
-    data::TensorData, unsafe_init, Shape
+    data::TensorData, unsafe_init, view::{self, Stride}, Shape
 };
 
 pub struct Tensor<T: Type=f32> {
@@ -12,6 +12,9 @@ pub struct Tensor<T: Type=f32> {
     pub offset: usize,
     pub size: usize,
 
+    // element strides, reverse-indexed like the shape's dims
+    pub(super) stride: Stride,
+
     pub(super) data: Arc<TensorData<T>>,
 }
 
@@ -35,6 +38,7 @@ impl<T: Type> Tensor<T> {
         assert_eq!(shape.size(), data.len());
 
         Self {
+            stride: view::contiguous_stride(&shape),
             shape,
             offset: 0,
             size: data.len(),
@@ -62,17 +66,24 @@ impl<T: Type> Tensor<T> {
     pub fn get(&self, offset: usize) -> Option<&T> {
         unsafe {
             if offset < self.size {
-                let count = offset + self.offset;
-
-                self.as_ptr().add(count).as_ref()
+                self.as_ptr().offset(self.data_offset(offset)).as_ref()
             } else {
                 None
             }
         }
     }
 
+    /// Returns the tensor's items as a flat slice. Only contiguous tensors 
+    /// have a slice, so views from `transpose` or `broadcast_to` need 
+    /// `to_contiguous()` first.
     #[inline]
     pub fn as_slice(&self) -> &[T] {
+        assert!(
+            self.is_contiguous(), 
+            "as_slice requires a contiguous tensor, use to_contiguous() for views {:?}",
+            self.shape().as_vec()
+        );
+
         let len = self.size;
 
         unsafe {
@@ -87,6 +98,8 @@ impl<T: Type> Tensor<T> {
     // todo: remove because without a length, it's meaningless?
     #[inline]
     pub fn as_wrap_slice(&self, offset: usize) -> &[T] {
+        assert!(self.is_contiguous(), "as_wrap_slice requires a contiguous tensor");
+
         let count = if offset < self.size {
             offset
         } else {
@@ -112,11 +125,11 @@ impl<T: Type> Tensor<T> {
             offset % self.size
         };
 
-        let count = self.offset + offset;
-        assert!(count + len <= self.size);
+        assert!(offset + len <= self.size);
+        assert!(self.is_contiguous(), "as_wrap_slice_n requires a contiguous tensor");
 
         unsafe {
-            ptr::slice_from_raw_parts(self.as_ptr().add(count), len)
+            ptr::slice_from_raw_parts(self.as_ptr().add(offset), len)
                 .as_ref()
                 .unwrap()
         }
@@ -127,20 +140,23 @@ impl<T: Type> Tensor<T> {
         self.data.as_ptr().add(self.offset)
     }
 
-    // Returns a possibly-wrapped pointer at the offset to support
-    // broadcast
+    // Returns a possibly-wrapped pointer at the logical offset to support
+    // broadcast, following the strides of views
     #[inline]
     pub unsafe fn as_wrap_ptr(&self, offset: usize) -> *const T {
-        if offset < self.size {
-            self.data.as_ptr().add(self.offset + offset)
+        let offset = if offset < self.size {
+            offset
         } else {
-            self.data.as_ptr().add(self.offset + offset % self.size)
-        }
+            offset % self.size
+        };
+
+        self.as_ptr().offset(self.data_offset(offset))
     }
 
     pub fn subslice_flat(&self, offset: usize, len: usize, shape: impl Into<Shape>) -> Self {
         assert!(offset <= self.size);
         assert!(offset + len <= self.size);
+        assert!(self.is_contiguous(), "subslice_flat requires a contiguous tensor");
 
         let shape = shape.into();
 
@@ -148,6 +164,7 @@ impl<T: Type> Tensor<T> {
         assert!(shape_len == len || shape.size() == 0 && len == 1);
 
         Self {
+            stride: view::contiguous_stride(&shape),
             shape,
 
             offset: self.offset + offset,
@@ -164,27 +181,35 @@ impl<T: Type> Tensor<T> {
         // assert!(offset <= dim_0);
         assert!(offset + len <= dim_0, "subslice end={} with shape={:?}", offset + len, self.shape().as_vec());
 
-        let size : usize = self.shape().as_vec()[1..].iter().product();
-
-        let mut shape = Vec::from(self.shape.as_vec());
-        shape[0] = len;
-
-        self.subslice_flat(offset * size, len * size, shape)
+        self.slice_axis(0, offset, len, 1)
     }
 
     #[inline]
-    pub fn iter(&self) -> slice::Iter<T> {
-        self.as_slice().iter()
+    pub fn iter(&self) -> Iter<'_, T> {
+        if self.is_contiguous() {
+            Iter::Slice(self.as_slice().iter())
+        } else {
+            Iter::Strided(self, 0..self.size)
+        }
     }
 
+    ///
+    /// Iterates over the rows, borrowing them from contiguous tensors and
+    /// copying them from views.
+    ///
     #[inline]
-    pub fn iter_row(&self) -> slice::ChunksExact<T> {
-        let dim = self.cols();
-
-        if self.rank() > 1 {
-            self.as_slice().chunks_exact(dim).into_iter()
+    pub fn iter_row(&self) -> IterRow<'_, T>
+    where
+        T: Clone
+    {
+        let cols = if self.rank() > 1 { self.cols() } else { 1 };
+
+        if self.is_contiguous() {
+            IterRow::Slice(self.as_slice().chunks_exact(cols))
         } else {
-            self.as_slice().chunks_exact(1).into_iter()
+            let rows = if cols > 0 { self.size / cols } else { 0 };
+
+            IterRow::Strided(self, 0..rows, cols)
         }
     }
 }
@@ -233,7 +258,7 @@ impl<T: Type + Clone> Tensor<T> {
         data.reserve_exact(len);
 
         let data = vec.iter()
-            .flat_map(|tensor| tensor.as_slice().iter().map(|v| v.clone()))
+            .flat_map(|tensor| tensor.iter().cloned())
             .collect();
 
         Tensor::from_vec(data, shape)
@@ -276,6 +301,8 @@ impl<T: Type> Clone for Tensor<T> {
             offset: self.offset,
             size: self.size,
 
+            stride: self.stride,
+
             data: self.data.clone(),
         }
     }
@@ -283,18 +310,94 @@ impl<T: Type> Clone for Tensor<T> {
 
 impl<T: Type + PartialEq> PartialEq for Tensor<T> {
     fn eq(&self, other: &Self) -> bool {
-        self.shape == other.shape && self.as_slice() == other.as_slice()
+        self.shape == other.shape && self.iter().eq(other.iter())
     }
 }
 
 impl<'a, T: Type> IntoIterator for &'a Tensor<T> {
     type Item = &'a T;
-    type IntoIter = slice::Iter<'a, T>;
+    type IntoIter = Iter<'a, T>;
 
     fn into_iter(self) -> Self::IntoIter {
         self.iter()
     }
 }
+
+/// Iterator over a tensor's items in row-major order, walking the strides
+/// when the tensor is a non-contiguous view.
+pub enum Iter<'a, T: Type> {
+    Slice(slice::Iter<'a, T>),
+    Strided(&'a Tensor<T>, Range<usize>),
+}
+
+impl<'a, T: Type> Iterator for Iter<'a, T> {
+    type Item = &'a T;
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        match self {
+            Iter::Slice(iter) => iter.next(),
+            Iter::Strided(tensor, range) => {
+                range.next().and_then(|i| tensor.get(i))
+            }
+        }
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        match self {
+            Iter::Slice(iter) => iter.size_hint(),
+            Iter::Strided(_, range) => range.size_hint(),
+        }
+    }
+}
+
+impl<T: Type> DoubleEndedIterator for Iter<'_, T> {
+    #[inline]
+    fn next_back(&mut self) -> Option<Self::Item> {
+        match self {
+            Iter::Slice(iter) => iter.next_back(),
+            Iter::Strided(tensor, range) => {
+                range.next_back().and_then(|i| tensor.get(i))
+            }
+        }
+    }
+}
+
+impl<T: Type> ExactSizeIterator for Iter<'_, T> {}
+
+pub enum IterRow<'a, T: Type + Clone> {
+    Slice(slice::ChunksExact<'a, T>),
+    Strided(&'a Tensor<T>, Range<usize>, usize),
+}
+
+impl<'a, T: Type + Clone> Iterator for IterRow<'a, T> {
+    type Item = Cow<'a, [T]>;
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        match self {
+            IterRow::Slice(iter) => iter.next().map(Cow::Borrowed),
+            IterRow::Strided(tensor, rows, cols) => {
+                rows.next().map(|row| {
+                    (row * *cols..(row + 1) * *cols)
+                        .map(|i| tensor.get(i).unwrap().clone())
+                        .collect()
+                })
+            }
+        }
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        match self {
+            IterRow::Slice(iter) => iter.size_hint(),
+            IterRow::Strided(_, rows, _) => rows.size_hint(),
+        }
+    }
+}
+
+impl<T: Type + Clone> ExactSizeIterator for IterRow<'_, T> {}
 impl<T: Type> AsRef<Tensor<T>> for Tensor<T> {
     fn as_ref(&self) -> &Tensor<T> {
     	self
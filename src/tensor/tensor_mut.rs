@@ -0,0 +1,137 @@
+use std::{ops::{Deref, DerefMut}, slice, sync::Arc};
+
+use super::{unsafe_init, Shape, Tensor, Type};
+
+///
+/// Unique, contiguous access to a tensor's items for in-place updates,
+/// returned by `Tensor::make_mut`. Derefs to a mutable slice in row-major
+/// order.
+///
+pub struct TensorMut<'a, T: Type> {
+    tensor: &'a mut Tensor<T>,
+}
+
+impl<T: Type + Clone> Tensor<T> {
+    ///
+    /// Copy-on-write access to the items. The data is cloned only when
+    /// the storage is shared with another tensor or when the tensor is a
+    /// non-contiguous view.
+    ///
+    pub fn make_mut(&mut self) -> TensorMut<'_, T> {
+        if Arc::get_mut(&mut self.data).is_none() || ! self.is_contiguous() {
+            *self = self.to_unique();
+        }
+
+        TensorMut { tensor: self }
+    }
+
+    /// Returns the items as a mutable slice, cloning shared storage first.
+    #[inline]
+    pub fn as_mut_slice(&mut self) -> &mut [T] {
+        self.make_mut().into_mut_slice()
+    }
+
+    fn to_unique(&self) -> Self {
+        unsafe {
+            unsafe_init::<T>(self.size(), self.shape().clone(), |o| {
+                for (i, value) in self.iter().enumerate() {
+                    o.add(i).write(value.clone());
+                }
+            })
+        }
+    }
+}
+
+impl<'a, T: Type> TensorMut<'a, T> {
+    #[inline]
+    pub fn shape(&self) -> &Shape {
+        self.tensor.shape()
+    }
+
+    #[inline]
+    pub fn into_mut_slice(self) -> &'a mut [T] {
+        mut_slice(self.tensor)
+    }
+}
+
+impl<T: Type> Deref for TensorMut<'_, T> {
+    type Target = [T];
+
+    fn deref(&self) -> &Self::Target {
+        self.tensor.as_slice()
+    }
+}
+
+impl<T: Type> DerefMut for TensorMut<'_, T> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        mut_slice(self.tensor)
+    }
+}
+
+// The tensor must be contiguous and own its data, as checked by make_mut
+fn mut_slice<T: Type>(tensor: &mut Tensor<T>) -> &mut [T] {
+    let offset = tensor.offset;
+    let len = tensor.size;
+
+    let data = Arc::get_mut(&mut tensor.data)
+        .expect("TensorMut requires unique data");
+
+    unsafe {
+        slice::from_raw_parts_mut(data.as_mut_ptr().add(offset), len)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::ten;
+
+    #[test]
+    fn make_mut_unique() {
+        let mut t = ten![1., 2., 3.];
+        let ptr = unsafe { t.as_ptr() };
+
+        t.make_mut()[1] = 20.;
+
+        assert_eq!(t, ten![1., 20., 3.]);
+        assert_eq!(unsafe { t.as_ptr() }, ptr);
+    }
+
+    #[test]
+    fn make_mut_shared() {
+        let a = ten![1., 2., 3.];
+        let mut b = a.clone();
+
+        b.as_mut_slice()[0] = 10.;
+
+        assert_eq!(a, ten![1., 2., 3.]);
+        assert_eq!(b, ten![10., 2., 3.]);
+    }
+
+    #[test]
+    fn make_mut_view() {
+        let mut t = ten![[1., 2.], [3., 4.]].transpose();
+
+        let mut t_mut = t.make_mut();
+        assert_eq!(t_mut.shape().as_vec(), &[2, 2]);
+        assert_eq!(&t_mut[..], &[1., 3., 2., 4.]);
+
+        t_mut.fill(0.);
+        t_mut[3] = 1.;
+
+        assert!(t.is_contiguous());
+        assert_eq!(t, ten![[0., 0.], [0., 1.]]);
+    }
+
+    #[test]
+    fn make_mut_subslice() {
+        let t = ten![[1., 2.], [3., 4.], [5., 6.]];
+        let mut t1 = t.subslice(1, 1);
+        drop(t);
+
+        for v in t1.as_mut_slice() {
+            *v *= 10.;
+        }
+
+        assert_eq!(t1, ten![[30., 40.]]);
+    }
+}
@@ -0,0 +1,69 @@
+use crate::{ten, tensor::{Axis, Shape, TensorError}};
+
+#[test]
+fn try_reshape() {
+    let t = ten![[1, 2, 3], [4, 5, 6]];
+
+    assert_eq!(t.clone().try_reshape([3, 2]).unwrap(), ten![[1, 2], [3, 4], [5, 6]]);
+
+    assert_eq!(
+        t.clone().try_reshape([4, 2]).unwrap_err(),
+        TensorError::ShapeMismatch { op: "reshape", a: vec![2, 3], b: vec![4, 2] }
+    );
+
+    // a view that can't be reshaped in place is copied
+    assert_eq!(t.transpose().try_reshape([6]).unwrap(), ten![1, 4, 2, 5, 3, 6]);
+}
+
+#[test]
+#[should_panic(expected = "reshape shapes do not match [2, 3] [4, 2]")]
+fn reshape_panic_message() {
+    let _ = ten![[1, 2, 3], [4, 5, 6]].reshape([4, 2]);
+}
+
+#[test]
+fn try_broadcast_to() {
+    let t = ten![1, 2];
+
+    assert_eq!(t.try_broadcast_to([2, 2]).unwrap(), ten![[1, 2], [1, 2]]);
+
+    assert_eq!(
+        t.try_broadcast_to([2, 3]).unwrap_err(),
+        TensorError::ShapeMismatch { op: "broadcast_to", a: vec![2], b: vec![2, 3] }
+    );
+
+    assert!(ten![[1, 2]].try_broadcast_to([2]).is_err());
+}
+
+#[test]
+fn shape_try_broadcast_to() {
+    let a = Shape::from([3, 2]);
+
+    assert_eq!(a.try_broadcast_to(&Shape::from([2])), Ok(Shape::from([3, 2])));
+    assert_eq!(a.try_broadcast_to(&Shape::from([4, 3, 2])), Ok(Shape::from([4, 3, 2])));
+
+    assert_eq!(
+        a.try_broadcast_to(&Shape::from([3])),
+        Err(TensorError::ShapeMismatch { op: "broadcast", a: vec![3, 2], b: vec![3] })
+    );
+}
+
+#[test]
+fn try_axis_from_rank() {
+    assert_eq!(Axis::axis(1).try_axis_from_rank("op", 2), Ok(1));
+    assert_eq!(Axis::axis(-2).try_axis_from_rank("op", 2), Ok(0));
+    assert_eq!(Axis::axis_opt(None).try_axis_from_rank("op", 2), Ok(0));
+
+    assert_eq!(
+        Axis::axis(2).try_axis_from_rank("op", 2),
+        Err(TensorError::AxisOutOfRange { op: "op", axis: 2, rank: 2 })
+    );
+    assert!(Axis::axis(-3).try_axis_from_rank("op", 2).is_err());
+}
+
+#[test]
+fn error_display() {
+    let err = TensorError::AxisOutOfRange { op: "split", axis: 3, rank: 2 };
+
+    assert_eq!(err.to_string(), "split axis 3 is out of range for rank 2");
+}
@@ -1,5 +1,7 @@
 mod from;
 mod tensor;
 mod data;
+mod error;
 mod shape;
 mod map;
+mod view;
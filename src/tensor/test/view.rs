@@ -0,0 +1,130 @@
+use crate::{ten, tensor::Tensor};
+
+#[test]
+fn permute_dims() {
+    let t = ten![[[1, 2], [3, 4], [5, 6]]];
+    assert_eq!(t.strides(), vec![6, 2, 1]);
+    assert!(t.is_contiguous());
+
+    let p = t.permute_dims([2, 0, 1]);
+    assert_eq!(p.shape().as_vec(), vec![2, 1, 3]);
+    assert_eq!(p.strides(), vec![1, 6, 2]);
+    assert!(! p.is_contiguous());
+    assert_eq!(p, ten![[[1, 3, 5]], [[2, 4, 6]]]);
+
+    assert_eq!(t.swapaxes(0, -1), ten![[[1], [3], [5]], [[2], [4], [6]]]);
+}
+
+#[test]
+fn broadcast_to() {
+    let t = ten![1, 2, 3];
+
+    let b = t.broadcast_to([2, 3]);
+    assert_eq!(b.strides(), vec![0, 1]);
+    assert_eq!(b.size(), 6);
+    assert_eq!(b, ten![[1, 2, 3], [1, 2, 3]]);
+
+    let c = ten![[1], [2]].broadcast_to([2, 3]);
+    assert_eq!(c, ten![[1, 1, 1], [2, 2, 2]]);
+}
+
+#[test]
+#[should_panic]
+fn broadcast_to_mismatch() {
+    ten![1, 2, 3].broadcast_to([2, 2]);
+}
+
+#[test]
+fn slice_axis_step() {
+    let t = ten![[1, 2, 3, 4], [5, 6, 7, 8]];
+
+    assert_eq!(t.slice_axis(1, 0, 2, 2), ten![[1, 3], [5, 7]]);
+    assert_eq!(t.slice_axis(-1, 3, 4, -1), ten![[4, 3, 2, 1], [8, 7, 6, 5]]);
+    assert_eq!(t.slice_axis(0, 1, 2, -1), ten![[5, 6, 7, 8], [1, 2, 3, 4]]);
+
+    let r = t.slice_axis(-1, 3, 2, -2).slice_axis(0, 1, 1, 1);
+    assert_eq!(r, ten![[8, 6]]);
+    assert_eq!(r[1], 6);
+    assert_eq!(r[(0, 0)], 8);
+}
+
+#[test]
+fn to_contiguous() {
+    let t = ten![[1, 2, 3], [4, 5, 6]].t();
+    assert!(! t.is_contiguous());
+
+    let c = t.to_contiguous();
+    assert!(c.is_contiguous());
+    assert_eq!(c.as_slice(), &[1, 4, 2, 5, 3, 6]);
+    assert_eq!(c, t);
+}
+
+#[test]
+#[should_panic]
+fn as_slice_view_panic() {
+    let t = ten![[1, 2, 3], [4, 5, 6]].t();
+
+    t.as_slice();
+}
+
+#[test]
+fn reshape_view() {
+    let t = ten![[[1, 2], [3, 4]], [[5, 6], [7, 8]]].slice_axis(-1, 0, 1, 1);
+    assert_eq!(t.strides(), vec![4, 2, 1]);
+
+    let r = t.clone().reshape([2, 2]);
+    assert_eq!(r.strides(), vec![4, 2]);
+    assert_eq!(r, ten![[1, 3], [5, 7]]);
+
+    assert_eq!(t.clone().squeeze(), ten![[1, 3], [5, 7]]);
+    assert_eq!(t.t().flatten(), ten![1, 3, 5, 7]);
+}
+
+#[test]
+fn reshape_transpose_copy() {
+    let t = ten![[1., 2., 3.], [4., 5., 6.]].transpose();
+
+    assert_eq!(t.clone().flatten(), ten![1., 4., 2., 5., 3., 6.]);
+    assert_eq!(t.clone().reshape([6]), ten![1., 4., 2., 5., 3., 6.]);
+    assert_eq!(t.reshape([2, 3]), ten![[1., 4., 2.], [5., 3., 6.]]);
+}
+
+#[test]
+fn rows_transpose() {
+    let t = ten![[1., 2., 3.], [4., 5., 6.]].transpose();
+
+    assert_eq!(t.map_row(|row| [row[0] + row[1]]), ten![5., 7., 9.]);
+    assert_eq!(t.fold_row(None, [0.; 2], |s, row| [s[0] + row[0], s[1] + row[1]]), ten![6., 15.]);
+
+    let rows: Vec<Vec<f32>> = t.iter_row().map(|row| row.to_vec()).collect();
+    assert_eq!(rows, vec![vec![1., 4.], vec![2., 5.], vec![3., 6.]]);
+
+    // wrapped pointers follow the view's strides
+    unsafe {
+        assert_eq!(*t.as_wrap_ptr(1), 4.);
+        assert_eq!(*t.as_wrap_ptr(9), 5.);
+    }
+}
+
+#[test]
+fn map_view() {
+    let t = ten![[1, 2, 3], [4, 5, 6]].t();
+
+    assert_eq!(t.map(|v| v * 10), ten![[10, 40], [20, 50], [30, 60]]);
+    assert_eq!(&t + ten![100, 200], ten![[101, 204], [102, 205], [103, 206]]);
+    assert_eq!(t.reduce_sum_axis(-1), ten![5, 7, 9]);
+
+    let v: Vec<i32> = t.iter().rev().copied().collect();
+    assert_eq!(v, vec![6, 3, 5, 2, 4, 1]);
+    assert_eq!(format!("{:?}", t), "Tensor<i32> {\n[[1 4],\n [2 5],\n [3 6]], shape: [3, 2]}");
+}
+
+#[test]
+fn view_shares_data() {
+    let t: Tensor<i32> = ten![[1, 2], [3, 4]];
+
+    let v = t.t().broadcast_to([3, 2, 2]);
+    assert_eq!(v.strides(), vec![0, 1, 2]);
+    assert_eq!(v.offset(), 0);
+    assert_eq!(v.unstack(0)[2], ten![[1, 3], [2, 4]]);
+}
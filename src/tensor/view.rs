@@ -0,0 +1,338 @@
+use super::{unsafe_init, Axis, Shape, Tensor, TensorError, Type};
+
+///
+/// Element strides for each axis, reverse-indexed like Shape's dims, so
+/// stride[0] is the column stride.
+///
+pub(super) type Stride = [isize; Shape::MAX_RANK];
+
+pub(super) fn contiguous_stride(shape: &Shape) -> Stride {
+    let mut stride = [0; Shape::MAX_RANK];
+    let mut size = 1;
+
+    for (i, item) in stride.iter_mut().enumerate().take(shape.rank()) {
+        *item = size;
+        size *= shape.rdim(i) as isize;
+    }
+
+    stride
+}
+
+impl<T: Type> Tensor<T> {
+    ///
+    /// Returns the element stride of each axis, ordered outside-in like
+    /// `Shape::as_vec`.
+    ///
+    pub fn strides(&self) -> Vec<isize> {
+        let rank = self.rank();
+
+        (0..rank).map(|i| self.stride[rank - 1 - i]).collect()
+    }
+
+    ///
+    /// True if the items are stored densely in row-major order, which
+    /// is required for `as_slice()`.
+    ///
+    pub fn is_contiguous(&self) -> bool {
+        if self.size == 0 {
+            return true;
+        }
+
+        let mut size = 1;
+
+        for i in 0..self.rank() {
+            let dim = self.rdim(i);
+
+            if dim != 1 && self.stride[i] != size {
+                return false;
+            }
+
+            size *= dim as isize;
+        }
+
+        true
+    }
+
+    /// Storage offset, relative to self.offset, of the row-major index
+    #[inline]
+    pub(super) fn data_offset(&self, index: usize) -> isize {
+        let mut index = index;
+        let mut offset = 0;
+
+        for i in 0..self.rank() {
+            let dim = self.rdim(i);
+
+            offset += (index % dim) as isize * self.stride[i];
+            index /= dim;
+        }
+
+        offset
+    }
+
+    pub(super) fn view(&self, dims: &[usize], strides: &[isize], offset: isize) -> Self {
+        assert_eq!(dims.len(), strides.len());
+        assert!(offset >= 0);
+
+        let shape = Shape::from(dims);
+
+        let mut stride = [0; Shape::MAX_RANK];
+        for (i, value) in strides.iter().rev().enumerate() {
+            stride[i] = *value;
+        }
+
+        Self {
+            size: shape.size(),
+            shape,
+            offset: offset as usize,
+            stride,
+            data: self.data.clone(),
+        }
+    }
+
+    ///
+    /// Permutes the axes as a view without copying, where axes[i] is the
+    /// source axis for the new axis i.
+    ///
+    pub fn permute_dims(&self, axes: impl AsRef<[usize]>) -> Self {
+        let axes = axes.as_ref();
+        let rank = self.rank();
+
+        assert_eq!(axes.len(), rank,
+            "permute_dims axes {:?} must match rank of {:?}", axes, self.shape().as_vec()
+        );
+
+        let mut is_used = [false; Shape::MAX_RANK];
+        for axis in axes {
+            assert!(*axis < rank && ! is_used[*axis],
+                "permute_dims axes {:?} is not a permutation for {:?}", axes, self.shape().as_vec()
+            );
+            is_used[*axis] = true;
+        }
+
+        let strides = self.strides();
+
+        let dims: Vec<usize> = axes.iter().map(|a| self.dim(*a)).collect();
+        let strides: Vec<isize> = axes.iter().map(|a| strides[*a]).collect();
+
+        self.view(&dims, &strides, self.offset as isize)
+    }
+
+    ///
+    /// Swaps two axes as a view without copying.
+    ///
+    pub fn swapaxes(&self, a: impl Into<Axis>, b: impl Into<Axis>) -> Self {
+        let rank = self.rank();
+        let a = a.into().axis_from_rank(rank);
+        let b = b.into().axis_from_rank(rank);
+
+        let mut axes: Vec<usize> = (0..rank).collect();
+        axes.swap(a, b);
+
+        self.permute_dims(axes)
+    }
+
+    ///
+    /// Broadcasts to a larger shape as a view, using a zero stride for
+    /// new and size-1 axes.
+    ///
+    pub fn broadcast_to(&self, shape: impl Into<Shape>) -> Self {
+        self.try_broadcast_to(shape).unwrap_or_else(|err| panic!("{}", err))
+    }
+
+    pub fn try_broadcast_to(&self, shape: impl Into<Shape>) -> Result<Self, TensorError> {
+        let shape = shape.into();
+        let rank = self.rank();
+
+        let mut stride = [0; Shape::MAX_RANK];
+
+        for (i, item) in stride.iter_mut().enumerate().take(rank) {
+            if i < shape.rank() && self.rdim(i) == shape.rdim(i) {
+                *item = self.stride[i];
+            } else if i >= shape.rank() || self.rdim(i) != 1 {
+                return Err(TensorError::ShapeMismatch {
+                    op: "broadcast_to",
+                    a: self.shape().as_vec(),
+                    b: shape.as_vec(),
+                });
+            }
+        }
+
+        Ok(Self {
+            size: shape.size(),
+            shape,
+            offset: self.offset,
+            stride,
+            data: self.data.clone(),
+        })
+    }
+
+    ///
+    /// Selects len items along the axis as a view, starting at start and
+    /// advancing by step, which may be negative.
+    ///
+    pub fn slice_axis(
+        &self,
+        axis: impl Into<Axis>,
+        start: usize,
+        len: usize,
+        step: isize
+    ) -> Self {
+        assert!(self.rank() > 0, "slice_axis requires rank > 0");
+        assert!(step != 0, "slice_axis step must not be zero");
+
+        let axis = axis.into().axis_from_rank(self.rank());
+        let dim = self.dim(axis);
+
+        if len > 0 {
+            let end = start as isize + (len as isize - 1) * step;
+
+            assert!(start < dim && 0 <= end && end < dim as isize,
+                "slice_axis start={} len={} step={} is invalid for axis {} of {:?}",
+                start, len, step, axis, self.shape().as_vec()
+            );
+        }
+
+        let mut dims = self.shape().as_vec();
+        let mut strides = self.strides();
+
+        let offset = self.offset as isize + start as isize * strides[axis];
+
+        dims[axis] = len;
+        strides[axis] *= step;
+
+        self.view(&dims, &strides, offset)
+    }
+
+    ///
+    /// Selects a single item along the axis, returning a view with the
+    /// axis removed.
+    ///
+    pub(crate) fn index_axis(&self, axis: usize, index: usize) -> Self {
+        assert!(axis < self.rank());
+        assert!(index < self.dim(axis),
+            "index {} is out of bounds for axis {} of {:?}",
+            index, axis, self.shape().as_vec()
+        );
+
+        let mut dims = self.shape().as_vec();
+        let mut strides = self.strides();
+
+        let offset = self.offset as isize + index as isize * strides[axis];
+
+        dims.remove(axis);
+        strides.remove(axis);
+
+        self.view(&dims, &strides, offset)
+    }
+
+    ///
+    /// Stride for a reshaped view of a non-contiguous tensor when the
+    /// reshape only splits or merges axes that are contiguous with each
+    /// other.
+    ///
+    pub(super) fn reshape_stride(&self, shape: &Shape) -> Option<Stride> {
+        // adapted from numpy's attempt_nocopy_reshape
+        let old: Vec<(usize, isize)> = self.shape().as_vec().into_iter()
+            .zip(self.strides())
+            .filter(|(dim, _)| *dim != 1)
+            .collect();
+
+        let new_dims = shape.as_vec();
+        let mut new_strides = vec![1; new_dims.len()];
+
+        let (mut oi, mut oj) = (0, 1);
+        let (mut ni, mut nj) = (0, 1);
+
+        while ni < new_dims.len() && oi < old.len() {
+            let mut np = new_dims[ni];
+            let mut op = old[oi].0;
+
+            while np != op {
+                if np < op {
+                    np *= new_dims[nj];
+                    nj += 1;
+                } else {
+                    op *= old[oj].0;
+                    oj += 1;
+                }
+            }
+
+            for ok in oi..oj - 1 {
+                if old[ok].1 != old[ok + 1].0 as isize * old[ok + 1].1 {
+                    return None;
+                }
+            }
+
+            new_strides[nj - 1] = old[oj - 1].1;
+            for nk in (ni + 1..nj).rev() {
+                new_strides[nk - 1] = new_strides[nk] * new_dims[nk] as isize;
+            }
+
+            ni = nj;
+            nj += 1;
+            oi = oj;
+            oj += 1;
+        }
+
+        let mut stride = [0; Shape::MAX_RANK];
+        for (i, value) in new_strides.iter().rev().enumerate() {
+            stride[i] = *value;
+        }
+
+        Some(stride)
+    }
+}
+
+impl<T: Type + Clone> Tensor<T> {
+    ///
+    /// Returns a densely-stored copy of a view, or a shared clone if the
+    /// tensor is already contiguous.
+    ///
+    pub fn to_contiguous(&self) -> Self {
+        if self.is_contiguous() {
+            return self.clone();
+        }
+
+        unsafe {
+            unsafe_init::<T>(self.size(), self.shape().clone(), |o| {
+                for (i, value) in self.iter().enumerate() {
+                    o.add(i).write(value.clone());
+                }
+            })
+        }
+    }
+}
+
+///
+/// Items accessor for kernels that index with broadcast wrapping, using
+/// the slice directly for contiguous tensors.
+///
+pub(super) struct Items<'a, T: Type> {
+    tensor: &'a Tensor<T>,
+    slice: Option<&'a [T]>,
+}
+
+impl<'a, T: Type> Items<'a, T> {
+    pub(super) fn new(tensor: &'a Tensor<T>) -> Self {
+        let slice = if tensor.is_contiguous() {
+            Some(tensor.as_slice())
+        } else {
+            None
+        };
+
+        Self { tensor, slice }
+    }
+
+    #[inline]
+    pub(super) fn get(&self, index: usize) -> &'a T {
+        match self.slice {
+            Some(slice) => &slice[index],
+            None => self.tensor.get(index).unwrap(),
+        }
+    }
+
+    #[inline]
+    pub(super) fn wrap(&self, index: usize) -> &'a T {
+        self.get(index % self.tensor.size())
+    }
+}
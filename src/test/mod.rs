@@ -1,8 +1,59 @@
-use std::{ops, sync::{Arc, Mutex, OnceLock}};
+use std::{fmt, ops, sync::{Arc, Mutex, OnceLock}};
 
+use num_complex::Complex;
 use num_traits::{Num, One, Signed, Zero};
 
-use crate::tensor::Type;
+use crate::tensor::{Tensor, Type};
+
+///
+/// Asserts the tensors have the same shape and their items differ by at
+/// most tol, where NaN is never close.
+///
+#[track_caller]
+pub(crate) fn assert_close<T: Close + Type + fmt::Debug>(
+    a: impl Into<Tensor<T>>,
+    b: impl Into<Tensor<T>>,
+    tol: f64
+) {
+    let (a, b) = (a.into(), b.into());
+
+    assert_eq!(a.shape(), b.shape(), "shapes differ for {:?} != {:?}", a, b);
+
+    for (x, y) in a.iter().zip(b.iter()) {
+        assert!(x.distance(y) <= tol, "{:?} != {:?}", a, b);
+    }
+}
+
+///
+/// Item distance for `assert_close`
+///
+pub(crate) trait Close {
+    fn distance(&self, other: &Self) -> f64;
+}
+
+impl Close for f32 {
+    fn distance(&self, other: &Self) -> f64 {
+        (*self as f64 - *other as f64).abs()
+    }
+}
+
+impl Close for f64 {
+    fn distance(&self, other: &Self) -> f64 {
+        (self - other).abs()
+    }
+}
+
+impl Close for Complex<f32> {
+    fn distance(&self, other: &Self) -> f64 {
+        (self - other).norm() as f64
+    }
+}
+
+impl Close for Complex<f64> {
+    fn distance(&self, other: &Self) -> f64 {
+        (self - other).norm()
+    }
+}
 
 ///
 /// Drop testing
@@ -19,9 +70,10 @@ impl Drop for Dead {
 impl Type for Dead {}
 
 ///
-/// Minimal testing tensor type with only debugging/assertion traits
+/// Minimal testing tensor type with only debugging/assertion traits, and
+/// Clone for reshape, which copies views
 /// 
-#[derive(PartialEq, Debug)]
+#[derive(Clone, PartialEq, Debug)]
 pub struct T(pub usize);
 
 impl Type for T {}